@@ -0,0 +1,48 @@
+//! Compares generating a MemoryCache on one thread against generating it on rayon's global pool.
+use std::sync::Arc;
+use criterion::{criterion_group, criterion_main, Criterion};
+use damselfly3::damselfly::memory::memory_cache::MemoryCache;
+use damselfly3::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+use damselfly3::damselfly::update_interval::update_interval_factory::UpdateIntervalFactory;
+use damselfly3::damselfly::update_interval::UpdateInterval;
+
+const UPDATE_COUNT: usize = 200_000;
+const SLOT_COUNT: usize = 4096;
+const SLOT_SIZE: usize = 64;
+const BLOCK_SIZE: usize = 32;
+const CACHE_INTERVAL: usize = 1000;
+
+/// Builds a log that repeatedly allocates and frees variable sized chunks in fixed size slots.
+fn synthetic_updates() -> Vec<UpdateInterval> {
+    let callstack = Arc::new("bench".to_string());
+    let updates: Vec<MemoryUpdateType> = (0..UPDATE_COUNT)
+        .map(|timestamp| {
+            let slot = (timestamp / 2 * 7919) % SLOT_COUNT;
+            let address = slot * SLOT_SIZE;
+            let size = 8 + (slot * 13) % (SLOT_SIZE - 8);
+            if timestamp % 2 == 0 {
+                Allocation::new(address, size, callstack.clone(), timestamp, timestamp.to_string()).wrap_in_enum()
+            } else {
+                Free::new(address, size, callstack.clone(), timestamp, timestamp.to_string()).wrap_in_enum()
+            }
+        })
+        .collect();
+    UpdateIntervalFactory::new(updates).construct_enum_vector()
+}
+
+fn generate_cache(c: &mut Criterion) {
+    let update_intervals = synthetic_updates();
+    let serial_pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+    let mut group = c.benchmark_group("memory_cache_generation");
+    group.sample_size(10);
+    group.bench_function("serial", |b| b.iter(|| {
+        serial_pool.install(|| MemoryCache::new(BLOCK_SIZE, update_intervals.clone(), CACHE_INTERVAL))
+    }));
+    group.bench_function("parallel", |b| b.iter(|| {
+        MemoryCache::new(BLOCK_SIZE, update_intervals.clone(), CACHE_INTERVAL)
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, generate_cache);
+criterion_main!(benches);
@@ -0,0 +1,43 @@
+//! Compares block queries on a small and a large log. query_block looks blocks up in an interval
+//! tree, so the larger log should not be proportionally slower.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use damselfly3::damselfly::memory::json_event_parser::{JsonEvent, JsonEventOp, JsonEventParser};
+use damselfly3::damselfly::viewer::damselfly_viewer::DamselflyViewer;
+use damselfly3::damselfly::viewer::damselfly_viewer_builder::DamselflyViewerBuilder;
+
+const UPDATE_COUNTS: [usize; 2] = [1_000, 20_000];
+
+/// Builds a viewer over a log of non-overlapping allocations that are never freed.
+fn build_viewer(update_count: usize) -> DamselflyViewer {
+    let events: Vec<JsonEvent> = (0..update_count)
+        .map(|index| JsonEvent {
+            op: JsonEventOp::Alloc,
+            addr: index as u64 * 16,
+            size: 8,
+            timestamp: index as u64,
+            pool: "heap".to_string(),
+            callstack: "bench".to_string(),
+        })
+        .collect();
+    let log_path = std::env::temp_dir().join(format!("query_block_bench_{update_count}.json"));
+    std::fs::write(&log_path, serde_json::to_string(&events).unwrap()).unwrap();
+    DamselflyViewerBuilder::new(log_path.to_str().unwrap())
+        .cache_size(update_count as u64)
+        .build(JsonEventParser::new())
+        .unwrap()
+}
+
+fn query_block(c: &mut Criterion) {
+    let mut group = c.benchmark_group("query_block");
+    for update_count in UPDATE_COUNTS {
+        let viewer = build_viewer(update_count);
+        let instance = &viewer.damselflies[0];
+        group.bench_with_input(BenchmarkId::from_parameter(update_count), &update_count, |b, &update_count| {
+            b.iter(|| instance.query_block(0, update_count))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, query_block);
+criterion_main!(benches);
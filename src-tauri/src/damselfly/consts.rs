@@ -1,10 +1,11 @@
 pub const DEFAULT_MEMORYSPAN: usize = 2048;
-pub const DEFAULT_MEMORY_SIZE: usize = 4294967295;
 pub const DEFAULT_SAMPLE_INTERVAL: u64 = 50000;
 pub const DEFAULT_ROW_LENGTH: usize = 64;
 pub const MIN_ROW_LENGTH: usize = 4;
 pub const DEFAULT_BLOCK_SIZE: usize = 32;
+pub const MAP_PNG_BLOCK_PIXELS: u32 = 8;
 pub const DEFAULT_BLOCKS_TO_TRUNCATE: usize = 256;
+pub const COMPACTED_GAP_BLOCKS: usize = 4;
 pub const MAX_BLOCK_SIZE: usize = 16777216;
 pub const MAX_MAP_SPAN: usize = 16777216;
 pub const DEFAULT_OPERATION_LOG_SIZE: usize = 32;
@@ -16,6 +17,9 @@ pub const TEST_BINARY_PATH: &str = "/work/dev/hp/dune/build/output/threadx-corte
 pub const TEST_GADDR2LINE_PATH: &str = "./gaddr2line";
 pub const GRAPH_VERTICAL_SCALE_OFFSET: f64 = 1.2;
 pub const DEFAULT_CACHE_INTERVAL: u64 = 1000;
+pub const VIEWER_CACHE_SCHEMA_VERSION: u32 = 14;
+pub const CACHE_HIT_MAX_REPLAYED_UPDATES: usize = 100;
+pub const MAX_CHURN_WINDOWS: u64 = 1_000_000;
 pub const DEFAULT_TICK_RATE: u64 = 100;
 pub const LARGE_FILE_TICK_RATE: u64 = 500;
 pub const TEST_LOG: &str = "00000811: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> + 0 14
@@ -0,0 +1,59 @@
+//! Errors returned by the Damselfly API. Serialisable so the frontend can tell them apart.
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DamselflyError {
+    /// The viewer has not been initialised with a log yet.
+    NotInitialised,
+    /// No DamselflyInstance exists at the requested index.
+    InstanceNotFound(u64),
+    /// Timestamp exceeds the last timestamp in the log.
+    TimestampOutOfRange { timestamp: u64, max_timestamp: u64 },
+    /// Reading or writing a file failed.
+    Io(String),
+    /// The log has no memory access records, which the requested analysis needs.
+    NoAccessEvents,
+    /// The viewer was configured with invalid settings.
+    InvalidConfiguration(String),
+    /// A viewer cache file was written by a different schema version and must be regenerated.
+    StaleCache { version: u32, expected_version: u32 },
+    /// A viewer cache file could not be encoded or decoded.
+    InvalidCache(String),
+    /// The log is not in the format its parser expects.
+    InvalidLog(String),
+}
+
+impl Display for DamselflyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DamselflyError::NotInitialised => write!(f, "Viewer is not initialised"),
+            DamselflyError::InstanceNotFound(damselfly_instance) =>
+                write!(f, "Damselfly instance not found: {damselfly_instance}"),
+            DamselflyError::TimestampOutOfRange { timestamp, max_timestamp } =>
+                write!(f, "Timestamp {timestamp} out of range (max timestamp: {max_timestamp})"),
+            DamselflyError::Io(message) => write!(f, "IO error: {message}"),
+            DamselflyError::NoAccessEvents =>
+                write!(f, "Log has no memory access records (@ address size). This requires a trace that logs accesses"),
+            DamselflyError::InvalidConfiguration(message) => write!(f, "Invalid configuration: {message}"),
+            DamselflyError::StaleCache { version, expected_version } =>
+                write!(f, "Viewer cache has schema version {version} but {expected_version} is required. Reparse the log to regenerate it"),
+            DamselflyError::InvalidCache(message) => write!(f, "Invalid viewer cache: {message}"),
+            DamselflyError::InvalidLog(message) => write!(f, "Invalid log: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for DamselflyError {}
+
+impl From<std::io::Error> for DamselflyError {
+    fn from(error: std::io::Error) -> Self {
+        DamselflyError::Io(error.to_string())
+    }
+}
+
+impl From<DamselflyError> for String {
+    fn from(error: DamselflyError) -> Self {
+        error.to_string()
+    }
+}
@@ -16,3 +16,25 @@ pub mod memory_usage_sample;
 pub mod memory_usage_stats;
 pub mod memory_pool;
 pub mod memory_pool_list;
+pub mod leak_finder;
+pub mod placement_simulator;
+pub mod memory_access;
+pub mod unused_allocation_finder;
+pub mod size_distribution;
+pub mod snapshot_parser;
+pub mod free_region_finder;
+pub mod anomaly;
+pub mod anomaly_detector;
+pub mod efficiency_calculator;
+pub mod update_sort_key;
+pub mod address_reuse_finder;
+pub mod placement_result;
+pub mod allocation_density;
+pub mod fragmentation_culprit_finder;
+pub mod massif_parser;
+pub mod allocation_site_ranker;
+pub mod lifetime_distribution;
+pub mod allocation_pairer;
+pub mod symbolicator;
+pub mod json_event_parser;
+pub mod summary_stats;
@@ -0,0 +1,82 @@
+//! Utility methods to find addresses that allocations keep landing on.
+use std::collections::{HashMap, HashSet};
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+
+pub struct AddressReuseFinder;
+
+impl AddressReuseFinder {
+    /// Counts how many allocations were made at each address, and returns the addresses with the
+    /// most. High counts suggest a pooled or recycled buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates to count allocations in.
+    /// * `n`: Number of addresses to return.
+    ///
+    /// returns: Vec<(address, allocation count)>, sorted by descending count, then ascending address.
+    pub fn get_most_reused_addresses(memory_updates: &[MemoryUpdateType], n: usize) -> Vec<(usize, usize)> {
+        let mut allocation_counts: HashMap<usize, usize> = HashMap::new();
+        for update in memory_updates {
+            if !matches!(update, MemoryUpdateType::Free(_)) {
+                *allocation_counts.entry(update.get_absolute_address()).or_default() += 1;
+            }
+        }
+        let mut allocation_counts: Vec<(usize, usize)> = allocation_counts.into_iter().collect();
+        allocation_counts.sort_by(|prev, next| next.1.cmp(&prev.1).then(prev.0.cmp(&next.0)));
+        allocation_counts.truncate(n);
+        allocation_counts
+    }
+
+    /// Counts the distinct callstacks that allocated at an address. A reused slot touched by many
+    /// call sites is suspicious.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates to search.
+    /// * `address`: Address that allocations start at.
+    ///
+    /// returns: Number of distinct allocation callstacks.
+    pub fn get_callstack_diversity(memory_updates: &[MemoryUpdateType], address: usize) -> usize {
+        memory_updates
+            .iter()
+            .filter(|update| !matches!(update, MemoryUpdateType::Free(_)) && update.get_absolute_address() == address)
+            .map(|update| update.get_callstack())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::address_reuse_finder::AddressReuseFinder;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate};
+
+    #[test]
+    fn get_most_reused_addresses_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates = vec![
+            Allocation::new(64, 8, callstack.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(0, 8, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Allocation::new(0, 8, callstack.clone(), 3, "0".to_string()).wrap_in_enum(),
+            Allocation::new(32, 8, callstack, 4, "0".to_string()).wrap_in_enum(),
+        ];
+        assert_eq!(AddressReuseFinder::get_most_reused_addresses(&updates, 2), vec![(0, 2), (32, 1)]);
+        assert_eq!(AddressReuseFinder::get_most_reused_addresses(&updates, 10).len(), 3);
+    }
+
+    #[test]
+    fn get_callstack_diversity_test() {
+        let updates = vec![
+            Allocation::new(0, 8, Arc::new("first".to_string()), 0, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 8, Arc::new("release".to_string()), 1, "0".to_string()).wrap_in_enum(),
+            Allocation::new(0, 8, Arc::new("second".to_string()), 2, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 8, Arc::new("release".to_string()), 3, "0".to_string()).wrap_in_enum(),
+            Allocation::new(0, 8, Arc::new("first".to_string()), 4, "0".to_string()).wrap_in_enum(),
+            Allocation::new(8, 8, Arc::new("third".to_string()), 5, "0".to_string()).wrap_in_enum(),
+        ];
+        assert_eq!(AddressReuseFinder::get_callstack_diversity(&updates, 0), 2);
+        assert_eq!(AddressReuseFinder::get_callstack_diversity(&updates, 16), 0);
+    }
+}
@@ -0,0 +1,56 @@
+//! Utility methods to find where in a pool allocations cluster.
+use crate::damselfly::memory::free_region_finder::FreeRegionFinder;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+
+pub struct AllocationDensity;
+
+impl AllocationDensity {
+    /// Divides the pool into equally sized regions and counts the live allocations starting in
+    /// each one at a timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    /// * `timestamp`: Timestamp to count live allocations at.
+    /// * `pool_start`: First address of the pool.
+    /// * `pool_end`: Address just past the end of the pool.
+    /// * `region_count`: Number of regions to divide the pool into.
+    ///
+    /// returns: Vec of allocation counts, one per region in address order. Allocations outside
+    /// the pool are counted in the nearest region.
+    pub fn get_allocation_density(memory_updates: &[MemoryUpdateType], timestamp: usize, pool_start: usize,
+                                  pool_end: usize, region_count: usize) -> Vec<usize> {
+        let mut density = vec![0; region_count];
+        if region_count == 0 {
+            return density;
+        }
+        let region_size = pool_end.saturating_sub(pool_start).div_ceil(region_count).max(1);
+        for address in FreeRegionFinder::get_live_allocations_at(memory_updates, timestamp).keys() {
+            let region = (address.saturating_sub(pool_start) / region_size).min(region_count - 1);
+            density[region] += 1;
+        }
+        density
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::allocation_density::AllocationDensity;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate};
+
+    #[test]
+    fn get_allocation_density_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates = vec![
+            Allocation::new(0, 8, callstack.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(8, 8, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Allocation::new(40, 8, callstack.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Allocation::new(120, 8, callstack.clone(), 3, "0".to_string()).wrap_in_enum(),
+            Free::new(8, 8, callstack, 4, "0".to_string()).wrap_in_enum(),
+        ];
+        assert_eq!(AllocationDensity::get_allocation_density(&updates, 3, 0, 128, 4), vec![2, 1, 0, 1]);
+        assert_eq!(AllocationDensity::get_allocation_density(&updates, 4, 0, 128, 4), vec![1, 1, 0, 1]);
+        assert!(AllocationDensity::get_allocation_density(&updates, 4, 0, 128, 0).is_empty());
+    }
+}
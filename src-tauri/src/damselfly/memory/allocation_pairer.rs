@@ -0,0 +1,69 @@
+//! Pairs allocations with the frees that release them.
+use std::collections::HashMap;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+
+pub struct AllocationPairer;
+
+impl AllocationPairer {
+    /// Pairs each allocation with the free of its address. A free releases the latest live
+    /// allocation at its address, and frees of addresses that aren't allocated are ignored. A
+    /// reallocation is both the free of its old address and the allocation of its new one.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    ///
+    /// returns: Vec<(allocation index, free index)> of indices into memory_updates, in the order
+    /// the allocations were made. The free index is None if the allocation is never freed.
+    pub fn pair_allocs_with_frees(memory_updates: &[MemoryUpdateType]) -> Vec<(usize, Option<usize>)> {
+        let mut pairs: Vec<(usize, Option<usize>)> = Vec::new();
+        // Address -> indices into pairs that are still live, latest last
+        let mut live_allocations: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (index, update) in memory_updates.iter().enumerate() {
+            match update {
+                MemoryUpdateType::Allocation(_) => {
+                    live_allocations.entry(update.get_absolute_address()).or_default().push(pairs.len());
+                    pairs.push((index, None));
+                }
+                MemoryUpdateType::Free(_) => {
+                    if let Some(pair_index) = live_allocations
+                        .get_mut(&update.get_absolute_address())
+                        .and_then(|indices| indices.pop()) {
+                        pairs[pair_index].1 = Some(index);
+                    }
+                }
+                // frees the old region, then allocates the new one
+                MemoryUpdateType::Reallocation(reallocation) => {
+                    if let Some(pair_index) = live_allocations
+                        .get_mut(&reallocation.get_old_address())
+                        .and_then(|indices| indices.pop()) {
+                        pairs[pair_index].1 = Some(index);
+                    }
+                    live_allocations.entry(update.get_absolute_address()).or_default().push(pairs.len());
+                    pairs.push((index, None));
+                }
+            }
+        }
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::allocation_pairer::AllocationPairer;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate};
+
+    #[test]
+    fn pair_allocs_with_frees_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates = vec![
+            Allocation::new(0, 8, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(64, 8, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack.clone(), 3, "0001.003 s".to_string()).wrap_in_enum(),
+            Allocation::new(0, 8, callstack, 4, "0001.004 s".to_string()).wrap_in_enum(),
+        ];
+        assert_eq!(AllocationPairer::pair_allocs_with_frees(&updates), vec![(0, Some(2)), (1, None), (4, None)]);
+    }
+}
@@ -0,0 +1,66 @@
+//! Utility methods to rank allocation sites by the memory they hold.
+use std::collections::HashMap;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+use crate::damselfly::update_interval::update_queue_compressor::UpdateQueueCompressor;
+
+pub struct AllocationSiteRanker;
+
+impl AllocationSiteRanker {
+    /// Groups the allocations live at a timestamp by callstack, ranking the callstacks by the
+    /// bytes they hold.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    /// * `timestamp`: Timestamp to look at. Allocations freed at or before it are not live.
+    ///
+    /// returns: Vec<(callstack, live bytes, live allocations)>, sorted by descending bytes, then
+    /// descending allocations, then callstack.
+    pub fn get_allocation_sites_ranked(memory_updates: &[MemoryUpdateType], timestamp: usize) -> Vec<(String, usize, usize)> {
+        // callstack -> (live bytes, live allocations)
+        let mut allocation_sites: HashMap<String, (usize, usize)> = HashMap::new();
+        for allocation in UpdateQueueCompressor::live_set_at(memory_updates, timestamp as u64) {
+            let allocation_site = allocation_sites.entry(allocation.get_callstack().to_string()).or_default();
+            allocation_site.0 += allocation.get_absolute_size();
+            allocation_site.1 += 1;
+        }
+
+        let mut allocation_sites: Vec<(String, usize, usize)> = allocation_sites
+            .into_iter()
+            .map(|(callstack, (bytes, count))| (callstack, bytes, count))
+            .collect();
+        allocation_sites.sort_by(|prev, next| next.1.cmp(&prev.1).then(next.2.cmp(&prev.2)).then(prev.0.cmp(&next.0)));
+        allocation_sites
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::allocation_site_ranker::AllocationSiteRanker;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate};
+
+    #[test]
+    fn get_allocation_sites_ranked_test() {
+        let small = Arc::new("small".to_string());
+        let large = Arc::new("large".to_string());
+        let tied = Arc::new("tied".to_string());
+        let updates = vec![
+            Allocation::new(0, 8, small.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(8, 8, small.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Allocation::new(16, 64, large.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Allocation::new(80, 16, tied, 3, "0".to_string()).wrap_in_enum(),
+            Free::new(16, 64, large, 4, "0".to_string()).wrap_in_enum(),
+        ];
+        assert_eq!(AllocationSiteRanker::get_allocation_sites_ranked(&updates, 3), vec![
+            ("large".to_string(), 64, 1),
+            ("small".to_string(), 16, 2),
+            ("tied".to_string(), 16, 1),
+        ]);
+        // The free at 4 removes the large allocation
+        assert_eq!(AllocationSiteRanker::get_allocation_sites_ranked(&updates, 4), vec![
+            ("small".to_string(), 16, 2),
+            ("tied".to_string(), 16, 1),
+        ]);
+    }
+}
@@ -0,0 +1,81 @@
+//! Something suspicious found in a log, such as a double free.
+use serde::{Deserialize, Serialize};
+
+/// How serious an anomaly is. Ordered, so anomalies can be filtered by a minimum severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AnomalySeverity {
+    /// Usually a side effect of another anomaly.
+    Info,
+    /// Suspicious, but has benign explanations, such as a capture that started mid-stream.
+    Warning,
+    /// Almost certainly a bug in the program that produced the log.
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AnomalyKind {
+    /// A free of an address whose allocation has already been freed.
+    DoubleFree,
+    /// An access to memory that was freed and has not been reallocated.
+    UseAfterFree,
+    /// A free whose size differs from the size of the allocation it frees.
+    SizeMismatch,
+    /// A free of an address that was never allocated in the log.
+    OrphanFree,
+    /// More bytes have been freed than allocated so far.
+    NegativeUsage,
+}
+
+impl AnomalyKind {
+    pub fn get_severity(&self) -> AnomalySeverity {
+        match self {
+            AnomalyKind::DoubleFree | AnomalyKind::UseAfterFree => AnomalySeverity::Error,
+            AnomalyKind::SizeMismatch | AnomalyKind::OrphanFree => AnomalySeverity::Warning,
+            AnomalyKind::NegativeUsage => AnomalySeverity::Info,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Anomaly {
+    kind: AnomalyKind,
+    severity: AnomalySeverity,
+    timestamp: usize,
+    address: usize,
+}
+
+impl Anomaly {
+    /// Constructor. Severity is derived from the kind.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind`: Kind of anomaly.
+    /// * `timestamp`: Timestamp of the update or access that caused the anomaly.
+    /// * `address`: Address involved.
+    ///
+    /// returns: Anomaly
+    pub fn new(kind: AnomalyKind, timestamp: usize, address: usize) -> Self {
+        Self {
+            kind,
+            severity: kind.get_severity(),
+            timestamp,
+            address,
+        }
+    }
+
+    pub fn get_kind(&self) -> AnomalyKind {
+        self.kind
+    }
+
+    pub fn get_severity(&self) -> AnomalySeverity {
+        self.severity
+    }
+
+    pub fn get_timestamp(&self) -> usize {
+        self.timestamp
+    }
+
+    pub fn get_address(&self) -> usize {
+        self.address
+    }
+}
@@ -0,0 +1,136 @@
+//! Utility methods to find anomalies in a log, such as double frees and use-after-frees.
+use std::collections::{BTreeMap, HashMap};
+use crate::damselfly::memory::anomaly::{Anomaly, AnomalyKind, AnomalySeverity};
+use crate::damselfly::memory::memory_access::MemoryAccess;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+use crate::damselfly::update_interval::update_queue_compressor::UpdateQueueCompressor;
+
+pub struct AnomalyDetector;
+
+impl AnomalyDetector {
+    /// Replays updates and accesses to find anomalies.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    /// * `memory_accesses`: Accesses, sorted by timestamp. Use-after-frees can only be found if
+    ///   the log has accesses.
+    /// * `min_severity`: Anomalies below this severity are left out.
+    ///
+    /// returns: Vec<Anomaly>, sorted by timestamp.
+    pub fn find_anomalies(memory_updates: &[MemoryUpdateType], memory_accesses: Option<&[MemoryAccess]>, min_severity: AnomalySeverity) -> Vec<Anomaly> {
+        // address -> size
+        let mut live_allocations: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut freed_allocations: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut allocated_addresses: HashMap<usize, usize> = HashMap::new();
+        let mut usage: i128 = 0;
+        let mut anomalies = Vec::new();
+        let mut access_iter = memory_accesses.unwrap_or_default().iter().peekable();
+
+        let live_set_changes = UpdateQueueCompressor::live_set_changes(memory_updates);
+        for (update, (mut freed, _)) in memory_updates.iter().zip(live_set_changes) {
+            // a reallocation frees its old region, then allocates its new one
+            for update in update.split_reallocation() {
+                let (address, size, timestamp) = (update.get_absolute_address(), update.get_absolute_size(), update.get_timestamp());
+                match update {
+                    MemoryUpdateType::Allocation(_) => {
+                        live_allocations.insert(address, size);
+                        freed_allocations.remove(&address);
+                        allocated_addresses.insert(address, size);
+                        usage += size as i128;
+                    }
+                    MemoryUpdateType::Free(_) => {
+                        match freed.take() {
+                            Some(allocation) => {
+                                let allocated_size = allocation.get_absolute_size();
+                                live_allocations.remove(&address);
+                                if allocated_size != size {
+                                    anomalies.push(Anomaly::new(AnomalyKind::SizeMismatch, timestamp, address));
+                                }
+                                freed_allocations.insert(address, allocated_size);
+                            }
+                            None if allocated_addresses.contains_key(&address) =>
+                                anomalies.push(Anomaly::new(AnomalyKind::DoubleFree, timestamp, address)),
+                            None => anomalies.push(Anomaly::new(AnomalyKind::OrphanFree, timestamp, address)),
+                        }
+                        let previous_usage = usage;
+                        usage -= size as i128;
+                        if usage < 0 && previous_usage >= 0 {
+                            anomalies.push(Anomaly::new(AnomalyKind::NegativeUsage, timestamp, address));
+                        }
+                    }
+                    MemoryUpdateType::Reallocation(_) => unreachable!("[AnomalyDetector::find_anomalies]: Reallocations are split into a free and an alloc"),
+                }
+            }
+
+            let timestamp = update.get_timestamp();
+            // Accesses are timestamped with the latest update before them
+            while let Some(access) = access_iter.next_if(|access| access.get_timestamp() <= timestamp) {
+                let address = access.get_address();
+                if Self::find_containing(&live_allocations, address).is_none()
+                    && Self::find_containing(&freed_allocations, address).is_some() {
+                    anomalies.push(Anomaly::new(AnomalyKind::UseAfterFree, access.get_timestamp(), address));
+                }
+            }
+        }
+
+        anomalies.retain(|anomaly| anomaly.get_severity() >= min_severity);
+        anomalies
+    }
+
+    /// Finds the region in a map of address -> size that contains an address.
+    fn find_containing(regions: &BTreeMap<usize, usize>, address: usize) -> Option<(usize, usize)> {
+        regions
+            .range(..=address)
+            .next_back()
+            .filter(|(&start, &size)| address < start + size)
+            .map(|(&start, &size)| (start, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::anomaly::{AnomalyKind, AnomalySeverity};
+    use crate::damselfly::memory::anomaly_detector::AnomalyDetector;
+    use crate::damselfly::memory::memory_access::MemoryAccess;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+
+    fn build_updates() -> Vec<MemoryUpdateType> {
+        let callstack = Arc::new("test".to_string());
+        vec![
+            Free::new(500, 8, callstack.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(0, 16, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 16, callstack.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 16, callstack.clone(), 3, "0".to_string()).wrap_in_enum(),
+            Allocation::new(32, 8, callstack.clone(), 4, "0".to_string()).wrap_in_enum(),
+            Free::new(32, 4, callstack, 5, "0".to_string()).wrap_in_enum(),
+        ]
+    }
+
+    #[test]
+    fn find_anomalies_test() {
+        let accesses = vec![MemoryAccess::new(4, 1, 1), MemoryAccess::new(8, 4, 2)];
+        let anomalies: Vec<(AnomalyKind, usize)> = AnomalyDetector::find_anomalies(&build_updates(), Some(&accesses), AnomalySeverity::Info)
+            .iter()
+            .map(|anomaly| (anomaly.get_kind(), anomaly.get_timestamp()))
+            .collect();
+        assert_eq!(anomalies, vec![
+            (AnomalyKind::OrphanFree, 0),
+            (AnomalyKind::NegativeUsage, 0),
+            (AnomalyKind::NegativeUsage, 2),
+            (AnomalyKind::UseAfterFree, 2),
+            (AnomalyKind::DoubleFree, 3),
+            (AnomalyKind::SizeMismatch, 5),
+        ]);
+    }
+
+    #[test]
+    fn find_anomalies_min_severity_test() {
+        let anomalies: Vec<AnomalyKind> = AnomalyDetector::find_anomalies(&build_updates(), None, AnomalySeverity::Error)
+            .iter()
+            .map(|anomaly| anomaly.get_kind())
+            .collect();
+        assert_eq!(anomalies, vec![AnomalyKind::DoubleFree]);
+    }
+}
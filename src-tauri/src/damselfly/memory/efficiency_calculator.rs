@@ -0,0 +1,89 @@
+//! Utility methods to measure how efficiently a pool's reserved space is used.
+use std::collections::BTreeMap;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+use crate::damselfly::update_interval::update_queue_compressor::UpdateQueueCompressor;
+
+pub struct EfficiencyCalculator;
+
+impl EfficiencyCalculator {
+    /// Computes the ratio of requested live bytes to reserved bytes after each update.
+    ///
+    /// Reserved bytes are the extent from the start of the pool to the end of the highest live
+    /// allocation, rounded up to a whole block. Space lost to rounding, padding and fragmentation
+    /// within that extent lowers the ratio, so it depends on the block size.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    /// * `pool_start`: First address of the pool.
+    /// * `block_size`: Block size of the map, in bytes.
+    ///
+    /// returns: Vec<[index, efficiency]>, where efficiency is between 0 and 1, and 0 when nothing
+    /// is live.
+    pub fn get_efficiency_plot_points(memory_updates: &[MemoryUpdateType], pool_start: usize, block_size: usize) -> Vec<[f64; 2]> {
+        let block_size = block_size.max(1);
+        // end -> number of live allocations ending there
+        let mut live_ends: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut live_bytes: u128 = 0;
+        let mut vector = Vec::new();
+
+        for (index, (freed, allocated)) in UpdateQueueCompressor::live_set_changes(memory_updates).into_iter().enumerate() {
+            // a reallocation frees its old region, then allocates its new one
+            if let Some(freed) = freed {
+                live_bytes -= freed.get_absolute_size() as u128;
+                Self::remove_end(&mut live_ends, freed.get_absolute_address() + freed.get_absolute_size());
+            }
+            if let Some(allocated) = allocated {
+                live_bytes += allocated.get_absolute_size() as u128;
+                *live_ends.entry(allocated.get_absolute_address() + allocated.get_absolute_size()).or_default() += 1;
+            }
+
+            let extent = live_ends
+                .keys()
+                .next_back()
+                .map(|highest_end| highest_end.saturating_sub(pool_start).div_ceil(block_size) * block_size)
+                .unwrap_or(0);
+            let efficiency = if extent == 0 { 0.0 } else { live_bytes as f64 / extent as f64 };
+            vector.push([index as f64, efficiency]);
+        }
+        vector
+    }
+
+    fn remove_end(live_ends: &mut BTreeMap<usize, usize>, end: usize) {
+        if let Some(count) = live_ends.get_mut(&end) {
+            *count -= 1;
+            if *count == 0 {
+                live_ends.remove(&end);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::efficiency_calculator::EfficiencyCalculator;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+
+    fn build_updates() -> Vec<MemoryUpdateType> {
+        let callstack = Arc::new("test".to_string());
+        vec![
+            Allocation::new(0, 16, callstack.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(32, 8, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Free::new(32, 8, callstack.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 16, callstack, 3, "0".to_string()).wrap_in_enum(),
+        ]
+    }
+
+    #[test]
+    fn efficiency_plot_points_test() {
+        let plot_points = EfficiencyCalculator::get_efficiency_plot_points(&build_updates(), 0, 1);
+        assert_eq!(plot_points, vec![[0.0, 1.0], [1.0, 0.6], [2.0, 1.0], [3.0, 0.0]]);
+    }
+
+    #[test]
+    fn efficiency_plot_points_block_rounding_test() {
+        let plot_points = EfficiencyCalculator::get_efficiency_plot_points(&build_updates(), 0, 32);
+        assert_eq!(plot_points, vec![[0.0, 0.5], [1.0, 0.375], [2.0, 0.5], [3.0, 0.0]]);
+    }
+}
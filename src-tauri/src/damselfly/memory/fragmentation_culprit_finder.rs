@@ -0,0 +1,111 @@
+//! Utility methods to attribute external fragmentation to the call sites that cause it.
+use std::collections::{BTreeMap, HashMap};
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+use crate::damselfly::update_interval::update_queue_compressor::UpdateQueueCompressor;
+
+pub struct FragmentationCulpritFinder;
+
+impl FragmentationCulpritFinder {
+    /// Finds the call sites whose live allocations split free space at a timestamp.
+    ///
+    /// Every free gap between two live allocations is attributed to both allocations bordering
+    /// it, and the attributions are aggregated by callstack. A gap bordered on both sides by the
+    /// same callstack is only counted once for it. Free space at either end of the pool is not
+    /// fragmentation, so it is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    /// * `timestamp`: Timestamp to look at.
+    /// * `n`: Number of call sites to return.
+    ///
+    /// returns: Vec<(callstack, gaps bordered, free bytes bordered)>, sorted by descending gaps
+    /// bordered, then descending free bytes.
+    pub fn get_fragmentation_culprits(memory_updates: &[MemoryUpdateType], timestamp: usize, n: usize) -> Vec<(String, usize, usize)> {
+        let live_set = UpdateQueueCompressor::live_set_at(memory_updates, timestamp as u64);
+        // address -> allocation
+        let live_allocations: BTreeMap<usize, &MemoryUpdateType> = live_set
+            .iter()
+            .map(|allocation| (allocation.get_absolute_address(), allocation))
+            .collect();
+
+        // callstack -> (gaps bordered, free bytes bordered)
+        let mut culprits: HashMap<String, (usize, usize)> = HashMap::new();
+        let mut previous: Option<&MemoryUpdateType> = None;
+        for allocation in live_allocations.values() {
+            if let Some(previous_allocation) = previous {
+                if allocation.get_start() > previous_allocation.get_end() {
+                    let gap = allocation.get_start() - previous_allocation.get_end();
+                    let (left_callstack, right_callstack) = (previous_allocation.get_callstack(), allocation.get_callstack());
+                    let mut bordering_callstacks = vec![left_callstack.clone()];
+                    if right_callstack != left_callstack {
+                        bordering_callstacks.push(right_callstack);
+                    }
+                    for callstack in bordering_callstacks {
+                        let culprit = culprits.entry(callstack.to_string()).or_default();
+                        culprit.0 += 1;
+                        culprit.1 += gap;
+                    }
+                }
+            }
+            // Keep whichever allocation reaches furthest, in case allocations overlap
+            let is_previous_further = previous.is_some_and(|previous_allocation| previous_allocation.get_end() >= allocation.get_end());
+            if !is_previous_further {
+                previous = Some(allocation);
+            }
+        }
+
+        let mut culprits: Vec<(String, usize, usize)> = culprits
+            .into_iter()
+            .map(|(callstack, (gaps, bytes))| (callstack, gaps, bytes))
+            .collect();
+        culprits.sort_by(|prev, next| next.1.cmp(&prev.1).then(next.2.cmp(&prev.2)).then(prev.0.cmp(&next.0)));
+        culprits.truncate(n);
+        culprits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::fragmentation_culprit_finder::FragmentationCulpritFinder;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate};
+
+    #[test]
+    fn get_fragmentation_culprits_test() {
+        let splitter = Arc::new("splitter".to_string());
+        let buffer = Arc::new("buffer".to_string());
+        let updates = vec![
+            Allocation::new(0, 32, buffer.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(32, 4, splitter.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Allocation::new(36, 32, buffer.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Allocation::new(68, 4, splitter, 3, "0".to_string()).wrap_in_enum(),
+            Allocation::new(72, 32, buffer.clone(), 4, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 32, buffer.clone(), 5, "0".to_string()).wrap_in_enum(),
+            Free::new(36, 32, buffer.clone(), 6, "0".to_string()).wrap_in_enum(),
+        ];
+        // Only the gap at 36 is between two live allocations, both from splitter, so it is
+        // counted once
+        assert_eq!(FragmentationCulpritFinder::get_fragmentation_culprits(&updates, 6, 5), vec![
+            ("splitter".to_string(), 1, 32),
+        ]);
+        // Nothing is free before the frees
+        assert!(FragmentationCulpritFinder::get_fragmentation_culprits(&updates, 4, 5).is_empty());
+    }
+
+    #[test]
+    fn get_fragmentation_culprits_different_callstacks_test() {
+        let splitter = Arc::new("splitter".to_string());
+        let buffer = Arc::new("buffer".to_string());
+        let updates = vec![
+            Allocation::new(0, 4, splitter.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(16, 4, buffer, 1, "0".to_string()).wrap_in_enum(),
+            Allocation::new(32, 4, splitter, 2, "0".to_string()).wrap_in_enum(),
+        ];
+        // Both gaps are bordered by splitter on one side and buffer on the other
+        assert_eq!(FragmentationCulpritFinder::get_fragmentation_culprits(&updates, 2, 5), vec![
+            ("buffer".to_string(), 2, 24),
+            ("splitter".to_string(), 2, 24),
+        ]);
+    }
+}
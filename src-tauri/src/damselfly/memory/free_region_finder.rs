@@ -0,0 +1,107 @@
+//! Utility methods to find the free regions of a pool at a point in time.
+use std::collections::BTreeMap;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+use crate::damselfly::update_interval::update_queue_compressor::UpdateQueueCompressor;
+
+pub struct FreeRegionFinder;
+
+impl FreeRegionFinder {
+    /// Replays updates up to and including a timestamp, and finds the gaps between the
+    /// allocations that are still live.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    /// * `timestamp`: Timestamp to find free regions at.
+    /// * `pool_start`: First address of the pool.
+    /// * `pool_end`: Address just past the end of the pool.
+    ///
+    /// returns: Vec<(start, size)> of free regions, sorted by address.
+    pub fn get_free_regions_at(memory_updates: &[MemoryUpdateType], timestamp: usize, pool_start: usize, pool_end: usize) -> Vec<(usize, usize)> {
+        let live_allocations = Self::get_live_allocations_at(memory_updates, timestamp);
+        let mut free_regions = Vec::new();
+        let mut cursor = pool_start;
+        for (&address, &size) in &live_allocations {
+            if address > cursor {
+                free_regions.push((cursor, address - cursor));
+            }
+            cursor = cursor.max(address + size);
+        }
+        if pool_end > cursor {
+            free_regions.push((cursor, pool_end - cursor));
+        }
+        free_regions
+    }
+
+    /// Finds the allocations that are live at a timestamp, using UpdateQueueCompressor::live_set_at.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    /// * `timestamp`: Timestamp to find live allocations at.
+    ///
+    /// returns: BTreeMap of address -> size.
+    pub fn get_live_allocations_at(memory_updates: &[MemoryUpdateType], timestamp: usize) -> BTreeMap<usize, usize> {
+        UpdateQueueCompressor::live_set_at(memory_updates, timestamp as u64)
+            .iter()
+            .map(|allocation| (allocation.get_absolute_address(), allocation.get_absolute_size()))
+            .collect()
+    }
+
+    /// Finds the largest allocation with a given alignment that fits in any of the free regions.
+    /// A region that does not start on an alignment boundary loses the padding before the first
+    /// aligned address in it.
+    ///
+    /// # Arguments
+    ///
+    /// * `free_regions`: Free regions as (start, size).
+    /// * `alignment`: Alignment in bytes. 0 and 1 both mean unaligned.
+    ///
+    /// returns: Size in bytes of the largest aligned allocation that fits, or 0 if none does.
+    pub fn largest_aligned_free(free_regions: &[(usize, usize)], alignment: usize) -> usize {
+        let alignment = alignment.max(1);
+        free_regions
+            .iter()
+            .map(|&(start, size)| {
+                let padding = (alignment - start % alignment) % alignment;
+                size.saturating_sub(padding)
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::free_region_finder::FreeRegionFinder;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+
+    fn build_updates() -> Vec<MemoryUpdateType> {
+        let callstack = Arc::new("test".to_string());
+        vec![
+            Allocation::new(0, 4, callstack.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(20, 12, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Allocation::new(40, 8, callstack.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Free::new(20, 12, callstack, 3, "0".to_string()).wrap_in_enum(),
+        ]
+    }
+
+    #[test]
+    fn get_free_regions_at_test() {
+        let updates = build_updates();
+        assert_eq!(FreeRegionFinder::get_free_regions_at(&updates, 2, 0, 64), vec![(4, 16), (32, 8), (48, 16)]);
+        assert_eq!(FreeRegionFinder::get_free_regions_at(&updates, 3, 0, 64), vec![(4, 36), (48, 16)]);
+    }
+
+    #[test]
+    fn largest_aligned_free_test() {
+        let free_regions = vec![(4, 36), (48, 16)];
+        assert_eq!(FreeRegionFinder::largest_aligned_free(&free_regions, 1), 36);
+        // 4 -> 16 loses 12 bytes of padding, leaving 24
+        assert_eq!(FreeRegionFinder::largest_aligned_free(&free_regions, 16), 24);
+        // 4 -> 32 loses 28 bytes, leaving 8, and 48 -> 64 leaves nothing
+        assert_eq!(FreeRegionFinder::largest_aligned_free(&free_regions, 32), 8);
+        assert_eq!(FreeRegionFinder::largest_aligned_free(&[], 8), 0);
+    }
+}
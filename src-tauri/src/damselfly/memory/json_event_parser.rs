@@ -0,0 +1,292 @@
+//! Parser for logs in Damselfly's JSON event format, for feeding Damselfly from other tools.
+//!
+//! A log is a JSON array of events, one per allocation or free, in the order they happened:
+//! [{ "op": "alloc", "addr": 4096, "size": 16, "timestamp": 1000, "pool": "heap", "callstack": "main.c:5" }, ...]
+//! op is "alloc" or "free", and timestamp is in microseconds. Callstacks are used as given, so
+//! they should already be symbolised. Each distinct pool name becomes a pool spanning the updates
+//! made in it.
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::sync::Arc;
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+
+use crate::damselfly::damselfly_error::DamselflyError;
+use crate::damselfly::memory::memory_parsers::{MemoryParser, MemorySysTraceParser, ParseResults, ParserKind, PoolRestrictedParseResults};
+use crate::damselfly::memory::memory_pool::MemoryPool;
+use crate::damselfly::memory::memory_pool_list::MemoryPoolList;
+use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+
+/// Kind of memory operation in a JSON event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonEventOp {
+    Alloc,
+    Free,
+}
+
+/// A single event in a JSON event log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonEvent {
+    pub op: JsonEventOp,
+    pub addr: u64,
+    pub size: u64,
+    /// Microseconds.
+    pub timestamp: u64,
+    pub pool: String,
+    pub callstack: String,
+}
+
+/// Parser for JSON event logs.
+#[derive(Default)]
+pub struct JsonEventParser;
+
+impl MemoryParser for JsonEventParser {
+    fn get_kind(&self) -> ParserKind {
+        ParserKind::JsonEvent
+    }
+
+    /// Parses a JSON event log into a Vec of MemoryUpdateTypes, each containing an Allocation or a
+    /// Free.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: Raw log file.
+    /// * `_binary_path`: Unused, as callstacks are already symbolised.
+    ///
+    /// returns: ParseResults, with a pool for each pool name. Empty if the log is not a valid JSON
+    /// event log.
+    fn parse_log_directly(self, log: &str, _binary_path: &str) -> ParseResults {
+        let events = match Self::parse_events(log) {
+            Ok(events) => events,
+            Err(error) => {
+                eprintln!("{}", format!("[JsonEventParser::parse_log_directly]: {error}").yellow());
+                return ParseResults::new(Vec::new(), MemoryPoolList::default(), 0);
+            }
+        };
+        let pools: Vec<MemoryPool> = Self::group_by_pool(&events)
+            .into_iter()
+            .map(|(pool, _)| pool)
+            .collect();
+        let memory_updates: Vec<MemoryUpdateType> = events
+            .iter()
+            .enumerate()
+            .map(|(timestamp, event)| Self::to_update(event, timestamp))
+            .collect();
+        let max_timestamp = memory_updates.len() as u64;
+        ParseResults::new(memory_updates, MemoryPoolList::new(pools.into_iter().collect()), max_timestamp)
+    }
+
+    /// Parses a JSON event log using its file path.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_path`: File path to log.
+    /// * `binary_path`: Unused, as callstacks are already symbolised.
+    ///
+    /// returns: ParseResults. Empty if the log cannot be read or is not a valid JSON event log.
+    fn parse_log(self, log_path: &str, binary_path: &str) -> ParseResults {
+        eprintln!("[JsonEventParser::parse_log]: log: {log_path}");
+        match std::fs::read_to_string(log_path) {
+            Ok(log) => self.parse_log_directly(log.as_str(), binary_path),
+            Err(error) => {
+                eprintln!("{}", format!("[JsonEventParser::parse_log]: Failed to read {log_path}: {error}").yellow());
+                ParseResults::new(Vec::new(), MemoryPoolList::default(), 0)
+            }
+        }
+    }
+
+    /// Parses a JSON event log, splitting its updates into pools by their pool names rather than
+    /// by address, so pools whose address ranges overlap are kept apart. Each pool is grown by the
+    /// padding so it still spans its padded updates.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: File path to log.
+    /// * `_binary_paths`: Unused, as callstacks are already symbolised.
+    /// * `left_padding`: Padding to add to the left of each operation (by shifting its address left)
+    /// * `right_padding`: Padding to add to the right of each operation (by increasing its size)
+    /// * `pool_filter`: Names of the pools to return, or None to return every pool.
+    ///
+    /// returns: Result<Vec<PoolRestrictedParseResults, Global>, DamselflyError>, in ascending order
+    /// of pool start. Err if the log cannot be read or is not a valid JSON event log.
+    fn parse_log_contents_split_by_pools(self, log: &str, _binary_paths: &[(&str, &str)], left_padding: usize, right_padding: usize, pool_filter: Option<&[String]>) -> Result<Vec<PoolRestrictedParseResults>, DamselflyError> {
+        eprintln!("[JsonEventParser::parse_log_contents_split_by_pools]: log: {log}");
+        let log = std::fs::read_to_string(log)?;
+        let events = Self::parse_events(&log)?;
+        let max_timestamp = events.len() as u64;
+        let mut pool_restricted_parse_results: Vec<PoolRestrictedParseResults> = Self::group_by_pool(&events)
+            .into_iter()
+            .map(|(mut pool, memory_updates)| {
+                // Padded updates reach left_padding below the lowest address and right_padding past
+                // the highest end
+                pool.set_start(pool.get_start().saturating_sub(left_padding));
+                pool.set_size(pool.get_size() + left_padding + right_padding);
+                PoolRestrictedParseResults::new(memory_updates, max_timestamp, pool)
+            })
+            .collect();
+        pool_restricted_parse_results.sort_by(|first, second| first.pool.cmp(&second.pool));
+        Ok(PoolRestrictedParseResults::filter_pools(pool_restricted_parse_results, pool_filter))
+    }
+}
+
+impl JsonEventParser {
+    pub fn new() -> JsonEventParser {
+        JsonEventParser
+    }
+
+    /// Checks if a log file is a JSON event log, reading only up to its first non-whitespace
+    /// character.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_path`: File path to log.
+    ///
+    /// returns: true if the log starts with a JSON array, false if it does not or cannot be read.
+    pub fn is_json_event_log_file(log_path: &str) -> bool {
+        let Ok(log_file) = File::open(log_path) else {
+            return false;
+        };
+        BufReader::new(log_file)
+            .bytes()
+            .map_while(Result::ok)
+            .find(|byte| !byte.is_ascii_whitespace())
+            .is_some_and(|byte| byte == b'[')
+    }
+
+    /// Parses the events in a raw JSON event log.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: Raw log file.
+    ///
+    /// returns: Events in the order they appear in the log, or DamselflyError::InvalidLog if the log
+    /// is not a JSON array of events.
+    pub fn parse_events(log: &str) -> Result<Vec<JsonEvent>, DamselflyError> {
+        serde_json::from_str(log)
+            .map_err(|error| DamselflyError::InvalidLog(format!("Invalid JSON event log: {error}")))
+    }
+
+    /// Groups events into pools by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `events`: Events in the order they appear in the log.
+    ///
+    /// returns: For each pool name, a pool spanning its updates and the updates themselves,
+    /// timestamped by their position in the whole log.
+    fn group_by_pool(events: &[JsonEvent]) -> Vec<(MemoryPool, Vec<MemoryUpdateType>)> {
+        let mut updates_by_pool: BTreeMap<&str, Vec<MemoryUpdateType>> = BTreeMap::new();
+        for (timestamp, event) in events.iter().enumerate() {
+            updates_by_pool.entry(event.pool.as_str()).or_default().push(Self::to_update(event, timestamp));
+        }
+        updates_by_pool
+            .into_iter()
+            .map(|(pool_name, memory_updates)| {
+                let span = MemorySysTraceParser::get_updates_span(&memory_updates);
+                let pool = MemoryPool::new(span.0 as usize, (span.1 - span.0) as usize, pool_name.to_string());
+                (pool, memory_updates)
+            })
+            .collect()
+    }
+
+    fn to_update(event: &JsonEvent, timestamp: usize) -> MemoryUpdateType {
+        let callstack = Arc::new(event.callstack.clone());
+        let real_timestamp = format!("{} us", event.timestamp);
+        match event.op {
+            JsonEventOp::Alloc =>
+                Allocation::new(event.addr as usize, event.size as usize, callstack, timestamp, real_timestamp).wrap_in_enum(),
+            JsonEventOp::Free =>
+                Free::new(event.addr as usize, event.size as usize, callstack, timestamp, real_timestamp).wrap_in_enum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::damselfly::damselfly_error::DamselflyError;
+    use crate::damselfly::memory::json_event_parser::{JsonEvent, JsonEventOp, JsonEventParser};
+    use crate::damselfly::memory::memory_parsers::MemoryParser;
+    use crate::damselfly::memory::memory_update::MemoryUpdateType;
+    use crate::damselfly::memory::utility::Utility;
+
+    fn test_events() -> Vec<JsonEvent> {
+        let event = |op, addr, size, timestamp, pool: &str| JsonEvent {
+            op,
+            addr,
+            size,
+            timestamp,
+            pool: pool.to_string(),
+            callstack: "main.c:5".to_string(),
+        };
+        vec![
+            event(JsonEventOp::Alloc, 4096, 16, 1000, "heap"),
+            event(JsonEventOp::Alloc, 256, 64, 1500, "scratch"),
+            event(JsonEventOp::Alloc, 4160, 32, 2000, "heap"),
+            event(JsonEventOp::Free, 4096, 16, 2500, "heap"),
+        ]
+    }
+
+    #[test]
+    fn json_event_round_trip_test() {
+        let events = test_events();
+        let log = serde_json::to_string(&events).unwrap();
+        assert!(log.starts_with(r#"[{"op":"alloc","addr":4096,"size":16,"timestamp":1000,"pool":"heap""#));
+        assert_eq!(JsonEventParser::parse_events(&log), Ok(events));
+
+        let parse_results = JsonEventParser::new().parse_log_directly(&log, "");
+        let updates: Vec<(bool, usize, usize, usize, u64)> = parse_results.memory_updates
+            .iter()
+            .map(|update| (
+                matches!(update, MemoryUpdateType::Allocation(_)),
+                update.get_absolute_address(),
+                update.get_absolute_size(),
+                update.get_timestamp(),
+                Utility::convert_to_microseconds(update.get_real_timestamp()),
+            ))
+            .collect();
+        assert_eq!(updates, vec![
+            (true, 4096, 16, 0, 1000),
+            (true, 256, 64, 1, 1500),
+            (true, 4160, 32, 2, 2000),
+            (false, 4096, 16, 3, 2500),
+        ]);
+        assert_eq!(parse_results.max_timestamp, 4);
+    }
+
+    #[test]
+    fn json_event_split_by_pools_test() {
+        let log_path = std::env::temp_dir().join("json_event_parser_split_by_pools_test.json");
+        std::fs::write(&log_path, serde_json::to_string(&test_events()).unwrap()).unwrap();
+        let log_path = log_path.to_str().unwrap();
+        assert!(JsonEventParser::is_json_event_log_file(log_path));
+
+        let pools = JsonEventParser::new().parse_log_contents_split_by_pools(log_path, &[], 8, 8, None).unwrap();
+        let bounds: Vec<(&str, usize, usize, usize)> = pools
+            .iter()
+            .map(|pool| (pool.pool.get_name(), pool.pool.get_start(), pool.pool.get_size(), pool.memory_updates.len()))
+            .collect();
+        // Sorted by start, and shifted and grown like the padded updates
+        assert_eq!(bounds, vec![("scratch", 248, 80, 1), ("heap", 4088, 112, 3)]);
+        let event_counts: Vec<(u64, u64)> = pools.iter().map(|pool| (pool.alloc_count, pool.free_count)).collect();
+        assert_eq!(event_counts, vec![(1, 0), (2, 1)]);
+
+        let filter = vec!["heap".to_string()];
+        let pools = JsonEventParser::new().parse_log_contents_split_by_pools(log_path, &[], 0, 0, Some(&filter)).unwrap();
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].pool.get_name(), "heap");
+    }
+
+    #[test]
+    fn json_event_invalid_log_test() {
+        assert!(matches!(JsonEventParser::parse_events(r#"[{"op":"resize"}]"#), Err(DamselflyError::InvalidLog(_))));
+
+        let log_path = std::env::temp_dir().join("json_event_parser_invalid_log_test.json");
+        std::fs::write(&log_path, "[{").unwrap();
+        let pools = JsonEventParser::new().parse_log_contents_split_by_pools(log_path.to_str().unwrap(), &[], 0, 0, None);
+        assert!(matches!(pools, Err(DamselflyError::InvalidLog(_))));
+        let pools = JsonEventParser::new().parse_log_contents_split_by_pools("/nonexistent/events.json", &[], 0, 0, None);
+        assert!(matches!(pools, Err(DamselflyError::Io(_))));
+    }
+}
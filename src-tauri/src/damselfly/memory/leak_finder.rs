@@ -0,0 +1,132 @@
+//! Utility methods to find allocations that are never freed.
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use crate::damselfly::memory::allocation_pairer::AllocationPairer;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+
+pub struct LeakFinder;
+
+impl LeakFinder {
+    /// Flags each update that is an allocation with no corresponding free later in the log.
+    ///
+    /// This is a backward pass: walking from the end of the log, every free marks its address
+    /// as freed. An allocation whose address is marked has been freed at some later point, and
+    /// consumes the mark so that an earlier allocation at the same address must find its own free.
+    /// An allocation whose address is not marked is never freed.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    ///
+    /// returns: Vec<bool>, where index i is true if memory_updates[i] is a leaked allocation.
+    pub fn find_leaked_allocations(memory_updates: &[MemoryUpdateType]) -> Vec<bool> {
+        let mut freed_later: HashSet<usize> = HashSet::new();
+        let mut leaked = vec![false; memory_updates.len()];
+        for (index, update) in memory_updates.iter().enumerate().rev() {
+            match update {
+                MemoryUpdateType::Allocation(_) => {
+                    leaked[index] = !freed_later.remove(&update.get_absolute_address());
+                }
+                MemoryUpdateType::Free(_) => {
+                    freed_later.insert(update.get_absolute_address());
+                }
+                // allocates the new region, after freeing the old one
+                MemoryUpdateType::Reallocation(reallocation) => {
+                    leaked[index] = !freed_later.remove(&update.get_absolute_address());
+                    freed_later.insert(reallocation.get_old_address());
+                }
+            }
+        }
+        leaked
+    }
+
+    /// Computes the bytes leaked up to each update.
+    ///
+    /// This takes two passes. The first is the backward pass in find_leaked_allocations, which
+    /// identifies allocations that are never freed. The second is a forward pass which sums the
+    /// sizes of those allocations in the order they were made, so the final point is the total
+    /// number of bytes leaked over the whole log.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    ///
+    /// returns: Vec<[index, cumulative leaked bytes]>
+    pub fn get_cumulative_leak_plot_points(memory_updates: &[MemoryUpdateType]) -> Vec<[f64; 2]> {
+        let leaked = Self::find_leaked_allocations(memory_updates);
+        let mut cumulative_leaked_bytes: u128 = 0;
+        let mut vector = Vec::new();
+        for (index, update) in memory_updates.iter().enumerate() {
+            if leaked[index] {
+                cumulative_leaked_bytes += update.get_absolute_size() as u128;
+            }
+            vector.push([index as f64, cumulative_leaked_bytes as f64]);
+        }
+        vector
+    }
+
+    /// Gets allocations that are still live at the end of the log, with how long they have been
+    /// live.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    /// * `final_timestamp`: Timestamp at the end of the log.
+    ///
+    /// returns: Vec<(allocation, age)>, oldest first, where age is final_timestamp minus the
+    /// allocation's timestamp.
+    pub fn get_leak_candidates(memory_updates: &[MemoryUpdateType], final_timestamp: u64) -> Vec<(MemoryUpdateType, u64)> {
+        let mut leak_candidates: Vec<(MemoryUpdateType, u64)> = AllocationPairer::pair_allocs_with_frees(memory_updates)
+            .into_iter()
+            .filter(|(_, free_index)| free_index.is_none())
+            .map(|(allocation_index, _)| {
+                let allocation = memory_updates[allocation_index].clone();
+                let age = final_timestamp.saturating_sub(allocation.get_timestamp() as u64);
+                (allocation, age)
+            })
+            .collect();
+        leak_candidates.sort_by_key(|(_, age)| Reverse(*age));
+        leak_candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::leak_finder::LeakFinder;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+
+    fn build_updates() -> Vec<MemoryUpdateType> {
+        let callstack = Arc::new("test".to_string());
+        vec![
+            Allocation::new(0, 8, callstack.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(8, 16, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Allocation::new(0, 4, callstack.clone(), 3, "0".to_string()).wrap_in_enum(),
+            Allocation::new(32, 4, callstack, 4, "0".to_string()).wrap_in_enum(),
+        ]
+    }
+
+    #[test]
+    fn find_leaked_allocations_test() {
+        let leaked = LeakFinder::find_leaked_allocations(&build_updates());
+        assert_eq!(leaked, vec![false, true, false, true, true]);
+    }
+
+    #[test]
+    fn cumulative_leak_plot_points_test() {
+        let plot_points = LeakFinder::get_cumulative_leak_plot_points(&build_updates());
+        assert_eq!(plot_points, vec![[0.0, 0.0], [1.0, 16.0], [2.0, 16.0], [3.0, 20.0], [4.0, 24.0]]);
+    }
+
+    #[test]
+    fn get_leak_candidates_test() {
+        let updates = build_updates();
+        let leak_candidates = LeakFinder::get_leak_candidates(&updates, 4);
+        assert_eq!(leak_candidates, vec![
+            (updates[1].clone(), 3),
+            (updates[3].clone(), 1),
+            (updates[4].clone(), 0),
+        ]);
+    }
+}
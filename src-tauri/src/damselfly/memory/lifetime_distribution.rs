@@ -0,0 +1,84 @@
+//! Utility methods for characterising how long allocations live, to help size pools.
+use crate::damselfly::memory::allocation_pairer::AllocationPairer;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+
+pub struct LifetimeDistribution;
+
+impl LifetimeDistribution {
+    /// Pairs each allocation with the free of its address and gets how long it lived. Frees of
+    /// addresses that aren't allocated are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates to pair, in timestamp order.
+    /// * `end_timestamp`: Timestamp used as the end of allocations that are never freed.
+    ///
+    /// returns: Vec<lifetime in timestamp units>, in the order the allocations were made.
+    pub fn get_allocation_lifetimes(memory_updates: &[MemoryUpdateType], end_timestamp: u64) -> Vec<u64> {
+        AllocationPairer::pair_allocs_with_frees(memory_updates)
+            .into_iter()
+            .map(|(allocation_index, free_index)| {
+                let freed_at = free_index.map_or(end_timestamp, |free_index| memory_updates[free_index].get_timestamp() as u64);
+                freed_at.saturating_sub(memory_updates[allocation_index].get_timestamp() as u64)
+            })
+            .collect()
+    }
+
+    /// Buckets lifetimes into a histogram of equal-width buckets spanning 0 to the longest lifetime.
+    ///
+    /// # Arguments
+    ///
+    /// * `lifetimes`: Lifetimes to bucket.
+    /// * `bucket_count`: Number of buckets.
+    ///
+    /// returns: Vec<(upper_bound, count)> in ascending order, where each bucket counts lifetimes
+    /// above the previous bucket's upper bound and at most its own. Empty if there are no
+    /// lifetimes or buckets.
+    pub fn get_lifetime_histogram(lifetimes: &[u64], bucket_count: usize) -> Vec<(u64, u64)> {
+        let Some(longest_lifetime) = lifetimes.iter().max() else {
+            return Vec::new();
+        };
+        if bucket_count == 0 {
+            return Vec::new();
+        }
+        let bucket_width = longest_lifetime.div_ceil(bucket_count as u64).max(1);
+        let mut histogram: Vec<(u64, u64)> = (1..=bucket_count as u64)
+            .map(|bucket| (bucket * bucket_width, 0))
+            .collect();
+        for lifetime in lifetimes {
+            let bucket = (lifetime.saturating_sub(1) / bucket_width) as usize;
+            histogram[bucket.min(bucket_count - 1)].1 += 1;
+        }
+        histogram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::lifetime_distribution::LifetimeDistribution;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate};
+
+    #[test]
+    fn get_allocation_lifetimes_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates = vec![
+            Allocation::new(0, 8, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(64, 8, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack.clone(), 3, "0001.003 s".to_string()).wrap_in_enum(),
+            // Never allocated, so ignored
+            Free::new(128, 8, callstack.clone(), 4, "0001.004 s".to_string()).wrap_in_enum(),
+            Allocation::new(0, 8, callstack.clone(), 5, "0001.005 s".to_string()).wrap_in_enum(),
+            Free::new(64, 8, callstack, 6, "0001.006 s".to_string()).wrap_in_enum(),
+        ];
+        assert_eq!(LifetimeDistribution::get_allocation_lifetimes(&updates, 6), vec![3, 5, 1]);
+    }
+
+    #[test]
+    fn get_lifetime_histogram_test() {
+        assert_eq!(LifetimeDistribution::get_lifetime_histogram(&[3, 5, 1], 2), vec![(3, 2), (6, 1)]);
+        assert_eq!(LifetimeDistribution::get_lifetime_histogram(&[0, 0], 2), vec![(1, 2), (2, 0)]);
+        assert!(LifetimeDistribution::get_lifetime_histogram(&[], 4).is_empty());
+        assert!(LifetimeDistribution::get_lifetime_histogram(&[3, 5, 1], 0).is_empty());
+    }
+}
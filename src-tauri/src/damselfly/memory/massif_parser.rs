@@ -0,0 +1,436 @@
+//! Parser for Valgrind massif output (massif.out.* files).
+//!
+//! Massif records heap snapshots rather than individual operations, and does not record addresses.
+//! Each detailed snapshot has a heap tree giving the bytes live at each call site, and every other
+//! snapshot only gives the total heap size in mem_heap_B. Consecutive snapshots are diffed per call
+//! site into synthetic allocations and frees: growth at a call site becomes a new allocation, and
+//! shrinkage frees that call site's most recent allocations. Allocations are given consecutive
+//! synthetic addresses, which are never reused.
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+use owo_colors::OwoColorize;
+
+use crate::damselfly::damselfly_error::DamselflyError;
+use crate::damselfly::memory::memory_parsers::{MemoryParser, ParseResults, ParserKind, PoolRestrictedParseResults};
+use crate::damselfly::memory::memory_pool::MemoryPool;
+use crate::damselfly::memory::memory_pool_list::MemoryPoolList;
+use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+
+/// Call site that heap bytes are attributed to when a snapshot has no heap tree.
+const UNATTRIBUTED_CALLSTACK: &str = "(unattributed)";
+
+/// Bytes live at each call site in a snapshot: callstack -> bytes.
+type CallSiteBytes = BTreeMap<Arc<String>, usize>;
+
+/// Parser for massif output.
+#[derive(Default)]
+pub struct MassifParser {
+    memory_updates: Vec<MemoryUpdateType>,
+    // callstack -> live (address, size), oldest first
+    live_allocations: HashMap<Arc<String>, Vec<(usize, usize)>>,
+    next_address: usize,
+}
+
+impl MemoryParser for MassifParser {
+    fn get_kind(&self) -> ParserKind {
+        ParserKind::Massif
+    }
+
+    /// Parses massif output into a Vec of MemoryUpdateTypes, each containing an Allocation or a Free.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: Raw massif output.
+    /// * `_binary_path`: Unused, as massif has already symbolised its call sites.
+    ///
+    /// returns: ParseResults. Empty if a snapshot is malformed.
+    fn parse_log_directly(self, log: &str, _binary_path: &str) -> ParseResults {
+        self.parse_log_contents(log).unwrap_or_else(|error| {
+            eprintln!("{}", format!("[MassifParser::parse_log_directly]: {error}").yellow());
+            ParseResults::new(Vec::new(), MemoryPoolList::default(), 0)
+        })
+    }
+
+    /// Parses massif output using its file path.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_path`: File path to massif output.
+    /// * `_binary_path`: Unused, as massif has already symbolised its call sites.
+    ///
+    /// returns: ParseResults. Empty if the file cannot be read or a snapshot is malformed.
+    fn parse_log(self, log_path: &str, binary_path: &str) -> ParseResults {
+        eprintln!("[MassifParser::parse_log]: log: {log_path}");
+        match std::fs::read_to_string(log_path) {
+            Ok(log) => self.parse_log_directly(log.as_str(), binary_path),
+            Err(error) => {
+                eprintln!("{}", format!("[MassifParser::parse_log]: Failed to read {log_path}: {error}").yellow());
+                ParseResults::new(Vec::new(), MemoryPoolList::default(), 0)
+            }
+        }
+    }
+
+    /// Parses massif output. Massif only profiles a single heap, so every update is placed in a
+    /// single pool named massif-heap, spanning the synthetic addresses.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: File path to massif output.
+    /// * `_binary_paths`: Unused, as massif has already symbolised its call sites.
+    /// * `left_padding`: Padding to add to the left of each operation (by shifting its address left)
+    /// * `right_padding`: Padding to add to the right of each operation (by increasing its size)
+    /// * `pool_filter`: Names of the pools to return, or None to return every pool.
+    ///
+    /// returns: Result<Vec<PoolRestrictedParseResults, Global>, DamselflyError>. Err if the file
+    /// cannot be read or a snapshot is malformed.
+    fn parse_log_contents_split_by_pools(self, log: &str, _binary_paths: &[(&str, &str)], left_padding: usize, right_padding: usize, pool_filter: Option<&[String]>) -> Result<Vec<PoolRestrictedParseResults>, DamselflyError> {
+        eprintln!("[MassifParser::parse_log_contents_split_by_pools]: log: {log}");
+        let log = std::fs::read_to_string(log)?;
+        let mut parse_results = self.parse_log_contents(&log)?;
+        let pool_size = parse_results.memory_updates
+            .iter()
+            .map(|update| update.get_end())
+            .max()
+            .unwrap_or(0);
+        if pool_size == 0 {
+            return Ok(Vec::new());
+        }
+        // Synthetic addresses start at 0, so shift them right to leave room for the left padding
+        for update in parse_results.memory_updates.iter_mut() {
+            update.set_absolute_address(update.get_absolute_address() + left_padding);
+        }
+        let pool = MemoryPool::new(0, pool_size + right_padding, "massif-heap".to_string());
+        Ok(PoolRestrictedParseResults::filter_pools(vec![PoolRestrictedParseResults::new(parse_results.memory_updates, parse_results.max_timestamp, pool)], pool_filter))
+    }
+}
+
+impl MassifParser {
+    pub fn new() -> MassifParser {
+        MassifParser::default()
+    }
+
+    /// Checks if a log is massif output, by looking for the desc: header on its first non-empty
+    /// line.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: Raw log file.
+    ///
+    /// returns: true if the log is massif output.
+    pub fn is_massif_log(log: &str) -> bool {
+        log.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .is_some_and(|line| line.starts_with("desc:"))
+    }
+
+    /// Checks if a log file is massif output, reading only up to its first non-empty line.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_path`: File path to log.
+    ///
+    /// returns: true if the log is massif output, false if it is not or cannot be read.
+    pub fn is_massif_log_file(log_path: &str) -> bool {
+        let Ok(log_file) = File::open(log_path) else {
+            return false;
+        };
+        BufReader::new(log_file)
+            .lines()
+            .map_while(Result::ok)
+            .find(|line| !line.trim().is_empty())
+            .is_some_and(|line| Self::is_massif_log(&line))
+    }
+
+    /// Parses raw massif output, consuming itself and returning parse results.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: Raw massif output.
+    ///
+    /// returns: Result<ParseResults, DamselflyError>, with DamselflyError::InvalidLog if a snapshot
+    /// is malformed.
+    fn parse_log_contents(mut self, log: &str) -> Result<ParseResults, DamselflyError> {
+        let mut previous_snapshot: CallSiteBytes = BTreeMap::new();
+        let time_unit = Self::parse_time_unit(log);
+        for (time, heap_bytes, heap_tree) in Self::parse_snapshots(log)? {
+            let snapshot = match heap_tree {
+                Some(heap_tree) => heap_tree,
+                None => Self::attribute_heap_total(&previous_snapshot, heap_bytes),
+            };
+            self.diff_snapshots(&previous_snapshot, &snapshot, &format!("{time} {time_unit}"));
+            previous_snapshot = snapshot;
+        }
+        let max_timestamp = self.memory_updates.len() as u64;
+        Ok(ParseResults::new(self.memory_updates, MemoryPoolList::default(), max_timestamp))
+    }
+
+    /// Gets the unit to give snapshot times as real timestamps. Only --time-unit=ms is a real
+    /// time; instructions (i) and bytes allocated (B) are treated as microseconds, which keeps
+    /// snapshots in order on realtime graphs.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: Raw massif output.
+    ///
+    /// returns: "ms" or "us".
+    fn parse_time_unit(log: &str) -> &'static str {
+        let time_unit = log.lines()
+            .find_map(|line| line.strip_prefix("time_unit:"))
+            .map(str::trim);
+        match time_unit {
+            Some("ms") => "ms",
+            _ => "us",
+        }
+    }
+
+    /// Splits massif output into snapshots.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: Raw massif output.
+    ///
+    /// returns: Vec<(time, mem_heap_B, bytes live at each call site if the snapshot has a heap tree)>,
+    /// or DamselflyError::InvalidLog if a snapshot field is malformed or outside a snapshot.
+    pub fn parse_snapshots(log: &str) -> Result<Vec<(u64, usize, Option<CallSiteBytes>)>, DamselflyError> {
+        let mut snapshots = Vec::new();
+        let mut lines = log.lines().peekable();
+        while let Some(line) = lines.next() {
+            if line.starts_with("snapshot=") {
+                snapshots.push((0, 0, None));
+            } else if let Some(time) = line.strip_prefix("time=") {
+                let snapshot = snapshots.last_mut()
+                    .ok_or_else(|| DamselflyError::InvalidLog("Massif time outside snapshot".to_string()))?;
+                snapshot.0 = time.trim().parse()
+                    .map_err(|_| DamselflyError::InvalidLog(format!("Invalid massif time: {time}")))?;
+            } else if let Some(heap_bytes) = line.strip_prefix("mem_heap_B=") {
+                let snapshot = snapshots.last_mut()
+                    .ok_or_else(|| DamselflyError::InvalidLog("Massif mem_heap_B outside snapshot".to_string()))?;
+                snapshot.1 = heap_bytes.trim().parse()
+                    .map_err(|_| DamselflyError::InvalidLog(format!("Invalid massif mem_heap_B: {heap_bytes}")))?;
+            } else if line.starts_with("heap_tree=") && line != "heap_tree=empty" {
+                let mut tree_lines = Vec::new();
+                while let Some(tree_line) = lines.next_if(|tree_line| tree_line.trim_start().starts_with('n')) {
+                    tree_lines.push(tree_line);
+                }
+                let snapshot = snapshots.last_mut()
+                    .ok_or_else(|| DamselflyError::InvalidLog("Massif heap_tree outside snapshot".to_string()))?;
+                snapshot.2 = Some(Self::parse_heap_tree(&tree_lines)?);
+            }
+        }
+        Ok(snapshots)
+    }
+
+    /// Parses a heap tree into the bytes live at each call site. Each leaf is a call site, and
+    /// its callstack is the path from the allocation function to the leaf, innermost frame first.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree_lines`: Lines of the heap tree, each nN: bytes description, indented by depth.
+    ///
+    /// returns: Map of callstack -> bytes, or DamselflyError::InvalidLog if a node is malformed.
+    pub fn parse_heap_tree(tree_lines: &[&str]) -> Result<CallSiteBytes, DamselflyError> {
+        let mut call_sites = BTreeMap::new();
+        let mut frames: Vec<&str> = Vec::new();
+        for tree_line in tree_lines {
+            let depth = tree_line.len() - tree_line.trim_start().len();
+            let (children, node) = tree_line.trim_start().split_once(": ")
+                .ok_or_else(|| DamselflyError::InvalidLog(format!("Invalid massif heap tree node: {tree_line}")))?;
+            let (bytes, description) = node.split_once(' ').unwrap_or((node, ""));
+            let bytes: usize = bytes.parse()
+                .map_err(|_| DamselflyError::InvalidLog(format!("Invalid massif heap tree bytes: {bytes}")))?;
+            frames.truncate(depth);
+            frames.push(Self::strip_frame_address(description));
+            // The root is the allocation functions themselves, so it is not part of any callstack
+            if children == "n0" && depth > 0 && bytes > 0 {
+                *call_sites.entry(Arc::new(frames[1..].join("\n"))).or_default() += bytes;
+            }
+        }
+        Ok(call_sites)
+    }
+
+    /// Strips the code address from a heap tree description, e.g.
+    /// 0x4005B8: main (example.c:5) -> main (example.c:5)
+    fn strip_frame_address(description: &str) -> &str {
+        match description.split_once(": ") {
+            Some((address, frame)) if address.starts_with("0x") => frame,
+            _ => description,
+        }
+    }
+
+    /// Reconstructs the bytes at each call site for a snapshot without a heap tree, by keeping
+    /// the previous snapshot's call sites and attributing the difference in total to an
+    /// unattributed call site. If the heap shrank by more than the unattributed bytes, everything
+    /// becomes unattributed.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous_snapshot`: Bytes at each call site in the previous snapshot.
+    /// * `heap_bytes`: mem_heap_B of this snapshot.
+    ///
+    /// returns: Map of callstack -> bytes.
+    fn attribute_heap_total(previous_snapshot: &CallSiteBytes, heap_bytes: usize) -> CallSiteBytes {
+        let mut snapshot: CallSiteBytes = previous_snapshot
+            .iter()
+            .filter(|(callstack, _)| callstack.as_str() != UNATTRIBUTED_CALLSTACK)
+            .map(|(callstack, bytes)| (callstack.clone(), *bytes))
+            .collect();
+        let attributed_bytes: usize = snapshot.values().sum();
+        let unattributed_callstack = Arc::new(UNATTRIBUTED_CALLSTACK.to_string());
+        if heap_bytes >= attributed_bytes {
+            if heap_bytes > attributed_bytes {
+                snapshot.insert(unattributed_callstack, heap_bytes - attributed_bytes);
+            }
+        } else {
+            snapshot.clear();
+            if heap_bytes > 0 {
+                snapshot.insert(unattributed_callstack, heap_bytes);
+            }
+        }
+        snapshot
+    }
+
+    /// Turns the difference between two consecutive snapshots into updates. Call sites that
+    /// shrink free their most recent allocations first, reallocating the remainder if an
+    /// allocation is only partly freed. Call sites that grow then allocate the difference.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous_snapshot`: Bytes at each call site in the previous snapshot.
+    /// * `snapshot`: Bytes at each call site in this snapshot.
+    /// * `real_timestamp`: Time of this snapshot with its unit, used as the real timestamp of its
+    ///   updates.
+    ///
+    /// returns: ()
+    fn diff_snapshots(&mut self, previous_snapshot: &CallSiteBytes, snapshot: &CallSiteBytes, real_timestamp: &str) {
+        for (callstack, &previous_bytes) in previous_snapshot {
+            let bytes = snapshot.get(callstack).copied().unwrap_or(0);
+            if bytes < previous_bytes {
+                self.shrink_call_site(callstack, previous_bytes - bytes, real_timestamp);
+            }
+        }
+        for (callstack, &bytes) in snapshot {
+            let previous_bytes = previous_snapshot.get(callstack).copied().unwrap_or(0);
+            if bytes > previous_bytes {
+                self.allocate(callstack, bytes - previous_bytes, real_timestamp);
+            }
+        }
+    }
+
+    /// Frees bytes from a call site, most recent allocation first.
+    fn shrink_call_site(&mut self, callstack: &Arc<String>, mut bytes: usize, real_timestamp: &str) {
+        let mut remainder = 0;
+        while bytes > 0 {
+            let Some((address, size)) = self.live_allocations.get_mut(callstack).and_then(Vec::pop) else {
+                break;
+            };
+            let timestamp = self.memory_updates.len();
+            self.memory_updates.push(Free::new(address, size, callstack.clone(), timestamp, real_timestamp.to_string()).wrap_in_enum());
+            remainder = size.saturating_sub(bytes);
+            bytes = bytes.saturating_sub(size);
+        }
+        if remainder > 0 {
+            self.allocate(callstack, remainder, real_timestamp);
+        }
+    }
+
+    /// Allocates bytes for a call site at the next synthetic address.
+    fn allocate(&mut self, callstack: &Arc<String>, size: usize, real_timestamp: &str) {
+        let timestamp = self.memory_updates.len();
+        let address = self.next_address;
+        self.next_address += size;
+        self.memory_updates.push(Allocation::new(address, size, callstack.clone(), timestamp, real_timestamp.to_string()).wrap_in_enum());
+        self.live_allocations.entry(callstack.clone()).or_default().push((address, size));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::damselfly::damselfly_error::DamselflyError;
+    use crate::damselfly::memory::massif_parser::MassifParser;
+    use crate::damselfly::memory::memory_parsers::MemoryParser;
+    use crate::damselfly::memory::memory_update::MemoryUpdateType;
+    use crate::damselfly::viewer::damselfly_viewer_builder::DamselflyViewerBuilder;
+
+    const MASSIF_TEST_LOG: &str = include_str!("../../../tests/fixtures/massif.out.test");
+
+    #[test]
+    fn is_massif_log_test() {
+        assert!(MassifParser::is_massif_log(MASSIF_TEST_LOG));
+        assert!(!MassifParser::is_massif_log("SNAPSHOTS\nSNAPSHOT 1000:10"));
+    }
+
+    #[test]
+    fn parse_snapshots_test() {
+        let snapshots = MassifParser::parse_snapshots(MASSIF_TEST_LOG).unwrap();
+        assert_eq!(snapshots.len(), 4);
+        assert_eq!(snapshots[2], (1600, 1100, None));
+        let heap_tree = snapshots[1].2.as_ref().unwrap();
+        let call_sites: Vec<(&str, usize)> = heap_tree
+            .iter()
+            .map(|(callstack, bytes)| (callstack.as_str(), *bytes))
+            .collect();
+        assert_eq!(call_sites, vec![
+            ("main (example.c:12)", 400),
+            ("make_buffer (example.c:5)\nmain (example.c:10)", 600),
+        ]);
+    }
+
+    #[test]
+    fn parse_massif_log_test() {
+        let parse_results = MassifParser::new().parse_log_directly(MASSIF_TEST_LOG, "");
+        let updates: Vec<(bool, usize, usize, &str)> = parse_results.memory_updates
+            .iter()
+            .map(|update| (
+                matches!(update, MemoryUpdateType::Allocation(_)),
+                update.get_absolute_address(),
+                update.get_absolute_size(),
+                update.get_real_timestamp().as_str(),
+            ))
+            .collect();
+        assert_eq!(updates, vec![
+            // Snapshot 1: both call sites appear
+            (true, 0, 400, "1200 us"),
+            (true, 400, 600, "1200 us"),
+            // Snapshot 2: the extra 100 bytes are unattributed
+            (true, 1000, 100, "1600 us"),
+            // Snapshot 3: make_buffer shrinks to 200, main (example.c:12) grows to 600
+            (false, 1000, 100, "2400 us"),
+            (false, 400, 600, "2400 us"),
+            (true, 1100, 200, "2400 us"),
+            (true, 1300, 200, "2400 us"),
+        ]);
+        assert_eq!(parse_results.max_timestamp, 7);
+    }
+
+    #[test]
+    fn load_massif_log_test() {
+        let log_path = std::env::temp_dir().join("massif_parser_load_massif_log_test.out");
+        std::fs::write(&log_path, MASSIF_TEST_LOG).unwrap();
+        let viewer = DamselflyViewerBuilder::new(log_path.to_str().unwrap())
+            .cache_size(1)
+            .build(MassifParser::new())
+            .unwrap();
+        assert_eq!(viewer.damselflies.len(), 1);
+        assert_eq!(viewer.damselflies[0].get_usage_graph_no_fallbacks().len(), 7);
+        assert!(!viewer.damselflies[0].get_usage_graph_realtime_sampled().is_empty());
+    }
+
+    #[test]
+    fn truncated_massif_log_test() {
+        // Cut off partway through a node of snapshot 1's heap tree
+        let truncated_log = &MASSIF_TEST_LOG[..MASSIF_TEST_LOG.find("  n0: 600").unwrap() + "  n0".len()];
+        assert!(matches!(MassifParser::parse_snapshots(truncated_log), Err(DamselflyError::InvalidLog(_))));
+        assert!(matches!(MassifParser::parse_snapshots("snapshot=0\ntime=12\nmem_heap_B="), Err(DamselflyError::InvalidLog(_))));
+        assert!(matches!(MassifParser::parse_snapshots("time=12\n"), Err(DamselflyError::InvalidLog(_))));
+
+        let log_path = std::env::temp_dir().join("massif_parser_truncated_massif_log_test.out");
+        std::fs::write(&log_path, truncated_log).unwrap();
+        let pools = MassifParser::new().parse_log_contents_split_by_pools(log_path.to_str().unwrap(), &[], 0, 0, None);
+        assert!(matches!(pools, Err(DamselflyError::InvalidLog(_))));
+        let pools = MassifParser::new().parse_log_contents_split_by_pools("/nonexistent/massif.out.1", &[], 0, 0, None);
+        assert!(matches!(pools, Err(DamselflyError::Io(_))));
+    }
+}
@@ -0,0 +1,44 @@
+//! A read or write of memory, logged between allocations and frees in traces that support it.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryAccess {
+    address: usize,
+    size: usize,
+    timestamp: usize,
+}
+
+impl MemoryAccess {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: Address accessed.
+    /// * `size`: Bytes accessed.
+    /// * `timestamp`: Timestamp of the latest memory update logged before this access.
+    ///
+    /// returns: MemoryAccess
+    pub fn new(address: usize, size: usize, timestamp: usize) -> Self {
+        Self {
+            address,
+            size,
+            timestamp,
+        }
+    }
+
+    pub fn get_address(&self) -> usize {
+        self.address
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get_timestamp(&self) -> usize {
+        self.timestamp
+    }
+
+    pub fn set_timestamp(&mut self, new_timestamp: usize) {
+        self.timestamp = new_timestamp;
+    }
+}
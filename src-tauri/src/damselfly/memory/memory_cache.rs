@@ -4,10 +4,13 @@
 //! 
 //! Do not use MemoryCacheSnapshot directly - it is best to generate and manage the cache
 //! using a MemoryCache object.
-use std::collections::HashMap;
+use rayon::prelude::*;
+use rust_lapper::Lapper;
+use crate::damselfly::consts::CACHE_HIT_MAX_REPLAYED_UPDATES;
 use crate::damselfly::memory::memory_cache_snapshot::MemoryCacheSnapshot;
 use crate::damselfly::memory::memory_status::MemoryStatus;
 use crate::damselfly::update_interval::UpdateInterval;
+use crate::damselfly::update_interval::update_interval_sorter::UpdateIntervalSorter;
 use crate::damselfly::update_interval::utility::Utility;
 use crate::damselfly::viewer::memory_canvas::MemoryCanvas;
 
@@ -39,6 +42,21 @@ impl MemoryCache {
             interval,
         }
     }
+
+    /// Constructor that picks the interval so the cache holds at most target_count snapshots,
+    /// keeping memory bounded regardless of how many updates the log has.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_size`: Bytes spanned by each block of the map.
+    /// * `update_intervals`: Vector of all updates
+    /// * `target_count`: Maximum number of cache snapshots to generate.
+    ///
+    /// returns: MemoryCache
+    pub fn with_target_snapshot_count(block_size: usize, update_intervals: Vec<UpdateInterval>, target_count: usize) -> Self {
+        let interval = update_intervals.len().div_ceil(target_count.max(1)).max(1);
+        MemoryCache::new(block_size, update_intervals, interval)
+    }
     
     /// Renders the map at a specific timestamp using stored caches.
     /// 
@@ -57,9 +75,58 @@ impl MemoryCache {
         }
     }
 
+    /// Gets the range of timestamps covered by each cache snapshot, in order. Snapshot i is
+    /// used to render maps for timestamps in [start, end).
+    /// 
+    /// returns: Vec<(start, end)> 
+    pub fn get_cache_layout(&self) -> Vec<(usize, usize)> {
+        let update_count = self.update_intervals.len();
+        (0..self.memory_cache_snapshots.len())
+            .map(|cache_index| {
+                let start = cache_index * self.interval;
+                (start, ((cache_index + 1) * self.interval).min(update_count))
+            })
+            .collect()
+    }
+
+    /// Gets the number of updates that have to be replayed over a cache snapshot to render the
+    /// map at a timestamp.
+    /// 
+    /// # Arguments 
+    /// 
+    /// * `timestamp`: Timestamp in operation time.
+    /// 
+    /// returns: Some(offset into the snapshot), or None if no snapshot covers the timestamp. 
+    pub fn get_render_offset(&self, timestamp: usize) -> Option<usize> {
+        let cache_index = timestamp / self.interval;
+        if cache_index >= self.memory_cache_snapshots.len() || timestamp >= self.update_intervals.len() {
+            return None;
+        }
+        Some(timestamp - cache_index * self.interval)
+    }
+
+    /// Checks if the map at a timestamp can be rendered almost instantly from a cache snapshot,
+    /// replaying at most CACHE_HIT_MAX_REPLAYED_UPDATES updates over it.
+    /// 
+    /// # Arguments 
+    /// 
+    /// * `timestamp`: Timestamp in operation time.
+    /// 
+    /// returns: true on a cache hit, false if the render needs an expensive replay. 
+    pub fn is_timestamp_cached(&self, timestamp: usize) -> bool {
+        self.get_render_offset(timestamp)
+            .is_some_and(|offset| offset <= CACHE_HIT_MAX_REPLAYED_UPDATES)
+    }
+
     /// Generates the cache by separating updates into buckets of size interval and painting a map
     /// for each one.
     /// Not exposed for public use; use MemoryCache::new() instead, which calls this internally.
+    ///
+    /// Each snapshot's base is the map after every earlier bucket, so painting the bases is
+    /// inherently sequential. Working out which updates each bucket paints onto each block (and in
+    /// what order) does not depend on the canvas, so that is done for every bucket in parallel
+    /// first, and the sequential pass only replays those plans. Snapshots are then assembled from
+    /// the bases in parallel.
     /// 
     /// # Arguments 
     /// 
@@ -70,33 +137,109 @@ impl MemoryCache {
     /// returns: (Vec<MemoryCacheSnapshot, Global>, Vec<Interval<usize, MemoryUpdateType>, Global>) 
     fn generate_cache(update_intervals: &Vec<UpdateInterval>, interval: usize, block_size: usize) -> (Vec<MemoryCacheSnapshot>, Vec<UpdateInterval>) {
         let (start, stop) = Utility::get_canvas_span(update_intervals);
-        let final_timestamp = update_intervals.len() - 1;
-
-        let mut buckets: HashMap<usize, Vec<UpdateInterval>> = HashMap::new();
-        
-        // Categories update into buckets in the hashmap
-        for (index, update) in update_intervals.iter().enumerate() {
-            let cache_index = index / interval;
-            buckets
-                .entry(cache_index)
-                .and_modify(|bucket| bucket.push(update.clone()))
-                .or_insert(vec![update.clone()]);
-        }
-        
-        // Iterate through every possible cache index from [0..=final_timestamp / interval]
-        let mut memory_cache_snapshots = Vec::new();
         let mut current_canvas = MemoryCanvas::new(start, stop, block_size, vec![]);
         current_canvas.insert_blocks();
-        
-        for cache_index in 0..=final_timestamp / interval {
-            let updates_in_bucket = buckets.get(&cache_index).cloned().unwrap_or(Vec::new());
-            memory_cache_snapshots.push(MemoryCacheSnapshot::new(current_canvas.clone(), updates_in_bucket.clone()));
-            current_canvas.paint_temporary_updates(updates_in_bucket.clone());
+
+        // Parallel: find the updates overlapping each block, per bucket
+        let buckets: Vec<&[UpdateInterval]> = update_intervals.chunks(interval).collect();
+        let paint_plans: Vec<Vec<(usize, Vec<UpdateInterval>)>> = buckets
+            .par_iter()
+            .map(|bucket| Self::plan_bucket(&current_canvas, bucket))
+            .collect();
+
+        // Sequential: each base is the previous base with the previous bucket painted over it
+        let mut bases = Vec::with_capacity(buckets.len());
+        for paint_plan in &paint_plans {
+            bases.push(current_canvas.clone());
+            for (block_index, updates) in paint_plan {
+                let block = &mut current_canvas.blocks[*block_index];
+                for update in updates {
+                    block.paint_block(&update.val);
+                }
+            }
         }
 
+        // Parallel: pair each base with the updates to replay over it
+        let memory_cache_snapshots = bases
+            .into_par_iter()
+            .zip(buckets.par_iter())
+            .map(|(base, bucket)| MemoryCacheSnapshot::new(base, bucket.to_vec()))
+            .collect();
+
         (memory_cache_snapshots, update_intervals.clone())
     }
 
+    /// Finds the blocks a bucket of updates paints over, and the order it paints them in. Only
+    /// reads the canvas's block bounds, so buckets can be planned independently of each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas`: Canvas whose blocks will be painted.
+    /// * `bucket`: Updates in the bucket.
+    ///
+    /// returns: (block index, overlapping updates sorted by timestamp) for every block the bucket
+    /// overlaps.
+    fn plan_bucket(canvas: &MemoryCanvas, bucket: &[UpdateInterval]) -> Vec<(usize, Vec<UpdateInterval>)> {
+        let bucket_lapper = Lapper::new(bucket.to_vec());
+        canvas.blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(block_index, block)| {
+                let mut overlapping_operations: Vec<&UpdateInterval> = bucket_lapper
+                    .find(block.get_block_start(), block.get_block_stop())
+                    .collect();
+                if overlapping_operations.is_empty() {
+                    return None;
+                }
+                UpdateIntervalSorter::sort_by_timestamp(&mut overlapping_operations);
+                Some((block_index, overlapping_operations.into_iter().cloned().collect()))
+            })
+            .collect()
+    }
+
+    /// Appends updates that follow the cached ones. The last snapshot's bucket is filled first,
+    /// then new snapshots are painted on top of it, so existing snapshots are not regenerated.
+    /// The whole cache is regenerated only if an update falls outside the cached canvas.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_update_intervals`: Updates to append, in order.
+    /// * `block_size`: Bytes spanned by each block of the map.
+    ///
+    /// returns: ()
+    pub fn append_update_intervals(&mut self, new_update_intervals: Vec<UpdateInterval>, block_size: usize) {
+        let (new_start, new_stop) = Utility::get_canvas_span(&new_update_intervals);
+        let is_within_canvas = self.memory_cache_snapshots
+            .last()
+            .map(|memory_cache_snapshot| memory_cache_snapshot.get_base().get_span())
+            .is_some_and(|(start, stop)| start <= new_start && new_stop <= stop);
+        self.update_intervals.extend(new_update_intervals.iter().cloned());
+        if !is_within_canvas {
+            eprintln!("[MemoryCache::append_update_intervals]: Update outside the cached canvas. Recomputing cache.");
+            (self.memory_cache_snapshots, self.update_intervals) = Self::generate_cache(&self.update_intervals, self.interval, block_size);
+            return;
+        }
+
+        let mut new_update_intervals = new_update_intervals.into_iter();
+        if let Some(last_snapshot) = self.memory_cache_snapshots.last_mut() {
+            let room = self.interval.saturating_sub(last_snapshot.get_temporary_update_count());
+            last_snapshot.extend_temporary_updates(new_update_intervals.by_ref().take(room));
+        }
+        let remaining_update_intervals: Vec<UpdateInterval> = new_update_intervals.collect();
+        let Some(last_snapshot) = self.memory_cache_snapshots.last() else {
+            return;
+        };
+        let mut current_canvas = last_snapshot.paint_all();
+        for bucket in remaining_update_intervals.chunks(self.interval) {
+            self.memory_cache_snapshots.push(MemoryCacheSnapshot::new(current_canvas.clone(), bucket.to_vec()));
+            current_canvas.paint_temporary_updates(bucket.to_vec());
+        }
+    }
+
+    pub fn get_interval(&self) -> usize {
+        self.interval
+    }
+
     /// Changes the block size. This regenerates the entire cache which is quite slow, so use this
     /// sparingly.
     /// 
@@ -110,3 +253,74 @@ impl MemoryCache {
         self.memory_cache_snapshots = Self::generate_cache(&self.update_intervals, self.interval, new_block_size).0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::memory_cache::MemoryCache;
+    use crate::damselfly::memory::memory_update::{Allocation, MemoryUpdate, MemoryUpdateType};
+    use crate::damselfly::update_interval::update_interval_factory::UpdateIntervalFactory;
+
+    #[test]
+    fn get_cache_layout_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = (0..5)
+            .map(|index| Allocation::new(index * 8, 8, callstack.clone(), index, "0".to_string()).wrap_in_enum())
+            .collect();
+        let update_intervals = UpdateIntervalFactory::new(updates).construct_enum_vector();
+        let memory_cache = MemoryCache::new(4, update_intervals, 2);
+        assert_eq!(memory_cache.get_cache_layout(), vec![(0, 2), (2, 4), (4, 5)]);
+    }
+
+    #[test]
+    fn get_render_offset_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = (0..5)
+            .map(|index| Allocation::new(index * 8, 8, callstack.clone(), index, "0".to_string()).wrap_in_enum())
+            .collect();
+        let update_intervals = UpdateIntervalFactory::new(updates).construct_enum_vector();
+        let memory_cache = MemoryCache::new(4, update_intervals, 2);
+        assert_eq!(memory_cache.get_render_offset(2), Some(0));
+        assert_eq!(memory_cache.get_render_offset(3), Some(1));
+        assert_eq!(memory_cache.get_render_offset(5), None);
+        assert!(memory_cache.is_timestamp_cached(3));
+        assert!(!memory_cache.is_timestamp_cached(5));
+    }
+
+    #[test]
+    fn append_update_intervals_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = (0..7)
+            .map(|index| Allocation::new((index % 4) * 8, 8, callstack.clone(), index, "0".to_string()).wrap_in_enum())
+            .chain([Allocation::new(256, 8, callstack.clone(), 7, "0".to_string()).wrap_in_enum()])
+            .collect();
+        let update_intervals = UpdateIntervalFactory::new(updates).construct_enum_vector();
+        let rebuilt = MemoryCache::new(4, update_intervals.clone(), 2);
+
+        let mut appended = MemoryCache::new(4, update_intervals[..3].to_vec(), 2);
+        // Fills the last bucket, then adds new snapshots within the canvas
+        appended.append_update_intervals(update_intervals[3..7].to_vec(), 4);
+        // Outside the canvas, so the cache is regenerated
+        appended.append_update_intervals(update_intervals[7..].to_vec(), 4);
+        assert_eq!(appended.get_cache_layout(), rebuilt.get_cache_layout());
+        for timestamp in 0..8 {
+            assert_eq!(appended.query_cache(timestamp), rebuilt.query_cache(timestamp));
+        }
+    }
+
+    #[test]
+    fn with_target_snapshot_count_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = (0..100_000)
+            .map(|index| Allocation::new((index % 128) * 8, 8 + index % 3, callstack.clone(), index, "0".to_string()).wrap_in_enum())
+            .collect();
+        let update_intervals = UpdateIntervalFactory::new(updates).construct_enum_vector();
+        let memory_cache = MemoryCache::with_target_snapshot_count(8, update_intervals.clone(), 50);
+        assert!(memory_cache.get_cache_layout().len() <= 50);
+        // A single snapshot replays every update, so it is always correct
+        let uncached = MemoryCache::new(8, update_intervals, 100_000);
+        for timestamp in [0, 1, 1999, 2000, 12_345, 54_321, 99_999] {
+            assert_eq!(memory_cache.query_cache(timestamp), uncached.query_cache(timestamp));
+        }
+    }
+}
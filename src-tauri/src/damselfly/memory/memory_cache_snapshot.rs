@@ -81,4 +81,28 @@ impl MemoryCacheSnapshot {
     pub fn get_base(&self) -> &MemoryCanvas {
         &self.base
     }
+
+    pub fn get_temporary_update_count(&self) -> usize {
+        self.temporary_updates.len()
+    }
+
+    /// Adds updates to paint over the base, after the existing temporary updates.
+    ///
+    /// # Arguments
+    ///
+    /// * `temporary_updates`: Updates to add, in order.
+    ///
+    /// returns: ()
+    pub fn extend_temporary_updates(&mut self, temporary_updates: impl IntoIterator<Item = UpdateInterval>) {
+        self.temporary_updates.extend(temporary_updates);
+    }
+
+    /// Paints every temporary update over a copy of the base, giving the base of the next snapshot.
+    ///
+    /// returns: MemoryCanvas
+    pub fn paint_all(&self) -> MemoryCanvas {
+        let mut canvas = self.base.clone();
+        canvas.paint_temporary_updates(self.temporary_updates.clone());
+        canvas
+    }
 }
\ No newline at end of file
@@ -2,19 +2,30 @@
 //! To implement your own parser (for other log formats), write a struct that implements MemoryParser.
 //! Then, in main.rs, change initialise_viewer to accept your struct instead.
 //! MemorySysTraceParser parses logfiles generated by SysTraceParser, and is the default parsing struct.
+//! Besides allocations (+), frees (-) and stacktraces (^), it also reads optional memory access
+//! records (@ address size), which some traces include, and an optional header naming the binary
+//! that produced the trace (BINARY path build_id).
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
 use std::io::Read;
 use std::iter::Peekable;
 use std::str::{FromStr, Split};
 use std::sync::Arc;
 
-use addr2line::Context;
+use flate2::read::GzDecoder;
+use object::Object;
 use owo_colors::OwoColorize;
 
+use serde::{Deserialize, Serialize};
+
+use crate::damselfly::consts::DEFAULT_GADDR2LINE_PATH;
+use crate::damselfly::damselfly_error::DamselflyError;
+use crate::damselfly::memory::anomaly::{AnomalyKind, AnomalySeverity};
+use crate::damselfly::memory::anomaly_detector::AnomalyDetector;
+use crate::damselfly::memory::memory_access::MemoryAccess;
 use crate::damselfly::memory::memory_pool::MemoryPool;
 use crate::damselfly::memory::memory_pool_list::MemoryPoolList;
-use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType, Reallocation};
+use crate::damselfly::memory::symbolicator::{Addr2LineSymbolicator, Gaddr2LineSymbolicator, Symbolicator};
 
 /// Raw text in logs are parsed into one of the following.
 #[derive(Clone)]
@@ -23,6 +34,8 @@ pub enum RecordType {
     Allocation(usize, usize, String, String),
     // (address, callstack, real_timestamp)
     Free(usize, String, String),
+    // (old address, address, size, callstack, real_timestamp)
+    Reallocation(usize, usize, usize, String, String),
     // (address, callstack)
     StackTrace(usize, String),
     // (address, size)
@@ -31,15 +44,46 @@ pub enum RecordType {
     PoolName(String),
 }
 
+/// Kind of parser a viewer was built with, recorded so a log that is followed as it is written
+/// is parsed the same way it was loaded.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParserKind {
+    /// MemorySysTraceParser, with the settings it was built with. A custom symbolicator is not
+    /// recorded, so followed logs fall back to the default one.
+    SysTrace {
+        collapse_duplicate_updates: bool,
+        gaddr2line_path: Option<String>,
+        symbolicate: bool,
+    },
+    Snapshot,
+    Massif,
+    JsonEvent,
+    /// A parser implemented outside Damselfly.
+    #[default]
+    Custom,
+}
+
 /// Required methods for a MemoryParser.
 pub trait MemoryParser {
+    /// Gets the kind of this parser. Parsers implemented outside Damselfly can leave this as
+    /// ParserKind::Custom.
+    fn get_kind(&self) -> ParserKind {
+        ParserKind::Custom
+    }
+
     fn parse_log_directly(self, log: &str, binary_path: &str) -> ParseResults;
     fn parse_log(self, log_path: &str, binary_path: &str) -> ParseResults;
-    fn parse_log_contents_split_by_pools(self, log: &str, binary_path: &str, left_padding: usize, right_padding: usize) -> Vec<PoolRestrictedParseResults>;
+    fn parse_log_contents_split_by_pools(self, log: &str, binary_paths: &[(&str, &str)], left_padding: usize, right_padding: usize, pool_filter: Option<&[String]>) -> Result<Vec<PoolRestrictedParseResults>, DamselflyError>;
+
+    /// Parses a log file and splits its memory operations into pools, resolving every pool's
+    /// callstacks against a single binary.
+    fn parse_log_contents_split_by_pools_with_binary(self, log: &str, binary_path: &str, left_padding: usize, right_padding: usize) -> Result<Vec<PoolRestrictedParseResults>, DamselflyError>
+    where Self: Sized {
+        self.parse_log_contents_split_by_pools(log, &[("", binary_path)], left_padding, right_padding, None)
+    }
 }
 
 /// Parser for SysTraceParser logs.
-#[derive(Default)]
 pub struct MemorySysTraceParser {
     time: usize,
     record_queue: Vec<RecordType>,
@@ -49,6 +93,28 @@ pub struct MemorySysTraceParser {
     symbols: HashMap<usize, String>,
     prefix: String,
     counter: u64,
+    collapse_duplicate_updates: bool,
+    memory_accesses: Vec<MemoryAccess>,
+    // Trailing partial line from the last call to parse_log_incremental
+    pending_line: String,
+    // Binary used to symbolise addresses that first appear in parse_log_incremental
+    binary_path: String,
+    // Shared with the parsers created for each binary in parse_log_contents_split_by_pools
+    symbolicator: Arc<dyn Symbolicator>,
+    // If false, the symbolicator is never run and callstacks keep their raw addresses
+    symbolicate: bool,
+    // gaddr2line used by the default symbolicator, or None for DEFAULT_GADDR2LINE_PATH
+    gaddr2line_path: Option<String>,
+    // Pool ID given on the line of the alloc/free waiting in the record queue
+    queued_pool_id: Option<String>,
+    // Pool ID given on the line of each baked update, in the same order as memory_updates
+    update_pool_ids: Vec<Option<String>>,
+}
+
+impl Default for MemorySysTraceParser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// MemoryParsers should return this: memory operations sorted into pools along with the max timestamp.
@@ -56,6 +122,17 @@ pub struct PoolRestrictedParseResults {
     pub memory_updates: Vec<MemoryUpdateType>,
     pub max_timestamp: u64,
     pub pool: MemoryPool,
+    /// Accesses within the pool, or None if the log has no access records.
+    pub memory_accesses: Option<Vec<MemoryAccess>>,
+    /// Anomalies found while parsing updates within the pool.
+    pub parse_anomalies: Vec<ParseAnomaly>,
+    /// Number of allocations in memory_updates, counting each reallocation.
+    pub alloc_count: u64,
+    /// Number of frees in memory_updates, counting each reallocation.
+    pub free_count: u64,
+    /// Number of consecutive duplicate updates collapsed from the whole log, if duplicate
+    /// collapsing was enabled.
+    pub collapsed_duplicates: u64,
 }
 
 impl PoolRestrictedParseResults {
@@ -69,10 +146,57 @@ impl PoolRestrictedParseResults {
     /// 
     /// returns: PoolRestrictedParseResults 
     pub fn new(memory_updates: Vec<MemoryUpdateType>, max_timestamp: u64, pool: MemoryPool) -> Self {
+        let (alloc_count, free_count) = Self::count_events(&memory_updates);
         Self {
             memory_updates,
             max_timestamp,
-            pool
+            pool,
+            memory_accesses: None,
+            parse_anomalies: Vec::new(),
+            alloc_count,
+            free_count,
+            collapsed_duplicates: 0,
+        }
+    }
+
+    /// Counts the allocations and frees in a list of updates. A reallocation counts as both, as
+    /// it frees its old region and allocates its new one.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates to count.
+    ///
+    /// returns: (alloc_count, free_count)
+    pub fn count_events(memory_updates: &[MemoryUpdateType]) -> (u64, u64) {
+        let mut counts = (0, 0);
+        for update in memory_updates {
+            match update {
+                MemoryUpdateType::Allocation(_) => counts.0 += 1,
+                MemoryUpdateType::Free(_) => counts.1 += 1,
+                MemoryUpdateType::Reallocation(_) => {
+                    counts.0 += 1;
+                    counts.1 += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    /// Keeps only the pools named in a filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool_restricted_parse_results`: Results for each pool.
+    /// * `pool_filter`: Names of the pools to keep, or None to keep every pool.
+    ///
+    /// returns: Results of the pools whose names are in the filter, in their original order.
+    pub fn filter_pools(pool_restricted_parse_results: Vec<Self>, pool_filter: Option<&[String]>) -> Vec<Self> {
+        match pool_filter {
+            None => pool_restricted_parse_results,
+            Some(pool_names) => pool_restricted_parse_results
+                .into_iter()
+                .filter(|parse_results| pool_names.iter().any(|pool_name| pool_name == parse_results.pool.get_name()))
+                .collect(),
         }
     }
 }
@@ -82,6 +206,15 @@ pub struct ParseResults {
     pub memory_updates: Vec<MemoryUpdateType>,
     pub max_timestamp: u64,
     pub pool_list: MemoryPoolList,
+    /// Number of consecutive duplicate updates removed, if duplicate collapsing was enabled.
+    pub collapsed_duplicates: u64,
+    /// Memory accesses, or None if the log has no access records.
+    pub memory_accesses: Option<Vec<MemoryAccess>>,
+    /// Anomalies found while parsing, such as double frees.
+    pub parse_anomalies: Vec<ParseAnomaly>,
+    /// Pool ID given on the line of each update, in the same order as memory_updates. Empty if
+    /// the parser does not read pool IDs.
+    pub update_pool_ids: Vec<Option<String>>,
 }
 
 impl ParseResults {
@@ -99,11 +232,69 @@ impl ParseResults {
             memory_updates,
             pool_list,
             max_timestamp,
+            collapsed_duplicates: 0,
+            memory_accesses: None,
+            parse_anomalies: Vec::new(),
+            update_pool_ids: Vec::new(),
         }
     }
+
+    /// Gets the pool ID given on an update's line.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: Index of the update in memory_updates.
+    ///
+    /// returns: The pool ID, or None if the line did not give one.
+    pub fn get_update_pool_id(&self, index: usize) -> Option<&str> {
+        self.update_pool_ids.get(index)?.as_deref()
+    }
+}
+
+/// A free that does not match a live allocation, found while parsing. Frees of unallocated
+/// addresses are reported as AnomalyKind::OrphanFree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseAnomaly {
+    timestamp: usize,
+    address: usize,
+    kind: AnomalyKind,
+}
+
+impl ParseAnomaly {
+    pub fn new(timestamp: usize, address: usize, kind: AnomalyKind) -> Self {
+        Self {
+            timestamp,
+            address,
+            kind,
+        }
+    }
+
+    pub fn get_timestamp(&self) -> usize {
+        self.timestamp
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: usize) {
+        self.timestamp = timestamp;
+    }
+
+    pub fn get_address(&self) -> usize {
+        self.address
+    }
+
+    pub fn get_kind(&self) -> AnomalyKind {
+        self.kind
+    }
 }
 
 impl MemoryParser for MemorySysTraceParser {
+    fn get_kind(&self) -> ParserKind {
+        ParserKind::SysTrace {
+            collapse_duplicate_updates: self.collapse_duplicate_updates,
+            gaddr2line_path: self.gaddr2line_path.clone(),
+            symbolicate: self.symbolicate,
+        }
+    }
+
     /// Parses a log file into a Vec of MemoryUpdateTypes, each containing an Allocation or a Free.
     ///
     /// # Arguments
@@ -116,7 +307,7 @@ impl MemoryParser for MemorySysTraceParser {
         self.parse_log_contents(log, binary_path)
     }
 
-    /// Parses a log using its file path.
+    /// Parses a log using its file path. Gzip-compressed logs are decompressed transparently.
     /// 
     /// # Arguments 
     /// 
@@ -126,22 +317,100 @@ impl MemoryParser for MemorySysTraceParser {
     /// returns: ParseResults 
     fn parse_log(self, log_path: &str, binary_path: &str) -> ParseResults {
         eprintln!("[MemorySysTraceParser::parse_log]: log: {log_path} binary: {binary_path}");
-        let log = std::fs::read_to_string(log_path).unwrap();
+        let log = Self::read_log(log_path)
+            .unwrap_or_else(|error| panic!("[MemorySysTraceParser::parse_log]: {error}"));
         self.parse_log_contents(log.as_str(), binary_path)
     }
 
-    /// Parses a log file and splits its memory operations into pools.
+    /// Parses a log file and splits its memory operations into pools. When pools use different
+    /// binaries, the log is parsed once without symbols, then each pool's callstacks are resolved
+    /// against its own debuginfo.
     /// 
     /// # Arguments 
     /// 
     /// * `log`: Raw log file.
-    /// * `binary_path`: File path to threadxApp binary for debuginfo.
+    /// * `binary_paths`: (pool name pattern, binary path) pairs. A pool uses the binary of the first
+    ///   pattern its name contains, or the first binary if none match.
     /// * `left_padding`: Padding to add to the left of each operation (by shifting its address left)
     /// * `right_padding`: Padding to add to the right of each operation (by increasing its size)
+    /// * `pool_filter`: Names of the pools to return, or None to return every pool.
     /// 
-    /// returns: Vec<PoolRestrictedParseResults, Global> 
-    fn parse_log_contents_split_by_pools(self, log: &str, binary_path: &str, left_padding: usize, right_padding: usize) -> Vec<PoolRestrictedParseResults> {
-        let mut parse_results = self.parse_log(log, binary_path);
+    /// returns: Result<Vec<PoolRestrictedParseResults, Global>, DamselflyError> 
+    fn parse_log_contents_split_by_pools(self, log: &str, binary_paths: &[(&str, &str)], left_padding: usize, right_padding: usize, pool_filter: Option<&[String]>) -> Result<Vec<PoolRestrictedParseResults>, DamselflyError> {
+        let mut distinct_binary_paths: Vec<&str> = Vec::new();
+        for (_, binary_path) in binary_paths {
+            if !distinct_binary_paths.contains(binary_path) {
+                distinct_binary_paths.push(binary_path);
+            }
+        }
+        eprintln!("[MemorySysTraceParser::parse_log_contents_split_by_pools]: log: {log} binaries: {distinct_binary_paths:?}");
+        let log_contents = Self::read_log(log)?;
+        if distinct_binary_paths.len() <= 1 {
+            let binary_path = distinct_binary_paths.first().copied().unwrap_or("");
+            let pool_restricted_parse_results = Self::split_by_pools(self.parse_log_contents(&log_contents, binary_path), left_padding, right_padding);
+            return Ok(PoolRestrictedParseResults::filter_pools(pool_restricted_parse_results, pool_filter));
+        }
+
+        // Parse once without symbols, then resolve each pool's callstacks against its own binary
+        let symbolicator = Arc::clone(&self.symbolicator);
+        let symbolicate = self.symbolicate;
+        let parse_results = self.with_symbolication(false).parse_log_contents(&log_contents, "");
+        let mut pool_restricted_parse_results = PoolRestrictedParseResults::filter_pools(
+            Self::split_by_pools(parse_results, left_padding, right_padding),
+            pool_filter,
+        );
+        if symbolicate {
+            for binary_path in distinct_binary_paths {
+                let mut pools: Vec<&mut PoolRestrictedParseResults> = pool_restricted_parse_results
+                    .iter_mut()
+                    .filter(|pool_parse_results| Self::select_binary_for_pool(pool_parse_results.pool.get_name(), binary_paths) == binary_path)
+                    .collect();
+                let binary_path = Self::resolve_binary_path(&log_contents, binary_path);
+                Self::symbolicate_pools(symbolicator.as_ref(), &mut pools, &binary_path);
+            }
+        }
+        Ok(pool_restricted_parse_results)
+    }
+}
+
+impl MemorySysTraceParser {
+    /// Reads a log file, decompressing it if it starts with the gzip magic bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_path`: File path to log, compressed or not.
+    ///
+    /// returns: The log's text, DamselflyError::Io if it cannot be read or decompressed, or
+    /// DamselflyError::InvalidLog if an uncompressed log is not UTF-8.
+    fn read_log(log_path: &str) -> Result<String, DamselflyError> {
+        const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+        let bytes = std::fs::read(log_path)?;
+        if !bytes.starts_with(&GZIP_MAGIC) {
+            return String::from_utf8(bytes)
+                .map_err(|error| DamselflyError::InvalidLog(format!("Log is not UTF-8: {error}")));
+        }
+        let mut log = String::new();
+        GzDecoder::new(bytes.as_slice())
+            .read_to_string(&mut log)
+            .map_err(|error| DamselflyError::Io(format!("Failed to decompress gzipped log: {error}")))?;
+        Ok(log)
+    }
+
+    /// Splits parse results into pools. Updates whose lines give a pool ID are routed to the pool
+    /// of that name, so pools interleaved in the log keep their own updates in order. IDs of pools
+    /// the log does not declare are routed to a synthetic "unknown" pool spanning their updates.
+    /// Updates without a pool ID go to the pools containing them. If the log declares no pools and
+    /// gives no pool IDs, a default pool spanning every update is used.
+    ///
+    /// # Arguments
+    ///
+    /// * `parse_results`: Results of parsing the whole log.
+    /// * `left_padding`: Padding to add to the left of each operation (by shifting its address left)
+    /// * `right_padding`: Padding to add to the right of each operation (by increasing its size)
+    ///
+    /// returns: Vec<PoolRestrictedParseResults, Global>
+    fn split_by_pools(mut parse_results: ParseResults, left_padding: usize, right_padding: usize) -> Vec<PoolRestrictedParseResults> {
+        let routed_pool_names = Self::route_updates_to_pools(&mut parse_results);
         if parse_results.pool_list.get_pools().is_empty() {
             let span = Self::get_updates_span(&parse_results.memory_updates);
             parse_results.pool_list.add_pool(MemoryPool::new(span.0 as usize, (span.1 - span.0) as usize, "_default pool".to_string()));
@@ -159,17 +428,189 @@ impl MemoryParser for MemorySysTraceParser {
         for pool in shifted_pools {
             let updates_in_pool = parse_results.memory_updates
                 .iter()
-                .filter(|update| pool.contains(update.get_start(), update.get_end()))
+                .zip(&routed_pool_names)
+                .filter(|(update, routed_pool_name)| match routed_pool_name {
+                    Some(pool_name) => pool_name == pool.get_name(),
+                    None => pool.contains(update.get_start(), update.get_end()),
+                })
+                .map(|(update, _)| update.clone())
+                .collect();
+            let mut pool_parse_results = PoolRestrictedParseResults::new(updates_in_pool, parse_results.max_timestamp, pool.clone());
+            pool_parse_results.memory_accesses = parse_results.memory_accesses.as_ref().map(|memory_accesses| {
+                memory_accesses
+                    .iter()
+                    .filter(|access| pool.contains(access.get_address(), access.get_address() + access.get_size()))
+                    .cloned()
+                    .collect()
+            });
+            pool_parse_results.parse_anomalies = parse_results.parse_anomalies
+                .iter()
+                .filter(|parse_anomaly| pool.contains(parse_anomaly.get_address(), parse_anomaly.get_address()))
                 .cloned()
                 .collect();
-            pool_restricted_parse_results.push(PoolRestrictedParseResults::new(updates_in_pool, parse_results.max_timestamp, pool.clone()));
+            pool_parse_results.collapsed_duplicates = parse_results.collapsed_duplicates;
+            pool_restricted_parse_results.push(pool_parse_results);
         }
 
         pool_restricted_parse_results
     }
-}
 
-impl MemorySysTraceParser {
+    /// Finds the pool each update is routed to by the pool ID on its line, adding an "unknown"
+    /// pool for IDs of pools the log does not declare.
+    ///
+    /// # Arguments
+    ///
+    /// * `parse_results`: Results of parsing the whole log.
+    ///
+    /// returns: For each update, the name of the pool it is routed to, or None if its line gave no
+    /// pool ID.
+    fn route_updates_to_pools(parse_results: &mut ParseResults) -> Vec<Option<String>> {
+        let routed_pool_names: Vec<Option<String>> = (0..parse_results.memory_updates.len())
+            .map(|index| {
+                let pool_id = parse_results.get_update_pool_id(index)?;
+                let is_declared = parse_results.pool_list.get_pools().iter().any(|pool| pool.get_name() == pool_id);
+                Some(if is_declared { pool_id.to_string() } else { "unknown".to_string() })
+            })
+            .collect();
+        let unknown_updates: Vec<MemoryUpdateType> = parse_results.memory_updates
+            .iter()
+            .zip(&routed_pool_names)
+            .filter(|(_, routed_pool_name)| routed_pool_name.as_deref() == Some("unknown"))
+            .map(|(update, _)| update.clone())
+            .collect();
+        if !unknown_updates.is_empty() {
+            let span = Self::get_updates_span(&unknown_updates);
+            parse_results.pool_list.add_pool(MemoryPool::new(span.0 as usize, (span.1 - span.0) as usize, "unknown".to_string()));
+        }
+        routed_pool_names
+    }
+
+    /// Resolves the raw addresses in the callstacks of pools parsed without symbolication. If the
+    /// addresses can't be symbolicated, the callstacks keep their raw addresses.
+    ///
+    /// # Arguments
+    ///
+    /// * `symbolicator`: Symbolicator to use.
+    /// * `pools`: Pools whose callstacks belong to the binary.
+    /// * `binary_path`: Path to the threadApp binary for debuginfo.
+    ///
+    /// returns: ()
+    fn symbolicate_pools(symbolicator: &dyn Symbolicator, pools: &mut [&mut PoolRestrictedParseResults], binary_path: &str) {
+        let callstacks: HashSet<Arc<String>> = pools
+            .iter()
+            .flat_map(|pool_parse_results| &pool_parse_results.memory_updates)
+            .map(|update| update.get_callstack())
+            .collect();
+        let resolved_callstacks = Self::resolve_raw_callstacks(symbolicator, &callstacks, binary_path);
+        if resolved_callstacks.is_empty() {
+            return;
+        }
+        for pool_parse_results in pools.iter_mut() {
+            for update in pool_parse_results.memory_updates.iter_mut() {
+                if let Some(resolved_callstack) = resolved_callstacks.get(&update.get_callstack()) {
+                    update.set_callstack(resolved_callstack.clone());
+                }
+            }
+        }
+    }
+
+    /// Resolves the raw addresses in callstacks of a log parsed with symbolication disabled, using
+    /// this parser's symbolicator, so the callstacks can be upgraded in place after loading.
+    ///
+    /// # Arguments
+    ///
+    /// * `callstacks`: Distinct callstacks to resolve.
+    /// * `binary_path`: Path to the threadApp binary for debuginfo.
+    ///
+    /// returns: The resolved callstack of each callstack with raw addresses, or an empty map if
+    /// there is no binary or it can't be symbolicated.
+    pub fn resymbolicate_callstacks(&self, callstacks: &HashSet<Arc<String>>, binary_path: &str) -> HashMap<Arc<String>, Arc<String>> {
+        Self::resolve_raw_callstacks(self.symbolicator.as_ref(), callstacks, binary_path)
+    }
+
+    /// Resolves the raw addresses in callstacks. Every address across the callstacks is passed to
+    /// the symbolicator in a single batch, and the prefix common to the symbols is trimmed like in
+    /// parse_symbols.
+    ///
+    /// # Arguments
+    ///
+    /// * `symbolicator`: Symbolicator to use.
+    /// * `callstacks`: Distinct callstacks to resolve.
+    /// * `binary_path`: Path to the threadApp binary for debuginfo.
+    ///
+    /// returns: The resolved callstack of each callstack with raw addresses, or an empty map if
+    /// there is no binary or it can't be symbolicated.
+    fn resolve_raw_callstacks(symbolicator: &dyn Symbolicator, callstacks: &HashSet<Arc<String>>, binary_path: &str) -> HashMap<Arc<String>, Arc<String>> {
+        if binary_path.is_empty() {
+            return HashMap::new();
+        }
+        let addresses: Vec<usize> = callstacks
+            .iter()
+            .flat_map(|callstack| callstack.lines().filter_map(Self::parse_raw_address_symbol).collect::<Vec<usize>>())
+            .collect::<HashSet<usize>>()
+            .into_iter()
+            .collect();
+        if addresses.is_empty() {
+            return HashMap::new();
+        }
+        let Some(symbols) = symbolicator.symbolicate(&addresses, binary_path) else {
+            return HashMap::new();
+        };
+        let prefix = if symbols.iter().any(|symbol| symbol.starts_with('/')) {
+            Self::longest_common_prefix(&symbols)
+        } else {
+            String::new()
+        };
+        let symbols: HashMap<usize, String> = addresses
+            .into_iter()
+            .zip(symbols.iter().map(|symbol| symbol.trim_start_matches(&prefix).to_string()))
+            .collect();
+        callstacks
+            .iter()
+            .filter(|callstack| callstack.lines().any(|frame| Self::parse_raw_address_symbol(frame).is_some()))
+            .map(|callstack| {
+                let resolved_callstack: String = callstack
+                    .lines()
+                    .map(|frame| {
+                        let symbol = Self::parse_raw_address_symbol(frame)
+                            .and_then(|address| symbols.get(&address))
+                            .map_or(frame, String::as_str);
+                        format!("{symbol}\n")
+                    })
+                    .collect();
+                (callstack.clone(), Arc::new(resolved_callstack))
+            })
+            .collect()
+    }
+
+    /// Parses a callstack frame formatted by raw_address_symbols back into its address.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame`: A single callstack frame.
+    ///
+    /// returns: The address, or None if the frame is not a raw address.
+    fn parse_raw_address_symbol(frame: &str) -> Option<usize> {
+        usize::from_str_radix(frame.strip_prefix("0x")?, 16).ok()
+    }
+
+    /// Selects the binary whose callstacks belong to a pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool_name`: Name of the pool.
+    /// * `binary_paths`: (pool name pattern, binary path) pairs.
+    ///
+    /// returns: The binary of the first pattern the pool name contains, or the first binary if none
+    /// match.
+    pub fn select_binary_for_pool<'a>(pool_name: &str, binary_paths: &[(&str, &'a str)]) -> &'a str {
+        binary_paths
+            .iter()
+            .find(|(pattern, _)| pool_name.contains(pattern))
+            .or(binary_paths.first())
+            .map_or("", |(_, binary_path)| binary_path)
+    }
+
     pub fn new() -> MemorySysTraceParser {
         MemorySysTraceParser {
             time: 0,
@@ -180,9 +621,165 @@ impl MemorySysTraceParser {
             symbols: HashMap::new(),
             prefix: String::new(),
             counter: 0,
+            collapse_duplicate_updates: false,
+            memory_accesses: Vec::new(),
+            pending_line: String::new(),
+            binary_path: String::new(),
+            symbolicator: Arc::new(Self::default_symbolicator(None)),
+            symbolicate: true,
+            gaddr2line_path: None,
+            queued_pool_id: None,
+            update_pool_ids: Vec::new(),
         }
     }
 
+    /// Sets the gaddr2line used to symbolise binaries that addr2line can't read, such as those
+    /// built by the Green Hills toolchain.
+    ///
+    /// # Arguments
+    ///
+    /// * `gaddr2line_path`: Path to gaddr2line, or None for DEFAULT_GADDR2LINE_PATH.
+    ///
+    /// returns: MemorySysTraceParser
+    pub fn with_gaddr2line_path(mut self, gaddr2line_path: Option<&str>) -> MemorySysTraceParser {
+        self.symbolicator = Arc::new(Self::default_symbolicator(gaddr2line_path));
+        self.gaddr2line_path = gaddr2line_path.map(str::to_string);
+        self
+    }
+
+    /// Sets the backend used to resolve callstack addresses to symbols. Defaults to addr2line,
+    /// falling back to gaddr2line for binaries addr2line can't read.
+    ///
+    /// # Arguments
+    ///
+    /// * `symbolicator`: Symbolicator to use.
+    ///
+    /// returns: MemorySysTraceParser
+    pub fn with_symbolicator(mut self, symbolicator: Box<dyn Symbolicator>) -> MemorySysTraceParser {
+        self.symbolicator = Arc::from(symbolicator);
+        self
+    }
+
+    /// Enables or disables symbolication. On by default. When off, the symbolicator is never run
+    /// and callstacks hold raw hex addresses, which makes large logs much faster to load.
+    ///
+    /// # Arguments
+    ///
+    /// * `symbolicate`: Whether to resolve callstack addresses to symbols.
+    ///
+    /// returns: MemorySysTraceParser
+    pub fn with_symbolication(mut self, symbolicate: bool) -> MemorySysTraceParser {
+        self.symbolicate = symbolicate;
+        self
+    }
+
+    fn default_symbolicator(gaddr2line_path: Option<&str>) -> Addr2LineSymbolicator {
+        let gaddr2line_path = gaddr2line_path.unwrap_or(DEFAULT_GADDR2LINE_PATH);
+        Addr2LineSymbolicator::new().with_fallback(Box::new(Gaddr2LineSymbolicator::new(gaddr2line_path)))
+    }
+
+    /// Enables or disables collapsing of exact-duplicate consecutive updates. Off by default.
+    /// Some traces log the same free twice in a row, which corrupts block counting.
+    /// 
+    /// # Arguments 
+    /// 
+    /// * `collapse_duplicate_updates`: Whether to collapse duplicates after parsing.
+    /// 
+    /// returns: MemorySysTraceParser 
+    pub fn with_duplicate_collapsing(mut self, collapse_duplicate_updates: bool) -> MemorySysTraceParser {
+        self.collapse_duplicate_updates = collapse_duplicate_updates;
+        self
+    }
+
+    /// Prepares the parser to follow a log that has already been parsed, so parse_log_incremental
+    /// continues where the full parse left off.
+    ///
+    /// # Arguments
+    ///
+    /// * `binary_path`: File path to threadxApp binary for debuginfo.
+    /// * `memory_updates`: Updates already parsed from the log, used to find the sizes of frees.
+    /// * `max_timestamp`: Max timestamp of the parsed log. New updates are timestamped after it.
+    ///
+    /// returns: MemorySysTraceParser
+    pub fn resume_after(mut self, binary_path: &str, memory_updates: Vec<MemoryUpdateType>, max_timestamp: u64) -> MemorySysTraceParser {
+        self.binary_path = binary_path.to_string();
+        self.memory_updates = memory_updates;
+        self.time = max_timestamp as usize;
+        self.counter = max_timestamp;
+        self
+    }
+
+    /// Parses bytes appended to a log, keeping parser state between calls so a log can be
+    /// followed as it is written. A line split across two calls is buffered until the rest of it
+    /// arrives. An update is only returned once the next allocation or free is read, as its
+    /// stacktrace may still be on its way.
+    ///
+    /// Stacktraces of updates baked before following began, and lines that fail to parse, are
+    /// skipped. Access records are not supported here and are discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_bytes`: Bytes appended to the log since the last call.
+    ///
+    /// returns: Updates completed by these bytes.
+    pub fn parse_log_incremental(&mut self, new_bytes: &str) -> Vec<MemoryUpdateType> {
+        self.pending_line.push_str(new_bytes);
+        let Some(last_newline) = self.pending_line.rfind('\n') else {
+            return Vec::new();
+        };
+        let partial_line = self.pending_line.split_off(last_newline + 1);
+        let complete_lines = std::mem::replace(&mut self.pending_line, partial_line);
+        self.symbolise_new_addresses(&complete_lines);
+
+        let mut new_updates = Vec::new();
+        for line in complete_lines.lines() {
+            if self.is_line_useless_and_load_pool(line) {
+                continue;
+            }
+            let record = match self.line_to_record(line) {
+                Ok(record) => record,
+                Err(error) => {
+                    eprintln!("{}", format!("{error}: {line}").yellow());
+                    continue;
+                }
+            };
+            match record {
+                RecordType::StackTrace(..) if self.record_queue.is_empty() => continue,
+                RecordType::StackTrace(..) => self.process_stacktrace(record),
+                _ => {
+                    if let Some(memory_update) = self.process_alloc_or_free(Some(record)) {
+                        self.memory_updates.push(memory_update.clone());
+                        self.counter += 1;
+                        new_updates.push(memory_update);
+                    }
+                }
+            }
+        }
+        self.memory_accesses.clear();
+        new_updates
+    }
+
+    /// Looks up symbols for stacktrace addresses that have not been seen before, using the binary
+    /// set by resume_after. Does nothing if there is no binary.
+    ///
+    /// # Arguments
+    ///
+    /// * `lines`: Complete lines from the log.
+    ///
+    /// returns: ()
+    fn symbolise_new_addresses(&mut self, lines: &str) {
+        if !std::path::Path::new(&self.binary_path).is_file() {
+            return;
+        }
+        let addresses: Vec<usize> = lines
+            .lines()
+            .filter(|line| line.split('>').nth(1).is_some_and(|dataline| dataline.trim().starts_with("^ ")))
+            .filter_map(|line| usize::from_str_radix(&Self::extract_trace_address(line), 16).ok())
+            .collect();
+        let binary_path = self.binary_path.clone();
+        self.resolve_uncached_symbols(&addresses, &binary_path);
+    }
+
     /// Parses a raw log, consuming itself and returning parse results.
     /// 
     /// # Arguments 
@@ -192,7 +789,10 @@ impl MemorySysTraceParser {
     /// 
     /// returns: ParseResults 
     fn parse_log_contents(mut self, log: &str, binary_path: &str) -> ParseResults {
-        self.parse_symbols(log, binary_path);
+        let binary_path = Self::resolve_binary_path(log, binary_path);
+        self.parse_symbols(log, binary_path.as_str());
+        // parse_symbols reads every line, so discard accesses it loaded without timestamps
+        self.memory_accesses.clear();
         let mut log_iter = log.split('\n').peekable();
         while let Some(line) = log_iter.peek() {
             println!("Reading line: {}", line.cyan());
@@ -206,11 +806,160 @@ impl MemorySysTraceParser {
             self.counter += 1;
         }
         println!("Processing complete.");
-        ParseResults::new(self.memory_updates, self.pool_list, self.counter)
+        let mut collapsed_duplicates = 0;
+        if self.collapse_duplicate_updates {
+            let mut previous_update = None;
+            let mut update_pool_ids = self.update_pool_ids.into_iter();
+            self.update_pool_ids = self.memory_updates
+                .iter()
+                .filter_map(|update| {
+                    let pool_id = update_pool_ids.next().flatten();
+                    let is_duplicate = previous_update.is_some_and(|previous_update| Self::is_consecutive_duplicate(previous_update, update));
+                    previous_update = Some(update);
+                    (!is_duplicate).then_some(pool_id)
+                })
+                .collect();
+            let (memory_updates, duplicates) = Self::collapse_consecutive_duplicates(self.memory_updates);
+            println!("Collapsed {} duplicate updates.", duplicates.yellow());
+            self.memory_updates = memory_updates;
+            self.counter = self.memory_updates.len() as u64;
+            collapsed_duplicates = duplicates;
+        }
+        let mut parse_results = ParseResults::new(self.memory_updates, self.pool_list, self.counter);
+        parse_results.collapsed_duplicates = collapsed_duplicates;
+        parse_results.update_pool_ids = self.update_pool_ids;
+        parse_results.parse_anomalies = Self::find_parse_anomalies(&parse_results.memory_updates);
+        if !parse_results.parse_anomalies.is_empty() {
+            println!("Found {} double frees or frees of unallocated addresses.", parse_results.parse_anomalies.len().yellow());
+        }
+        if !self.memory_accesses.is_empty() {
+            parse_results.memory_accesses = Some(self.memory_accesses);
+        }
+        parse_results
+    }
+
+    /// Finds frees that do not match a live allocation. These are the double frees and orphan frees
+    /// found by AnomalyDetector.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates in the order they were parsed.
+    ///
+    /// returns: Vec<ParseAnomaly>, sorted by timestamp.
+    pub fn find_parse_anomalies(memory_updates: &[MemoryUpdateType]) -> Vec<ParseAnomaly> {
+        AnomalyDetector::find_anomalies(memory_updates, None, AnomalySeverity::Info)
+            .into_iter()
+            .filter(|anomaly| matches!(anomaly.get_kind(), AnomalyKind::DoubleFree | AnomalyKind::OrphanFree))
+            .map(|anomaly| ParseAnomaly::new(anomaly.get_timestamp(), anomaly.get_address(), anomaly.get_kind()))
+            .collect()
+    }
+
+    /// Removes updates that are exact duplicates of the update immediately before them - same
+    /// kind, address, size and real timestamp. Operation timestamps are reassigned afterwards so
+    /// they remain consecutive.
+    /// 
+    /// # Arguments 
+    /// 
+    /// * `memory_updates`: Updates in the order they were parsed.
+    /// 
+    /// returns: (deduplicated updates, number of updates removed) 
+    pub fn collapse_consecutive_duplicates(memory_updates: Vec<MemoryUpdateType>) -> (Vec<MemoryUpdateType>, u64) {
+        let mut collapsed_updates: Vec<MemoryUpdateType> = Vec::new();
+        let mut duplicates = 0;
+        for update in memory_updates {
+            if collapsed_updates.last().is_some_and(|prev_update| Self::is_consecutive_duplicate(prev_update, &update)) {
+                duplicates += 1;
+                continue;
+            }
+            collapsed_updates.push(update);
+        }
+        for (index, update) in collapsed_updates.iter_mut().enumerate() {
+            update.set_timestamp(index);
+        }
+        (collapsed_updates, duplicates)
+    }
+
+    fn is_consecutive_duplicate(prev_update: &MemoryUpdateType, update: &MemoryUpdateType) -> bool {
+        std::mem::discriminant(prev_update) == std::mem::discriminant(update)
+            && prev_update.get_absolute_address() == update.get_absolute_address()
+            && prev_update.get_absolute_size() == update.get_absolute_size()
+            && prev_update.get_real_timestamp() == update.get_real_timestamp()
     }
     
 
 
+    /// Finds the binary header in a log, if there is one. The header is a line in the form
+    /// "BINARY path build_id", where the build ID is optional.
+    /// 
+    /// # Arguments 
+    /// 
+    /// * `log`: Raw log file.
+    /// 
+    /// returns: Option<(path, Option<build_id>)> 
+    pub fn parse_binary_header(log: &str) -> Option<(String, Option<String>)> {
+        for line in log.split('\n') {
+            let dataline = line.split('>').nth(1).unwrap_or(line).trim();
+            let mut split_dataline = dataline.split_whitespace();
+            if split_dataline.next() != Some("BINARY") {
+                continue;
+            }
+            let path = split_dataline.next()?.to_string();
+            let build_id = split_dataline.next().map(|build_id| build_id.to_lowercase());
+            return Some((path, build_id));
+        }
+        None
+    }
+
+    /// Picks the binary to use for symbolication. If no binary path was supplied, the path in the
+    /// log's binary header is used, as long as it exists. If the header has a build ID, it is
+    /// compared against the chosen binary and a warning is printed on mismatch, as symbols resolved
+    /// against the wrong binary are garbage.
+    /// 
+    /// # Arguments 
+    /// 
+    /// * `log`: Raw log file.
+    /// * `binary_path`: Binary path supplied by the user. May be empty.
+    /// 
+    /// returns: Binary path to use. 
+    fn resolve_binary_path(log: &str, binary_path: &str) -> String {
+        let Some((header_path, header_build_id)) = Self::parse_binary_header(log) else {
+            return binary_path.to_string();
+        };
+
+        let mut resolved_path = binary_path.to_string();
+        if binary_path.is_empty() {
+            if std::path::Path::new(&header_path).exists() {
+                println!("Using binary from log header: {}", header_path.cyan());
+                resolved_path = header_path;
+            } else {
+                eprintln!("{}", format!("[MemorySysTraceParser::resolve_binary_path]: Binary in log header does not exist: {header_path}").yellow());
+            }
+        }
+
+        if let Some(header_build_id) = header_build_id {
+            match Self::read_build_id(&resolved_path) {
+                Some(build_id) if build_id == header_build_id => {}
+                Some(build_id) => eprintln!("{}", format!("[MemorySysTraceParser::resolve_binary_path]: Build ID mismatch: log was produced by {header_build_id}, but {resolved_path} is {build_id}. Symbols are likely to be wrong.").yellow()),
+                None => eprintln!("{}", format!("[MemorySysTraceParser::resolve_binary_path]: Unable to read build ID of {resolved_path} to compare against {header_build_id}").yellow()),
+            }
+        }
+        resolved_path
+    }
+
+    /// Reads the build ID of a binary as a lowercase hex string.
+    /// 
+    /// # Arguments 
+    /// 
+    /// * `binary_path`: Path to the binary.
+    /// 
+    /// returns: The build ID, or None if the binary cannot be read or has no build ID. 
+    fn read_build_id(binary_path: &str) -> Option<String> {
+        let buffer = std::fs::read(binary_path).ok()?;
+        let object = object::File::parse(&*buffer).ok()?;
+        let build_id = object.build_id().ok()??;
+        Some(build_id.iter().map(|byte| format!("{byte:02x}")).collect())
+    }
+
     /// Computes the range of addresses spanned by a list of updates.
     /// This is used to compute the bounds of the default pool if no pools are specified in the log.
     /// 
@@ -244,6 +993,7 @@ impl MemorySysTraceParser {
     /// Free information
     /// Stacktrace information
     /// Pool information (calls load_poolbounds and load_poolname accordingly)
+    /// Access information (calls load_access)
     ///
     /// # Arguments
     ///
@@ -264,7 +1014,15 @@ impl MemorySysTraceParser {
                 self.load_poolname(trimmed_string);
                 return true;
             }
-            if trimmed_string.starts_with("+ ") || trimmed_string.starts_with("- ") || trimmed_string.starts_with("^ ") {
+            if trimmed_string.starts_with("@ ") {
+                // mark access lines as useless to avoid interfering with alloc/free parsing
+                if let Err(error) = self.load_access(trimmed_string) {
+                    eprintln!("{}", format!("[MemorySysTraceParser::is_line_useless_and_load_pool]: {error}").yellow());
+                }
+                return true;
+            }
+            if trimmed_string.starts_with("+ ") || trimmed_string.starts_with("- ") || trimmed_string.starts_with("~ ")
+                || trimmed_string.starts_with("^ ") {
                 return false;
             }
         }
@@ -329,6 +1087,34 @@ impl MemorySysTraceParser {
         self.potential_pool = MemoryPool::default();
     }
 
+    /// Loads a memory access record into the parser's list of accesses.
+    /// The access is timestamped with the latest alloc/free seen so far - including one still
+    /// waiting in the record queue for its stacktrace - so it can later be ordered against updates.
+    /// 
+    /// # Arguments 
+    /// 
+    /// * `line`: Raw line from the log, in the form "@ address size". Size is optional and 
+    ///   defaults to 1 byte.
+    /// 
+    /// returns: Result<(), DamselflyError>, with DamselflyError::InvalidLog if the address or size
+    /// is missing or not hex. Nothing is loaded in that case.
+    fn load_access(&mut self, line: &str) -> Result<(), DamselflyError> {
+        let split_line = line.split(' ').collect::<Vec<_>>();
+        let address = split_line
+            .get(1)
+            .ok_or_else(|| DamselflyError::InvalidLog(format!("Access has no address: {line}")))?;
+        let address = usize::from_str_radix(address, 16)
+            .map_err(|error| DamselflyError::InvalidLog(format!("Failed to parse access address {address}: {error}")))?;
+        let size = match split_line.get(2) {
+            Some(size) => usize::from_str_radix(size, 16)
+                .map_err(|error| DamselflyError::InvalidLog(format!("Failed to parse access size {size}: {error}")))?,
+            None => 1,
+        };
+        let updates_seen = self.time + usize::from(!self.record_queue.is_empty());
+        self.memory_accesses.push(MemoryAccess::new(address, size, updates_seen.saturating_sub(1)));
+        Ok(())
+    }
+
     /// Extracts all memory addresses from the log, ignoring lines that are deemed useless by
     /// is_line_useless.
     ///
@@ -388,27 +1174,68 @@ impl MemorySysTraceParser {
     /// returns: nothing, as the longest prefix and symbols are stored as struct fields.
     fn parse_symbols(&mut self, log: &str, binary_path: &str) {
         let addresses = self.extract_addresses_from_log(log);
-        let mut file = File::open(binary_path).unwrap();
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer).unwrap();
-        let object = object::File::parse(&*buffer).unwrap();
-        let ctx = Context::new(&object).unwrap();
-
-        let mut symbols = Vec::new();
-        for address in &addresses {
-            let mut symbol = String::new();
-            if let Ok(Some(location)) = ctx.find_location(*address as u64) {
-                symbol.push_str(location.file.unwrap());
-                symbol.push(':');
-                symbol.push_str(location.line.unwrap().to_string().as_str());
-                symbols.push(symbol);
-            } else {
-                symbols.push("[UNKNOWN SYMBOL]".to_string());
-            }
+        self.symbols.clear();
+        self.prefix = if self.resolve_uncached_symbols(&addresses, binary_path) {
+            Self::longest_common_prefix(&self.symbols.values().cloned().collect())
+        } else {
+            String::new()
+        };
+    }
+
+    /// Resolves addresses that haven't been resolved yet in this parse. self.symbols caches every
+    /// resolution, so however often an address repeats in the log, it is passed to the
+    /// symbolicator once, and all new addresses are passed in a single batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `addresses`: Addresses to resolve. May contain duplicates.
+    /// * `binary_path`: Path to the threadApp binary for debuginfo.
+    ///
+    /// returns: false if the addresses couldn't be symbolicated, in which case their raw addresses
+    /// are cached instead. True otherwise.
+    fn resolve_uncached_symbols(&mut self, addresses: &[usize], binary_path: &str) -> bool {
+        let uncached_addresses: Vec<usize> = addresses
+            .iter()
+            .copied()
+            .filter(|address| !self.symbols.contains_key(address))
+            .collect::<HashSet<usize>>()
+            .into_iter()
+            .collect();
+        if uncached_addresses.is_empty() {
+            return true;
+        }
+        let symbols = self.find_symbols(&uncached_addresses, binary_path);
+        let symbolicated = symbols.is_some();
+        let symbols = symbols.unwrap_or_else(|| Self::raw_address_symbols(&uncached_addresses));
+        self.symbols.extend(uncached_addresses.into_iter().zip(symbols));
+        symbolicated
+    }
+
+    /// Finds symbols (FILENAME:LINE_NO) of addresses using the parser's symbolicator.
+    ///
+    /// # Arguments
+    ///
+    /// * `addresses`: Addresses to look up.
+    /// * `binary_path`: Path to the threadApp binary for debuginfo.
+    ///
+    /// returns: A symbol for each address, in the same order, or None if there is no binary, it
+    /// can't be symbolicated, or symbolication is disabled.
+    fn find_symbols(&self, addresses: &[usize], binary_path: &str) -> Option<Vec<String>> {
+        if binary_path.is_empty() || !self.symbolicate {
+            return None;
         }
+        self.symbolicator.symbolicate(addresses, binary_path)
+    }
 
-        self.prefix = Self::longest_common_prefix(&symbols);
-        self.symbols = addresses.into_iter().zip(symbols).collect();
+    /// Formats addresses as hex, for when they can't be symbolised.
+    ///
+    /// # Arguments
+    ///
+    /// * `addresses`: Addresses to format.
+    ///
+    /// returns: "0x..." for each address, in the same order.
+    fn raw_address_symbols(addresses: &[usize]) -> Vec<String> {
+        addresses.iter().map(|address| format!("0x{address:x}")).collect()
     }
 
     /// Looks up the symbol corresponding to a hex address.
@@ -479,7 +1306,10 @@ impl MemorySysTraceParser {
                 .expect("[MemorySysTraceParser::process_operation]: Failed to process line");
             match record {
                 RecordType::StackTrace(_, _) => self.process_stacktrace(record),
-                _ => { baked_instruction = self.process_alloc_or_free(Some(record)) },
+                _ => {
+                    baked_instruction = self.process_alloc_or_free(Some(record));
+                    self.queued_pool_id = Self::extract_pool_id(line);
+                },
             }
             if baked_instruction.is_some() { break; }
         }
@@ -556,6 +1386,13 @@ impl MemorySysTraceParser {
                             free_callstack.push('\n');
                         }
                     }
+                    RecordType::Reallocation(old_address, address, _, ref mut reallocation_callstack, _) => {
+                        // Traces may give either the old or the new address
+                        if *trace_address == old_address || *trace_address == address {
+                            reallocation_callstack.push_str(trace_callstack);
+                            reallocation_callstack.push('\n');
+                        }
+                    }
                     RecordType::StackTrace(_, _) =>
                         panic!("[MemorySysTraceParser::bake_memory_update]: First instruction in instruction queue is a stacktrace, but it should be an alloc/free"),
                     RecordType::PoolBounds(_, _) =>
@@ -567,6 +1404,8 @@ impl MemorySysTraceParser {
         }
 
         // Stack tracing complete, so we instantiate the MemoryUpdateType with the required data and return it
+        let pool_id = self.queued_pool_id.take();
+        self.update_pool_ids.push(pool_id);
         let memory_update;
         match first_rec {
             RecordType::Allocation(address, size, callstack, real_timestamp) => {
@@ -579,6 +1418,12 @@ impl MemorySysTraceParser {
                 memory_update = Free::new(address, free_size, Arc::new(callstack), self.time, real_timestamp).wrap_in_enum();
                 self.time += 1;
             },
+            RecordType::Reallocation(old_address, address, size, callstack, real_timestamp) => {
+                // Like frees, the log does not say how many bytes the old region held
+                let old_size = self.find_latest_allocation_size(old_address);
+                memory_update = Reallocation::new(old_address, old_size, address, size, Arc::new(callstack), self.time, real_timestamp).wrap_in_enum();
+                self.time += 1;
+            },
             RecordType::StackTrace(..) => panic!("[MemorySysTraceParser::bake_memory_update]: First instruction in instruction queue is a stacktrace, but it should be an alloc/free"),
             RecordType::PoolBounds(..) => panic!("[MemorySysTraceParser::bake_memory_update]: First instruction in instruction queue is a poolbounds, but it should be an alloc/free"),
             RecordType::PoolName(..) => panic!("[MemorySysTraceParser::bake_memory_update]: First instruction in instruction queue is a poolname, but it should be an alloc/free"),
@@ -596,14 +1441,11 @@ impl MemorySysTraceParser {
     /// 
     /// returns: usize 
     fn find_latest_allocation_size(&self, address: usize) -> usize {
-        for memory_update in self.memory_updates.iter().rev() {
-            if let MemoryUpdateType::Allocation(allocation) = memory_update {
-                if allocation.get_absolute_address() == address {
-                    return allocation.get_absolute_size();
-                }
-            }
-        }
-        0
+        self.memory_updates
+            .iter()
+            .rev()
+            .find(|memory_update| !matches!(memory_update, MemoryUpdateType::Free(_)) && memory_update.get_absolute_address() == address)
+            .map_or(0, |allocation| allocation.get_absolute_size())
     }
 
     /// Processes a StackTrace record by pushing it to the record queue in the parser, which must later
@@ -676,6 +1518,10 @@ impl MemorySysTraceParser {
                 record = RecordType::Free(0, String::new(), String::new());
                 address_needed = true;
             },
+            "~" => {
+                record = RecordType::Reallocation(0, 0, 0, String::new(), String::new());
+                address_needed = true;
+            },
             "^" => {
                 record = {
                     let symbol = self.lookup_symbol(Self::extract_trace_address(split_dataline[2]))
@@ -706,6 +1552,14 @@ impl MemorySysTraceParser {
                 *default_address = address;
                 *default_real_timestamp = full_timestamp;
             },
+            RecordType::Reallocation(ref mut default_old_address, ref mut default_address, ref mut default_size, _, ref mut default_real_timestamp) => {
+                *default_old_address = address;
+                *default_address = usize::from_str_radix(split_dataline.get(2).ok_or("[MemorySysTraceParser::parse_line]: Realloc missing new address")?, 16)
+                    .expect("[MemorySysTraceParser::parse_line]: Failed to convert new address to decimal");
+                *default_size = usize::from_str_radix(split_dataline.get(3).ok_or("[MemorySysTraceParser::parse_line]: Realloc missing size")?, 16)
+                    .expect("[MemorySysTraceParser::parse_line]: Failed to read size");
+                *default_real_timestamp = full_timestamp;
+            },
             RecordType::StackTrace(ref mut default_address, _) => *default_address = address,
             RecordType::PoolBounds(ref mut default_address, ref mut default_size) => {
                 *default_address = usize::from_str_radix(split_dataline[1], 16)
@@ -721,6 +1575,26 @@ impl MemorySysTraceParser {
         Ok(record)
     }
 
+    /// Extracts the pool ID from an alloc, free or realloc line, given as an optional last field
+    /// after the other fields, e.g. "+ e150202c 14 malloc_pool", "- e150202c malloc_pool" or
+    /// "~ e150202c e1502040 20 malloc_pool".
+    ///
+    /// # Arguments
+    ///
+    /// * `line`: Raw alloc or free line from the log.
+    ///
+    /// returns: The pool ID, or None if the line does not give one.
+    fn extract_pool_id(line: &str) -> Option<String> {
+        let mut split_dataline = line.split('>').nth(1)?.split_whitespace();
+        let pool_id = match split_dataline.next()? {
+            "+" => split_dataline.nth(2),
+            "-" => split_dataline.nth(1),
+            "~" => split_dataline.nth(3),
+            _ => None,
+        };
+        pool_id.map(str::to_string)
+    }
+
     pub fn get_pool_list(&self) -> &MemoryPoolList {
         &self.pool_list
     }
@@ -728,10 +1602,19 @@ impl MemorySysTraceParser {
 
 #[cfg(test)]
 mod tests {
-    use crate::damselfly::consts::TEST_BINARY_PATH;
-    use crate::damselfly::memory::memory_parsers::{MemoryParser, MemorySysTraceParser, RecordType};
+    use std::collections::HashSet;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use crate::damselfly::consts::{TEST_BINARY_PATH, TEST_LOG};
+    use crate::damselfly::damselfly_error::DamselflyError;
+    use crate::damselfly::memory::anomaly::AnomalyKind;
+    use crate::damselfly::memory::memory_parsers::{MemoryParser, MemorySysTraceParser, ParseAnomaly, RecordType};
     use crate::damselfly::memory::memory_pool::MemoryPool;
-    use crate::damselfly::memory::memory_update::{MemoryUpdate, MemoryUpdateType};
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+    use crate::damselfly::memory::symbolicator::Symbolicator;
 
     #[test]
     fn is_line_useless_test() {
@@ -758,6 +1641,63 @@ mod tests {
         assert!(mst_parser.is_line_useless_and_load_pool(iter.peek().unwrap()));
     }
 
+    #[test]
+    fn parse_binary_header_test() {
+        let log = "00000001: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:  unknown > BINARY /tmp/threadxApp 0A1B2C\n\
+                   00000811: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> + 0 14";
+        let (path, build_id) = MemorySysTraceParser::parse_binary_header(log).unwrap();
+        assert_eq!(path, "/tmp/threadxApp");
+        assert_eq!(build_id, Some("0a1b2c".to_string()));
+
+        let (path, build_id) = MemorySysTraceParser::parse_binary_header("BINARY ./threadxApp").unwrap();
+        assert_eq!(path, "./threadxApp");
+        assert_eq!(build_id, None);
+
+        assert!(MemorySysTraceParser::parse_binary_header("00000811: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> + 0 14").is_none());
+    }
+
+    #[test]
+    fn load_access_test() {
+        let access_record = "00001070: 039dcb32 |V|A|005|        0 us   0003.677 s    < DT:0xE14DEEBC> @ e150202c 4";
+        let mut mst_parser = MemorySysTraceParser::new();
+        mst_parser.time = 3;
+        mst_parser.record_queue.push(RecordType::Allocation(0xe150202c, 20, "".to_string(), "".to_string()));
+        assert!(mst_parser.is_line_useless_and_load_pool(access_record));
+        assert_eq!(mst_parser.memory_accesses.len(), 1);
+        assert_eq!(mst_parser.memory_accesses[0].get_address(), 0xe150202c);
+        assert_eq!(mst_parser.memory_accesses[0].get_size(), 4);
+        assert_eq!(mst_parser.memory_accesses[0].get_timestamp(), 3);
+    }
+
+    #[test]
+    fn load_access_malformed_test() {
+        let mut mst_parser = MemorySysTraceParser::new();
+        assert!(matches!(mst_parser.load_access("@"), Err(DamselflyError::InvalidLog(_))));
+        assert!(matches!(mst_parser.load_access("@ e150202g 4"), Err(DamselflyError::InvalidLog(_))));
+        assert!(matches!(mst_parser.load_access("@ e150202c four"), Err(DamselflyError::InvalidLog(_))));
+        let access_record = "00001070: 039dcb32 |V|A|005|        0 us   0003.677 s    < DT:0xE14DEEBC> @ e150202c four";
+        assert!(mst_parser.is_line_useless_and_load_pool(access_record));
+        assert!(mst_parser.memory_accesses.is_empty());
+    }
+
+    #[test]
+    fn collapse_consecutive_duplicates_test() {
+        let updates = vec![
+            Allocation::new(0, 8, Arc::new("test".to_string()), 0, "0003.677 s".to_string()).wrap_in_enum(),
+            Free::new(0, 8, Arc::new("test".to_string()), 1, "0003.678 s".to_string()).wrap_in_enum(),
+            Free::new(0, 8, Arc::new("test".to_string()), 2, "0003.678 s".to_string()).wrap_in_enum(),
+            Allocation::new(0, 8, Arc::new("test".to_string()), 3, "0003.679 s".to_string()).wrap_in_enum(),
+            Free::new(0, 8, Arc::new("test".to_string()), 4, "0003.680 s".to_string()).wrap_in_enum(),
+        ];
+        let (collapsed_updates, duplicates) = MemorySysTraceParser::collapse_consecutive_duplicates(updates);
+        assert_eq!(duplicates, 1);
+        assert_eq!(collapsed_updates.len(), 4);
+        assert!(matches!(collapsed_updates[2], MemoryUpdateType::Allocation(_)));
+        for (index, update) in collapsed_updates.iter().enumerate() {
+            assert_eq!(update.get_timestamp(), index);
+        }
+    }
+
     #[test]
     fn bake_memory_update_alloc_test() {
         let mut mst_parser = MemorySysTraceParser::new();
@@ -863,6 +1803,7 @@ mod tests {
             RecordType::Free(..) => panic!("Wrong type: Free"),
             RecordType::StackTrace(..) => panic!("Wrong type: Stacktrace"),
             RecordType::PoolBounds(..) => panic!("Wrong type: PoolBounds"),
+            RecordType::Reallocation(..) => panic!("Wrong type: Reallocation"),
             RecordType::PoolName(..) => panic!("Wrong type: PoolName"),
         }
     }
@@ -896,6 +1837,7 @@ mod tests {
                 assert_eq!(allocation.get_absolute_size(), 4);
                 assert_eq!(*allocation.get_callstack(), "1\n2\n3\n");
             }
+            MemoryUpdateType::Reallocation(_) => panic!("Wrong type: Reallocation"),
             MemoryUpdateType::Free(_) => panic!("Wrong type: Free"),
         }
 
@@ -922,6 +1864,7 @@ mod tests {
                 assert_eq!(allocation.get_absolute_size(), 4);
                 assert_eq!(*allocation.get_callstack(), "4\n5\n6\n");
             }
+            MemoryUpdateType::Reallocation(_) => panic!("Wrong type: Reallocation"),
             MemoryUpdateType::Free(_) => panic!("Wrong type: Free"),
         }
 
@@ -932,6 +1875,7 @@ mod tests {
 
         match memory_update {
             MemoryUpdateType::Allocation(_) => panic!("Wrong type: Allocation"),
+            MemoryUpdateType::Reallocation(_) => panic!("Wrong type: Reallocation"),
             MemoryUpdateType::Free(free) => {
                 assert_eq!(free.get_absolute_address(), 0);
                 assert_eq!(*free.get_callstack(), "callstack3");
@@ -948,6 +1892,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_log_incremental_test() {
+        let mut mst_parser = MemorySysTraceParser::new();
+        let log = "\
+00001068: 039dcb32 |V|A|005|        0 us   0003.677 s    < DT:0xE14DEEBC> + e150202c 14
+00001069: 039dcb32 |V|A|005|        0 us   0003.677 s    < DT:0xE14DEEBC> ^ e150202c [e045d83b]
+00001087: 039dcdad |V|A|005|       17 us   0003.678 s    < DT:0xE14DEEBC> - e150202c
+00001088: 039dcdad |V|A|005|        0 us   0003.678 s    < DT:0xE14DEEBC> ^ e150202c [e045d889]
+00001089: 039dcdad |V|A|005|        0 us   0003.679 s    < DT:0xE14DEEBC> + e1502040 20
+";
+        // Split the log in the middle of the free line
+        let split_index = log.find("- e150202c").unwrap();
+        let first_updates = mst_parser.parse_log_incremental(&log[..split_index]);
+        assert!(first_updates.is_empty());
+        let second_updates = mst_parser.parse_log_incremental(&log[split_index..]);
+        assert_eq!(second_updates.len(), 2);
+        assert!(matches!(second_updates[0], MemoryUpdateType::Allocation(_)));
+        assert_eq!(second_updates[0].get_absolute_address(), 0xe150202c);
+        assert_eq!(second_updates[0].get_timestamp(), 0);
+        assert!(matches!(second_updates[1], MemoryUpdateType::Free(_)));
+        // The size of the free comes from the allocation parsed in the earlier call
+        assert_eq!(second_updates[1].get_absolute_size(), 0x14);
+        assert_eq!(second_updates[1].get_timestamp(), 1);
+        // The last allocation waits for the next update in case more stacktraces follow
+        let third_updates = mst_parser.parse_log_incremental(
+            "00001090: 039dcdad |V|A|005|        0 us   0003.680 s    < DT:0xE14DEEBC> - e1502040\n");
+        assert_eq!(third_updates.len(), 1);
+        assert_eq!(third_updates[0].get_absolute_address(), 0xe1502040);
+    }
+
+    #[test]
+    fn find_parse_anomalies_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates = vec![
+            Allocation::new(0, 8, callstack.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Free::new(16, 0, callstack.clone(), 3, "0".to_string()).wrap_in_enum(),
+            Allocation::new(0, 8, callstack.clone(), 4, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack, 5, "0".to_string()).wrap_in_enum(),
+        ];
+        assert_eq!(MemorySysTraceParser::find_parse_anomalies(&updates), vec![
+            ParseAnomaly::new(2, 0, AnomalyKind::DoubleFree),
+            ParseAnomaly::new(3, 16, AnomalyKind::OrphanFree),
+        ]);
+    }
+
     #[test]
     fn line_to_record_alloc_test() {
         let mst_parser = MemorySysTraceParser::new();
@@ -963,6 +1954,7 @@ mod tests {
             RecordType::Free(..) => panic!("Wrong record type: Free"),
             RecordType::StackTrace(..) => panic!("Wrong record type: Stacktrace"),
             RecordType::PoolBounds(..) => panic!("Wrong record type: PoolBounds"),
+            RecordType::Reallocation(..) => panic!("Wrong record type: Reallocation"),
             RecordType::PoolName(..) => panic!("Wrong record type: PoolName"),
         }
     }
@@ -981,6 +1973,7 @@ mod tests {
             }
             RecordType::StackTrace(..) => panic!("Wrong type: Stacktrace"),
             RecordType::PoolBounds(..) => panic!("Wrong type: PoolBounds"),
+            RecordType::Reallocation(..) => panic!("Wrong type: Reallocation"),
             RecordType::PoolName(..) => panic!("Wrong type: PoolName"),
         }
     }
@@ -997,6 +1990,7 @@ mod tests {
                 assert_eq!(address, 3780124716);
             }
             RecordType::PoolBounds(..) => panic!("Wrong type: PoolBounds"),
+            RecordType::Reallocation(..) => panic!("Wrong type: Reallocation"),
             RecordType::PoolName(..) => panic!("Wrong type: PoolName"),
         }
     }
@@ -1014,6 +2008,7 @@ mod tests {
                 assert_eq!(address, 3780124716);
                 assert_eq!(size, 100);
             }
+            RecordType::Reallocation(..) => panic!("Wrong type: Reallocation"),
             RecordType::PoolName(..) => panic!("Wrong type: PoolName"),
         }
     }
@@ -1028,12 +2023,45 @@ mod tests {
             RecordType::Free(..) => panic!("Wrong type: Free"),
             RecordType::StackTrace(..) => panic!("Wrong type: StackTrace"),
             RecordType::PoolBounds(..) => panic!("Wrong type: StackTrace"),
+            RecordType::Reallocation(..) => panic!("Wrong type: Reallocation"),
             RecordType::PoolName(name) => {
                 assert_eq!(name, "malloc_pool");
             }
         }
     }
 
+    #[test]
+    fn parse_gzipped_log_test() {
+        let log_path = std::env::temp_dir().join("memory_parsers_parse_gzipped_log_test.log.gz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(TEST_LOG.as_bytes()).unwrap();
+        std::fs::write(&log_path, encoder.finish().unwrap()).unwrap();
+
+        let gzipped_parse_results = MemorySysTraceParser::new().parse_log(log_path.to_str().unwrap(), "");
+        let parse_results = MemorySysTraceParser::new().parse_log_directly(TEST_LOG, "");
+        assert_eq!(gzipped_parse_results.memory_updates.len(), 5);
+        assert_eq!(gzipped_parse_results.memory_updates, parse_results.memory_updates);
+    }
+
+    #[test]
+    fn read_corrupt_log_test() {
+        // Gzip magic bytes, then a truncated stream
+        let log_path = std::env::temp_dir().join("memory_parsers_read_corrupt_log_test.log.gz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(TEST_LOG.as_bytes()).unwrap();
+        let gzipped_log = encoder.finish().unwrap();
+        std::fs::write(&log_path, &gzipped_log[..gzipped_log.len() / 2]).unwrap();
+        let log_path = log_path.to_str().unwrap();
+        assert!(matches!(MemorySysTraceParser::read_log(log_path), Err(DamselflyError::Io(_))));
+        let pools = MemorySysTraceParser::new().parse_log_contents_split_by_pools(log_path, &[], 0, 0, None);
+        assert!(matches!(pools, Err(DamselflyError::Io(_))));
+
+        let log_path = std::env::temp_dir().join("memory_parsers_read_corrupt_log_test.log");
+        std::fs::write(&log_path, [0xff, 0xfe, 0x00]).unwrap();
+        assert!(matches!(MemorySysTraceParser::read_log(log_path.to_str().unwrap()), Err(DamselflyError::InvalidLog(_))));
+        assert!(matches!(MemorySysTraceParser::read_log("/nonexistent/trace.log"), Err(DamselflyError::Io(_))));
+    }
+
     #[test]
     fn parse_log_test() {
         let mst_parser = MemorySysTraceParser::new();
@@ -1147,6 +2175,98 @@ mod tests {
                    &String::from("/work/hpdev/dune/src/fw/print/engine/PageBasedEngine/Bratwurst/Remote/LibBratwurstProtobuf/src/FormatterRasterInterfaceMessages.pb-c.c:208"));
     }
 
+    #[test]
+    fn parse_log_symbols_missing_binary_test() {
+        let mut mst_parser = MemorySysTraceParser::new();
+        let log = "\
+00000811: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> + e150202c 14
+00000812: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> ^ e150202c [e045d83b]
+00000827: 039da2f5 |V|A|005|       11 us   0003.677 s    < DT:0xE14DEEBC> ^ e150202c [e04865ef]
+";
+        mst_parser.parse_symbols(log, "./missing_binary");
+        assert_eq!(mst_parser.symbols.get(&0xe045d83b), Some(&String::from("0xe045d83b")));
+        assert_eq!(mst_parser.symbols.get(&0xe04865ef), Some(&String::from("0xe04865ef")));
+        assert!(mst_parser.prefix.is_empty());
+    }
+
+    #[test]
+    fn parse_log_resolves_each_address_once_test() {
+        /// Counts the addresses it is asked to resolve.
+        struct CountingSymbolicator(Arc<AtomicUsize>);
+
+        impl Symbolicator for CountingSymbolicator {
+            fn symbolicate(&self, addresses: &[usize], _binary_path: &str) -> Option<Vec<String>> {
+                self.0.fetch_add(addresses.len(), Ordering::SeqCst);
+                Some(addresses.iter().map(|address| format!("/src/{address:x}.c:1")).collect())
+            }
+        }
+
+        let mut log = String::new();
+        for index in 0..50 {
+            let trace_address = if index % 2 == 0 { "e045d83b" } else { "e04865ef" };
+            log.push_str(&format!("00000811: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> + {:x} 14\n", index * 32));
+            log.push_str(&format!("00000812: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> ^ {:x} [{trace_address}]\n", index * 32));
+        }
+        let resolutions = Arc::new(AtomicUsize::new(0));
+        let parse_results = MemorySysTraceParser::new()
+            .with_symbolicator(Box::new(CountingSymbolicator(Arc::clone(&resolutions))))
+            .parse_log_directly(&log, "./counted_binary");
+
+        assert_eq!(parse_results.memory_updates.len(), 50);
+        assert_eq!(resolutions.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn parse_log_without_symbolication_test() {
+        struct PanickingSymbolicator;
+
+        impl Symbolicator for PanickingSymbolicator {
+            fn symbolicate(&self, _addresses: &[usize], _binary_path: &str) -> Option<Vec<String>> {
+                panic!("symbolication should be skipped");
+            }
+        }
+
+        let log = "\
+00000811: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> + e150202c 14
+00000812: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> ^ e150202c [e045d83b]
+";
+        let parse_results = MemorySysTraceParser::new()
+            .with_symbolicator(Box::new(PanickingSymbolicator))
+            .with_symbolication(false)
+            .parse_log_directly(log, TEST_BINARY_PATH);
+        assert_eq!(parse_results.memory_updates.len(), 1);
+        assert!(parse_results.memory_updates[0].get_callstack().contains("0xe045d83b"));
+    }
+
+    #[test]
+    fn resymbolicate_callstacks_test() {
+        struct NamingSymbolicator;
+
+        impl Symbolicator for NamingSymbolicator {
+            fn symbolicate(&self, addresses: &[usize], _binary_path: &str) -> Option<Vec<String>> {
+                Some(addresses.iter().map(|address| format!("function_{address:x}")).collect())
+            }
+        }
+
+        let log = "\
+00000811: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> + e150202c 14
+00000812: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> ^ e150202c [e045d83b]
+";
+        let parse_results = MemorySysTraceParser::new()
+            .with_symbolication(false)
+            .parse_log_directly(log, "");
+        let raw_callstack = parse_results.memory_updates[0].get_callstack();
+        let parser = MemorySysTraceParser::new().with_symbolicator(Box::new(NamingSymbolicator));
+        let callstacks = HashSet::from([raw_callstack.clone()]);
+
+        let resolved_callstacks = parser.resymbolicate_callstacks(&callstacks, "threadxApp");
+        assert_eq!(resolved_callstacks.get(&raw_callstack).unwrap().as_str(), "function_e045d83b\n");
+        // Already resolved, so there is nothing left to replace
+        let resolved_callstack_set = HashSet::from([resolved_callstacks[&raw_callstack].clone()]);
+        assert!(parser.resymbolicate_callstacks(&resolved_callstack_set, "threadxApp").is_empty());
+        assert!(parser.resymbolicate_callstacks(&callstacks, "").is_empty());
+    }
+
     #[test]
     fn longest_common_prefix_test() {
         let strings = vec![String::from("/work/hpdev/dune/src/fw/sox_adapters/framework/mem/src/mem_mgr.cpp:1056"),
@@ -1154,6 +2274,128 @@ mod tests {
         assert_eq!(MemorySysTraceParser::longest_common_prefix(&strings), String::from("/work/hpdev/dune/src/fw/"));
     }
 
+    #[test]
+    fn split_interleaved_pools_test() {
+        let log_path = std::env::temp_dir().join("memory_parsers_split_interleaved_pools_test.log");
+        let log = "\
+00000161: 03c305a7 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLBOUNDS 1000 256
+00000162: 03c305a7 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLNAME heap_a
+00000163: 03c305a7 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLBOUNDS 2000 256
+00000164: 03c305f0 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLNAME heap_b
+00000165: 03c305f0 |V|A|005|        0 us   0003.938 s    < DT:  unknown > + 1000 10 heap_a
+00000166: 03c305f0 |V|A|005|        0 us   0003.938 s    < DT:  unknown > ^ 1000 [e03c2221]
+00000167: 03c305f0 |V|A|005|        0 us   0003.939 s    < DT:  unknown > + 2000 20 heap_b
+00000168: 03c305f0 |V|A|005|        0 us   0003.940 s    < DT:  unknown > + 1010 10 heap_a
+00000169: 03c305f0 |V|A|005|        0 us   0003.941 s    < DT:  unknown > + 3000 8 heap_c
+00000170: 03c305f0 |V|A|005|        0 us   0003.942 s    < DT:  unknown > - 2000 heap_b
+00000171: 03c305f0 |V|A|005|        0 us   0003.943 s    < DT:  unknown > - 1000 heap_a
+00000172: 03c305f0 |V|A|005|        0 us   0003.944 s    < DT:  unknown > + 2040 8
+00000173: 03c305f0 |V|A|005|        0 us   0003.944 s    < DT:  unknown > ^ 2040 [e03c2221]
+";
+        std::fs::write(&log_path, log).unwrap();
+        let mut pools = MemorySysTraceParser::new()
+            .parse_log_contents_split_by_pools(log_path.to_str().unwrap(), &[], 0, 0, None).unwrap();
+        pools.sort_by(|first, second| first.pool.get_name().cmp(second.pool.get_name()));
+        let pool_updates: Vec<(&str, Vec<usize>)> = pools
+            .iter()
+            .map(|pool| (pool.pool.get_name(), pool.memory_updates.iter().map(|update| update.get_timestamp()).collect()))
+            .collect();
+        assert_eq!(pool_updates, vec![
+            ("heap_a", vec![0, 2, 5]),
+            // The last update gives no pool ID, so it is routed by address
+            ("heap_b", vec![1, 4, 6]),
+            ("unknown", vec![3]),
+        ]);
+        assert_eq!(pools[2].pool.get_start(), 0x3000);
+        assert_eq!(pools[2].pool.get_size(), 8);
+    }
+
+    #[test]
+    fn split_by_pools_with_binary_per_pool_test() {
+        /// Records each batch it is asked to resolve, and names the binary in each symbol.
+        struct RecordingSymbolicator(Arc<Mutex<Vec<(String, usize)>>>);
+
+        impl Symbolicator for RecordingSymbolicator {
+            fn symbolicate(&self, addresses: &[usize], binary_path: &str) -> Option<Vec<String>> {
+                self.0.lock().unwrap().push((binary_path.to_string(), addresses.len()));
+                Some(addresses.iter().map(|address| format!("{address:x} ({binary_path})")).collect())
+            }
+        }
+
+        let log_path = std::env::temp_dir().join("memory_parsers_split_by_pools_with_binary_per_pool_test.log");
+        let log = "\
+00000161: 03c305a7 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLBOUNDS 1000 256
+00000162: 03c305a7 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLNAME core0_heap
+00000163: 03c305a7 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLBOUNDS 2000 256
+00000164: 03c305f0 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLNAME core1_heap
+00000165: 03c305f0 |V|A|005|        0 us   0003.938 s    < DT:  unknown > + 1000 10 core0_heap
+00000166: 03c305f0 |V|A|005|        0 us   0003.938 s    < DT:  unknown > ^ 1000 [e03c2221]
+00000167: 03c305f0 |V|A|005|        0 us   0003.939 s    < DT:  unknown > + 2000 20 core1_heap
+00000168: 03c305f0 |V|A|005|        0 us   0003.939 s    < DT:  unknown > ^ 2000 [e03c2221]
+00000169: 03c305f0 |V|A|005|        0 us   0003.940 s    < DT:  unknown > + 1010 10 core0_heap
+00000170: 03c305f0 |V|A|005|        0 us   0003.940 s    < DT:  unknown > ^ 1010 [e04865ef]
+";
+        std::fs::write(&log_path, log).unwrap();
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let mut pools = MemorySysTraceParser::new()
+            .with_symbolicator(Box::new(RecordingSymbolicator(Arc::clone(&batches))))
+            .parse_log_contents_split_by_pools(log_path.to_str().unwrap(), &[("core0", "./core0.elf"), ("core1", "./core1.elf")], 0, 0, None).unwrap();
+        pools.sort_by(|first, second| first.pool.get_name().cmp(second.pool.get_name()));
+        let callstacks: Vec<(&str, Vec<String>)> = pools
+            .iter()
+            .map(|pool| (pool.pool.get_name(), pool.memory_updates.iter().map(|update| update.get_callstack().to_string()).collect()))
+            .collect();
+        assert_eq!(callstacks, vec![
+            ("core0_heap", vec!["e03c2221 (./core0.elf)\n".to_string(), "e04865ef (./core0.elf)\n".to_string()]),
+            ("core1_heap", vec!["e03c2221 (./core1.elf)\n".to_string()]),
+        ]);
+        // The log is parsed once, and each binary only resolves its own pools' addresses
+        let mut batches = batches.lock().unwrap().clone();
+        batches.sort();
+        assert_eq!(batches, vec![("./core0.elf".to_string(), 2), ("./core1.elf".to_string(), 1)]);
+    }
+
+    #[test]
+    fn parse_reallocations_test() {
+        let log = "\
+00000165: 03c305f0 |V|A|005|        0 us   0003.938 s    < DT:  unknown > + 1000 10
+00000166: 03c305f0 |V|A|005|        0 us   0003.939 s    < DT:  unknown > ~ 1000 1000 20
+00000167: 03c305f0 |V|A|005|        0 us   0003.940 s    < DT:  unknown > ~ 1000 2000 8
+00000168: 03c305f0 |V|A|005|        0 us   0003.940 s    < DT:  unknown > ^ 1000 [e03c2221]
+00000169: 03c305f0 |V|A|005|        0 us   0003.941 s    < DT:  unknown > - 2000
+00000170: 03c305f0 |V|A|005|        0 us   0003.941 s    < DT:  unknown > ^ 2000 [e03c2221]
+";
+        let memory_updates = MemorySysTraceParser::new().parse_log_directly(log, "").memory_updates;
+        assert_eq!(memory_updates.len(), 4);
+        let reallocations: Vec<(usize, usize, usize, usize, bool)> = memory_updates[1..3]
+            .iter()
+            .map(|update| match update {
+                MemoryUpdateType::Reallocation(reallocation) => (
+                    reallocation.get_old_address(),
+                    reallocation.get_old_size(),
+                    update.get_absolute_address(),
+                    update.get_absolute_size(),
+                    update.get_callstack().is_empty(),
+                ),
+                _ => panic!("Wrong type: {update}"),
+            })
+            .collect();
+        // In place, then moving, with old sizes taken from the latest allocation of the old address
+        assert_eq!(reallocations, vec![(0x1000, 0x10, 0x1000, 0x20, true), (0x1000, 0x20, 0x2000, 0x8, false)]);
+        // Frees after a reallocation free its new size
+        assert_eq!(memory_updates[3].get_absolute_size(), 0x8);
+        assert!(MemorySysTraceParser::find_parse_anomalies(&memory_updates).is_empty());
+    }
+
+    #[test]
+    fn select_binary_for_pool_test() {
+        let binary_paths = [("core0", "./core0.elf"), ("core1", "./core1.elf")];
+        assert_eq!(MemorySysTraceParser::select_binary_for_pool("core1_heap", &binary_paths), "./core1.elf");
+        assert_eq!(MemorySysTraceParser::select_binary_for_pool("core0_heap", &binary_paths), "./core0.elf");
+        assert_eq!(MemorySysTraceParser::select_binary_for_pool("shared_heap", &binary_paths), "./core0.elf");
+        assert_eq!(MemorySysTraceParser::select_binary_for_pool("shared_heap", &[]), "");
+    }
+
     #[test]
     fn get_pool_bounds_test() {
         let mst_parser = MemorySysTraceParser::new();
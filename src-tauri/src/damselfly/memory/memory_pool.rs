@@ -1,7 +1,8 @@
 //! A pool of memory.
 use std::cmp::Ordering;
+use serde::{Deserialize, Serialize};
 
-#[derive(Default, Clone, Hash)]
+#[derive(Default, Clone, Hash, Serialize, Deserialize)]
 pub struct MemoryPool {
     start: usize,
     size: usize,
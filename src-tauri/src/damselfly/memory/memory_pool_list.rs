@@ -21,4 +21,25 @@ impl MemoryPoolList {
     pub fn get_pools(&self) -> &HashSet<MemoryPool> {
         &self.pools
     }
+
+    /// Coalesces pools whose [start, start + size) ranges intersect into a single pool spanning
+    /// all of them, named after its constituent pools in address order. Use this when an
+    /// allocator reuses the same region under different pool names.
+    pub fn merge_overlapping(&mut self) {
+        let mut pools: Vec<MemoryPool> = self.pools.drain().collect();
+        pools.sort_by(|a, b| a.get_start().cmp(&b.get_start()).then_with(|| a.get_name().cmp(b.get_name())));
+
+        let mut merged_pools: Vec<MemoryPool> = Vec::new();
+        for pool in pools {
+            match merged_pools.last_mut() {
+                Some(merged_pool) if pool.get_start() < merged_pool.get_start() + merged_pool.get_size() => {
+                    let stop = (merged_pool.get_start() + merged_pool.get_size()).max(pool.get_start() + pool.get_size());
+                    merged_pool.set_size(stop - merged_pool.get_start());
+                    merged_pool.set_name(format!("{}+{}", merged_pool.get_name(), pool.get_name()));
+                }
+                _ => merged_pools.push(pool),
+            }
+        }
+        self.pools = merged_pools.into_iter().collect();
+    }
 }
\ No newline at end of file
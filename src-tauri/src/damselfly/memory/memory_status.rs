@@ -16,6 +16,9 @@ pub enum MemoryStatus {
     PartiallyAllocated(usize, usize, usize, Arc<String>),
     /// parent address, total size, address, callstack
     Free(usize, usize, usize, Arc<String>),
+    /// Guard bytes (redzone) padding an allocation.
+    /// parent address, total size, address, callstack
+    Guard(usize, usize, usize, Arc<String>),
     /// address
     Unused(usize),
 }
@@ -42,6 +45,7 @@ impl MemoryStatus {
             MemoryStatus::Allocated(parent_address, _, _, _) => Some(*parent_address),
             MemoryStatus::PartiallyAllocated(parent_address, _, _, _) => Some(*parent_address),
             MemoryStatus::Free(parent_address, _, _, _) => Some(*parent_address),
+            MemoryStatus::Guard(parent_address, _, _, _) => Some(*parent_address),
             MemoryStatus::Unused(_) => None,
         }
     }
@@ -51,6 +55,7 @@ impl MemoryStatus {
             MemoryStatus::Allocated(_, _, address, _) => *address,
             MemoryStatus::PartiallyAllocated(_, _, address, _) => *address,
             MemoryStatus::Free(_, _, address, _) => *address,
+            MemoryStatus::Guard(_, _, address, _) => *address,
             MemoryStatus::Unused(address) => *address
         }
     }
@@ -68,6 +73,9 @@ impl Display for MemoryStatus {
             MemoryStatus::Free(parent_address, size, _address, callstack) => {
                 format!("F {} {} {}", parent_address, size, callstack)
             }
+            MemoryStatus::Guard(parent_address, size, _address, callstack) => {
+                format!("G {} {} {}", parent_address, size, callstack)
+            }
             MemoryStatus::Unused(_address) => "U".to_string(),
         };
         write!(f, "{}", str)
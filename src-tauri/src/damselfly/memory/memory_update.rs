@@ -1,6 +1,7 @@
-//! A memory update: Allocation or Free.
+//! A memory update: Allocation, Free or Reallocation.
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
+use owo_colors::OwoColorize;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::{MapAccess, SeqAccess};
@@ -8,7 +9,8 @@ use serde::de::{MapAccess, SeqAccess};
 #[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub enum MemoryUpdateType {
     Allocation(Allocation),
-    Free(Free)
+    Free(Free),
+    Reallocation(Reallocation),
 }
 
 impl MemoryUpdateType {
@@ -16,6 +18,7 @@ impl MemoryUpdateType {
         match self {
             MemoryUpdateType::Allocation(allocation) => allocation.get_absolute_address(),
             MemoryUpdateType::Free(free) => free.get_absolute_address(),
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.get_absolute_address(),
         }
     }
 
@@ -23,6 +26,7 @@ impl MemoryUpdateType {
         match self {
             MemoryUpdateType::Allocation(allocation) => allocation.set_absolute_address(new_address),
             MemoryUpdateType::Free(free) => free.set_absolute_address(new_address),
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.set_absolute_address(new_address),
         }
     }
     
@@ -30,6 +34,7 @@ impl MemoryUpdateType {
         match self {
             MemoryUpdateType::Allocation(allocation) => allocation.get_absolute_size(),
             MemoryUpdateType::Free(free) => free.get_absolute_size(),
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.get_absolute_size(),
         }
     }
     
@@ -37,6 +42,7 @@ impl MemoryUpdateType {
         match self {
             MemoryUpdateType::Allocation(allocation) => allocation.set_absolute_size(new_size),
             MemoryUpdateType::Free(free) => free.set_absolute_size(new_size),
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.set_absolute_size(new_size),
         }
     }
 
@@ -44,6 +50,15 @@ impl MemoryUpdateType {
         match self {
             MemoryUpdateType::Allocation(allocation) => allocation.get_callstack(),
             MemoryUpdateType::Free(free) => free.get_callstack(),
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.get_callstack(),
+        }
+    }
+
+    pub fn set_callstack(&mut self, new_callstack: Arc<String>) {
+        match self {
+            MemoryUpdateType::Allocation(allocation) => allocation.set_callstack(new_callstack),
+            MemoryUpdateType::Free(free) => free.set_callstack(new_callstack),
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.set_callstack(new_callstack),
         }
     }
 
@@ -51,6 +66,7 @@ impl MemoryUpdateType {
         match self {
             MemoryUpdateType::Allocation(allocation) => allocation.get_absolute_address(),
             MemoryUpdateType::Free(free) => free.get_absolute_address(),
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.get_absolute_address(),
         }
     }
 
@@ -58,6 +74,7 @@ impl MemoryUpdateType {
         match self {
             MemoryUpdateType::Allocation(allocation) => allocation.get_absolute_address() + allocation.get_absolute_size(),
             MemoryUpdateType::Free(free) => free.get_absolute_address() + free.get_absolute_size(),
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.get_absolute_address() + reallocation.get_absolute_size(),
         }
     }
     
@@ -65,6 +82,7 @@ impl MemoryUpdateType {
         match self {
             MemoryUpdateType::Allocation(allocation) => allocation.get_timestamp(),
             MemoryUpdateType::Free(free) => free.get_timestamp(),
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.get_timestamp(),
         }
     }
 
@@ -72,6 +90,7 @@ impl MemoryUpdateType {
         match self {
             MemoryUpdateType::Allocation(allocation) => allocation.set_timestamp(new_timestamp),
             MemoryUpdateType::Free(free) => free.set_timestamp(new_timestamp),
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.set_timestamp(new_timestamp),
         }
     }
 
@@ -79,6 +98,74 @@ impl MemoryUpdateType {
         match self {
             MemoryUpdateType::Allocation(allocation) => allocation.get_real_timestamp(),
             MemoryUpdateType::Free(free) => free.get_real_timestamp(),
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.get_real_timestamp(),
+        }
+    }
+
+    /// Gets a copy of the update with padding removed, to show the address and size that were
+    /// actually logged.
+    ///
+    /// # Arguments
+    ///
+    /// * `left_padding`: Padding that was added to the left of the update (shifting its address).
+    /// * `right_padding`: Padding that was added to the right of the update (increasing its size).
+    ///
+    /// returns: MemoryUpdateType, with the address and size floored at 0 if the padding is larger
+    /// than them.
+    pub fn with_padding_trimmed(&self, left_padding: usize, right_padding: usize) -> MemoryUpdateType {
+        let mut update_with_padding_trimmed = self.clone();
+        update_with_padding_trimmed.set_absolute_size(self.get_absolute_size().saturating_sub(right_padding));
+        update_with_padding_trimmed.set_absolute_address(self.get_absolute_address().saturating_sub(left_padding));
+        update_with_padding_trimmed
+    }
+
+    /// Checks if trimming padding from the update would take its address or size below 0, which
+    /// means it was not padded as expected, e.g. an allocation at address 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `left_padding`: Padding that was added to the left of the update.
+    /// * `right_padding`: Padding that was added to the right of the update.
+    ///
+    /// returns: bool
+    pub fn padding_underflows(&self, left_padding: usize, right_padding: usize) -> bool {
+        let old_region_underflows = match self {
+            MemoryUpdateType::Reallocation(reallocation) =>
+                reallocation.get_old_address() < left_padding || reallocation.get_old_size() < right_padding,
+            _ => false,
+        };
+        old_region_underflows || self.get_absolute_address() < left_padding || self.get_absolute_size() < right_padding
+    }
+
+    /// Formats the update for the operation log with padding removed. If the padding cannot be
+    /// removed, the update is shown as is and flagged instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `left_padding`: Padding that was added to the left of the update.
+    /// * `right_padding`: Padding that was added to the right of the update.
+    ///
+    /// returns: String
+    pub fn to_string_with_padding_trimmed(&self, left_padding: usize, right_padding: usize) -> String {
+        if self.padding_underflows(left_padding, right_padding) {
+            eprintln!("{}", format!("[MemoryUpdateType::to_string_with_padding_trimmed]: Padding underflows update: {self}").yellow());
+            format!("{self} [padding underflow]")
+        } else {
+            self.with_padding_trimmed(left_padding, right_padding).to_string()
+        }
+    }
+
+    /// Splits a reallocation into the free of its old region and the allocation of its new
+    /// region, for consumers that only track allocs and frees. Other updates are left as is.
+    ///
+    /// returns: Vec<MemoryUpdateType>, with the free before the allocation.
+    pub fn split_reallocation(&self) -> Vec<MemoryUpdateType> {
+        match self {
+            MemoryUpdateType::Reallocation(reallocation) => {
+                let (free, allocation) = reallocation.to_free_and_allocation();
+                vec![free.wrap_in_enum(), allocation.wrap_in_enum()]
+            }
+            _ => vec![self.clone()],
         }
     }
 }
@@ -90,6 +177,8 @@ impl Display for MemoryUpdateType {
                 allocation.to_string(),
             MemoryUpdateType::Free(free) =>
                 free.to_string(),
+            MemoryUpdateType::Reallocation(reallocation) =>
+                reallocation.to_string(),
         };
         write!(f, "{}", str)
     }
@@ -101,6 +190,7 @@ pub trait MemoryUpdate {
     fn get_absolute_size(&self) -> usize;
     fn set_absolute_size(&mut self, new_size: usize);
     fn get_callstack(&self) -> Arc<String>;
+    fn set_callstack(&mut self, new_callstack: Arc<String>);
     fn get_timestamp(&self) -> usize;
     fn set_timestamp(&mut self, new_timestamp: usize);
     fn get_real_timestamp(&self) -> &String;
@@ -171,6 +261,65 @@ impl Free {
     }
 }
 
+/// A reallocation, which frees the old region and allocates the new one in a single operation. If
+/// the new address is the old address, the reallocation is in place.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Reallocation {
+    old_address: usize,
+    old_size: usize,
+    address: usize,
+    size: usize,
+    callstack: Arc<String>,
+    timestamp: usize,
+    real_timestamp: String,
+}
+
+impl Reallocation {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `old_address`: Address of the region being reallocated.
+    /// * `old_size`: Size of the region being reallocated.
+    /// * `address`: Address of the new region.
+    /// * `size`: Size of the new region.
+    /// * `callstack`: Callstack of the reallocation.
+    /// * `timestamp`: Absolute operation timestamp of the reallocation.
+    /// * `real_timestamp`: String representing the real timestamp e.g. "0020.939 s"
+    ///
+    /// returns: Reallocation
+    pub fn new(old_address: usize, old_size: usize, address: usize, size: usize, callstack: Arc<String>, timestamp: usize, real_timestamp: String) -> Reallocation {
+        Reallocation {
+            old_address,
+            old_size,
+            address,
+            size,
+            callstack,
+            timestamp,
+            real_timestamp,
+        }
+    }
+
+    pub fn get_old_address(&self) -> usize {
+        self.old_address
+    }
+
+    pub fn get_old_size(&self) -> usize {
+        self.old_size
+    }
+
+    /// Gets the free of the old region and the allocation of the new region that make up this
+    /// reallocation, both at its timestamp.
+    ///
+    /// returns: (Free, Allocation)
+    pub fn to_free_and_allocation(&self) -> (Free, Allocation) {
+        (
+            Free::new(self.old_address, self.old_size, self.get_callstack(), self.timestamp, self.real_timestamp.clone()),
+            Allocation::new(self.address, self.size, self.get_callstack(), self.timestamp, self.real_timestamp.clone()),
+        )
+    }
+}
+
 impl MemoryUpdate for Allocation {
     fn get_absolute_address(&self) -> usize {
         self.address
@@ -192,6 +341,10 @@ impl MemoryUpdate for Allocation {
         Arc::clone(&(self.callstack))
     }
 
+    fn set_callstack(&mut self, new_callstack: Arc<String>) {
+        self.callstack = new_callstack;
+    }
+
     fn get_timestamp(&self) -> usize {
         self.timestamp
     }
@@ -230,6 +383,10 @@ impl MemoryUpdate for Free {
         Arc::clone(&(self.callstack))
     }
 
+    fn set_callstack(&mut self, new_callstack: Arc<String>) {
+        self.callstack = new_callstack;
+    }
+
     fn get_timestamp(&self) -> usize {
         self.timestamp
     }
@@ -247,6 +404,54 @@ impl MemoryUpdate for Free {
     }
 }
 
+impl MemoryUpdate for Reallocation {
+    fn get_absolute_address(&self) -> usize {
+        self.address
+    }
+
+    /// Moves the new region, moving the old region by the same amount so padding is applied to
+    /// both.
+    fn set_absolute_address(&mut self, new_address: usize) {
+        self.old_address = self.old_address.wrapping_add(new_address.wrapping_sub(self.address));
+        self.address = new_address;
+    }
+
+    fn get_absolute_size(&self) -> usize {
+        self.size
+    }
+
+    /// Resizes the new region, resizing the old region by the same amount so padding is applied
+    /// to both.
+    fn set_absolute_size(&mut self, new_size: usize) {
+        self.old_size = self.old_size.wrapping_add(new_size.wrapping_sub(self.size));
+        self.size = new_size;
+    }
+
+    fn get_callstack(&self) -> Arc<String> {
+        Arc::clone(&(self.callstack))
+    }
+
+    fn set_callstack(&mut self, new_callstack: Arc<String>) {
+        self.callstack = new_callstack;
+    }
+
+    fn get_timestamp(&self) -> usize {
+        self.timestamp
+    }
+
+    fn set_timestamp(&mut self, new_timestamp: usize) {
+        self.timestamp = new_timestamp;
+    }
+
+    fn get_real_timestamp(&self) -> &String {
+        &self.real_timestamp
+    }
+
+    fn wrap_in_enum(self) -> MemoryUpdateType {
+        MemoryUpdateType::Reallocation(self)
+    }
+}
+
 impl Display for Allocation {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let str = format!("[{} {}] ALLOC: 0x{:x} {}B",
@@ -269,6 +474,19 @@ impl Display for Free {
     }
 }
 
+impl Display for Reallocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let str = format!("[{} {}] REALLOC: 0x{:x} {}B -> 0x{:x} {}B",
+                          self.get_timestamp(),
+                          self.get_real_timestamp(),
+                          self.old_address,
+                          self.old_size,
+                          self.get_absolute_address(),
+                          self.get_absolute_size());
+        write!(f, "{}", str)
+    }
+}
+
 /// Serialize implementations for IPC to the frontend via Tauri
 impl Serialize for Allocation {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
@@ -514,4 +732,79 @@ impl<'de> Deserialize<'de> for Free {
         const FIELDS: &[&str] = &["address", "size", "callstack", "timestamp", "real_timestamp"];
         deserializer.deserialize_struct("Free", FIELDS, FreeVisitor)
     }
-}
\ No newline at end of file
+}
+impl Serialize for Reallocation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let mut state = serializer.serialize_struct("Reallocation", 7)?;
+        state.serialize_field("old_address", &self.old_address)?;
+        state.serialize_field("old_size", &self.old_size)?;
+        state.serialize_field("address", &self.address)?;
+        state.serialize_field("size", &self.size)?;
+        state.serialize_field("callstack", &*self.callstack)?;
+        state.serialize_field("timestamp", &self.timestamp)?;
+        state.serialize_field("real_timestamp", &self.real_timestamp)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Reallocation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de> {
+        // Same fields as Reallocation, but with an owned callstack
+        #[derive(Deserialize)]
+        struct ReallocationFields {
+            old_address: usize,
+            old_size: usize,
+            address: usize,
+            size: usize,
+            callstack: String,
+            timestamp: usize,
+            real_timestamp: String,
+        }
+
+        let fields = ReallocationFields::deserialize(deserializer)?;
+        Ok(Reallocation::new(fields.old_address, fields.old_size, fields.address, fields.size,
+                             Arc::new(fields.callstack), fields.timestamp, fields.real_timestamp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::memory_update::{Allocation, MemoryUpdate, MemoryUpdateType, Reallocation};
+
+    #[test]
+    fn with_padding_trimmed_underflow_test() {
+        let update = Allocation::new(0, 4, Arc::new(String::new()), 0, "0001.000 s".to_string()).wrap_in_enum();
+        assert!(update.padding_underflows(8, 0));
+        assert!(update.padding_underflows(0, 8));
+        assert!(!update.padding_underflows(0, 4));
+        let trimmed = update.with_padding_trimmed(8, 8);
+        assert_eq!(trimmed.get_absolute_address(), 0);
+        assert_eq!(trimmed.get_absolute_size(), 0);
+        assert_eq!(update.to_string_with_padding_trimmed(8, 0), format!("{update} [padding underflow]"));
+
+        let update = Allocation::new(16, 24, Arc::new(String::new()), 0, "0001.000 s".to_string()).wrap_in_enum();
+        assert_eq!(update.to_string_with_padding_trimmed(8, 8), update.with_padding_trimmed(8, 8).to_string());
+        assert!(!update.to_string_with_padding_trimmed(8, 8).contains("underflow"));
+    }
+
+    #[test]
+    fn reallocation_padding_test() {
+        let mut update = Reallocation::new(64, 16, 128, 32, Arc::new(String::new()), 0, "0001.000 s".to_string()).wrap_in_enum();
+        update.set_absolute_address(update.get_absolute_address() - 8);
+        update.set_absolute_size(update.get_absolute_size() + 8);
+        let MemoryUpdateType::Reallocation(reallocation) = &update else {
+            panic!("Wrong type");
+        };
+        assert_eq!((reallocation.get_old_address(), reallocation.get_old_size()), (56, 24));
+        assert_eq!((update.get_absolute_address(), update.get_absolute_size()), (120, 40));
+        let (free, allocation) = reallocation.to_free_and_allocation();
+        assert_eq!((free.get_absolute_address(), free.get_absolute_size()), (56, 24));
+        assert_eq!((allocation.get_absolute_address(), allocation.get_absolute_size()), (120, 40));
+        assert_eq!(update.to_string(), "[0 0001.000 s] REALLOC: 0x38 24B -> 0x78 40B");
+
+        let json = serde_json::to_string(&update).unwrap();
+        assert_eq!(serde_json::from_str::<MemoryUpdateType>(&json).unwrap(), update);
+    }
+}
@@ -1,7 +1,8 @@
 //! Memory usage at a specific timestamp.
 use std::cmp::Ordering;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct MemoryUsage {
     memory_used_absolute: i128,
     distinct_blocks: u128,
@@ -9,6 +10,13 @@ pub struct MemoryUsage {
     largest_free_block: (usize, usize, usize),
     free_blocks: usize,
     free_segment_fragmentation: u128,
+    free_segment_fragmentation_percentage: f64,
+    memory_used_percentage: f64,
+    live_allocations: usize,
+    // free blocks with a live allocation on both sides
+    free_gaps: usize,
+    // mean size of the live allocated blocks, merging adjacent allocations
+    mean_allocated_block_size: f64,
     latest_operation: usize,
     timestamp_microseconds: u64,
     timestamp: u64
@@ -24,6 +32,11 @@ impl MemoryUsage {
             largest_free_block,
             free_blocks,
             free_segment_fragmentation,
+            free_segment_fragmentation_percentage: 0.0,
+            memory_used_percentage: 0.0,
+            live_allocations: 0,
+            free_gaps: 0,
+            mean_allocated_block_size: 0.0,
             latest_operation,
             timestamp_microseconds,
             timestamp
@@ -80,7 +93,51 @@ impl MemoryUsage {
     pub fn set_free_segment_fragmentation(&mut self, free_segment_fragmentation: u128) {
         self.free_segment_fragmentation = free_segment_fragmentation
     }
+
+    pub fn get_free_segment_fragmentation_percentage(&self) -> f64 {
+        self.free_segment_fragmentation_percentage
+    }
+
+    pub fn set_free_segment_fragmentation_percentage(&mut self, free_segment_fragmentation_percentage: f64) {
+        self.free_segment_fragmentation_percentage = free_segment_fragmentation_percentage
+    }
     
+    pub fn get_memory_used_percentage(&self) -> f64 {
+        self.memory_used_percentage
+    }
+
+    pub fn set_memory_used_percentage(&mut self, memory_used_percentage: f64) {
+        self.memory_used_percentage = memory_used_percentage
+    }
+
+    pub fn get_live_allocations(&self) -> usize { self.live_allocations }
+
+    pub fn set_live_allocations(&mut self, live_allocations: usize) {
+        self.live_allocations = live_allocations;
+    }
+
+    pub fn get_free_gaps(&self) -> usize { self.free_gaps }
+
+    pub fn set_free_gaps(&mut self, free_gaps: usize) {
+        self.free_gaps = free_gaps;
+    }
+
+    pub fn get_mean_allocated_block_size(&self) -> f64 { self.mean_allocated_block_size }
+
+    pub fn set_mean_allocated_block_size(&mut self, mean_allocated_block_size: f64) {
+        self.mean_allocated_block_size = mean_allocated_block_size;
+    }
+
+    /// Gets the mean size of the live allocations.
+    ///
+    /// returns: Bytes in use divided by the number of live allocations, or 0 if there are none.
+    pub fn get_average_allocation_size(&self) -> f64 {
+        if self.live_allocations == 0 {
+            return 0.0;
+        }
+        self.memory_used_absolute as f64 / self.live_allocations as f64
+    }
+
     pub fn get_timestamp_microseconds(&self) -> u64 { self.timestamp_microseconds }
     
     pub fn set_timestamp_microseconds(&mut self, timestamp_microseconds: u64) {
@@ -1,5 +1,6 @@
 //! Generates MemoryUsages.
 use std::cmp::{max};
+use std::collections::HashSet;
 use owo_colors::OwoColorize;
 use crate::damselfly::memory::memory_update::{MemoryUpdate, MemoryUpdateType};
 use crate::damselfly::memory::memory_usage::MemoryUsage;
@@ -14,6 +15,18 @@ pub struct MemoryUsageFactory {
     left_padding: usize,
     right_padding: usize,
     counter: u64,
+    // Running stats after the last update processed, so appended updates can continue from them
+    current_usage: i128,
+    max_usage: i128,
+    peak_usage_timestamp: u64,
+    max_free_blocks: u128,
+    max_free_segment_fragmentation: u128,
+    max_largest_free_block: usize,
+    max_distinct_blocks: u128,
+    distinct_block_counter: DistinctBlockCounter,
+    live_allocations: HashSet<usize>,
+    // (total bytes allocated, total bytes freed)
+    lifetime_byte_totals: (u128, u128),
 }
 
 impl MemoryUsageFactory {
@@ -38,6 +51,16 @@ impl MemoryUsageFactory {
             left_padding,
             right_padding,
             counter: 0,
+            current_usage: 0,
+            max_usage: 0,
+            peak_usage_timestamp: 0,
+            max_free_blocks: 0,
+            max_free_segment_fragmentation: 0,
+            max_largest_free_block: 0,
+            max_distinct_blocks: 0,
+            distinct_block_counter: DistinctBlockCounter::new(vec![], left_padding, right_padding, Some((pool_start, pool_stop))),
+            live_allocations: HashSet::new(),
+            lifetime_byte_totals: (0, 0),
         }
     }
 
@@ -45,37 +68,93 @@ impl MemoryUsageFactory {
         self.memory_updates = updates;
     }
 
+    /// Calculates usage stats for the loaded updates, starting from an empty pool.
+    ///
+    /// returns: MemoryUsageStats
     pub fn calculate_usage_stats(&mut self) -> MemoryUsageStats {
-        let mut current_usage = 0;
-        let mut max_usage = 0;
-        let mut max_free_blocks: u128 = 0;
-        let mut max_free_segment_fragmentation = 0;
-        let mut max_largest_free_block = 0;
-        let mut memory_usages = Vec::new();
+        let memory_updates = std::mem::take(&mut self.memory_updates);
+        *self = MemoryUsageFactory::new(Vec::new(), self.left_padding, self.right_padding, self.lowest_address, self.highest_address);
+        let memory_usage_stats = self.append_usage_stats(&memory_updates);
+        self.memory_updates = memory_updates;
+        memory_usage_stats
+    }
 
-        let mut distinct_block_counter = DistinctBlockCounter::new(vec![], self.left_padding, self.right_padding, Some((self.lowest_address, self.highest_address)));
-        let mut max_distinct_blocks: u128 = 0;
+    /// Continues the usage stats from the last update processed, for updates appended to a log
+    /// that is being followed. Only the new updates are processed.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_updates`: Updates following the ones already processed, in order.
+    ///
+    /// returns: MemoryUsageStats whose memory usages are those of the new updates only. The max
+    /// stats and lifetime byte totals cover every update processed so far.
+    pub fn append_usage_stats(&mut self, new_updates: &[MemoryUpdateType]) -> MemoryUsageStats {
+        let pool_size = self.highest_address.saturating_sub(self.lowest_address);
+        let mut memory_usages = Vec::new();
 
-        for (index, update) in self.memory_updates.iter().enumerate() {
+        for update in new_updates {
             println!("Processing usage stats: {}", update.cyan());
-            current_usage += Self::get_total_usage_delta(update);
-            max_usage = max(max_usage, current_usage);
-            distinct_block_counter.push_update(update);
-            let distinct_blocks = distinct_block_counter.get_distinct_blocks();
-            let free_blocks = distinct_block_counter.get_free_blocks();
-            let largest_free_block = distinct_block_counter.get_largest_free_block();
-            let free_segment_fragmentation = distinct_block_counter.get_free_segment_fragmentation();
+            self.current_usage += Self::get_total_usage_delta(update);
+            // Strictly greater, so the earliest timestamp is kept if the peak is reached again
+            if self.current_usage > self.max_usage {
+                self.max_usage = self.current_usage;
+                self.peak_usage_timestamp = update.get_timestamp() as u64;
+            }
+            self.distinct_block_counter.push_update(update);
+            // Sizes already include padding, as updates are padded before their stats are calculated
+            match update {
+                MemoryUpdateType::Allocation(_) => {
+                    self.live_allocations.insert(update.get_absolute_address());
+                    self.lifetime_byte_totals.0 += update.get_absolute_size() as u128;
+                }
+                MemoryUpdateType::Free(_) => {
+                    self.live_allocations.remove(&update.get_absolute_address());
+                    self.lifetime_byte_totals.1 += update.get_absolute_size() as u128;
+                }
+                MemoryUpdateType::Reallocation(reallocation) => {
+                    self.live_allocations.remove(&reallocation.get_old_address());
+                    self.live_allocations.insert(update.get_absolute_address());
+                    self.lifetime_byte_totals.1 += reallocation.get_old_size() as u128;
+                    self.lifetime_byte_totals.0 += update.get_absolute_size() as u128;
+                }
+            };
+            let distinct_blocks = self.distinct_block_counter.get_distinct_blocks();
+            let free_blocks = self.distinct_block_counter.get_free_blocks();
+            let largest_free_block = self.distinct_block_counter.get_largest_free_block();
+            let free_segment_fragmentation = self.distinct_block_counter.get_free_segment_fragmentation();
             let real_timestamp_microseconds = Utility::convert_to_microseconds(update.get_real_timestamp());
-            max_distinct_blocks = max(max_distinct_blocks, distinct_blocks);
-            max_free_blocks = max(max_free_blocks, free_blocks.len() as u128);
-            max_free_segment_fragmentation = max(max_free_segment_fragmentation, free_segment_fragmentation);
-            max_largest_free_block = max(max_largest_free_block, largest_free_block.2);
+            self.max_distinct_blocks = max(self.max_distinct_blocks, distinct_blocks);
+            self.max_free_blocks = max(self.max_free_blocks, free_blocks.len() as u128);
+            self.max_free_segment_fragmentation = max(self.max_free_segment_fragmentation, free_segment_fragmentation);
+            self.max_largest_free_block = max(self.max_largest_free_block, largest_free_block.2);
 
-            memory_usages.push(MemoryUsage::new(current_usage, distinct_blocks, largest_free_block, free_blocks.len(), free_segment_fragmentation, index, real_timestamp_microseconds, self.counter));
+            let mut memory_usage = MemoryUsage::new(self.current_usage, distinct_blocks, largest_free_block, free_blocks.len(), free_segment_fragmentation, self.counter as usize, real_timestamp_microseconds, self.counter);
+            memory_usage.set_free_segment_fragmentation_percentage(self.distinct_block_counter.get_free_segment_fragmentation_percentage());
+            memory_usage.set_live_allocations(self.live_allocations.len());
+            memory_usage.set_free_gaps(self.distinct_block_counter.get_free_gap_count());
+            memory_usage.set_mean_allocated_block_size(self.distinct_block_counter.get_allocated_block_stats().2);
+            memory_usage.set_memory_used_percentage(Self::get_usage_percentage(self.current_usage, pool_size));
+            memory_usages.push(memory_usage);
             self.counter += 1;
         }
-        MemoryUsageStats::new(memory_usages, max_usage, max_free_blocks, max_distinct_blocks,
-                              max_free_segment_fragmentation, max_largest_free_block as u128)
+        MemoryUsageStats::new(memory_usages, self.max_usage, self.peak_usage_timestamp, self.max_free_blocks, self.max_distinct_blocks,
+                              self.max_free_segment_fragmentation, self.max_largest_free_block as u128)
+            .with_lifetime_byte_totals(self.lifetime_byte_totals.0, self.lifetime_byte_totals.1)
+    }
+
+    /// Gets memory usage as a percentage of the pool it's in.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_used_absolute`: Bytes in use.
+    /// * `pool_size`: Size of the pool in bytes.
+    ///
+    /// returns: Percentage of the pool in use, or 0 if the pool is empty.
+    fn get_usage_percentage(memory_used_absolute: i128, pool_size: usize) -> f64 {
+        if pool_size == 0 {
+            return 0.0;
+        }
+        memory_used_absolute as f64 * 100.0 / pool_size as f64
     }
 
     fn get_total_usage_delta(memory_update: &MemoryUpdateType) -> i128 {
@@ -86,6 +165,9 @@ impl MemoryUsageFactory {
             MemoryUpdateType::Free(free) => {
                 -(free.get_absolute_size() as i128)
             }
+            MemoryUpdateType::Reallocation(reallocation) => {
+                reallocation.get_absolute_size() as i128 - reallocation.get_old_size() as i128
+            }
         }
     }
 }
@@ -95,7 +177,7 @@ mod tests {
     use std::sync::Arc;
     use crate::damselfly::memory::memory_parsers::{MemoryParser, MemorySysTraceParser};
     use crate::damselfly::consts::{TEST_BINARY_PATH, TEST_LOG};
-    use crate::damselfly::memory::memory_update::{Allocation, MemoryUpdateType};
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdateType};
     use crate::damselfly::memory::memory_usage_factory::MemoryUsageFactory;
     use crate::damselfly::memory::memory_usage_stats::MemoryUsageStats;
 
@@ -143,6 +225,53 @@ mod tests {
         assert_eq!(memory_usages[4].get_latest_operation(), 4);
     }
 
+    #[test]
+    fn calculate_live_allocations_test() {
+        let first_update = MemoryUpdateType::Allocation(Allocation::new(0, 8, Arc::new(String::new()), 0, String::from("0001.676 s")));
+        let second_update = MemoryUpdateType::Allocation(Allocation::new(16, 8, Arc::new(String::new()), 1, String::from("0001.677 s")));
+        let third_update = MemoryUpdateType::Free(Free::new(0, 8, Arc::new(String::new()), 2, String::from("0001.678 s")));
+        // Never allocated, so ignored
+        let fourth_update = MemoryUpdateType::Free(Free::new(32, 8, Arc::new(String::new()), 3, String::from("0001.679 s")));
+        let usage_stats =
+            MemoryUsageFactory::new(vec![first_update, second_update, third_update, fourth_update], 0, 0, usize::MIN, usize::MAX)
+                .calculate_usage_stats();
+        let live_allocations: Vec<usize> = usage_stats.get_memory_usages()
+            .iter()
+            .map(|memory_usage| memory_usage.get_live_allocations())
+            .collect();
+        assert_eq!(live_allocations, vec![1, 2, 1, 1]);
+        assert_eq!(usage_stats.get_total_bytes_allocated(), 16);
+        assert_eq!(usage_stats.get_total_bytes_freed(), 16);
+    }
+
+    #[test]
+    fn calculate_memory_used_percentage_test() {
+        let first_update = MemoryUpdateType::Allocation(Allocation::new(1024, 256, Arc::new(String::new()), 0, String::from("0001.676 s")));
+        let second_update = MemoryUpdateType::Allocation(Allocation::new(1280, 512, Arc::new(String::new()), 1, String::from("0001.677 s")));
+        let third_update = MemoryUpdateType::Free(Free::new(1024, 256, Arc::new(String::new()), 2, String::from("0001.678 s")));
+        let usage_stats =
+            MemoryUsageFactory::new(vec![first_update, second_update, third_update], 0, 0, 1024, 2048)
+                .calculate_usage_stats();
+        let percentages: Vec<f64> = usage_stats.get_memory_usages()
+            .iter()
+            .map(|memory_usage| memory_usage.get_memory_used_percentage())
+            .collect();
+        assert_eq!(percentages, vec![25.0, 75.0, 50.0]);
+        assert_eq!(usage_stats.get_memory_usages()[1].get_memory_used_absolute(), 768);
+    }
+
+    #[test]
+    fn calculate_peak_usage_timestamp_test() {
+        let first_update = MemoryUpdateType::Allocation(Allocation::new(0, 8, Arc::new(String::new()), 0, String::from("0001.676 s")));
+        let second_update = MemoryUpdateType::Free(Free::new(0, 8, Arc::new(String::new()), 1, String::from("0001.677 s")));
+        let third_update = MemoryUpdateType::Allocation(Allocation::new(16, 8, Arc::new(String::new()), 2, String::from("0001.678 s")));
+        let usage_stats =
+            MemoryUsageFactory::new(vec![first_update, second_update, third_update], 0, 0, usize::MIN, usize::MAX)
+                .calculate_usage_stats();
+        assert_eq!(usage_stats.get_max_usage(), 8);
+        assert_eq!(usage_stats.get_peak_usage_timestamp(), 0);
+    }
+
     #[test]
     fn calculate_fragmentation_zero_padding_test() {
         let first_update = MemoryUpdateType::Allocation(Allocation::new(0, 8, Arc::new(String::new()), 0, String::from("0001.676 s")));
@@ -1,28 +1,49 @@
 //! Contains memory usage stats.
+use serde::{Deserialize, Serialize};
 use crate::damselfly::memory::memory_usage::MemoryUsage;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MemoryUsageStats {
     memory_usages: Vec<MemoryUsage>,
     max_usage: i128,
+    peak_usage_timestamp: u64,
     max_free_blocks: u128,
     max_distinct_blocks: u128,
     max_free_segment_fragmentation: u128,
     max_largest_free_block: u128,
+    total_bytes_allocated: u128,
+    total_bytes_freed: u128,
 }
 
 impl MemoryUsageStats {
-    pub fn new(memory_usages: Vec<MemoryUsage>, max_usage: i128, max_free_blocks: u128, max_distinct_blocks: u128,
-               max_free_segment_fragmentation: u128, max_largest_free_block: u128) -> Self {
+    pub fn new(memory_usages: Vec<MemoryUsage>, max_usage: i128, peak_usage_timestamp: u64, max_free_blocks: u128,
+               max_distinct_blocks: u128, max_free_segment_fragmentation: u128, max_largest_free_block: u128) -> Self {
         Self {
             memory_usages,
             max_usage,
+            peak_usage_timestamp,
             max_free_blocks,
             max_distinct_blocks,
             max_free_segment_fragmentation,
             max_largest_free_block,
+            total_bytes_allocated: 0,
+            total_bytes_freed: 0,
         }
     }
+
+    /// Sets the total bytes allocated and freed over the whole run.
+    ///
+    /// # Arguments
+    ///
+    /// * `total_bytes_allocated`: Sum of the sizes of every allocation.
+    /// * `total_bytes_freed`: Sum of the sizes of every free.
+    ///
+    /// returns: MemoryUsageStats
+    pub fn with_lifetime_byte_totals(mut self, total_bytes_allocated: u128, total_bytes_freed: u128) -> Self {
+        self.total_bytes_allocated = total_bytes_allocated;
+        self.total_bytes_freed = total_bytes_freed;
+        self
+    }
     
     pub fn get_memory_usages(&self) -> &Vec<MemoryUsage> {
         &self.memory_usages
@@ -32,6 +53,11 @@ impl MemoryUsageStats {
         self.max_usage
     }
     
+    /// Gets the operation timestamp at which max_usage was first reached.
+    pub fn get_peak_usage_timestamp(&self) -> u64 {
+        self.peak_usage_timestamp
+    }
+    
     pub fn get_max_free_blocks(&self) -> u128 {
         self.max_free_blocks
     }
@@ -42,4 +68,14 @@ impl MemoryUsageStats {
     
     pub fn get_max_free_segment_fragmentation(&self) -> u128 { self.max_free_segment_fragmentation }
     pub fn get_max_largest_free_block(&self) -> u128 { self.max_largest_free_block }
-}
\ No newline at end of file
+
+    /// Gets the sum of the sizes of every allocation over the whole run, including padding.
+    pub fn get_total_bytes_allocated(&self) -> u128 {
+        self.total_bytes_allocated
+    }
+
+    /// Gets the sum of the sizes of every free over the whole run, including padding.
+    pub fn get_total_bytes_freed(&self) -> u128 {
+        self.total_bytes_freed
+    }
+}
@@ -0,0 +1,64 @@
+//! Outcome of replaying a trace's allocation requests with a placement strategy.
+use serde::Serialize;
+use crate::damselfly::memory::placement_simulator::PlacementStrategy;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlacementResult {
+    strategy: PlacementStrategy,
+    capacity: usize,
+    peak_used: usize,
+    peak_fragmentation: f64,
+    peak_free_regions: usize,
+    failed_allocations: usize,
+}
+
+impl PlacementResult {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy`: Strategy used to place allocations.
+    /// * `capacity`: Size of the simulated pool in bytes.
+    /// * `peak_used`: Most bytes allocated at once.
+    /// * `peak_fragmentation`: Highest fragmentation seen, from 0 (all free space in one region)
+    ///   to 1.
+    /// * `peak_free_regions`: Most separate free regions at once.
+    /// * `failed_allocations`: Number of allocations that did not fit.
+    ///
+    /// returns: PlacementResult
+    pub fn new(strategy: PlacementStrategy, capacity: usize, peak_used: usize, peak_fragmentation: f64,
+               peak_free_regions: usize, failed_allocations: usize) -> Self {
+        Self {
+            strategy,
+            capacity,
+            peak_used,
+            peak_fragmentation,
+            peak_free_regions,
+            failed_allocations,
+        }
+    }
+
+    pub fn get_strategy(&self) -> PlacementStrategy {
+        self.strategy
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn get_peak_used(&self) -> usize {
+        self.peak_used
+    }
+
+    pub fn get_peak_fragmentation(&self) -> f64 {
+        self.peak_fragmentation
+    }
+
+    pub fn get_peak_free_regions(&self) -> usize {
+        self.peak_free_regions
+    }
+
+    pub fn get_failed_allocations(&self) -> usize {
+        self.failed_allocations
+    }
+}
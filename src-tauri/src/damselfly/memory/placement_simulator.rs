@@ -0,0 +1,270 @@
+//! Replays memory updates against a simulated pool of fixed capacity.
+//!
+//! Allocations are placed using a placement strategy (first-fit by default) rather than at their
+//! logged addresses, so the simulation answers whether the same sequence of requests would have
+//! succeeded in a smaller pool, or how fragmented the pool would be under a different allocator
+//! policy. Frees are paired with allocations by UpdateQueueCompressor::live_set_changes, and
+//! release whichever simulated region their allocation was placed in.
+use std::collections::{BTreeMap, HashMap};
+use serde::{Deserialize, Serialize};
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+use crate::damselfly::memory::placement_result::PlacementResult;
+use crate::damselfly::update_interval::update_queue_compressor::UpdateQueueCompressor;
+
+/// How the simulator picks a free region for an allocation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlacementStrategy {
+    /// The lowest-addressed free region that fits.
+    #[default]
+    FirstFit,
+    /// The smallest free region that fits, lowest-addressed on ties.
+    BestFit,
+}
+
+pub struct PlacementSimulator {
+    strategy: PlacementStrategy,
+    capacity: usize,
+    used: usize,
+    // start -> size
+    free_regions: BTreeMap<usize, usize>,
+    // allocation timestamp -> (simulated start, size)
+    placements: HashMap<usize, (usize, usize)>,
+}
+
+impl PlacementSimulator {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity`: Size of the simulated pool in bytes.
+    ///
+    /// returns: PlacementSimulator
+    pub fn new(capacity: usize) -> Self {
+        let mut free_regions = BTreeMap::new();
+        if capacity > 0 {
+            free_regions.insert(0, capacity);
+        }
+        Self {
+            strategy: PlacementStrategy::default(),
+            capacity,
+            used: 0,
+            free_regions,
+            placements: HashMap::new(),
+        }
+    }
+
+    /// Sets the placement strategy. First-fit by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy`: Strategy to place allocations with.
+    ///
+    /// returns: PlacementSimulator
+    pub fn with_strategy(mut self, strategy: PlacementStrategy) -> PlacementSimulator {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Replays updates until an allocation cannot be satisfied.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    /// * `capacity`: Size of the simulated pool in bytes.
+    ///
+    /// returns: Some((timestamp, failing allocation)) if an allocation fails, None if the whole
+    /// trace fits.
+    pub fn simulate_capacity(memory_updates: &[MemoryUpdateType], capacity: usize) -> Option<(usize, MemoryUpdateType)> {
+        let mut simulator = PlacementSimulator::new(capacity);
+        for (update, (freed, allocated)) in memory_updates.iter().zip(UpdateQueueCompressor::live_set_changes(memory_updates)) {
+            if !simulator.apply_change(freed.as_ref(), allocated.as_ref()) {
+                return Some((update.get_timestamp(), update.clone()));
+            }
+        }
+        None
+    }
+
+    /// Replays every update against an initially empty pool, recording the peak usage and
+    /// fragmentation. Allocations that do not fit are counted and skipped, along with their frees.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    /// * `capacity`: Size of the simulated pool in bytes.
+    /// * `strategy`: Strategy to place allocations with.
+    ///
+    /// returns: PlacementResult
+    pub fn simulate_placement(memory_updates: &[MemoryUpdateType], capacity: usize, strategy: PlacementStrategy) -> PlacementResult {
+        let mut simulator = PlacementSimulator::new(capacity).with_strategy(strategy);
+        let mut peak_used = 0;
+        let mut peak_fragmentation: f64 = 0.0;
+        let mut peak_free_regions = 0;
+        let mut failed_allocations = 0;
+        for (freed, allocated) in UpdateQueueCompressor::live_set_changes(memory_updates) {
+            if !simulator.apply_change(freed.as_ref(), allocated.as_ref()) {
+                failed_allocations += 1;
+            }
+            peak_used = peak_used.max(simulator.get_used());
+            peak_fragmentation = peak_fragmentation.max(simulator.get_fragmentation());
+            peak_free_regions = peak_free_regions.max(simulator.get_free_region_count());
+        }
+        PlacementResult::new(strategy, capacity, peak_used, peak_fragmentation, peak_free_regions, failed_allocations)
+    }
+
+    /// Applies the change an update makes to the live set: releases the freed allocation's
+    /// simulated region, then places the new allocation.
+    ///
+    /// # Arguments
+    ///
+    /// * `freed`: Allocation the update frees, if any.
+    /// * `allocated`: Allocation the update makes, if any.
+    ///
+    /// returns: false if the new allocation does not fit, true otherwise.
+    fn apply_change(&mut self, freed: Option<&MemoryUpdateType>, allocated: Option<&MemoryUpdateType>) -> bool {
+        if let Some((start, size)) = freed.and_then(|freed| self.placements.remove(&freed.get_timestamp())) {
+            self.release(start, size);
+        }
+        match allocated {
+            None => true,
+            Some(allocated) => match self.allocate(allocated.get_absolute_size()) {
+                None => false,
+                Some(start) => {
+                    self.placements.insert(allocated.get_timestamp(), (start, allocated.get_absolute_size()));
+                    true
+                }
+            },
+        }
+    }
+
+    /// Bytes currently allocated in the simulated pool.
+    pub fn get_used(&self) -> usize {
+        self.used
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn get_free_region_count(&self) -> usize {
+        self.free_regions.len()
+    }
+
+    /// Fragmentation of the free space: 1 - (largest free region / total free bytes).
+    /// 0 if all free space is in one region, or if there is no free space.
+    pub fn get_fragmentation(&self) -> f64 {
+        let free = self.capacity - self.used;
+        if free == 0 {
+            return 0.0;
+        }
+        let largest_free_region = self.free_regions.values().max().copied().unwrap_or(0);
+        1.0 - largest_free_region as f64 / free as f64
+    }
+
+    /// Finds a free region that fits using the placement strategy, and carves the allocation
+    /// from its start.
+    fn allocate(&mut self, size: usize) -> Option<usize> {
+        if self.used.saturating_add(size) > self.capacity {
+            return None;
+        }
+        let mut fitting_regions = self.free_regions
+            .iter()
+            .filter(|(_, region_size)| **region_size >= size)
+            .map(|(start, region_size)| (*start, *region_size));
+        let (start, region_size) = match self.strategy {
+            PlacementStrategy::FirstFit => fitting_regions.next(),
+            PlacementStrategy::BestFit => fitting_regions.min_by_key(|(start, region_size)| (*region_size, *start)),
+        }?;
+        self.free_regions.remove(&start);
+        if region_size > size {
+            self.free_regions.insert(start + size, region_size - size);
+        }
+        self.used += size;
+        Some(start)
+    }
+
+    /// Returns a region to the free list, coalescing it with adjacent free regions.
+    fn release(&mut self, start: usize, size: usize) {
+        self.used -= size;
+        if size == 0 {
+            return;
+        }
+        let mut new_start = start;
+        let mut new_size = size;
+        if let Some((&prev_start, &prev_size)) = self.free_regions.range(..start).next_back() {
+            if prev_start + prev_size == start {
+                self.free_regions.remove(&prev_start);
+                new_start = prev_start;
+                new_size += prev_size;
+            }
+        }
+        if let Some(next_size) = self.free_regions.remove(&(start + size)) {
+            new_size += next_size;
+        }
+        self.free_regions.insert(new_start, new_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+    use crate::damselfly::memory::placement_simulator::{PlacementSimulator, PlacementStrategy};
+
+    fn build_updates() -> Vec<MemoryUpdateType> {
+        let callstack = Arc::new("test".to_string());
+        vec![
+            Allocation::new(100, 8, callstack.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(200, 8, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Allocation::new(300, 8, callstack.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Free::new(100, 8, callstack.clone(), 3, "0".to_string()).wrap_in_enum(),
+            Free::new(300, 8, callstack.clone(), 4, "0".to_string()).wrap_in_enum(),
+            Allocation::new(400, 16, callstack, 5, "0".to_string()).wrap_in_enum(),
+        ]
+    }
+
+    #[test]
+    fn best_fit_test() {
+        // Fills the pool, then frees leave a 16 byte hole at 0 and a 4 byte hole at 24.
+        // First-fit puts the last 4 byte allocation in the 16 byte hole, splitting it, while
+        // best-fit fills the 4 byte hole exactly.
+        let callstack = Arc::new("test".to_string());
+        let updates = vec![
+            Allocation::new(100, 16, callstack.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(200, 8, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Allocation::new(300, 4, callstack.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Allocation::new(400, 4, callstack.clone(), 3, "0".to_string()).wrap_in_enum(),
+            Free::new(100, 16, callstack.clone(), 4, "0".to_string()).wrap_in_enum(),
+            Free::new(300, 4, callstack.clone(), 5, "0".to_string()).wrap_in_enum(),
+            Allocation::new(500, 4, callstack, 6, "0".to_string()).wrap_in_enum(),
+        ];
+        let first_fit = PlacementSimulator::simulate_placement(&updates, 32, PlacementStrategy::FirstFit);
+        let best_fit = PlacementSimulator::simulate_placement(&updates, 32, PlacementStrategy::BestFit);
+        assert_eq!(first_fit.get_failed_allocations(), 0);
+        assert_eq!(first_fit.get_peak_used(), 32);
+        assert_eq!(first_fit.get_peak_free_regions(), 2);
+        // 1 - 12/16 after the last allocation
+        assert!((first_fit.get_peak_fragmentation() - 0.25).abs() < 1e-9);
+        // 1 - 16/20 after the second free, and 0 after the last allocation
+        assert!((best_fit.get_peak_fragmentation() - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn trace_fits_test() {
+        assert!(PlacementSimulator::simulate_capacity(&build_updates(), 40).is_none());
+    }
+
+    #[test]
+    fn usage_exceeds_capacity_test() {
+        let (timestamp, _) = PlacementSimulator::simulate_capacity(&build_updates(), 20).unwrap();
+        assert_eq!(timestamp, 2);
+    }
+
+    #[test]
+    fn no_free_block_big_enough_test() {
+        // 24 bytes fit the first three allocations, but after the frees the free space is split
+        // into two 8 byte regions around the allocation at 200
+        let (timestamp, update) = PlacementSimulator::simulate_capacity(&build_updates(), 24).unwrap();
+        assert_eq!(timestamp, 5);
+        assert_eq!(update.get_absolute_address(), 400);
+    }
+}
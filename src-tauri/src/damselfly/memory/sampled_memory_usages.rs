@@ -6,6 +6,7 @@
 use crate::damselfly::memory::memory_usage::MemoryUsage;
 use crate::damselfly::memory::memory_usage_sample::MemoryUsageSample;
 use crate::damselfly::memory::sampled_memory_usages_factory::SampledMemoryUsagesFactory;
+use crate::damselfly::memory::utility::Utility;
 
 pub struct SampledMemoryUsages {
     samples: Vec<MemoryUsageSample>,
@@ -28,15 +29,41 @@ impl SampledMemoryUsages {
         &self.samples
     }
 
+    /// Gets the first and last operation timestamps in a sample.
+    ///
+    /// # Arguments
+    ///
+    /// * `realtime_timestamp`: Index of the sample, clamped to the last sample.
+    ///
+    /// returns: Some((first operation timestamp, last operation timestamp)), or None if there are
+    /// no samples.
     pub fn get_operation_timestamps_in_realtime_timestamp(
         &self,
         realtime_timestamp: u64,
-    ) -> (u64, u64) {
-        let clamped_timestamp = realtime_timestamp.clamp(0, self.samples.len() as u64 - 1);
-        let bucket = self.samples.get(clamped_timestamp as usize)
-            .expect("[SampledMemoryUsages::get_operation_timestamp_of_realtime_timestamp]: Realtime timestamp out of bounds");
+    ) -> Option<(u64, u64)> {
+        let clamped_timestamp = realtime_timestamp.min(self.samples.len().checked_sub(1)? as u64);
+        let bucket = &self.samples[clamped_timestamp as usize];
         eprintln!("[SampledMemoryUsages::get_operation_timestamps_in_realtime_timestamp]: bucket count: {}", self.samples.len());
-        (bucket.get_first(), bucket.get_last())
+        Some((bucket.get_first(), bucket.get_last()))
+    }
+
+    /// Gets the sample containing an operation, the inverse of
+    /// get_operation_timestamps_in_realtime_timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation_timestamp`: Operation timestamp. Operations after the last sample are treated
+    ///   as being in the last sample.
+    ///
+    /// returns: Index of the first sample containing the operation, or None if there are no
+    /// samples.
+    pub fn get_realtime_timestamp_of_operation_timestamp(&self, operation_timestamp: u64) -> Option<u64> {
+        let last_sample = self.samples.len().checked_sub(1)?;
+        let sample = self.samples
+            .iter()
+            .position(|sample| !sample.get_memory_usages().is_empty() && sample.get_last() >= operation_timestamp)
+            .unwrap_or(last_sample);
+        Some(sample as u64)
     }
 
     pub fn get_sample_interval(&self) -> u64 {
@@ -51,6 +78,31 @@ impl SampledMemoryUsages {
         self.sample_interval = new_sample_interval;
     }
 
+    /// Appends usages that follow the existing ones. Only the last sample and the samples after
+    /// it are recomputed, unless a new usage is timestamped before the last sample.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_memory_usages`: Usages to append, in order.
+    ///
+    /// returns: ()
+    pub fn append_memory_usages(&mut self, new_memory_usages: Vec<MemoryUsage>) {
+        let last_key = self.samples.len().saturating_sub(1) as u64 * self.sample_interval;
+        let is_after_last_sample = new_memory_usages.iter().all(|memory_usage| {
+            Utility::round_to_nearest_multiple_of(memory_usage.get_timestamp_microseconds(), self.sample_interval) >= last_key
+        });
+        self.memory_usages.extend(new_memory_usages.iter().cloned());
+        let Some(last_sample) = self.samples.pop().filter(|_| is_after_last_sample) else {
+            let memory_usages = std::mem::take(&mut self.memory_usages);
+            self.set_memory_usages(memory_usages);
+            return;
+        };
+        let mut memory_usages = last_sample.get_memory_usages().clone();
+        memory_usages.extend(new_memory_usages);
+        self.samples.extend(SampledMemoryUsagesFactory::new(self.sample_interval, memory_usages)
+            .divide_usages_into_buckets_from(last_key));
+    }
+
     pub fn set_memory_usages(&mut self, new_memory_usages: Vec<MemoryUsage>) {
         let buckets =
             SampledMemoryUsagesFactory::new(self.sample_interval, new_memory_usages.clone())
@@ -25,6 +25,19 @@ impl SampledMemoryUsagesFactory {
     ///
     /// returns: Vec of sampled MemoryUsageSamples, averaged for each sample.
     pub fn divide_usages_into_buckets(&self) -> Vec<MemoryUsageSample> {
+        self.divide_usages_into_buckets_from(0)
+    }
+
+    /// Divides MemoryUsages into buckets, starting from a bucket other than the first. Used to
+    /// extend existing samples with usages that were appended to them.
+    ///
+    /// # Arguments
+    ///
+    /// * `first_key`: Rounded timestamp of the first bucket to create. Usages must not fall in an
+    ///   earlier bucket.
+    ///
+    /// returns: Vec of sampled MemoryUsageSamples, averaged for each sample, from first_key on.
+    pub fn divide_usages_into_buckets_from(&self, first_key: u64) -> Vec<MemoryUsageSample> {
         let mut buckets = HashMap::new();
         for usage in &self.memory_usages {
             let rounded_timestamp = Utility::round_to_nearest_multiple_of(usage.get_timestamp_microseconds(), self.sample_interval);
@@ -37,7 +50,7 @@ impl SampledMemoryUsagesFactory {
         let last_key = *bucket_keys.last().unwrap_or(&0);
         let mut previous_averaged_usage = MemoryUsage::new(0, 0, (0, 0, 0), 0, 0, 0, 0, 0);
         let mut previous_first_last_operations = (u64::MAX, u64::MIN);
-        for key in (0..=last_key).step_by(self.sample_interval as usize) {
+        for key in (first_key..=last_key).step_by(self.sample_interval as usize) {
             match buckets.get(&key) {
                 None => {
                     averaged_buckets.push(MemoryUsageSample::new(Vec::new(), previous_first_last_operations.0, previous_first_last_operations.1, previous_averaged_usage.clone()));
@@ -59,6 +72,9 @@ impl SampledMemoryUsagesFactory {
                     let mut bucket_largest_free_block = (0, 0, 0);
                     let mut bucket_free_blocks = 0;
                     let mut bucket_free_segment_fragmentation = 0;
+                    let mut bucket_live_allocations = 0;
+                    let mut bucket_free_gaps = 0;
+                    let mut bucket_mean_allocated_block_size = 0.0;
                     let mut bucket_latest_operation = 0;
                     let mut bucket_timestamp = 0;
                     let mut first_last_operations: (u64, u64) = (u64::MAX, u64::MIN);
@@ -74,6 +90,9 @@ impl SampledMemoryUsagesFactory {
                         }
                         bucket_free_blocks += usage.get_free_blocks();
                         bucket_free_segment_fragmentation += usage.get_free_segment_fragmentation();
+                        bucket_live_allocations += usage.get_live_allocations();
+                        bucket_free_gaps += usage.get_free_gaps();
+                        bucket_mean_allocated_block_size += usage.get_mean_allocated_block_size();
                         bucket_latest_operation = usage.get_latest_operation();
                         bucket_timestamp = usage.get_timestamp();
                     }
@@ -83,6 +102,9 @@ impl SampledMemoryUsagesFactory {
                     bucket_usage.set_largest_free_block(bucket_largest_free_block);
                     bucket_usage.set_free_blocks(bucket_free_blocks / usages.len());
                     bucket_usage.set_free_segment_fragmentation(bucket_free_segment_fragmentation / usages.len() as u128);
+                    bucket_usage.set_live_allocations(bucket_live_allocations / usages.len());
+                    bucket_usage.set_free_gaps(bucket_free_gaps / usages.len());
+                    bucket_usage.set_mean_allocated_block_size(bucket_mean_allocated_block_size / usages.len() as f64);
                     bucket_usage.set_latest_operation(bucket_latest_operation);
                     bucket_usage.set_timestamp(bucket_timestamp);
                     previous_averaged_usage = bucket_usage.clone();
@@ -0,0 +1,163 @@
+//! Utility methods for characterising the distribution of allocation sizes in a log.
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+
+pub struct SizeDistribution;
+
+impl SizeDistribution {
+    /// Gets the size of every allocation in a list of updates. Frees are ignored, and reallocations
+    /// count as allocations of their new size.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates to get allocation sizes from.
+    ///
+    /// returns: Vec<usize>, in the order the allocations were made.
+    pub fn get_allocation_sizes(memory_updates: &[MemoryUpdateType]) -> Vec<usize> {
+        memory_updates
+            .iter()
+            .filter(|update| !matches!(update, MemoryUpdateType::Free(_)))
+            .map(|update| update.get_absolute_size())
+            .collect()
+    }
+
+    /// Sorts allocation sizes into the allocator's size classes, where each allocation is rounded
+    /// up to the smallest class that fits it, to quantify the bytes lost to rounding.
+    ///
+    /// # Arguments
+    ///
+    /// * `sizes`: Requested allocation sizes.
+    /// * `size_classes`: Size class boundaries in bytes, in any order.
+    ///
+    /// returns: Vec<(class_size, count, wasted_bytes)> for every class in ascending order, where
+    /// wasted_bytes is the sum of (class_size - requested_size). Allocations larger than every
+    /// class are counted in a final entry with a class_size of 0 and no wasted bytes.
+    pub fn get_size_class_distribution(sizes: &[usize], size_classes: &[usize]) -> Vec<(usize, usize, usize)> {
+        let mut size_classes = size_classes.to_vec();
+        size_classes.sort_unstable();
+        size_classes.dedup();
+        let mut distribution: Vec<(usize, usize, usize)> = size_classes
+            .iter()
+            .map(|class_size| (*class_size, 0, 0))
+            .collect();
+        let mut unclassed = 0;
+        for size in sizes {
+            let class_index = size_classes.partition_point(|class_size| class_size < size);
+            match distribution.get_mut(class_index) {
+                Some((class_size, count, wasted_bytes)) => {
+                    *count += 1;
+                    *wasted_bytes += *class_size - size;
+                }
+                None => unclassed += 1,
+            }
+        }
+        if unclassed > 0 {
+            distribution.push((0, unclassed, 0));
+        }
+        distribution
+    }
+
+    /// Buckets allocation sizes into a histogram with explicit bucket boundaries.
+    ///
+    /// # Arguments
+    ///
+    /// * `sizes`: Requested allocation sizes.
+    /// * `bucket_boundaries`: Upper bound of each bucket in bytes, in any order.
+    ///
+    /// returns: Vec<(upper_bound, count)> for every bucket in ascending order, where each bucket
+    /// counts sizes above the previous bucket's upper bound and at most its own. Sizes above every
+    /// boundary are counted in a final overflow bucket with an upper bound of usize::MAX.
+    pub fn get_size_histogram(sizes: &[usize], bucket_boundaries: &[usize]) -> Vec<(usize, u64)> {
+        let mut bucket_boundaries = bucket_boundaries.to_vec();
+        bucket_boundaries.sort_unstable();
+        bucket_boundaries.dedup();
+        let mut histogram: Vec<(usize, u64)> = bucket_boundaries
+            .iter()
+            .map(|upper_bound| (*upper_bound, 0))
+            .collect();
+        let mut overflow = 0;
+        for size in sizes {
+            match histogram.get_mut(bucket_boundaries.partition_point(|upper_bound| upper_bound < size)) {
+                Some((_, count)) => *count += 1,
+                None => overflow += 1,
+            }
+        }
+        histogram.push((usize::MAX, overflow));
+        histogram
+    }
+
+    /// Computes the Gini coefficient of a list of sizes. 0 means every size is equal, and values
+    /// approaching 1 mean a few huge sizes dominate many tiny ones.
+    ///
+    /// Uses the formula over the sizes sorted in ascending order:
+    /// G = sum((2i - n - 1) * x_i) / (n * sum(x_i)), for i = 1..n
+    ///
+    /// # Arguments
+    ///
+    /// * `sizes`: Sizes to compute the coefficient over.
+    ///
+    /// returns: Gini coefficient, or 0 if there are no sizes or they are all 0.
+    pub fn gini_coefficient(sizes: &[usize]) -> f64 {
+        let mut sorted_sizes = sizes.to_vec();
+        sorted_sizes.sort_unstable();
+        let n = sorted_sizes.len() as f64;
+        let total: f64 = sorted_sizes.iter().map(|size| *size as f64).sum();
+        if total == 0.0 {
+            return 0.0;
+        }
+        let weighted_sum: f64 = sorted_sizes
+            .iter()
+            .enumerate()
+            .map(|(index, size)| (2.0 * (index + 1) as f64 - n - 1.0) * *size as f64)
+            .sum();
+        weighted_sum / (n * total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate};
+    use crate::damselfly::memory::size_distribution::SizeDistribution;
+
+    #[test]
+    fn get_allocation_sizes_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates = vec![
+            Allocation::new(0, 8, callstack.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(8, 16, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack, 2, "0".to_string()).wrap_in_enum(),
+        ];
+        assert_eq!(SizeDistribution::get_allocation_sizes(&updates), vec![8, 16]);
+    }
+
+    #[test]
+    fn size_class_distribution_test() {
+        let sizes = [1, 8, 9, 16, 100];
+        assert_eq!(SizeDistribution::get_size_class_distribution(&sizes, &[32, 8, 16]), vec![
+            (8, 2, 7),
+            (16, 2, 7),
+            (32, 0, 0),
+            (0, 1, 0),
+        ]);
+    }
+
+    #[test]
+    fn size_histogram_test() {
+        let sizes = [1, 8, 9, 16, 100];
+        assert_eq!(SizeDistribution::get_size_histogram(&sizes, &[32, 8, 16]), vec![
+            (8, 2),
+            (16, 2),
+            (32, 0),
+            (usize::MAX, 1),
+        ]);
+        assert_eq!(SizeDistribution::get_size_histogram(&sizes, &[]), vec![(usize::MAX, 5)]);
+    }
+
+    #[test]
+    fn gini_coefficient_test() {
+        assert_eq!(SizeDistribution::gini_coefficient(&[]), 0.0);
+        assert_eq!(SizeDistribution::gini_coefficient(&[16, 16, 16, 16]), 0.0);
+        assert_eq!(SizeDistribution::gini_coefficient(&[0, 0, 0, 100]), 0.75);
+        assert!((SizeDistribution::gini_coefficient(&[3, 1, 2]) - 2.0 / 9.0).abs() < 1e-9);
+    }
+}
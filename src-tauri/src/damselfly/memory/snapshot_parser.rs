@@ -0,0 +1,282 @@
+//! Parser for logs made of periodic full heap dumps rather than an event stream.
+//!
+//! Each snapshot is a line listing every live allocation as hex address:size pairs:
+//! SNAPSHOT 1000:20 1040:8 ...
+//! Logs in this format start with a SNAPSHOTS header line. Consecutive snapshots are diffed into
+//! synthetic frees and allocations, so the rest of the pipeline can treat them like any other log.
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+use owo_colors::OwoColorize;
+
+use crate::damselfly::damselfly_error::DamselflyError;
+use crate::damselfly::memory::memory_parsers::{MemoryParser, MemorySysTraceParser, ParseResults, ParserKind, PoolRestrictedParseResults};
+use crate::damselfly::memory::memory_pool::MemoryPool;
+use crate::damselfly::memory::memory_pool_list::MemoryPoolList;
+use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+
+/// Parser for snapshot logs.
+#[derive(Default)]
+pub struct SnapshotParser {
+    memory_updates: Vec<MemoryUpdateType>,
+}
+
+impl MemoryParser for SnapshotParser {
+    fn get_kind(&self) -> ParserKind {
+        ParserKind::Snapshot
+    }
+
+    /// Parses a snapshot log into a Vec of MemoryUpdateTypes, each containing an Allocation or a Free.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: Raw log file.
+    /// * `_binary_path`: Unused, as snapshots do not contain callstacks.
+    ///
+    /// returns: ParseResults. Empty if a snapshot line is malformed.
+    fn parse_log_directly(self, log: &str, _binary_path: &str) -> ParseResults {
+        self.parse_log_contents(log).unwrap_or_else(|error| {
+            eprintln!("{}", format!("[SnapshotParser::parse_log_directly]: {error}").yellow());
+            ParseResults::new(Vec::new(), MemoryPoolList::default(), 0)
+        })
+    }
+
+    /// Parses a snapshot log using its file path.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_path`: File path to log
+    /// * `_binary_path`: Unused, as snapshots do not contain callstacks.
+    ///
+    /// returns: ParseResults. Empty if the log cannot be read or a snapshot line is malformed.
+    fn parse_log(self, log_path: &str, binary_path: &str) -> ParseResults {
+        eprintln!("[SnapshotParser::parse_log]: log: {log_path}");
+        match std::fs::read_to_string(log_path) {
+            Ok(log) => self.parse_log_directly(log.as_str(), binary_path),
+            Err(error) => {
+                eprintln!("{}", format!("[SnapshotParser::parse_log]: Failed to read {log_path}: {error}").yellow());
+                ParseResults::new(Vec::new(), MemoryPoolList::default(), 0)
+            }
+        }
+    }
+
+    /// Parses a snapshot log. Snapshots have no pool information, so every update is placed in a
+    /// single default pool spanning all of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: File path to log.
+    /// * `_binary_paths`: Unused, as snapshots do not contain callstacks.
+    /// * `left_padding`: Padding to add to the left of each operation (by shifting its address left)
+    /// * `right_padding`: Padding to add to the right of each operation (by increasing its size)
+    /// * `pool_filter`: Names of the pools to return, or None to return every pool.
+    ///
+    /// returns: Result<Vec<PoolRestrictedParseResults, Global>, DamselflyError>. Err if the log
+    /// cannot be read or a snapshot line is malformed.
+    fn parse_log_contents_split_by_pools(self, log: &str, _binary_paths: &[(&str, &str)], left_padding: usize, right_padding: usize, pool_filter: Option<&[String]>) -> Result<Vec<PoolRestrictedParseResults>, DamselflyError> {
+        eprintln!("[SnapshotParser::parse_log_contents_split_by_pools]: log: {log}");
+        let log = std::fs::read_to_string(log)?;
+        let parse_results = self.parse_log_contents(&log)?;
+        if parse_results.memory_updates.is_empty() {
+            return Ok(Vec::new());
+        }
+        let span = MemorySysTraceParser::get_updates_span(&parse_results.memory_updates);
+        let pool = MemoryPool::new(
+            span.0 as usize - left_padding,
+            (span.1 - span.0) as usize + right_padding,
+            "_default pool".to_string()
+        );
+        Ok(PoolRestrictedParseResults::filter_pools(vec![PoolRestrictedParseResults::new(parse_results.memory_updates, parse_results.max_timestamp, pool)], pool_filter))
+    }
+}
+
+impl SnapshotParser {
+    pub fn new() -> SnapshotParser {
+        SnapshotParser {
+            memory_updates: Vec::new(),
+        }
+    }
+
+    /// Checks if a log is in snapshot format, by looking for the SNAPSHOTS header on its first
+    /// non-empty line.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: Raw log file.
+    ///
+    /// returns: true if the log is made of snapshots.
+    pub fn is_snapshot_log(log: &str) -> bool {
+        log.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .is_some_and(|line| line == "SNAPSHOTS")
+    }
+
+    /// Checks if a log file is in snapshot format, reading only up to its first non-empty line.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_path`: File path to log.
+    ///
+    /// returns: true if the log is made of snapshots, false if it is not or cannot be read.
+    pub fn is_snapshot_log_file(log_path: &str) -> bool {
+        let Ok(log_file) = File::open(log_path) else {
+            return false;
+        };
+        BufReader::new(log_file)
+            .lines()
+            .map_while(Result::ok)
+            .find(|line| !line.trim().is_empty())
+            .is_some_and(|line| Self::is_snapshot_log(&line))
+    }
+
+    /// Parses a raw snapshot log, consuming itself and returning parse results.
+    ///
+    /// # Arguments
+    ///
+    /// * `log`: Raw log file.
+    ///
+    /// returns: Result<ParseResults, DamselflyError>, with DamselflyError::InvalidLog if a snapshot
+    /// line is malformed.
+    fn parse_log_contents(mut self, log: &str) -> Result<ParseResults, DamselflyError> {
+        let mut previous_snapshot = BTreeMap::new();
+        let mut snapshot_index = 0;
+        for line in log.lines() {
+            let Some(snapshot) = Self::parse_snapshot(line)? else {
+                continue;
+            };
+            self.diff_snapshots(&previous_snapshot, &snapshot, snapshot_index);
+            previous_snapshot = snapshot;
+            snapshot_index += 1;
+        }
+        let max_timestamp = self.memory_updates.len() as u64;
+        Ok(ParseResults::new(self.memory_updates, MemoryPoolList::default(), max_timestamp))
+    }
+
+    /// Parses a snapshot line into a map of live allocations.
+    ///
+    /// # Arguments
+    ///
+    /// * `line`: Raw line from the log.
+    ///
+    /// returns: Map of address -> size, None if the line is not a snapshot, or
+    /// DamselflyError::InvalidLog if an allocation in it is not a hex address:size pair.
+    pub fn parse_snapshot(line: &str) -> Result<Option<BTreeMap<usize, usize>>, DamselflyError> {
+        let mut split_line = line.split_whitespace();
+        if split_line.next() != Some("SNAPSHOT") {
+            return Ok(None);
+        }
+        let mut snapshot = BTreeMap::new();
+        for allocation in split_line {
+            let (address, size) = allocation.split_once(':')
+                .ok_or_else(|| DamselflyError::InvalidLog(format!("Invalid snapshot allocation: {allocation}")))?;
+            let address = usize::from_str_radix(address.trim_start_matches("0x"), 16)
+                .map_err(|_| DamselflyError::InvalidLog(format!("Invalid snapshot address: {address}")))?;
+            let size = usize::from_str_radix(size.trim_start_matches("0x"), 16)
+                .map_err(|_| DamselflyError::InvalidLog(format!("Invalid snapshot size: {size}")))?;
+            snapshot.insert(address, size);
+        }
+        Ok(Some(snapshot))
+    }
+
+    /// Turns the difference between two consecutive snapshots into updates. Allocations that
+    /// disappear or change size are freed first, then allocations that appear or change size
+    /// are allocated, so an address reused with a new size is freed before it is reallocated.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous_snapshot`: Live allocations in the previous snapshot.
+    /// * `snapshot`: Live allocations in this snapshot.
+    /// * `snapshot_index`: Index of this snapshot, used as the real timestamp of its updates in
+    ///   microseconds.
+    ///
+    /// returns: ()
+    fn diff_snapshots(&mut self, previous_snapshot: &BTreeMap<usize, usize>, snapshot: &BTreeMap<usize, usize>, snapshot_index: usize) {
+        let callstack = Arc::new(format!("snapshot {snapshot_index}"));
+        let real_timestamp = format!("{snapshot_index} us");
+        for (&address, &size) in previous_snapshot {
+            if snapshot.get(&address) != Some(&size) {
+                let timestamp = self.memory_updates.len();
+                self.memory_updates.push(Free::new(address, size, callstack.clone(), timestamp, real_timestamp.clone()).wrap_in_enum());
+            }
+        }
+        for (&address, &size) in snapshot {
+            if previous_snapshot.get(&address) != Some(&size) {
+                let timestamp = self.memory_updates.len();
+                self.memory_updates.push(Allocation::new(address, size, callstack.clone(), timestamp, real_timestamp.clone()).wrap_in_enum());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::damselfly::damselfly_error::DamselflyError;
+    use crate::damselfly::memory::memory_parsers::MemoryParser;
+    use crate::damselfly::memory::memory_update::MemoryUpdateType;
+    use crate::damselfly::memory::snapshot_parser::SnapshotParser;
+    use crate::damselfly::viewer::damselfly_viewer_builder::DamselflyViewerBuilder;
+
+    #[test]
+    fn is_snapshot_log_test() {
+        assert!(SnapshotParser::is_snapshot_log("\nSNAPSHOTS\nSNAPSHOT 1000:10"));
+        assert!(!SnapshotParser::is_snapshot_log("00000811: 039da1f3 |V|A|005|        0 us   0003.676 s    < DT:0xE14DEEBC> + 0 14"));
+    }
+
+    #[test]
+    fn diff_snapshots_test() {
+        let log = "SNAPSHOTS\n\
+                   SNAPSHOT 1000:10 1010:20\n\
+                   SNAPSHOT 1010:20 1030:8\n\
+                   SNAPSHOT 1010:8\n";
+        let parse_results = SnapshotParser::new().parse_log_directly(log, "");
+        let updates: Vec<(bool, usize, usize, usize)> = parse_results.memory_updates
+            .iter()
+            .map(|update| (
+                matches!(update, MemoryUpdateType::Allocation(_)),
+                update.get_absolute_address(),
+                update.get_absolute_size(),
+                update.get_timestamp(),
+            ))
+            .collect();
+        assert_eq!(updates, vec![
+            (true, 0x1000, 0x10, 0),
+            (true, 0x1010, 0x20, 1),
+            (false, 0x1000, 0x10, 2),
+            (true, 0x1030, 0x8, 3),
+            (false, 0x1010, 0x20, 4),
+            (false, 0x1030, 0x8, 5),
+            (true, 0x1010, 0x8, 6),
+        ]);
+        assert_eq!(parse_results.max_timestamp, 7);
+        assert_eq!(parse_results.memory_updates[2].get_real_timestamp(), "1 us");
+    }
+
+    #[test]
+    fn load_snapshot_log_test() {
+        let log_path = std::env::temp_dir().join("snapshot_parser_load_snapshot_log_test.log");
+        std::fs::write(&log_path, "SNAPSHOTS\nSNAPSHOT 1000:10 1010:20\nSNAPSHOT 1010:20 1030:8\n").unwrap();
+        let viewer = DamselflyViewerBuilder::new(log_path.to_str().unwrap())
+            .cache_size(1)
+            .build(SnapshotParser::new())
+            .unwrap();
+        assert_eq!(viewer.damselflies.len(), 1);
+        assert_eq!(viewer.damselflies[0].get_usage_graph_no_fallbacks().len(), 4);
+        assert!(!viewer.damselflies[0].get_usage_graph_realtime_sampled().is_empty());
+    }
+
+    #[test]
+    fn malformed_snapshot_log_test() {
+        assert!(matches!(SnapshotParser::parse_snapshot("SNAPSHOT 1000"), Err(DamselflyError::InvalidLog(_))));
+        assert!(matches!(SnapshotParser::parse_snapshot("SNAPSHOT 10g0:10"), Err(DamselflyError::InvalidLog(_))));
+        assert!(matches!(SnapshotParser::parse_snapshot("SNAPSHOT 1000:1x"), Err(DamselflyError::InvalidLog(_))));
+
+        let log_path = std::env::temp_dir().join("snapshot_parser_malformed_snapshot_log_test.log");
+        std::fs::write(&log_path, "SNAPSHOTS\nSNAPSHOT 1000:10\nSNAPSHOT 1000\n").unwrap();
+        let pools = SnapshotParser::new().parse_log_contents_split_by_pools(log_path.to_str().unwrap(), &[], 0, 0, None);
+        assert!(matches!(pools, Err(DamselflyError::InvalidLog(_))));
+        let pools = SnapshotParser::new().parse_log_contents_split_by_pools("/nonexistent/snapshots.log", &[], 0, 0, None);
+        assert!(matches!(pools, Err(DamselflyError::Io(_))));
+    }
+}
@@ -0,0 +1,69 @@
+//! Headline stats of a pool at a single timestamp, for the stats panel.
+use serde::Serialize;
+use crate::damselfly::memory::memory_usage::MemoryUsage;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct SummaryStats {
+    timestamp: u64,
+    memory_used_absolute: i128,
+    live_allocations: usize,
+    distinct_blocks: u128,
+    free_blocks: usize,
+    largest_free_block: usize,
+    free_segment_fragmentation: u128,
+    free_segment_fragmentation_percentage: f64,
+}
+
+impl SummaryStats {
+    /// Takes every stat from the same MemoryUsage, so they are consistent with each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_usage`: Usage at the timestamp.
+    ///
+    /// returns: SummaryStats
+    pub fn from_memory_usage(memory_usage: &MemoryUsage) -> Self {
+        Self {
+            timestamp: memory_usage.get_latest_operation() as u64,
+            memory_used_absolute: memory_usage.get_memory_used_absolute(),
+            live_allocations: memory_usage.get_live_allocations(),
+            distinct_blocks: memory_usage.get_distinct_blocks(),
+            free_blocks: memory_usage.get_free_blocks(),
+            largest_free_block: memory_usage.get_largest_free_block().2,
+            free_segment_fragmentation: memory_usage.get_free_segment_fragmentation(),
+            free_segment_fragmentation_percentage: memory_usage.get_free_segment_fragmentation_percentage(),
+        }
+    }
+
+    pub fn get_timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn get_memory_used_absolute(&self) -> i128 {
+        self.memory_used_absolute
+    }
+
+    pub fn get_live_allocations(&self) -> usize {
+        self.live_allocations
+    }
+
+    pub fn get_distinct_blocks(&self) -> u128 {
+        self.distinct_blocks
+    }
+
+    pub fn get_free_blocks(&self) -> usize {
+        self.free_blocks
+    }
+
+    pub fn get_largest_free_block(&self) -> usize {
+        self.largest_free_block
+    }
+
+    pub fn get_free_segment_fragmentation(&self) -> u128 {
+        self.free_segment_fragmentation
+    }
+
+    pub fn get_free_segment_fragmentation_percentage(&self) -> f64 {
+        self.free_segment_fragmentation_percentage
+    }
+}
@@ -0,0 +1,193 @@
+//! Backends that resolve callstack addresses to symbols (FILENAME:LINE_NO) using a binary's
+//! debuginfo. Implement Symbolicator to add another backend, then pass it to
+//! MemorySysTraceParser::with_symbolicator.
+use std::io::Write;
+use std::process::{Command, Stdio};
+use addr2line::Context;
+use owo_colors::OwoColorize;
+use crate::damselfly::consts::DEFAULT_GADDR2LINE_PATH;
+
+pub trait Symbolicator: Send + Sync {
+    /// Finds the symbol of each address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addresses`: Addresses to look up.
+    /// * `binary_path`: Path to the binary for debuginfo.
+    ///
+    /// returns: A symbol (FILENAME:LINE_NO, or [UNKNOWN SYMBOL]) for each address, in the same
+    /// order, or None if the binary can't be symbolicated at all.
+    fn symbolicate(&self, addresses: &[usize], binary_path: &str) -> Option<Vec<String>>;
+}
+
+/// Resolves addresses in-process against an ELF binary's DWARF debuginfo, using the addr2line
+/// crate. This is much faster than shelling out, but can't read every toolchain's output, so
+/// another symbolicator can be set as a fallback.
+#[derive(Default)]
+pub struct Addr2LineSymbolicator {
+    fallback: Option<Box<dyn Symbolicator>>,
+}
+
+impl Addr2LineSymbolicator {
+    pub fn new() -> Self {
+        Self {
+            fallback: None,
+        }
+    }
+
+    /// Sets the symbolicator to use when the binary can be read but addr2line can't parse it.
+    ///
+    /// # Arguments
+    ///
+    /// * `fallback`: Symbolicator to fall back to.
+    ///
+    /// returns: Addr2LineSymbolicator
+    pub fn with_fallback(mut self, fallback: Box<dyn Symbolicator>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+}
+
+impl Symbolicator for Addr2LineSymbolicator {
+    fn symbolicate(&self, addresses: &[usize], binary_path: &str) -> Option<Vec<String>> {
+        let buffer = match std::fs::read(binary_path) {
+            Ok(buffer) => buffer,
+            Err(error) => {
+                eprintln!("{}", format!("[Addr2LineSymbolicator::symbolicate]: Unable to read binary {binary_path}: {error}. Showing raw addresses instead.").yellow());
+                return None;
+            }
+        };
+        let Some(ctx) = object::File::parse(&*buffer).ok().and_then(|object| Context::new(&object).ok()) else {
+            return match &self.fallback {
+                Some(fallback) => fallback.symbolicate(addresses, binary_path),
+                None => {
+                    eprintln!("{}", format!("[Addr2LineSymbolicator::symbolicate]: Unable to parse debuginfo of {binary_path}. Showing raw addresses instead.").yellow());
+                    None
+                }
+            };
+        };
+
+        let mut symbols = Vec::new();
+        for address in addresses {
+            let mut symbol = String::new();
+            if let Ok(Some(location)) = ctx.find_location(*address as u64) {
+                symbol.push_str(location.file.unwrap());
+                symbol.push(':');
+                symbol.push_str(location.line.unwrap().to_string().as_str());
+                symbols.push(symbol);
+            } else {
+                symbols.push("[UNKNOWN SYMBOL]".to_string());
+            }
+        }
+        Some(symbols)
+    }
+}
+
+/// Resolves addresses by running the Green Hills gaddr2line on the binary. All addresses are
+/// resolved by a single invocation, which reads them from stdin.
+pub struct Gaddr2LineSymbolicator {
+    gaddr2line_path: String,
+}
+
+impl Gaddr2LineSymbolicator {
+    /// Constructor.
+    ///
+    /// # Arguments
+    ///
+    /// * `gaddr2line_path`: Path to gaddr2line.
+    ///
+    /// returns: Gaddr2LineSymbolicator
+    pub fn new(gaddr2line_path: &str) -> Self {
+        Self {
+            gaddr2line_path: gaddr2line_path.to_string(),
+        }
+    }
+}
+
+impl Default for Gaddr2LineSymbolicator {
+    fn default() -> Self {
+        Self::new(DEFAULT_GADDR2LINE_PATH)
+    }
+}
+
+impl Symbolicator for Gaddr2LineSymbolicator {
+    fn symbolicate(&self, addresses: &[usize], binary_path: &str) -> Option<Vec<String>> {
+        let gaddr2line_path = &self.gaddr2line_path;
+        let child = Command::new(gaddr2line_path)
+            .arg("-e")
+            .arg(binary_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(error) => {
+                eprintln!("{}", format!("[Gaddr2LineSymbolicator::symbolicate]: Unable to run {gaddr2line_path}: {error}. Showing raw addresses instead.").yellow());
+                return None;
+            }
+        };
+        // Written from another thread so a full stdout pipe can't deadlock us against gaddr2line
+        let input: String = addresses.iter().map(|address| format!("{address:x}\n")).collect();
+        let mut stdin = child.stdin.take().expect("[Gaddr2LineSymbolicator::symbolicate]: Failed to open stdin");
+        let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+        let output = child.wait_with_output();
+        let written = writer.join().expect("[Gaddr2LineSymbolicator::symbolicate]: Writer thread panicked");
+        let stdout = match output {
+            Ok(output) if output.status.success() && written.is_ok() => String::from_utf8_lossy(&output.stdout).into_owned(),
+            Ok(output) => {
+                eprintln!("{}", format!("[Gaddr2LineSymbolicator::symbolicate]: {gaddr2line_path} failed on {binary_path} ({}). Showing raw addresses instead.", output.status).yellow());
+                return None;
+            }
+            Err(error) => {
+                eprintln!("{}", format!("[Gaddr2LineSymbolicator::symbolicate]: Unable to run {gaddr2line_path}: {error}. Showing raw addresses instead.").yellow());
+                return None;
+            }
+        };
+        let symbols: Vec<String> = stdout
+            .lines()
+            .map(|line| if line.starts_with("??") { "[UNKNOWN SYMBOL]".to_string() } else { line.trim().to_string() })
+            .collect();
+        if symbols.len() != addresses.len() {
+            eprintln!("{}", format!("[Gaddr2LineSymbolicator::symbolicate]: Expected {} symbols from {gaddr2line_path}, got {}. Showing raw addresses instead.", addresses.len(), symbols.len()).yellow());
+            return None;
+        }
+        Some(symbols)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::damselfly::consts::TEST_BINARY_PATH;
+    use crate::damselfly::memory::symbolicator::{Addr2LineSymbolicator, Gaddr2LineSymbolicator, Symbolicator};
+
+    /// Symbolicates every address as the same symbol.
+    struct ConstantSymbolicator;
+
+    impl Symbolicator for ConstantSymbolicator {
+        fn symbolicate(&self, addresses: &[usize], _binary_path: &str) -> Option<Vec<String>> {
+            Some(vec!["fallback.c:1".to_string(); addresses.len()])
+        }
+    }
+
+    #[test]
+    fn missing_gaddr2line_test() {
+        assert_eq!(Gaddr2LineSymbolicator::new("./missing_gaddr2line").symbolicate(&[0xe045d83b], TEST_BINARY_PATH), None);
+    }
+
+    #[test]
+    fn addr2line_fallback_test() {
+        // Readable, but not an object file
+        let binary_path = std::env::temp_dir().join("symbolicator_addr2line_fallback_test.bin");
+        std::fs::write(&binary_path, "not an object file").unwrap();
+        let binary_path = binary_path.to_str().unwrap();
+
+        assert_eq!(Addr2LineSymbolicator::new().symbolicate(&[0x10], binary_path), None);
+        assert_eq!(
+            Addr2LineSymbolicator::new()
+                .with_fallback(Box::new(ConstantSymbolicator))
+                .symbolicate(&[0x10, 0x20], binary_path),
+            Some(vec!["fallback.c:1".to_string(), "fallback.c:1".to_string()])
+        );
+        assert_eq!(Addr2LineSymbolicator::new().symbolicate(&[0x10], "./missing_binary"), None);
+    }
+}
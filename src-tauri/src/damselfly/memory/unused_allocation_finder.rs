@@ -0,0 +1,74 @@
+//! Finds allocations that are freed without ever being accessed. Requires a trace with access events.
+use rust_lapper::{Interval, Lapper};
+use crate::damselfly::memory::memory_access::MemoryAccess;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+use crate::damselfly::update_interval::update_queue_compressor::UpdateQueueCompressor;
+
+pub struct UnusedAllocationFinder;
+
+impl UnusedAllocationFinder {
+    /// Finds allocations with no access overlapping them between their allocation and their free.
+    /// Allocations that are never freed are not reported, since they could still be accessed
+    /// after the trace ends.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates, sorted by timestamp.
+    /// * `memory_accesses`: Accesses, each timestamped with the latest update logged before it.
+    ///
+    /// returns: Vec of unused allocations, sorted by timestamp.
+    pub fn find_unused_allocations(memory_updates: &[MemoryUpdateType], memory_accesses: &[MemoryAccess]) -> Vec<MemoryUpdateType> {
+        let access_lapper: Lapper<usize, usize> = Lapper::new(
+            memory_accesses
+                .iter()
+                .map(|access| Interval {
+                    start: access.get_address(),
+                    stop: access.get_address() + access.get_size().max(1),
+                    val: access.get_timestamp(),
+                })
+                .collect()
+        );
+
+        let mut unused_allocations = Vec::new();
+        for (update, (freed, _)) in memory_updates.iter().zip(UpdateQueueCompressor::live_set_changes(memory_updates)) {
+            if let Some(allocation) = freed {
+                let allocated_at = allocation.get_timestamp();
+                let freed_at = update.get_timestamp();
+                let accessed = access_lapper
+                    .find(allocation.get_start(), allocation.get_end())
+                    .any(|access| (allocated_at..freed_at).contains(&access.val));
+                if !accessed {
+                    unused_allocations.push(allocation);
+                }
+            }
+        }
+
+        unused_allocations.sort_by_key(|allocation| allocation.get_timestamp());
+        unused_allocations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::memory_access::MemoryAccess;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate};
+    use crate::damselfly::memory::unused_allocation_finder::UnusedAllocationFinder;
+
+    #[test]
+    fn find_unused_allocations_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates = vec![
+            Allocation::new(0, 8, callstack.clone(), 0, "0".to_string()).wrap_in_enum(),
+            Allocation::new(8, 8, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Free::new(8, 8, callstack.clone(), 3, "0".to_string()).wrap_in_enum(),
+            Allocation::new(16, 8, callstack, 4, "0".to_string()).wrap_in_enum(),
+        ];
+        // access to the first allocation after it is allocated, and to the second after it is freed
+        let accesses = vec![MemoryAccess::new(4, 4, 1), MemoryAccess::new(8, 4, 3)];
+        let unused_allocations = UnusedAllocationFinder::find_unused_allocations(&updates, &accesses);
+        assert_eq!(unused_allocations.len(), 1);
+        assert_eq!(unused_allocations[0].get_absolute_address(), 8);
+    }
+}
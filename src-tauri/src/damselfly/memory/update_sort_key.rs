@@ -0,0 +1,60 @@
+//! Keys that lists of memory updates, like the operation log, can be sorted by.
+use serde::Deserialize;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum UpdateSortKey {
+    /// Ascending timestamp.
+    #[default]
+    Time,
+    /// Ascending address, then ascending timestamp.
+    Address,
+    /// Ascending size, then ascending timestamp.
+    Size,
+}
+
+impl UpdateSortKey {
+    /// Sorts updates by this key. Ties are broken by timestamp, so the order is always well-defined.
+    ///
+    /// # Arguments
+    ///
+    /// * `updates`: Updates to sort in place.
+    ///
+    /// returns: ()
+    pub fn sort_updates(&self, updates: &mut [MemoryUpdateType]) {
+        match self {
+            UpdateSortKey::Time => updates.sort_by_key(|update| update.get_timestamp()),
+            UpdateSortKey::Address => updates.sort_by_key(|update| (update.get_absolute_address(), update.get_timestamp())),
+            UpdateSortKey::Size => updates.sort_by_key(|update| (update.get_absolute_size(), update.get_timestamp())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
+    use crate::damselfly::memory::update_sort_key::UpdateSortKey;
+
+    fn build_updates() -> Vec<MemoryUpdateType> {
+        let callstack = Arc::new("test".to_string());
+        vec![
+            Free::new(16, 8, callstack.clone(), 2, "0".to_string()).wrap_in_enum(),
+            Allocation::new(32, 4, callstack.clone(), 1, "0".to_string()).wrap_in_enum(),
+            Allocation::new(16, 8, callstack, 0, "0".to_string()).wrap_in_enum(),
+        ]
+    }
+
+    fn sorted_timestamps(sort_key: UpdateSortKey) -> Vec<usize> {
+        let mut updates = build_updates();
+        sort_key.sort_updates(&mut updates);
+        updates.iter().map(|update| update.get_timestamp()).collect()
+    }
+
+    #[test]
+    fn sort_updates_test() {
+        assert_eq!(sorted_timestamps(UpdateSortKey::Time), vec![0, 1, 2]);
+        assert_eq!(sorted_timestamps(UpdateSortKey::Address), vec![0, 2, 1]);
+        assert_eq!(sorted_timestamps(UpdateSortKey::Size), vec![1, 0, 2]);
+    }
+}
@@ -8,4 +8,5 @@ pub mod overlap_finder;
 pub mod distinct_block_counter;
 pub mod utility;
 pub mod update_queue_compressor;
+pub mod serializable_update_interval;
 
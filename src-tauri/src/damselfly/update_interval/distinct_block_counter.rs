@@ -1,7 +1,7 @@
 //! State machine. Push updates to it and query statistics after each push. Despite its name it 
 //! computes statistics other than just no. of distinct blocks.
 use std::cmp::{max, min};
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 use crate::damselfly::memory::memory_update::{MemoryUpdate, MemoryUpdateType};
 use crate::damselfly::memory::NoHashMap;
@@ -20,6 +20,11 @@ pub struct DistinctBlockCounter {
     distinct_blocks: u128,
     free_blocks: Vec<(usize, usize)>,
     free_space: u128,
+    // live allocated blocks (runs of adjacent allocations), start -> end
+    allocated_blocks: BTreeMap<usize, usize>,
+    // size -> number of live allocated blocks of that size
+    allocated_block_sizes: BTreeMap<usize, usize>,
+    allocated_space: u128,
 }
 
 impl DistinctBlockCounter {
@@ -67,6 +72,9 @@ impl DistinctBlockCounter {
             distinct_blocks: 0,
             free_blocks: Vec::new(),
             free_space: 0,
+            allocated_blocks: BTreeMap::new(),
+            allocated_block_sizes: BTreeMap::new(),
+            allocated_space: 0,
         };
 
         /*
@@ -89,6 +97,12 @@ impl DistinctBlockCounter {
     /// 
     /// returns: () 
     pub fn push_update(&mut self, update: &MemoryUpdateType) {
+        if let MemoryUpdateType::Reallocation(reallocation) = update {
+            let (free, allocation) = reallocation.to_free_and_allocation();
+            self.push_update(&free.wrap_in_enum());
+            self.push_update(&allocation.wrap_in_enum());
+            return;
+        }
         let start = update.get_start().saturating_sub(self.left_padding);
         let end = update.get_end().saturating_add(self.right_padding);
         let mut left_attached = false;
@@ -120,6 +134,7 @@ impl DistinctBlockCounter {
                 self.ends_set.insert(end);
                 self.starts_tree.insert(start);
                 self.ends_tree.insert(end);
+                self.add_allocated_block(start, end);
             }
             MemoryUpdateType::Free(_) => {
                 // breaks a block into two blocks, increasing fragmentation
@@ -137,7 +152,9 @@ impl DistinctBlockCounter {
                 self.ends_set.remove(&end);
                 self.starts_tree.remove(&start);
                 self.ends_tree.remove(&end);
+                self.remove_allocated_block(start, end);
             }
+            MemoryUpdateType::Reallocation(_) => unreachable!("[DistinctBlockCounter::push_update]: Reallocations are pushed as a free and an alloc"),
         };
         
         if self.manually_track_memory_bounds {
@@ -148,6 +165,77 @@ impl DistinctBlockCounter {
         self.distinct_blocks = self.distinct_blocks.saturating_add_signed(block_delta as i128);
     }
 
+    /// Adds an allocation to the live allocated blocks, merging it with any blocks it is adjacent to
+    /// or overlaps.
+    ///
+    /// # Arguments
+    ///
+    /// * `start`: Padded start of the allocation.
+    /// * `end`: Padded end of the allocation.
+    ///
+    /// returns: ()
+    fn add_allocated_block(&mut self, start: usize, end: usize) {
+        // live blocks never touch, so they are sorted by end as well as by start
+        let touching_blocks: Vec<(usize, usize)> = self.allocated_blocks
+            .range(..=end)
+            .rev()
+            .take_while(|(_, block_end)| **block_end >= start)
+            .map(|(block_start, block_end)| (*block_start, *block_end))
+            .collect();
+        let mut merged_start = start;
+        let mut merged_end = end;
+        for (block_start, block_end) in touching_blocks {
+            self.remove_allocated_block_size(block_start, block_end);
+            merged_start = min(merged_start, block_start);
+            merged_end = max(merged_end, block_end);
+        }
+        self.insert_allocated_block_size(merged_start, merged_end);
+    }
+
+    /// Removes a free from the live allocated blocks, trimming or splitting every block it
+    /// overlaps. Frees outside any live block are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `start`: Padded start of the free.
+    /// * `end`: Padded end of the free.
+    ///
+    /// returns: ()
+    fn remove_allocated_block(&mut self, start: usize, end: usize) {
+        let overlapping_blocks: Vec<(usize, usize)> = self.allocated_blocks
+            .range(..end)
+            .rev()
+            .take_while(|(_, block_end)| **block_end > start)
+            .map(|(block_start, block_end)| (*block_start, *block_end))
+            .collect();
+        for (block_start, block_end) in overlapping_blocks {
+            self.remove_allocated_block_size(block_start, block_end);
+            if block_start < start {
+                self.insert_allocated_block_size(block_start, start);
+            }
+            if end < block_end {
+                self.insert_allocated_block_size(end, block_end);
+            }
+        }
+    }
+
+    fn insert_allocated_block_size(&mut self, start: usize, end: usize) {
+        self.allocated_blocks.insert(start, end);
+        *self.allocated_block_sizes.entry(end - start).or_default() += 1;
+        self.allocated_space += (end - start) as u128;
+    }
+
+    fn remove_allocated_block_size(&mut self, start: usize, end: usize) {
+        self.allocated_blocks.remove(&start);
+        if let Some(count) = self.allocated_block_sizes.get_mut(&(end - start)) {
+            *count -= 1;
+            if *count == 0 {
+                self.allocated_block_sizes.remove(&(end - start));
+            }
+        }
+        self.allocated_space -= (end - start) as u128;
+    }
+
     /// Calculates free blocks and stores them within the struct.
     pub fn calculate_free_blocks(&mut self) {
         let mut starts_iter = self.starts_tree.iter();
@@ -197,6 +285,19 @@ impl DistinctBlockCounter {
         0
     }
     
+    /// Gets the fraction of free space that is not in the largest free block, as a percentage.
+    /// 0 means all free space is in one block (or there is no free space), and values approaching
+    /// 100 mean free space is scattered across many small blocks.
+    ///
+    /// returns: (1 - (largest free block) / (total free bytes)) * 100
+    pub fn get_free_segment_fragmentation_percentage(&self) -> f64 {
+        let total_free: usize = self.free_blocks.iter().map(|block| block.1 - block.0).sum();
+        if total_free == 0 {
+            return 0.0;
+        }
+        (1.0 - self.get_largest_free_block().2 as f64 / total_free as f64) * 100.0
+    }
+
     /// Gets the largest free block
     /// 
     /// returns: (start, end, size)
@@ -233,6 +334,11 @@ impl DistinctBlockCounter {
                 new_start = free.get_absolute_address();
                 new_stop = new_start + free.get_absolute_size();
             }
+            MemoryUpdateType::Reallocation(reallocation) => {
+                new_start = min(reallocation.get_old_address(), reallocation.get_absolute_address());
+                new_stop = max(reallocation.get_old_address() + reallocation.get_old_size(),
+                               reallocation.get_absolute_address() + reallocation.get_absolute_size());
+            }
         }
         self.start = min(self.start, new_start);
         self.stop = max(self.stop, new_stop);
@@ -246,16 +352,41 @@ impl DistinctBlockCounter {
         self.free_blocks.clone()
     }
 
+    /// Gets the number of free gaps, i.e. free blocks with a live allocation on both sides.
+    /// Unlike get_free_blocks, free space running to the start or end of the memory bounds is not
+    /// counted, so a completely free pool has no gaps.
+    ///
+    /// returns: Number of free gaps.
+    pub fn get_free_gap_count(&self) -> usize {
+        self.free_blocks
+            .iter()
+            .filter(|(start, stop)| *start != self.start && *stop != self.stop)
+            .count()
+    }
+
+    /// Gets the size profile of the live allocated blocks, where adjacent allocations are merged
+    /// into one block like in get_distinct_blocks.
+    ///
+    /// returns: (min, max, mean) block size in bytes, or (0, 0, 0.0) if nothing is allocated.
+    pub fn get_allocated_block_stats(&self) -> (usize, usize, f64) {
+        let (Some(min_size), Some(max_size)) = (self.allocated_block_sizes.keys().next(), self.allocated_block_sizes.keys().next_back()) else {
+            return (0, 0, 0.0);
+        };
+        (*min_size, *max_size, self.allocated_space as f64 / self.allocated_blocks.len() as f64)
+    }
+
     pub fn get_memory_bounds(&self) -> (usize, usize) {
         (self.start, self.stop)
     }
 
 }
 
+#[cfg(test)]
 mod tests {
     use crate::damselfly::consts::{TEST_BINARY_PATH, TEST_LOG};
     use crate::damselfly::memory::memory_parsers::{MemoryParser, MemorySysTraceParser};
-    use crate::damselfly::memory::memory_update::MemoryUpdateType;
+    use std::sync::Arc;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
     use crate::damselfly::update_interval::distinct_block_counter::DistinctBlockCounter;
 
     fn _initialise_test_log() -> (Vec<MemoryUpdateType>, DistinctBlockCounter) {
@@ -287,4 +418,94 @@ mod tests {
         assert_eq!(distinct_blocks, 4);
         assert_eq!(free_blocks.len(), 3);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn free_segment_fragmentation_percentage_test() {
+        let callstack = Arc::new("test".to_string());
+        let mut distinct_block_counter = DistinctBlockCounter::new(vec![], 0, 0, Some((0, 100)));
+        assert_eq!(distinct_block_counter.get_free_segment_fragmentation_percentage(), 0.0);
+
+        // One free block: [0..100) minus [0..10)
+        distinct_block_counter.push_update(&Allocation::new(0, 10, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_free_segment_fragmentation_percentage(), 0.0);
+
+        // Free blocks [10..20) and [30..100), so 10 of the 80 free bytes are outside the largest
+        distinct_block_counter.push_update(&Allocation::new(20, 10, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_free_segment_fragmentation_percentage(), 12.5);
+
+        // No free space
+        distinct_block_counter.push_update(&Allocation::new(10, 10, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum());
+        distinct_block_counter.push_update(&Allocation::new(30, 70, callstack, 3, "0001.003 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_free_segment_fragmentation_percentage(), 0.0);
+    }
+
+    #[test]
+    fn free_gap_count_test() {
+        let callstack = Arc::new("test".to_string());
+        let mut distinct_block_counter = DistinctBlockCounter::new(vec![], 0, 0, Some((0, 100)));
+        assert_eq!(distinct_block_counter.get_free_gap_count(), 0);
+
+        // Free blocks [0..10) and [20..100) both touch the bounds
+        distinct_block_counter.push_update(&Allocation::new(10, 10, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_free_blocks().len(), 2);
+        assert_eq!(distinct_block_counter.get_free_gap_count(), 0);
+
+        // [20..40) is between two allocations
+        distinct_block_counter.push_update(&Allocation::new(40, 10, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_free_gap_count(), 1);
+
+        // Adjacent allocations leave no gap
+        distinct_block_counter.push_update(&Allocation::new(20, 20, callstack, 2, "0001.002 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_free_gap_count(), 0);
+    }
+
+    #[test]
+    fn allocated_block_stats_test() {
+        let callstack = Arc::new("test".to_string());
+        let mut distinct_block_counter = DistinctBlockCounter::new(vec![], 0, 0, Some((0, 100)));
+        assert_eq!(distinct_block_counter.get_allocated_block_stats(), (0, 0, 0.0));
+
+        distinct_block_counter.push_update(&Allocation::new(10, 10, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum());
+        distinct_block_counter.push_update(&Allocation::new(40, 20, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_allocated_block_stats(), (10, 20, 15.0));
+
+        // Glues both blocks into [10..60)
+        distinct_block_counter.push_update(&Allocation::new(20, 20, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_allocated_block_stats(), (50, 50, 50.0));
+
+        // Freeing the middle allocation splits it back up
+        distinct_block_counter.push_update(&Free::new(20, 20, callstack.clone(), 3, "0001.003 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_allocated_block_stats(), (10, 20, 15.0));
+
+        distinct_block_counter.push_update(&Free::new(10, 10, callstack, 4, "0001.004 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_allocated_block_stats(), (20, 20, 20.0));
+    }
+
+    #[test]
+    fn allocated_block_stats_overlap_test() {
+        let callstack = Arc::new("test".to_string());
+        let mut distinct_block_counter = DistinctBlockCounter::new(vec![], 0, 0, Some((0, 100)));
+
+        // Same start as a live block, so both are one block [10..30)
+        distinct_block_counter.push_update(&Allocation::new(10, 10, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum());
+        distinct_block_counter.push_update(&Allocation::new(10, 20, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_allocated_block_stats(), (20, 20, 20.0));
+
+        // Overlaps [10..30) and [40..50), merging everything into [10..50)
+        distinct_block_counter.push_update(&Allocation::new(40, 10, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum());
+        distinct_block_counter.push_update(&Allocation::new(25, 20, callstack, 3, "0001.003 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_allocated_block_stats(), (40, 40, 40.0));
+    }
+
+    #[test]
+    fn allocated_block_stats_straddling_free_test() {
+        let callstack = Arc::new("test".to_string());
+        let mut distinct_block_counter = DistinctBlockCounter::new(vec![], 0, 0, Some((0, 100)));
+        distinct_block_counter.push_update(&Allocation::new(10, 20, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum());
+        distinct_block_counter.push_update(&Allocation::new(40, 20, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum());
+
+        // Trims [10..30) to [10..20) and [40..60) to [50..60)
+        distinct_block_counter.push_update(&Free::new(20, 30, callstack, 2, "0001.002 s".to_string()).wrap_in_enum());
+        assert_eq!(distinct_block_counter.get_allocated_block_stats(), (10, 10, 10.0));
+    }
+}
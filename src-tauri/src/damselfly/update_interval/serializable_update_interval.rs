@@ -0,0 +1,34 @@
+//! Serializable mirror of UpdateInterval.
+//!
+//! UpdateInterval is an alias for rust_lapper::Interval, which does not implement Serialize,
+//! so intervals are converted into this struct before being sent to the frontend.
+use serde::{Deserialize, Serialize};
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+use crate::damselfly::update_interval::UpdateInterval;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerializableUpdateInterval {
+    pub start: usize,
+    pub stop: usize,
+    pub val: MemoryUpdateType,
+}
+
+impl From<&UpdateInterval> for SerializableUpdateInterval {
+    fn from(update_interval: &UpdateInterval) -> Self {
+        Self {
+            start: update_interval.start,
+            stop: update_interval.stop,
+            val: update_interval.val.clone(),
+        }
+    }
+}
+
+impl From<SerializableUpdateInterval> for UpdateInterval {
+    fn from(serializable_update_interval: SerializableUpdateInterval) -> Self {
+        UpdateInterval {
+            start: serializable_update_interval.start,
+            stop: serializable_update_interval.stop,
+            val: serializable_update_interval.val,
+        }
+    }
+}
@@ -11,6 +11,7 @@ impl UpdateIntervalSorter {
                 match &update_interval.val {
                     MemoryUpdateType::Allocation(allocation) => allocation.get_timestamp(),
                     MemoryUpdateType::Free(free) => free.get_timestamp(),
+                    MemoryUpdateType::Reallocation(reallocation) => reallocation.get_timestamp(),
                 }
             };
 
@@ -22,7 +23,7 @@ impl UpdateIntervalSorter {
 #[cfg(test)]
 mod tests {
     use crate::damselfly::consts::{OVERLAP_FINDER_TEST_LOG, TEST_BINARY_PATH};
-    use crate::damselfly::memory::memory_parsers::MemorySysTraceParser;
+    use crate::damselfly::memory::memory_parsers::{MemoryParser, MemorySysTraceParser};
     use crate::damselfly::memory::memory_update::{MemoryUpdate, MemoryUpdateType};
     use crate::damselfly::update_interval::overlap_finder::OverlapFinder;
     use crate::damselfly::update_interval::update_interval_factory::UpdateIntervalFactory;
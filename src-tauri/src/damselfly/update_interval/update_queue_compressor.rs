@@ -1,6 +1,7 @@
 //! Utility struct that compresses updates. It does this by deleting allocs that have a corresponding free.
 //! Use this when you only care about the result of a collection of updates.
-use crate::damselfly::memory::memory_update::{MemoryUpdate, MemoryUpdateType};
+use crate::damselfly::memory::allocation_pairer::AllocationPairer;
+use crate::damselfly::memory::memory_update::{Free, MemoryUpdate, MemoryUpdateType};
 use crate::damselfly::update_interval::UpdateInterval;
 
 pub struct UpdateQueueCompressor { }
@@ -12,54 +13,73 @@ impl UpdateQueueCompressor {
     /// 
     /// * `updates`: Updates to compress.
     /// 
-    /// returns: Compressed updates.
-    pub fn compress_to_allocs(updates: &Vec<MemoryUpdateType>) -> Vec<MemoryUpdateType> {
-        let mut compressed_updates = Vec::new();
-        for update in updates {
-            match update {
-                MemoryUpdateType::Allocation(allocation) => compressed_updates.push(allocation.clone().wrap_in_enum()),
-                MemoryUpdateType::Free(free) => {
-                    let alloc_to_remove = compressed_updates
-                        .iter()
-                        .position(|update| {
-                            match update {
-                                MemoryUpdateType::Allocation(allocation) =>
-                                    allocation.get_absolute_address() == free.get_absolute_address(),
-                                MemoryUpdateType::Free(_) => panic!("[UpdateQueueCompressor::compress_to_allocs_only]: Free found in compressed_updates"),
-                            }
-                        })
-                        .or(None);
-                    if let Some(alloc_to_remove) = alloc_to_remove {
-                        compressed_updates.remove(alloc_to_remove);
-                    }
-                }
-            };
+    /// returns: (compressed updates, orphaned frees). Orphaned frees are frees with no prior
+    /// allocation at their address, e.g. in logs that start mid-stream. They are skipped.
+    pub fn compress_to_allocs(updates: &[MemoryUpdateType]) -> (Vec<MemoryUpdateType>, Vec<MemoryUpdateType>) {
+        Self::compress(updates.iter())
+    }
+
+    /// Gets the allocations live at a timestamp: those made at or before it that aren't freed at or
+    /// before it. This is the set of allocations drawn on the map at that timestamp, so replaying
+    /// the updates after the timestamp over it reproduces the rest of the timeline.
+    ///
+    /// # Arguments
+    ///
+    /// * `updates`: Updates, sorted by timestamp.
+    /// * `timestamp`: Operation timestamp to get the live allocations at.
+    ///
+    /// returns: Live allocations, in the order they were made. Reallocations are replaced by the
+    /// allocation of their new region.
+    pub fn live_set_at(updates: &[MemoryUpdateType], timestamp: u64) -> Vec<MemoryUpdateType> {
+        let is_at_or_before = |index: usize| updates[index].get_timestamp() as u64 <= timestamp;
+        AllocationPairer::pair_allocs_with_frees(updates)
+            .into_iter()
+            .filter(|(allocation_index, free_index)|
+                is_at_or_before(*allocation_index) && !free_index.is_some_and(is_at_or_before))
+            .map(|(allocation_index, _)| Self::allocated_region(&updates[allocation_index]))
+            .collect()
+    }
+
+    /// Gets the change each update makes to the live set, pairing allocations with frees the same
+    /// way as live_set_at. Applying the changes of the updates up to a timestamp gives the live set
+    /// at that timestamp, so use this when sweeping through every update.
+    ///
+    /// # Arguments
+    ///
+    /// * `updates`: Updates, sorted by timestamp.
+    ///
+    /// returns: Vec<(freed allocation, new allocation)>, one per update. The freed allocation is
+    /// None for allocations and for frees of addresses that aren't live. Reallocations are
+    /// replaced by the allocation of their new region.
+    pub fn live_set_changes(updates: &[MemoryUpdateType]) -> Vec<(Option<MemoryUpdateType>, Option<MemoryUpdateType>)> {
+        let mut changes = vec![(None, None); updates.len()];
+        for (allocation_index, free_index) in AllocationPairer::pair_allocs_with_frees(updates) {
+            let allocation = Self::allocated_region(&updates[allocation_index]);
+            if let Some(free_index) = free_index {
+                changes[free_index].0 = Some(allocation.clone());
+            }
+            changes[allocation_index].1 = Some(allocation);
         }
-        compressed_updates
+        changes
     }
 
-    /// I don't remember how this differs from compress_to_allocs...
-    pub fn compress_ref_to_allocs(updates: &Vec<&MemoryUpdateType>) -> Vec<MemoryUpdateType> {
-        let mut compressed_updates = Vec::new();
-        for update in updates {
-            match update {
-                MemoryUpdateType::Allocation(allocation) => compressed_updates.push(allocation.clone().wrap_in_enum()),
-                MemoryUpdateType::Free(free) => {
-                    compressed_updates.remove(
-                        compressed_updates
-                            .iter()
-                            .position(|update| {
-                                match update {
-                                    MemoryUpdateType::Allocation(allocation) =>
-                                        allocation.get_absolute_address() == free.get_absolute_address(),
-                                    MemoryUpdateType::Free(_) => panic!("[UpdateQueueCompressor::compress_to_allocs_only]: Free found in compressed_updates"),
-                                }
-                            })
-                            .expect("[UpdateQueueCompressor::strip_frees_and_corresponding_allocs]: Cannot find alloc corresponding to free"));
-                }
-            };
+    /// Gets the allocation an alloc or reallocation makes.
+    fn allocated_region(update: &MemoryUpdateType) -> MemoryUpdateType {
+        match update {
+            MemoryUpdateType::Reallocation(reallocation) => reallocation.to_free_and_allocation().1.wrap_in_enum(),
+            update => update.clone(),
         }
-        compressed_updates
+    }
+
+    /// Compresses borrowed updates by removing allocs with corresponding frees.
+    ///
+    /// # Arguments
+    ///
+    /// * `updates`: Updates to compress.
+    ///
+    /// returns: (compressed updates, orphaned frees), like compress_to_allocs.
+    pub fn compress_ref_to_allocs(updates: &Vec<&MemoryUpdateType>) -> (Vec<MemoryUpdateType>, Vec<MemoryUpdateType>) {
+        Self::compress(updates.iter().copied())
     }
     
     /// Compresses a list of Intervals by deleting allocations that have corresponding frees.
@@ -68,36 +88,58 @@ impl UpdateQueueCompressor {
     /// 
     /// * `updates`: Intervals to compress.
     /// 
-    /// returns: Compressed intervals.
-    pub fn compress_intervals(updates: Vec<&UpdateInterval>) -> Vec<MemoryUpdateType> {
+    /// returns: (compressed updates, orphaned frees), like compress_to_allocs.
+    pub fn compress_intervals(updates: Vec<&UpdateInterval>) -> (Vec<MemoryUpdateType>, Vec<MemoryUpdateType>) {
+        Self::compress(updates.into_iter().map(|update| &update.val))
+    }
+
+    /// Removes allocs with corresponding frees. A free removes the latest remaining alloc at its
+    /// address, like AllocationPairer. A reallocation frees its old region, then allocates its new one.
+    fn compress<'a>(updates: impl Iterator<Item = &'a MemoryUpdateType>) -> (Vec<MemoryUpdateType>, Vec<MemoryUpdateType>) {
         let mut compressed_updates = Vec::new();
+        let mut orphaned_frees = Vec::new();
         for update in updates {
-            match &update.val {
+            match update {
                 MemoryUpdateType::Allocation(allocation) => compressed_updates.push(allocation.clone().wrap_in_enum()),
-                MemoryUpdateType::Free(free) => {
-                    compressed_updates.remove(
-                        compressed_updates
-                            .iter()
-                            .position(|update| {
-                                match update {
-                                    MemoryUpdateType::Allocation(allocation) => 
-                                        allocation.get_absolute_address() == free.get_absolute_address(),
-                                    MemoryUpdateType::Free(_) => panic!("[UpdateQueueCompressor::compress_intervals]: Free found in compressed_updates"),
-                                }
-                            })
-                            .expect("[UpdateQueueCompressor::compress_intervals]: Cannot find alloc corresponding to free"));
+                MemoryUpdateType::Free(free) => Self::remove_alloc(&mut compressed_updates, &mut orphaned_frees, free),
+                MemoryUpdateType::Reallocation(reallocation) => {
+                    let (free, allocation) = reallocation.to_free_and_allocation();
+                    Self::remove_alloc(&mut compressed_updates, &mut orphaned_frees, &free);
+                    compressed_updates.push(allocation.wrap_in_enum());
                 }
-            }
+            };
+        }
+        (compressed_updates, orphaned_frees)
+    }
+
+    /// Removes the latest remaining alloc at a free's address, or records the free as orphaned
+    /// if there is none.
+    fn remove_alloc(compressed_updates: &mut Vec<MemoryUpdateType>, orphaned_frees: &mut Vec<MemoryUpdateType>, free: &Free) {
+        let alloc_to_remove = compressed_updates
+            .iter()
+            .rposition(|update| {
+                match update {
+                    MemoryUpdateType::Allocation(allocation) =>
+                        allocation.get_absolute_address() == free.get_absolute_address(),
+                    _ => panic!("[UpdateQueueCompressor::compress]: Non-alloc found in compressed_updates"),
+                }
+            });
+        match alloc_to_remove {
+            Some(alloc_to_remove) => { compressed_updates.remove(alloc_to_remove); }
+            None => orphaned_frees.push(free.clone().wrap_in_enum()),
         }
-        compressed_updates
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::damselfly::consts::{OVERLAP_FINDER_TEST_LOG, TEST_BINARY_PATH};
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use crate::damselfly::consts::{OVERLAP_FINDER_TEST_LOG, TEST_BINARY_PATH, TEST_LOG};
+    use crate::damselfly::memory::memory_cache::MemoryCache;
     use crate::damselfly::memory::memory_parsers::{MemoryParser, MemorySysTraceParser};
-    use crate::damselfly::memory::memory_update::{MemoryUpdate, MemoryUpdateType};
+    use crate::damselfly::memory::memory_status::MemoryStatus;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType};
     use crate::damselfly::update_interval::overlap_finder::OverlapFinder;
     use crate::damselfly::update_interval::update_interval_factory::UpdateIntervalFactory;
     use crate::damselfly::update_interval::update_queue_compressor::UpdateQueueCompressor;
@@ -115,9 +157,10 @@ mod tests {
         let overlap_finder = initialise_test_log();
         let overlaps = overlap_finder.find_overlaps(0, 400);
         let updates = Utility::convert_intervals_to_updates(&overlaps);
-        let compressed_updates = UpdateQueueCompressor::compress_ref_to_allocs(&updates);
+        let (compressed_updates, orphaned_frees) = UpdateQueueCompressor::compress_ref_to_allocs(&updates);
 
         assert_eq!(compressed_updates.len(), 5);
+        assert!(orphaned_frees.is_empty());
         for update in &compressed_updates {
             assert!(matches!(*update, MemoryUpdateType::Allocation(_)));
         }
@@ -147,5 +190,90 @@ mod tests {
             assert_eq!(allocation.get_absolute_size(), 20);
         }
     }
-}
 
+    #[test]
+    fn compress_orphaned_free_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates = vec![
+            // Log starts mid-stream, so this address was allocated before it
+            Free::new(32, 8, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(0, 8, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Allocation::new(16, 8, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack, 3, "0001.003 s".to_string()).wrap_in_enum(),
+        ];
+        let (compressed_updates, orphaned_frees) = UpdateQueueCompressor::compress_to_allocs(&updates);
+        assert_eq!(compressed_updates, vec![updates[2].clone()]);
+        assert_eq!(orphaned_frees, vec![updates[0].clone()]);
+
+        let update_refs: Vec<&MemoryUpdateType> = updates.iter().collect();
+        assert_eq!(UpdateQueueCompressor::compress_ref_to_allocs(&update_refs), (compressed_updates.clone(), orphaned_frees.clone()));
+
+        let update_intervals = UpdateIntervalFactory::new(updates.clone()).construct_enum_vector();
+        assert_eq!(UpdateQueueCompressor::compress_intervals(update_intervals.iter().collect()), (compressed_updates, orphaned_frees));
+    }
+
+    #[test]
+    fn live_set_at_matches_map_test() {
+        let updates = MemorySysTraceParser::new().parse_log_directly(TEST_LOG, TEST_BINARY_PATH).memory_updates;
+        let memory_cache = MemoryCache::new(4, UpdateIntervalFactory::new(updates.clone()).construct_enum_vector(), 2);
+        for timestamp in [0, 2, 4] {
+            let live_addresses: HashSet<usize> = UpdateQueueCompressor::live_set_at(&updates, timestamp)
+                .iter()
+                .map(|update| update.get_absolute_address())
+                .collect();
+            assert_eq!(live_addresses.len(), timestamp as usize + 1);
+
+            let allocated_addresses: HashSet<usize> = memory_cache.query_cache(timestamp as usize)
+                .unwrap()
+                .iter()
+                .filter(|block| matches!(block, MemoryStatus::Allocated(..) | MemoryStatus::PartiallyAllocated(..)))
+                .filter_map(|block| block.get_parent_address())
+                .collect();
+            assert_eq!(allocated_addresses, live_addresses);
+        }
+    }
+
+    #[test]
+    fn live_set_at_excludes_freed_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates = vec![
+            Allocation::new(0, 8, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(16, 8, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Free::new(0, 8, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Allocation::new(0, 8, callstack, 3, "0001.003 s".to_string()).wrap_in_enum(),
+        ];
+        let live_timestamps = |timestamp: u64| -> Vec<usize> {
+            UpdateQueueCompressor::live_set_at(&updates, timestamp)
+                .iter()
+                .map(|update| update.get_timestamp())
+                .collect()
+        };
+        assert_eq!(live_timestamps(1), vec![0, 1]);
+        assert_eq!(live_timestamps(2), vec![1]);
+        assert_eq!(live_timestamps(3), vec![1, 3]);
+    }
+
+    #[test]
+    fn live_set_changes_pairs_like_compress_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates = vec![
+            Allocation::new(0, 8, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            // The free of the first allocation is missing from the log
+            Allocation::new(0, 16, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Free::new(0, 16, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Free::new(32, 8, callstack, 3, "0001.003 s".to_string()).wrap_in_enum(),
+        ];
+        let changes = UpdateQueueCompressor::live_set_changes(&updates);
+        assert_eq!(changes, vec![
+            (None, Some(updates[0].clone())),
+            (None, Some(updates[1].clone())),
+            (Some(updates[1].clone()), None),
+            (None, None),
+        ]);
+
+        // Every replay frees the latest allocation at an address
+        let (compressed_updates, _) = UpdateQueueCompressor::compress_to_allocs(&updates);
+        assert_eq!(compressed_updates, vec![updates[0].clone()]);
+        assert_eq!(UpdateQueueCompressor::live_set_at(&updates, 3), compressed_updates);
+    }
+}
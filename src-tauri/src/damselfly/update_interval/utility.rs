@@ -1,4 +1,5 @@
 //! Utility functions for update_interval.
+use std::cmp::{max, min};
 use crate::damselfly::memory::memory_update::{MemoryUpdate, MemoryUpdateType};
 use crate::damselfly::update_interval::UpdateInterval;
 
@@ -22,6 +23,13 @@ impl Utility {
                 let free_address = free.get_absolute_address();
                 (free_address, free_address + free.get_absolute_size())
             }
+            // spans both the old and new regions, so it is found when querying either
+            MemoryUpdateType::Reallocation(reallocation) => {
+                let old_address = reallocation.get_old_address();
+                let new_address = reallocation.get_absolute_address();
+                (min(old_address, new_address),
+                 max(old_address + reallocation.get_old_size(), new_address + reallocation.get_absolute_size()))
+            }
         };
 
         (start, stop)
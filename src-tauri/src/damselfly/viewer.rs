@@ -1,6 +1,19 @@
 pub mod graph_viewer;
 pub mod map_viewer;
 pub mod damselfly_viewer;
+pub mod damselfly_viewer_builder;
 pub mod memory_canvas;
+pub mod occupancy_bitmap;
+pub mod graph_exporter;
+pub mod graph_mode;
+pub mod diff;
+pub mod colour_mode;
+pub mod viewer_cache;
+pub mod operation_log_exporter;
+pub mod x_axis;
+pub mod graph_metric;
+pub mod set_block_size_mode;
+pub mod map_exporter;
+pub mod viewer_options;
 mod memory_block;
 mod damselfly_instance;
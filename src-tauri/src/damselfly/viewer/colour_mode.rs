@@ -0,0 +1,46 @@
+//! What drives the colour of each block on the memory map.
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum ColourMode {
+    /// Colour by status: allocated, partially allocated, freed or unused.
+    #[default]
+    ByStatus,
+    /// Colour by the callstack of the allocation occupying the block.
+    ByCallstack,
+}
+
+impl ColourMode {
+    /// Gets the colour id of a callstack in ByCallstack mode. The id is a hash of the callstack
+    /// string, so a callstack keeps its colour regardless of the time range or which other
+    /// callstacks are present. Ids fit in 32 bits, so they survive the frontend's f64 numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `callstack`: Callstack of the allocation occupying the block.
+    ///
+    /// returns: Colour id, never 0, as 0 is used for blocks with no allocation.
+    pub fn get_callstack_colour(callstack: &str) -> u64 {
+        // 32-bit FNV-1a, which unlike DefaultHasher is fixed across Rust releases
+        const FNV_OFFSET_BASIS: u32 = 0x811c9dc5;
+        const FNV_PRIME: u32 = 0x01000193;
+        let hash = callstack
+            .bytes()
+            .fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ byte as u32).wrapping_mul(FNV_PRIME));
+        u64::from(hash).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::damselfly::viewer::colour_mode::ColourMode;
+
+    #[test]
+    fn get_callstack_colour_test() {
+        // Reference 32-bit FNV-1a hashes
+        assert_eq!(ColourMode::get_callstack_colour(""), 0x811c9dc5);
+        assert_eq!(ColourMode::get_callstack_colour("a"), 0xe40c292c);
+        assert_eq!(ColourMode::get_callstack_colour("main.c:5"), ColourMode::get_callstack_colour("main.c:5"));
+        assert_ne!(ColourMode::get_callstack_colour("main.c:5"), ColourMode::get_callstack_colour("main.c:6"));
+    }
+}
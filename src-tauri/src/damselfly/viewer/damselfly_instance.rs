@@ -2,20 +2,72 @@
 //! To have multiple pools, instantiate a DamselflyInstance for each pool and store them in
 //! DamselflyViewer.
 use crate::damselfly::memory::memory_usage_stats::MemoryUsageStats;
+use owo_colors::OwoColorize;
 use rust_lapper::Lapper;
-use crate::damselfly::consts::{DEFAULT_OPERATION_LOG_SIZE, DEFAULT_SAMPLE_INTERVAL};
+use crate::damselfly::damselfly_error::DamselflyError;
+use std::cmp::min;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use crate::damselfly::consts::{COMPACTED_GAP_BLOCKS, DEFAULT_SAMPLE_INTERVAL, MAP_PNG_BLOCK_PIXELS};
+use crate::damselfly::memory::address_reuse_finder::AddressReuseFinder;
+use crate::damselfly::memory::allocation_density::AllocationDensity;
+use crate::damselfly::memory::allocation_site_ranker::AllocationSiteRanker;
+use crate::damselfly::memory::anomaly::{Anomaly, AnomalyKind, AnomalySeverity};
+use crate::damselfly::memory::anomaly_detector::AnomalyDetector;
+use crate::damselfly::memory::efficiency_calculator::EfficiencyCalculator;
+use crate::damselfly::memory::fragmentation_culprit_finder::FragmentationCulpritFinder;
+use crate::damselfly::memory::free_region_finder::FreeRegionFinder;
+use crate::damselfly::memory::leak_finder::LeakFinder;
+use crate::damselfly::memory::lifetime_distribution::LifetimeDistribution;
+use crate::damselfly::memory::memory_access::MemoryAccess;
+use crate::damselfly::memory::memory_parsers::{ParseAnomaly, PoolRestrictedParseResults};
 use crate::damselfly::memory::memory_status::MemoryStatus;
 use crate::damselfly::memory::memory_update::MemoryUpdateType;
+use crate::damselfly::memory::memory_usage_factory::MemoryUsageFactory;
+use crate::damselfly::memory::placement_result::PlacementResult;
+use crate::damselfly::memory::placement_simulator::{PlacementSimulator, PlacementStrategy};
 use crate::damselfly::memory::sampled_memory_usages::SampledMemoryUsages;
+use crate::damselfly::memory::size_distribution::SizeDistribution;
+use crate::damselfly::memory::summary_stats::SummaryStats;
+use crate::damselfly::memory::unused_allocation_finder::UnusedAllocationFinder;
+use crate::damselfly::update_interval::distinct_block_counter::DistinctBlockCounter;
+use crate::damselfly::update_interval::serializable_update_interval::SerializableUpdateInterval;
+use crate::damselfly::update_interval::overlap_finder::OverlapFinder;
 use crate::damselfly::update_interval::update_interval_factory::UpdateIntervalFactory;
+use crate::damselfly::update_interval::update_queue_compressor::UpdateQueueCompressor;
+use crate::damselfly::viewer::colour_mode::ColourMode;
+use crate::damselfly::viewer::graph_exporter::GraphExporter;
+use crate::damselfly::viewer::graph_mode::GraphMode;
 use crate::damselfly::viewer::graph_viewer::GraphViewer;
+use crate::damselfly::viewer::map_exporter::MapExporter;
+use crate::damselfly::viewer::operation_log_exporter::OperationLogExporter;
+use crate::damselfly::viewer::set_block_size_mode::SetBlockSizeMode;
+use crate::damselfly::viewer::x_axis::XAxis;
+use crate::damselfly::viewer::graph_metric::GraphMetric;
 use crate::damselfly::viewer::map_viewer::MapViewer;
+use crate::damselfly::viewer::occupancy_bitmap::OccupancyBitmap;
+
+/// (map, Vec<(compacted offset, address)>), from get_compacted_map_at.
+pub type CompactedMap = (Vec<(i64, u64, usize)>, Vec<(usize, usize)>);
 
 pub struct DamselflyInstance {
     name: String,
+    memory_updates: Vec<MemoryUpdateType>,
     graph_viewer: GraphViewer,
     map_viewer: MapViewer,
     full_lapper: Lapper<usize, MemoryUpdateType>,
+    memory_accesses: Option<Vec<MemoryAccess>>,
+    parse_anomalies: Vec<ParseAnomaly>,
+    collapsed_duplicates: u64,
+    colour_mode: ColourMode,
+    // callstack -> colour, for ColourMode::ByCallstack
+    callstack_colours: HashMap<Arc<String>, u64>,
+    // (callstack, occurrence count), computed on first use by get_distinct_callstacks
+    distinct_callstacks: Option<Vec<(String, u64)>>,
+    // Padding added to the size of each update, to recover the requested sizes
+    right_padding: usize,
+    // Usage stats state after the last update, created on the first call to append_updates
+    memory_usage_factory: Option<MemoryUsageFactory>,
 }
 
 impl DamselflyInstance {
@@ -37,6 +89,7 @@ impl DamselflyInstance {
     ) -> Self {
         let memory_usages = memory_usage_stats.get_memory_usages();
         let max_usage = memory_usage_stats.get_max_usage();
+        let peak_usage_timestamp = memory_usage_stats.get_peak_usage_timestamp();
         let max_distinct_blocks = memory_usage_stats.get_max_distinct_blocks();
         let max_free_blocks = memory_usage_stats.get_max_free_blocks();
         let max_free_segment_fragmentation = memory_usage_stats.get_max_free_segment_fragmentation();
@@ -45,26 +98,37 @@ impl DamselflyInstance {
         let sampled_memory_usages =
             SampledMemoryUsages::new(DEFAULT_SAMPLE_INTERVAL, memory_usages.clone());
 
-        let graph_viewer = GraphViewer::new(
+        let mut graph_viewer = GraphViewer::new(
             memory_usages.clone(),
             sampled_memory_usages,
             max_usage,
+            peak_usage_timestamp,
             max_free_blocks,
             max_distinct_blocks as usize,
             max_free_segment_fragmentation,
             max_largest_free_block,
             max_timestamp,
         );
+        graph_viewer.set_lifetime_byte_totals((memory_usage_stats.get_total_bytes_allocated(), memory_usage_stats.get_total_bytes_freed()));
 
-        let update_intervals = UpdateIntervalFactory::new(memory_updates).construct_enum_vector();
+        let update_intervals = UpdateIntervalFactory::new(memory_updates.clone()).construct_enum_vector();
         let map_viewer = MapViewer::new(name.clone(), update_intervals.clone(), lowest_address, highest_address, cache_size as u64);
         let full_lapper = Lapper::new(update_intervals);
 
         Self {
             name,
+            memory_updates,
             graph_viewer,
             map_viewer,
             full_lapper,
+            memory_accesses: None,
+            parse_anomalies: Vec::new(),
+            collapsed_duplicates: 0,
+            colour_mode: ColourMode::default(),
+            callstack_colours: HashMap::new(),
+            distinct_callstacks: None,
+            right_padding: 0,
+            memory_usage_factory: None,
         }
     }
 
@@ -78,7 +142,9 @@ impl DamselflyInstance {
     /// # Arguments
     ///
     /// * `timestamp`: Timestamp to render the map at.
-    /// * `truncate_after`: How large a region must be before it gets truncated.
+    /// * `truncate_after`: Number of consecutive identical blocks (not bytes) to show before the 
+    ///   rest of the region is truncated, or None to render the full map. Values larger than the
+    ///   number of blocks in the map are clamped to it.
     ///
     /// returns: (timestamp, Vec<(parent_address, status, address)>)
     ///
@@ -86,18 +152,178 @@ impl DamselflyInstance {
     /// represents a single block on the map (which could span several bytes).
     /// Each block has:
     /// a parent_address (address of the most recent allocation/free that overlaps this block)
-    /// a status (representing whether it is allocated, partially allocated, freed or unused)
+    /// a status (3 allocated, 2 partially allocated, 1 freed, 0 unused or 5 guard), or
+    /// a callstack colour if the colour mode is ColourMode::ByCallstack
     /// an address (the block's own address)
     pub fn get_map_full_at_nosync_colours_truncate(
         &mut self,
         timestamp: u64,
-        truncate_after: u64,
+        truncate_after: Option<u64>,
+    ) -> (u64, Vec<(i64, u64, usize)>) {
+        self.paint_map_full_truncate(timestamp, truncate_after, self.colour_mode)
+    }
+
+    /// Renders the memory map like get_map_full_at_nosync_colours_truncate, but run-length encoded,
+    /// so long runs of blocks with the same colour (such as free space on a sparse map) collapse
+    /// into a single run. Parent addresses are not included.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to render the map at.
+    /// * `truncate_after`: Number of consecutive identical blocks (not bytes) to show before the
+    ///   rest of the region is truncated, or None to render the full map.
+    ///
+    /// returns: (timestamp, Vec<(colour, run_length, start_offset)>), where start_offset is the
+    /// index of the run's first block in the map get_map_full_at_nosync_colours_truncate returns.
+    pub fn get_map_full_at_rle(&mut self, timestamp: u64, truncate_after: Option<u64>) -> (u64, Vec<(usize, u64, usize)>) {
+        let (timestamp, map) = self.get_map_full_at_nosync_colours_truncate(timestamp, truncate_after);
+        (timestamp, Self::run_length_encode(&map))
+    }
+
+    /// Renders the full memory map at a timestamp with long runs of free or unused blocks
+    /// collapsed to their first COMPACTED_GAP_BLOCKS blocks, so allocations in a sparse pool are
+    /// visible without scrolling past the empty space between them.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to render the map at.
+    ///
+    /// returns: (Vec<(parent_address, status, address)>, Vec<(compacted offset, address)>). The
+    /// second vec has an entry for the first block and for each block after a collapsed run,
+    /// giving its index in the compacted map and its real address. A block's address is that of
+    /// the latest entry at or before it plus a block size for each block since.
+    pub fn get_compacted_map_at(&mut self, timestamp: u64) -> CompactedMap {
+        self.map_viewer.set_timestamp(timestamp as usize);
+        let full_map = self.map_viewer.paint_map_full_from_cache();
+        let coloured_map = self.colour_and_truncate_map(&full_map, None, self.colour_mode);
+
+        let mut compacted_map: Vec<(i64, u64, usize)> = Vec::new();
+        let mut offset_map: Vec<(usize, usize)> = Vec::new();
+        let mut free_run = 0;
+        let mut resumed = true;
+        for (block, coloured_block) in full_map.iter().zip(coloured_map) {
+            if matches!(block, MemoryStatus::Free(..) | MemoryStatus::Unused(_)) {
+                free_run += 1;
+            } else {
+                free_run = 0;
+            }
+            if free_run > COMPACTED_GAP_BLOCKS {
+                resumed = true;
+                continue;
+            }
+            if resumed {
+                offset_map.push((compacted_map.len(), coloured_block.2));
+                resumed = false;
+            }
+            compacted_map.push(coloured_block);
+        }
+        (compacted_map, offset_map)
+    }
+
+    /// Collapses consecutive blocks of the same colour into runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `map`: Map blocks as (parent_address, colour, address).
+    ///
+    /// returns: Vec<(colour, run_length, start_offset)>
+    fn run_length_encode(map: &[(i64, u64, usize)]) -> Vec<(usize, u64, usize)> {
+        let mut runs: Vec<(usize, u64, usize)> = Vec::new();
+        for (offset, (_, colour, _)) in map.iter().enumerate() {
+            match runs.last_mut() {
+                Some((run_colour, run_length, _)) if *run_colour == *colour as usize => *run_length += 1,
+                _ => runs.push((*colour as usize, 1, offset)),
+            }
+        }
+        runs
+    }
+
+    /// Renders the memory map in full at a specified timestamp, like
+    /// get_map_full_at_nosync_colours_truncate, but with a given colour mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to render the map at.
+    /// * `truncate_after`: Number of consecutive identical blocks (not bytes) to show before the
+    ///   rest of the region is truncated, or None to render the full map.
+    /// * `colour_mode`: What the status of each block represents.
+    ///
+    /// returns: (timestamp, Vec<(parent_address, status, address)>)
+    fn paint_map_full_truncate(
+        &mut self,
+        timestamp: u64,
+        truncate_after: Option<u64>,
+        colour_mode: ColourMode,
     ) -> (u64, Vec<(i64, u64, usize)>) {
         self.map_viewer.set_timestamp(timestamp as usize);
         let full_map = self.map_viewer.paint_map_full_from_cache();
+        (timestamp, self.colour_and_truncate_map(&full_map, truncate_after, colour_mode))
+    }
+
+    /// Renders the memory map in full at a specified timestamp, like
+    /// get_map_full_at_nosync_colours_truncate, but at a given block size. Neither the cached map
+    /// nor the instance's block size are changed, so this can preview a zoom level before
+    /// committing to it with set_map_block_size. This bypasses the cache and replays every update
+    /// up to the timestamp, so it is much slower than get_map_full_at_nosync_colours_truncate.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to render the map at.
+    /// * `block_size`: Bytes spanned by each block of the map. Must be non-zero.
+    /// * `truncate_after`: Number of consecutive identical blocks (not bytes) to show before the
+    ///   rest of the region is truncated, or None to render the full map.
+    ///
+    /// returns: (timestamp, Vec<(parent_address, status, address)>)
+    pub fn get_map_full_at_with_block_size(
+        &self,
+        timestamp: u64,
+        block_size: usize,
+        truncate_after: Option<u64>,
+    ) -> (u64, Vec<(i64, u64, usize)>) {
+        let full_map = self.map_viewer.paint_map_full_at_block_size(timestamp as usize, block_size);
+        (timestamp, self.colour_and_truncate_map(&full_map, truncate_after, self.colour_mode))
+    }
+
+    /// Writes the full memory map at a timestamp to a PNG, coloured like the app's map, with each
+    /// block drawn as a MAP_PNG_BLOCK_PIXELS square.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to render the map at.
+    /// * `block_size`: Bytes spanned by each block of the map. Must be non-zero.
+    /// * `row_length`: Number of blocks in each row of the image. Must be non-zero.
+    /// * `path`: File path to write the PNG to.
+    ///
+    /// returns: Result<(), DamselflyError>
+    pub fn render_map_png(&self, timestamp: u64, block_size: usize, row_length: usize, path: &str) -> Result<(), DamselflyError> {
+        if block_size == 0 {
+            return Err(DamselflyError::InvalidConfiguration("block size must be greater than 0".to_string()));
+        }
+        let (_, map) = self.get_map_full_at_with_block_size(timestamp, block_size, None);
+        MapExporter::export_png(&map, path, row_length, MAP_PNG_BLOCK_PIXELS)
+    }
+
+    /// Converts a painted map to (parent_address, status, address) tuples, truncating runs of
+    /// identical blocks.
+    ///
+    /// # Arguments
+    ///
+    /// * `full_map`: Painted map.
+    /// * `truncate_after`: Number of consecutive identical blocks (not bytes) to show before the
+    ///   rest of the region is truncated, or None to render the full map.
+    /// * `colour_mode`: What the status of each block represents.
+    ///
+    /// returns: Vec<(parent_address, status, address)>
+    fn colour_and_truncate_map(
+        &self,
+        full_map: &[MemoryStatus],
+        truncate_after: Option<u64>,
+        colour_mode: ColourMode,
+    ) -> Vec<(i64, u64, usize)> {
+        let truncate_after = min(truncate_after.unwrap_or(u64::MAX), full_map.len() as u64);
 
         // parent address, address, status
-        let mut result: Vec<(i64, u64, usize)> = Vec::new();
+        let mut result: Vec<(i64, u64, usize)> = Vec::with_capacity(full_map.len());
         let mut consecutive_identical_blocks = 0;
 
         for (index, block) in full_map.iter().enumerate() {
@@ -113,11 +339,20 @@ impl DamselflyInstance {
                 continue;
             }
 
-            let status = match block {
-                MemoryStatus::Allocated(_, _, _, _) => 3,
-                MemoryStatus::PartiallyAllocated(_, _, _, _) => 2,
-                MemoryStatus::Free(_, _, _, _) => 1,
-                MemoryStatus::Unused(_) => 0,
+            let status = match colour_mode {
+                ColourMode::ByStatus => match block {
+                    MemoryStatus::Allocated(_, _, _, _) => 3,
+                    MemoryStatus::PartiallyAllocated(_, _, _, _) => 2,
+                    MemoryStatus::Free(_, _, _, _) => 1,
+                    MemoryStatus::Unused(_) => 0,
+                    MemoryStatus::Guard(_, _, _, _) => 5,
+                },
+                // Free, unused and guard blocks have no allocated bytes to take a colour from
+                ColourMode::ByCallstack => match block {
+                    MemoryStatus::Allocated(_, _, _, callstack) | MemoryStatus::PartiallyAllocated(_, _, _, callstack) =>
+                        self.callstack_colours.get(callstack).copied().unwrap_or(0),
+                    MemoryStatus::Free(_, _, _, _) | MemoryStatus::Unused(_) | MemoryStatus::Guard(_, _, _, _) => 0,
+                },
             };
 
             let parent_address: i64 = if block.get_parent_address().is_none() {
@@ -130,9 +365,137 @@ impl DamselflyInstance {
             result.push((parent_address, status, address));
         }
 
-        (timestamp, result)
+        result
+    }
+
+
+    /// Renders the memory map like get_map_full_at_nosync_colours_truncate, but also gives each
+    /// block a heat that fades recently freed blocks, so frees stand out during playback.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to render the map at.
+    /// * `truncate_after`: Number of consecutive identical blocks (not bytes) to show before the
+    ///   rest of the region is truncated, or None to render the full map.
+    /// * `decay_window`: Number of timestamps a freed block takes to fade to plain free.
+    ///
+    /// returns: (timestamp, Vec<(parent_address, status, address, heat)>)
+    ///
+    /// Heat is 1 for a block freed at the timestamp itself, and decays linearly to 0 over
+    /// decay_window timestamps. Blocks that are not free, or were freed earlier, have a heat of 0.
+    pub fn get_map_full_at_nosync_colours_truncate_decay(
+        &mut self,
+        timestamp: u64,
+        truncate_after: Option<u64>,
+        decay_window: u64,
+    ) -> (u64, Vec<(i64, u64, usize, f64)>) {
+        let (timestamp, map) = self.paint_map_full_truncate(timestamp, truncate_after, ColourMode::ByStatus);
+        let timestamp = min(timestamp as usize, self.memory_updates.len().saturating_sub(1));
+
+        // parent address -> timestamp of its most recent free within the window
+        let mut recent_frees: HashMap<usize, usize> = HashMap::new();
+        for update in self.memory_updates
+            .iter()
+            .rev()
+            .skip_while(|update| update.get_timestamp() > timestamp)
+            .take_while(|update| ((timestamp - update.get_timestamp()) as u64) < decay_window) {
+            let freed_address = match update {
+                MemoryUpdateType::Allocation(_) => continue,
+                MemoryUpdateType::Free(_) => update.get_absolute_address(),
+                MemoryUpdateType::Reallocation(reallocation) => reallocation.get_old_address(),
+            };
+            recent_frees.entry(freed_address).or_insert(update.get_timestamp());
+        }
+
+        let map = map
+            .into_iter()
+            .map(|(parent_address, status, address)| {
+                // status 1 is a free block
+                let heat = match recent_frees.get(&(parent_address as usize)) {
+                    Some(free_timestamp) if status == 1 && parent_address >= 0 =>
+                        1.0 - (timestamp - free_timestamp) as f64 / decay_window as f64,
+                    _ => 0.0,
+                };
+                (parent_address, status, address, heat)
+            })
+            .collect();
+        (timestamp as u64, map)
     }
 
+    /// Renders the memory map across a range of timestamps, showing which blocks were touched in
+    /// the range. Blocks untouched in the range keep their state as of start_timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_timestamp`: Start of the range. Updates at this timestamp are part of the initial
+    ///   state rather than the range.
+    /// * `end_timestamp`: End of the range, inclusive.
+    /// * `truncate_after`: Number of consecutive identical blocks (not bytes) to show before the
+    ///   rest of the region is truncated, or None to render the full map.
+    ///
+    /// returns: (end_timestamp, Vec<(parent_address, status, address)>)
+    ///
+    /// Statuses are the same as get_map_full_at_nosync_colours_truncate, except touched blocks are
+    /// 3 if they were last allocated in the range, 1 if they were last freed in the range, and 4
+    /// if they were churned (allocated then freed) at any point in the range.
+    pub fn get_map_full_between(
+        &mut self,
+        start_timestamp: u64,
+        end_timestamp: u64,
+        truncate_after: Option<u64>,
+    ) -> (u64, Vec<(i64, u64, usize)>) {
+        let end_timestamp = end_timestamp.max(start_timestamp);
+        let (_, mut map) = self.paint_map_full_truncate(start_timestamp, None, ColourMode::ByStatus);
+        let block_size = self.map_viewer.get_block_size();
+        let first_block_address = match map.first() {
+            Some((_, _, address)) => *address,
+            None => return (end_timestamp, map),
+        };
+
+        // block index -> (allocated in range, churned in range)
+        let mut touched_blocks: HashMap<usize, (bool, bool)> = HashMap::new();
+        let range_start = min(start_timestamp as usize + 1, self.memory_updates.len());
+        let range_end = min(end_timestamp as usize + 1, self.memory_updates.len());
+        // a reallocation frees its old region, then allocates its new one
+        for update in self.memory_updates[range_start..range_end].iter().flat_map(MemoryUpdateType::split_reallocation) {
+            let first_block = update.get_start().saturating_sub(first_block_address) / block_size;
+            let last_block = update.get_end().saturating_sub(first_block_address).div_ceil(block_size);
+            for block_index in first_block..min(last_block, map.len()) {
+                let (allocated, churned) = touched_blocks.entry(block_index).or_default();
+                match update {
+                    MemoryUpdateType::Allocation(_) => {
+                        *allocated = true;
+                        map[block_index].1 = 3;
+                    }
+                    MemoryUpdateType::Free(_) => {
+                        *churned |= *allocated;
+                        map[block_index].1 = 1;
+                    }
+                    MemoryUpdateType::Reallocation(_) => unreachable!("[DamselflyInstance::get_map_full_between]: Reallocations are split into a free and an alloc"),
+                }
+                map[block_index].0 = update.get_absolute_address() as i64;
+            }
+        }
+        for (block_index, (_, churned)) in touched_blocks {
+            if churned {
+                map[block_index].1 = 4;
+            }
+        }
+
+        let truncate_after = min(truncate_after.unwrap_or(u64::MAX), map.len() as u64);
+        let mut consecutive_identical_blocks = 0;
+        let mut previous_block: Option<(i64, u64)> = None;
+        map.retain(|(parent_address, status, _)| {
+            if previous_block == Some((*parent_address, *status)) {
+                consecutive_identical_blocks += 1;
+            } else {
+                consecutive_identical_blocks = 0;
+            }
+            previous_block = Some((*parent_address, *status));
+            consecutive_identical_blocks <= truncate_after
+        });
+        (end_timestamp, map)
+    }
 
     /// Renders the memory map in full at a specified timestamp, truncating regions that are too large
     /// for legibility.
@@ -140,7 +503,8 @@ impl DamselflyInstance {
     /// # Arguments
     ///
     /// * `timestamp`: A realtime timestamp that will be translated into an absolute operation timestamp.
-    /// * `truncate_after`: How large a region must be before it gets truncated.
+    /// * `truncate_after`: Number of consecutive identical blocks (not bytes) to show before the
+    ///   rest of the region is truncated, or None to render the full map.
     ///
     /// returns: (timestamp, Vec<(parent_address, status, address)>)
     ///
@@ -153,22 +517,227 @@ impl DamselflyInstance {
     pub fn get_map_full_at_nosync_colours_truncate_realtime_sampled(
         &mut self,
         timestamp: u64,
-        truncate_after: u64,
+        truncate_after: Option<u64>,
     ) -> (u64, Vec<(i64, u64, usize)>) {
         let operation_timestamp = self
             .graph_viewer
-            .get_operation_timestamp_of_realtime_timestamp(timestamp);
+            .get_operation_timestamp_of_realtime_timestamp(timestamp)
+            .unwrap_or(0);
         eprintln!("[DamselflyInstance::get_map_full_at_nosync_colours_truncate_realtime_sampled]: timestamp: {timestamp}");
         eprintln!("[DamselflyInstance::get_map_full_at_nosync_colours_truncate_realtime_sampled]: operation timestamp: {operation_timestamp}");
         self.get_map_full_at_nosync_colours_truncate(operation_timestamp, truncate_after)
     }
 
+    /// Renders the memory map at a specified timestamp as a bitmap with one bit per block,
+    /// which is much smaller than the full map for sparse pools.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to render the map at.
+    ///
+    /// returns: OccupancyBitmap
+    pub fn get_occupancy_bitmap_at(&mut self, timestamp: u64) -> OccupancyBitmap {
+        self.map_viewer.set_timestamp(timestamp as usize);
+        let full_map = self.map_viewer.paint_map_full_from_cache();
+        OccupancyBitmap::from_map(&full_map, self.map_viewer.get_block_size())
+    }
+
+    /// Counts the bytes spanned by the memory map at a timestamp, the same way the map draws them:
+    /// every block that is not free or unused counts as used in full, including partially
+    /// allocated blocks.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to render the map at.
+    ///
+    /// returns: (total_bytes, used_bytes, free_bytes)
+    pub fn get_map_occupancy_at(&mut self, timestamp: u64) -> (usize, usize, usize) {
+        self.map_viewer.set_timestamp(timestamp as usize);
+        let full_map = self.map_viewer.paint_map_full_from_cache();
+        let block_size = self.map_viewer.get_block_size();
+        let used_blocks = full_map
+            .iter()
+            .filter(|block| !matches!(block, MemoryStatus::Free(..) | MemoryStatus::Unused(_)))
+            .count();
+        let total_bytes = full_map.len() * block_size;
+        let used_bytes = used_blocks * block_size;
+        (total_bytes, used_bytes, total_bytes - used_bytes)
+    }
+
     /// Gets a graph, but with filler values so that all pools have the same number of
     /// points.
     ///
     /// returns: Vec<[timestamp, y-value]>
     pub fn get_usage_graph(&self) -> Vec<[f64; 2]> {
-        self.graph_viewer.get_usage_plot_points()
+        self.get_usage_graph_with_axis(XAxis::Operation)
+    }
+
+    /// Gets the usage graph with the x-axis spaced by operation or by wall-clock time, so the
+    /// caller can pick the spacing without a separate method per variant.
+    ///
+    /// # Arguments
+    ///
+    /// * `axis`: What the x-axis is spaced by.
+    ///
+    /// returns: Vec<[timestamp, y-value]>, where timestamp is in the units of the axis.
+    pub fn get_usage_graph_with_axis(&self, axis: XAxis) -> Vec<[f64; 2]> {
+        self.get_graph_with_axis(GraphMetric::Usage, axis)
+    }
+
+    /// Gets a graph of any metric with the x-axis spaced by operation or by wall-clock time. The
+    /// per-metric graph getters are wrappers around this.
+    ///
+    /// # Arguments
+    ///
+    /// * `metric`: Metric to graph.
+    /// * `axis`: What the x-axis is spaced by.
+    ///
+    /// returns: Vec<[timestamp, y-value]>, where timestamp is in the units of the axis.
+    pub fn get_graph_with_axis(&self, metric: GraphMetric, axis: XAxis) -> Vec<[f64; 2]> {
+        let graph_viewer = &self.graph_viewer;
+        match (metric, axis) {
+            (GraphMetric::Usage, XAxis::Operation) => graph_viewer.get_usage_plot_points(),
+            (GraphMetric::Usage, XAxis::Realtime) => graph_viewer.get_usage_plot_points_realtime_sampled(),
+            (GraphMetric::DistinctBlocks, XAxis::Operation) => graph_viewer.get_distinct_blocks_plot_points(),
+            (GraphMetric::DistinctBlocks, XAxis::Realtime) => graph_viewer.get_distinct_blocks_plot_points_realtime_sampled(),
+            (GraphMetric::FreeSegmentFragmentation, XAxis::Operation) => graph_viewer.get_free_segment_fragmentation_plot_points_no_fallbacks(),
+            (GraphMetric::FreeSegmentFragmentation, XAxis::Realtime) => graph_viewer.get_free_segment_fragmentation_plot_points_realtime_sampled(),
+            (GraphMetric::LargestFreeBlock, XAxis::Operation) => graph_viewer.get_largest_free_block_plot_points(),
+            (GraphMetric::LargestFreeBlock, XAxis::Realtime) => graph_viewer.get_largest_free_block_plot_points_realtime_sampled(),
+            (GraphMetric::FreeBlocks, XAxis::Operation) => graph_viewer.get_free_blocks_plot_points(),
+            (GraphMetric::FreeBlocks, XAxis::Realtime) => graph_viewer.get_free_blocks_plot_points_realtime_sampled(),
+            (GraphMetric::LiveAllocationCount, XAxis::Operation) => graph_viewer.get_live_allocations_plot_points(),
+            (GraphMetric::LiveAllocationCount, XAxis::Realtime) => graph_viewer.get_live_allocations_plot_points_realtime_sampled(),
+            (GraphMetric::FreeGapCount, XAxis::Operation) => graph_viewer.get_free_gap_count_plot_points(),
+            (GraphMetric::FreeGapCount, XAxis::Realtime) => graph_viewer.get_free_gap_count_plot_points_realtime_sampled(),
+            (GraphMetric::AverageAllocationSize, XAxis::Operation) => graph_viewer.get_average_allocation_size_plot_points(),
+            (GraphMetric::AverageAllocationSize, XAxis::Realtime) => graph_viewer.get_average_allocation_size_plot_points_realtime_sampled(),
+        }
+    }
+
+    /// Gets the usage graph smoothed with a centered moving average, to show the trend in noisy
+    /// workloads.
+    ///
+    /// # Arguments
+    ///
+    /// * `window`: Number of points to average over.
+    ///
+    /// returns: Vec<[timestamp, y-value]>, the same length as the unsmoothed graph.
+    pub fn get_usage_graph_smoothed(&self, window: usize) -> Vec<[f64; 2]> {
+        GraphViewer::smooth_plot_points(&self.get_usage_graph(), window)
+    }
+
+    /// Gets the high water mark of the usage graph, i.e. the peak usage so far at each point, to
+    /// overlay on the usage graph as an envelope.
+    ///
+    /// returns: Vec<[timestamp, y-value]>, the same length as get_usage_graph.
+    pub fn get_usage_highwater_graph(&self) -> Vec<[f64; 2]> {
+        GraphViewer::high_water_plot_points(&self.get_usage_graph())
+    }
+
+    /// Gets the realtime usage graph within a window, decimated to at most sample_count points
+    /// with the lowest and highest point of each stretch kept, so zoomed out graphs stay cheap
+    /// without hiding spikes.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_ts`: First realtime timestamp of the window.
+    /// * `end_ts`: Last realtime timestamp of the window.
+    /// * `sample_count`: Maximum number of points to return.
+    ///
+    /// returns: Vec<[timestamp, y-value]>
+    pub fn get_usage_graph_sampled_n(&self, start_ts: u64, end_ts: u64, sample_count: usize) -> Vec<[f64; 2]> {
+        GraphViewer::decimate_plot_points(&self.get_usage_graph_realtime_sampled(), start_ts as f64, end_ts as f64, sample_count)
+    }
+
+    /// Like get_usage_graph_sampled_n, but for the distinct blocks graph.
+    ///
+    /// returns: Vec<[timestamp, y-value]>
+    pub fn get_distinct_blocks_graph_sampled_n(&self, start_ts: u64, end_ts: u64, sample_count: usize) -> Vec<[f64; 2]> {
+        GraphViewer::decimate_plot_points(&self.get_distinct_blocks_graph_realtime_sampled(), start_ts as f64, end_ts as f64, sample_count)
+    }
+
+    /// Like get_usage_graph_sampled_n, but for the largest free block graph.
+    ///
+    /// returns: Vec<[timestamp, y-value]>
+    pub fn get_largest_free_block_graph_sampled_n(&self, start_ts: u64, end_ts: u64, sample_count: usize) -> Vec<[f64; 2]> {
+        GraphViewer::decimate_plot_points(&self.get_largest_free_block_graph_realtime_sampled(), start_ts as f64, end_ts as f64, sample_count)
+    }
+
+    /// Gets how busy the allocator is over time, as the number of allocations and frees in each
+    /// fixed window of real time.
+    ///
+    /// # Arguments
+    ///
+    /// * `window`: Window length in microseconds.
+    ///
+    /// returns: Vec<[window start, operation count]>, from the window of the first operation to
+    /// the window of the last, including windows with no operations. Empty if that would be more
+    /// than MAX_CHURN_WINDOWS windows.
+    pub fn get_churn_graph(&self, window: u64) -> Vec<[f64; 2]> {
+        self.graph_viewer.get_churn_plot_points(window)
+    }
+
+    /// Writes the usage graph to an SVG sparkline, with the peak marked.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: File path to write the SVG to.
+    /// * `width`: Width of the SVG in pixels.
+    /// * `height`: Height of the SVG in pixels.
+    ///
+    /// returns: Result<(), DamselflyError>
+    pub fn export_usage_svg(&self, path: &str, width: u32, height: u32) -> Result<(), DamselflyError> {
+        GraphExporter::export_svg_sparkline(&self.get_usage_graph(), path, width, height)
+    }
+
+    /// Writes the usage graph to a CSV file with a `timestamp,bytes` header.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: File path to write the CSV to.
+    /// * `mode`: Which variant of the usage graph to export.
+    ///
+    /// returns: Result<(), DamselflyError>
+    pub fn export_usage_csv(&self, path: &str, mode: GraphMode) -> Result<(), DamselflyError> {
+        let points = match mode {
+            GraphMode::Default => self.get_usage_graph(),
+            GraphMode::NoFallbacks => self.get_usage_graph_no_fallbacks(),
+            GraphMode::RealtimeSampled => self.get_usage_graph_realtime_sampled(),
+        };
+        GraphExporter::export_csv(&points, path)
+    }
+
+    /// Streams every operation in this pool to a file as newline-delimited JSON, for offline
+    /// analysis of the complete history.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: File path to write to.
+    /// * `left_padding`: Left padding to trim from each operation, as in get_operation_log.
+    /// * `right_padding`: Right padding to trim from each operation, as in get_operation_log.
+    ///   Like get_operation_log, operations the padding would underflow are written untrimmed.
+    ///   NDJSON records have no room for the underflow flag, so a warning is printed instead.
+    ///
+    /// returns: Result<(), DamselflyError>
+    pub fn export_operation_log_json(&self, path: &str, left_padding: usize, right_padding: usize) -> Result<(), DamselflyError> {
+        let underflowing_updates = self.memory_updates
+            .iter()
+            .filter(|update| update.padding_underflows(left_padding, right_padding))
+            .count();
+        if underflowing_updates > 0 {
+            eprintln!("{}", format!("[DamselflyInstance::export_operation_log_json]: Padding underflows {underflowing_updates} operations, which are exported untrimmed").yellow());
+        }
+        OperationLogExporter::export_ndjson(
+            self.memory_updates.iter().map(|update| {
+                if update.padding_underflows(left_padding, right_padding) {
+                    update.clone()
+                } else {
+                    update.with_padding_trimmed(left_padding, right_padding)
+                }
+            }),
+            path,
+        )
     }
 
     /// Gets a graph, but without filler values, so different pools may have different numbers
@@ -183,7 +752,7 @@ impl DamselflyInstance {
     ///
     /// returns: Vec<[timestamp, y-value]>
     pub fn get_usage_graph_realtime_sampled(&self) -> Vec<[f64; 2]> {
-        self.graph_viewer.get_usage_plot_points_realtime_sampled()
+        self.get_usage_graph_with_axis(XAxis::Realtime)
     }
 
     /// Gets a graph, but with filler values so that all pools have the same number of
@@ -191,7 +760,7 @@ impl DamselflyInstance {
     ///
     /// returns: Vec<[timestamp, y-value]>
     pub fn get_distinct_blocks_graph(&self) -> Vec<[f64; 2]> {
-        self.graph_viewer.get_distinct_blocks_plot_points()
+        self.get_graph_with_axis(GraphMetric::DistinctBlocks, XAxis::Operation)
     }
 
     /// Gets a graph, but without filler values, so different pools may have different numbers
@@ -206,7 +775,7 @@ impl DamselflyInstance {
     ///
     /// returns: Vec<[timestamp, y-value]>
     pub fn get_distinct_blocks_graph_realtime_sampled(&self) -> Vec<[f64; 2]> {
-        self.graph_viewer.get_distinct_blocks_plot_points_realtime_sampled()
+        self.get_graph_with_axis(GraphMetric::DistinctBlocks, XAxis::Realtime)
     }
 
     /// Gets a graph, but without filler values, so different pools may have different numbers
@@ -217,11 +786,28 @@ impl DamselflyInstance {
         self.graph_viewer.get_free_segment_fragmentation_plot_points_no_fallbacks()
     }
 
+    /// Gets a graph of the percentage of free space outside the largest free block, without
+    /// filler values. Unlike the other graphs, values are not scaled to the maximum.
+    ///
+    /// returns: Vec<[timestamp, y-value]>
+    pub fn get_free_segment_fragmentation_percentage_graph(&self) -> Vec<[f64; 2]> {
+        self.graph_viewer.get_free_segment_fragmentation_percentage_plot_points_no_fallbacks()
+    }
+
+    /// Gets a graph of memory usage as a percentage of the pool size, without filler values.
+    /// Unlike get_usage_graph, values are not scaled to peak usage. Absolute bytes are still
+    /// available through get_memory_usage_stats.
+    ///
+    /// returns: Vec<[timestamp, y-value]>
+    pub fn get_usage_percentage_graph(&self) -> Vec<[f64; 2]> {
+        self.graph_viewer.get_usage_percentage_plot_points_no_fallbacks()
+    }
+
     /// Gets a graph in realtime.
     ///
     /// returns: Vec<[timestamp, y-value]>
     pub fn get_free_segment_fragmentation_graph_realtime_sampled(&self) -> Vec<[f64; 2]> {
-        self.graph_viewer.get_free_segment_fragmentation_plot_points_realtime_sampled()
+        self.get_graph_with_axis(GraphMetric::FreeSegmentFragmentation, XAxis::Realtime)
     }
 
     /// Gets a graph, but without filler values, so different pools may have different numbers
@@ -236,7 +822,7 @@ impl DamselflyInstance {
     ///
     /// returns: Vec<[timestamp, y-value]>
     pub fn get_largest_free_block_graph_realtime_sampled(&self) -> Vec<[f64; 2]> {
-        self.graph_viewer.get_largest_free_block_plot_points_realtime_sampled()
+        self.get_graph_with_axis(GraphMetric::LargestFreeBlock, XAxis::Realtime)
     }
 
     /// Gets a graph, but with filler values so that all pools have the same number of
@@ -244,7 +830,7 @@ impl DamselflyInstance {
     ///
     /// returns: Vec<[timestamp, y-value]>
     pub fn get_largest_block_graph(&self) -> Vec<[f64; 2]> {
-        self.graph_viewer.get_largest_free_block_plot_points()
+        self.get_graph_with_axis(GraphMetric::LargestFreeBlock, XAxis::Operation)
     }
 
     /// Gets a graph, but without filler values, so different pools may have different numbers
@@ -259,8 +845,7 @@ impl DamselflyInstance {
     ///
     /// returns: Vec<[timestamp, y-value]>
     pub fn get_largest_block_graph_realtime_sampled(&self) -> Vec<[f64; 2]> {
-        self.graph_viewer
-            .get_largest_free_block_plot_points_realtime_sampled()
+        self.get_graph_with_axis(GraphMetric::LargestFreeBlock, XAxis::Realtime)
     }
 
     /// Gets a graph, but with filler values so that all pools have the same number of
@@ -268,7 +853,7 @@ impl DamselflyInstance {
     ///
     /// returns: Vec<[timestamp, y-value]>
     pub fn get_free_blocks_graph(&self) -> Vec<[f64; 2]> {
-        self.graph_viewer.get_free_blocks_plot_points()
+        self.get_graph_with_axis(GraphMetric::FreeBlocks, XAxis::Operation)
     }
 
     /// Gets a graph, but without filler values, so different pools may have different numbers
@@ -283,73 +868,1844 @@ impl DamselflyInstance {
     ///
     /// returns: Vec<[timestamp, y-value]>
     pub fn get_free_blocks_graph_realtime_sampled(&self) -> Vec<[f64; 2]> {
-        self.graph_viewer
-            .get_free_blocks_plot_points_realtime_sampled()
+        self.get_graph_with_axis(GraphMetric::FreeBlocks, XAxis::Realtime)
     }
 
-    /// Gets the latest operation shown in the current map state.
-    pub fn get_current_operation(&self) -> MemoryUpdateType {
-        self.map_viewer.get_current_operation()
+    /// Gets a graph of the number of live allocations (not bytes) after each operation, with filler
+    /// values so that all pools have the same number of points.
+    ///
+    /// returns: Vec<[timestamp, live allocations]>
+    pub fn get_live_allocation_count_graph(&self) -> Vec<[f64; 2]> {
+        self.get_graph_with_axis(GraphMetric::LiveAllocationCount, XAxis::Operation)
     }
 
-    /// Gets the full operation history.
-    pub fn get_operation_history(&self) -> Vec<MemoryUpdateType> {
-        self.map_viewer
-            .get_update_history(DEFAULT_OPERATION_LOG_SIZE)
+    /// Gets the live allocation count graph, but without filler values, so different pools may
+    /// have different numbers of points.
+    ///
+    /// returns: Vec<[timestamp, live allocations]>
+    pub fn get_live_allocation_count_graph_no_fallbacks(&self) -> Vec<[f64; 2]> {
+        self.graph_viewer.get_live_allocations_plot_points_no_fallbacks()
     }
 
-    /// Queries a block to get all updates that overlap it from t=0 until the specified timestamp.
+    /// Gets the live allocation count graph in realtime. Each point is the average count over its
+    /// sample.
     ///
-    /// # Arguments
+    /// returns: Vec<[timestamp, live allocations]>
+    pub fn get_live_allocation_count_graph_realtime_sampled(&self) -> Vec<[f64; 2]> {
+        self.get_graph_with_axis(GraphMetric::LiveAllocationCount, XAxis::Realtime)
+    }
+
+    /// Gets a graph of the number of free gaps after each operation, with filler values so that
+    /// all pools have the same number of points. A gap is a free block with a live allocation on
+    /// both sides, which is what a best-fit allocator has to choose between; free space running
+    /// to either end of the pool is not counted, unlike in get_free_blocks_graph.
     ///
-    /// * `address`: Address of the block (absolute).
-    /// * `timestamp`: Timestamp to query until.
+    /// returns: Vec<[timestamp, free gaps]>
+    pub fn get_free_gap_count_graph(&self) -> Vec<[f64; 2]> {
+        self.get_graph_with_axis(GraphMetric::FreeGapCount, XAxis::Operation)
+    }
+
+    /// Gets the free gap count graph, but without filler values, so different pools may have
+    /// different numbers of points.
     ///
-    /// returns: Vec<MemoryUpdateType, Global>
-    pub fn query_block(&self, address: usize, timestamp: usize) -> Vec<MemoryUpdateType> {
-        eprintln!("[DamselflyInstance::query_block]: optimestamp: {timestamp}");
-        eprintln!("[DamselflyInstance::query_block]: address: {address}");
-        self.full_lapper
-            .find(address, address + self.map_viewer.get_block_size())
-            .filter(|interval| interval.val.get_timestamp() <= timestamp)
-            .map(|interval| interval.val.clone())
-            .collect()
+    /// returns: Vec<[timestamp, free gaps]>
+    pub fn get_free_gap_count_graph_no_fallbacks(&self) -> Vec<[f64; 2]> {
+        self.graph_viewer.get_free_gap_count_plot_points_no_fallbacks()
     }
 
-    /// Queries a block to get all updates that overlap it.
+    /// Gets the free gap count graph in realtime. Each point is the average count over its sample.
     ///
-    /// # Arguments
+    /// returns: Vec<[timestamp, free gaps]>
+    pub fn get_free_gap_count_graph_realtime_sampled(&self) -> Vec<[f64; 2]> {
+        self.get_graph_with_axis(GraphMetric::FreeGapCount, XAxis::Realtime)
+    }
+
+    /// Gets a graph of the mean live allocated block size in bytes after each operation, with
+    /// filler values so that all pools have the same number of points. Adjacent allocations are
+    /// merged into one block, so this falls as memory becomes fragmented into small blocks.
     ///
-    /// * `address`: Address of the block.
+    /// returns: Vec<[timestamp, mean allocated block size]>
+    pub fn get_mean_allocated_block_size_graph(&self) -> Vec<[f64; 2]> {
+        self.graph_viewer.get_mean_allocated_block_size_plot_points()
+    }
+
+    /// Gets a graph of the mean live allocation size in bytes after each operation, with filler
+    /// values so that all pools have the same number of points.
     ///
-    /// returns: Vec<MemoryUpdateType, Global>
-    pub fn query_block_naive(&self, address: usize) -> Vec<MemoryUpdateType> {
-        eprintln!("[DamselflyInstance::query_block_naive]: address: {address}");
-        self.full_lapper
-            .find(address, address + self.map_viewer.get_block_size())
-            .map(|interval| interval.val.clone())
-            .collect()
+    /// returns: Vec<[timestamp, average allocation size]>, 0 where nothing is live.
+    pub fn get_average_allocation_size_graph(&self) -> Vec<[f64; 2]> {
+        self.get_graph_with_axis(GraphMetric::AverageAllocationSize, XAxis::Operation)
     }
 
-    /// Queries a block to get all updates that overlap it from t=0 until the specified realtime timestamp.
+    /// Gets the average allocation size graph in realtime, from the averaged bytes in use and live
+    /// allocation count of each sample.
+    ///
+    /// returns: Vec<[timestamp, average allocation size]>
+    pub fn get_average_allocation_size_graph_realtime_sampled(&self) -> Vec<[f64; 2]> {
+        self.get_graph_with_axis(GraphMetric::AverageAllocationSize, XAxis::Realtime)
+    }
+
+    /// Gets a graph of how efficiently the pool's reserved space is used: requested live bytes
+    /// divided by the extent occupied, rounded up to whole blocks. This is recomputed on each call
+    /// as it depends on the current block size.
+    ///
+    /// returns: Vec<[timestamp, efficiency]>
+    pub fn get_efficiency_graph(&self) -> Vec<[f64; 2]> {
+        EfficiencyCalculator::get_efficiency_plot_points(
+            &self.memory_updates,
+            self.map_viewer.get_lowest_address(),
+            self.map_viewer.get_block_size(),
+        )
+    }
+
+    /// Gets a graph of bytes allocated up to each update that are never freed.
+    ///
+    /// returns: Vec<[timestamp, leaked bytes]>
+    pub fn get_cumulative_leak_graph(&self) -> Vec<[f64; 2]> {
+        LeakFinder::get_cumulative_leak_plot_points(&self.memory_updates)
+    }
+
+    /// Gets allocations that are never freed by the end of this pool's updates, oldest first, so
+    /// the longest-lived unfreed blocks surface first.
+    ///
+    /// returns: Vec<(allocation, age in timestamp units)>
+    pub fn get_leak_candidates(&self) -> Vec<(MemoryUpdateType, u64)> {
+        let final_timestamp = self.memory_updates.last().map_or(0, |update| update.get_timestamp() as u64);
+        LeakFinder::get_leak_candidates(&self.memory_updates, final_timestamp)
+    }
+
+    /// Gets the number of free blocks at a timestamp. Reads the precomputed usage stats, so this
+    /// is cheap to call repeatedly.
     ///
     /// # Arguments
     ///
-    /// * `address`: Address of the block.
-    /// * `timestamp`: Realtime timestamp.
+    /// * `timestamp`: Operation timestamp, clamped to the last timestamp.
     ///
-    /// returns: Vec<MemoryUpdateType, Global>
-    pub fn query_block_realtime(&self, address: usize, timestamp: usize) -> Vec<MemoryUpdateType> {
-        let timestamp = self.graph_viewer.get_operation_timestamp_of_realtime_timestamp(timestamp as u64) as usize;
-        eprintln!("[DamselflyInstance::query_block_realtime]: realtime converted to optimestamp: {timestamp}");
-        self.full_lapper
-            .find(address, address + self.map_viewer.get_block_size())
-            .filter(|interval| interval.val.get_timestamp() <= timestamp)
-            .map(|interval| interval.val.clone())
-            .collect()
+    /// returns: usize
+    pub fn get_free_block_count_at(&self, timestamp: u64) -> usize {
+        self.graph_viewer
+            .get_memory_usage_at(timestamp)
+            .map(|memory_usage| memory_usage.get_free_blocks())
+            .unwrap_or(0)
     }
 
-    pub fn set_map_block_size(&mut self, new_size: usize) {
-        self.map_viewer.set_block_size(new_size);
+    /// Gets the headline stats at a timestamp in one call, all taken from the same snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Operation timestamp, clamped to the last timestamp.
+    ///
+    /// returns: SummaryStats, all zero if the pool has no operations.
+    pub fn get_summary_stats(&self, timestamp: u64) -> SummaryStats {
+        self.graph_viewer
+            .get_memory_usage_at(timestamp)
+            .map(SummaryStats::from_memory_usage)
+            .unwrap_or_default()
     }
-}
\ No newline at end of file
+
+    /// Gets the latest operation shown in the current map state.
+    pub fn get_current_operation(&self) -> MemoryUpdateType {
+        self.map_viewer.get_current_operation()
+    }
+
+    /// Gets the resolved callstack of any operation, independent of the map's current operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Operation timestamp.
+    ///
+    /// returns: The callstack, or DamselflyError::TimestampOutOfRange if no operation has that
+    /// timestamp.
+    pub fn get_callstack_at(&self, timestamp: u64) -> Result<String, DamselflyError> {
+        self.memory_updates
+            .binary_search_by_key(&(timestamp as usize), |update| update.get_timestamp())
+            .map(|index| self.memory_updates[index].get_callstack().to_string())
+            .map_err(|_| DamselflyError::TimestampOutOfRange {
+                timestamp,
+                max_timestamp: self.memory_updates.last().map_or(0, |update| update.get_timestamp() as u64),
+            })
+    }
+
+    /// Gets a page of the operation history up to the current timestamp, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset`: Number of operations to skip, counting back from the current timestamp.
+    /// * `limit`: Max number of operations to return.
+    ///
+    /// returns: Vec<MemoryUpdateType>, empty if offset is past the end of the history.
+    pub fn get_operation_history(&self, offset: usize, limit: usize) -> Vec<MemoryUpdateType> {
+        self.map_viewer
+            .get_update_history(offset, limit)
+    }
+
+    /// Queries a block to get all updates that overlap it from t=0 until the specified timestamp.
+    /// Lookups go through the interval tree, so they cost O(log n + k) for k overlapping updates
+    /// rather than a scan of every update.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: Address of the block (absolute).
+    /// * `timestamp`: Timestamp to query until. Timestamps past the last operation are treated as
+    ///   the last operation.
+    ///
+    /// returns: Vec<MemoryUpdateType, Global>, sorted by ascending timestamp.
+    pub fn query_block(&self, address: usize, timestamp: usize) -> Vec<MemoryUpdateType> {
+        eprintln!("[DamselflyInstance::query_block]: address: {address}");
+        self.query_range(address, address + self.map_viewer.get_block_size(), timestamp)
+    }
+
+    /// Queries an address range to get all updates whose bytes intersect it from t=0 until the
+    /// specified timestamp, e.g. for a selection spanning several blocks. Like query_block, this
+    /// goes through the interval tree, so wide ranges cost O(log n + k) for k overlapping updates.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_address`: First address of the range (absolute).
+    /// * `end_address`: Address just past the end of the range.
+    /// * `timestamp`: Timestamp to query until. Timestamps past the last operation are treated as
+    ///   the last operation.
+    ///
+    /// returns: Vec<MemoryUpdateType, Global>, sorted by ascending timestamp. Empty if the range
+    /// is empty.
+    pub fn query_range(&self, start_address: usize, end_address: usize, timestamp: usize) -> Vec<MemoryUpdateType> {
+        let timestamp = min(timestamp, self.memory_updates.last().map_or(0, |update| update.get_timestamp()));
+        eprintln!("[DamselflyInstance::query_range]: optimestamp: {timestamp}");
+        if start_address >= end_address {
+            return Vec::new();
+        }
+        let mut updates: Vec<MemoryUpdateType> = self.full_lapper
+            .find(start_address, end_address)
+            .filter(|interval| interval.val.get_timestamp() <= timestamp)
+            // a moving reallocation's interval spans the gap between its regions
+            .filter(|interval| interval.val
+                .split_reallocation()
+                .iter()
+                .any(|update| update.get_start() < end_address && start_address < update.get_end()))
+            .map(|interval| interval.val.clone())
+            .collect();
+        updates.sort_by_key(|update| update.get_timestamp());
+        updates
+    }
+
+    /// Queries a block to get all updates that overlap it.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: Address of the block.
+    ///
+    /// returns: Vec<MemoryUpdateType, Global>
+    pub fn query_block_naive(&self, address: usize) -> Vec<MemoryUpdateType> {
+        eprintln!("[DamselflyInstance::query_block_naive]: address: {address}");
+        self.full_lapper
+            .find(address, address + self.map_viewer.get_block_size())
+            .map(|interval| interval.val.clone())
+            .collect()
+    }
+
+    /// Queries a block to get all updates that overlap it from t=0 until the specified realtime timestamp.
+    /// The realtime timestamp indexes straight into the sampling table to find its last operation,
+    /// and the block is then looked up in the interval tree like query_block, so this also costs
+    /// O(log n + k) for k overlapping updates.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: Address of the block.
+    /// * `timestamp`: Realtime timestamp. Timestamps past the last sample are treated as the last
+    ///   sample.
+    ///
+    /// returns: Vec<MemoryUpdateType, Global>, sorted by ascending timestamp. Empty if there are no
+    /// realtime samples, as then there are no updates either.
+    pub fn query_block_realtime(&self, address: usize, timestamp: usize) -> Vec<MemoryUpdateType> {
+        let Some(operation_timestamp) = self.graph_viewer.get_operation_timestamp_of_realtime_timestamp(timestamp as u64) else {
+            return Vec::new();
+        };
+        eprintln!("[DamselflyInstance::query_block_realtime]: realtime converted to optimestamp: {operation_timestamp}");
+        self.query_block(address, operation_timestamp as usize)
+    }
+
+    /// Maps an x value of the realtime sampled graphs back to an operation, using the same samples
+    /// the graphs are built from, so clicking a realtime point can move the map there.
+    ///
+    /// # Arguments
+    ///
+    /// * `realtime_timestamp`: Realtime timestamp, clamped to the last sample.
+    ///
+    /// returns: The last operation in the sample, or 0 if there are no samples.
+    pub fn realtime_to_operation_index(&self, realtime_timestamp: u64) -> usize {
+        self.graph_viewer
+            .get_operation_timestamp_of_realtime_timestamp(realtime_timestamp)
+            .unwrap_or(0) as usize
+    }
+
+    /// Maps an operation to its x value on the realtime sampled graphs, the inverse of
+    /// realtime_to_operation_index.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation_index`: Operation timestamp, clamped to the last sample.
+    ///
+    /// returns: The realtime timestamp of the sample containing the operation, or 0 if there are
+    /// no samples.
+    pub fn operation_index_to_realtime(&self, operation_index: usize) -> u64 {
+        self.graph_viewer
+            .get_realtime_timestamp_of_operation_timestamp(operation_index as u64)
+            .unwrap_or(0)
+    }
+
+    /// Finds every operation whose callstack contains a substring, ignoring case, so the UI can
+    /// jump to each allocation from a known function.
+    ///
+    /// # Arguments
+    ///
+    /// * `substring`: Text to search the full resolved callstack for, e.g. a function or file name.
+    ///
+    /// returns: Vec<(timestamp, update)> sorted by timestamp. Empty if the substring is empty.
+    pub fn find_operations_by_callstack(&self, substring: &str) -> Vec<(u64, MemoryUpdateType)> {
+        if substring.is_empty() {
+            return Vec::new();
+        }
+        let substring = substring.to_lowercase();
+        let mut operations: Vec<(u64, MemoryUpdateType)> = self.memory_updates
+            .iter()
+            .filter(|update| Self::callstack_contains(update, &substring))
+            .map(|update| (update.get_timestamp() as u64, update.clone()))
+            .collect();
+        operations.sort_by_key(|(timestamp, _)| *timestamp);
+        operations
+    }
+
+    /// Gets a graph of the live bytes allocated from callstacks containing a substring, ignoring
+    /// case like find_operations_by_callstack, to overlay one call site's footprint on the usage
+    /// graph. Frees release matching allocations whatever their own callstack.
+    ///
+    /// # Arguments
+    ///
+    /// * `callstack_substring`: Text to search the full resolved callstack for.
+    ///
+    /// returns: Vec<[timestamp, live bytes]> with a point after each operation. Empty if the
+    /// substring is empty.
+    pub fn get_callstack_usage_graph(&self, callstack_substring: &str) -> Vec<[f64; 2]> {
+        if callstack_substring.is_empty() {
+            return Vec::new();
+        }
+        let substring = callstack_substring.to_lowercase();
+        let mut live_bytes: usize = 0;
+        let mut vector = Vec::new();
+        for (index, (freed, allocated)) in UpdateQueueCompressor::live_set_changes(&self.memory_updates).into_iter().enumerate() {
+            // a reallocation frees its old region, then allocates its new one
+            if let Some(freed) = freed.filter(|freed| Self::callstack_contains(freed, &substring)) {
+                live_bytes -= freed.get_absolute_size();
+            }
+            if let Some(allocated) = allocated.filter(|allocated| Self::callstack_contains(allocated, &substring)) {
+                live_bytes += allocated.get_absolute_size();
+            }
+            vector.push([index as f64, live_bytes as f64]);
+        }
+        vector
+    }
+
+    /// Checks if an update's callstack contains an already lowercased substring, ignoring case.
+    fn callstack_contains(update: &MemoryUpdateType, lowercase_substring: &str) -> bool {
+        update.get_callstack().to_lowercase().contains(lowercase_substring)
+    }
+
+    /// Gets every distinct callstack in the pool with the number of operations made from it, for
+    /// filtering by callstack and ranking the top allocators. Computed once, then cached.
+    ///
+    /// returns: Vec<(callstack, occurrence_count)>, sorted by descending count, then by callstack.
+    pub fn get_distinct_callstacks(&mut self) -> Vec<(String, u64)> {
+        let memory_updates = &self.memory_updates;
+        self.distinct_callstacks
+            .get_or_insert_with(|| {
+                let mut counts: HashMap<Arc<String>, u64> = HashMap::new();
+                for update in memory_updates {
+                    *counts.entry(update.get_callstack()).or_default() += 1;
+                }
+                let mut distinct_callstacks: Vec<(String, u64)> = counts
+                    .into_iter()
+                    .map(|(callstack, count)| (callstack.to_string(), count))
+                    .collect();
+                distinct_callstacks.sort_by(|first, second| second.1.cmp(&first.1).then_with(|| first.0.cmp(&second.0)));
+                distinct_callstacks
+            })
+            .clone()
+    }
+
+    /// Gets the life story of a single address: every allocation and free that covers it, in
+    /// chronological order, with who made it. When an address is freed and reallocated from a
+    /// different callstack, that transition is often where the bug is.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: Address to get the timeline for.
+    ///
+    /// returns: Vec<(timestamp, kind, callstack)>, where kind is "Allocation", "Free" or
+    /// "Reallocation".
+    pub fn get_address_callstack_timeline(&self, address: usize) -> Vec<(usize, String, String)> {
+        let mut updates: Vec<&MemoryUpdateType> = self.full_lapper
+            .find(address, address + 1)
+            .map(|interval| &interval.val)
+            .collect();
+        updates.sort_by_key(|update| update.get_timestamp());
+        updates
+            .into_iter()
+            .map(|update| {
+                let kind = match update {
+                    MemoryUpdateType::Allocation(_) => "Allocation",
+                    MemoryUpdateType::Free(_) => "Free",
+                    MemoryUpdateType::Reallocation(_) => "Reallocation",
+                };
+                (update.get_timestamp(), kind.to_string(), update.get_callstack().to_string())
+            })
+            .collect()
+    }
+
+    /// Gets the allocs and frees that cover a single address over the whole run. Reallocations
+    /// are split into the free of their old region and the allocation of their new region, and
+    /// only the parts covering the address are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: Address to look up.
+    ///
+    /// returns: Vec<MemoryUpdateType>, sorted by ascending timestamp.
+    fn get_address_updates(&self, address: usize) -> Vec<MemoryUpdateType> {
+        let mut updates: Vec<MemoryUpdateType> = self.full_lapper
+            .find(address, address + 1)
+            .flat_map(|interval| interval.val.split_reallocation())
+            .filter(|update| update.get_start() <= address && address < update.get_end())
+            .collect();
+        updates.sort_by_key(|update| update.get_timestamp());
+        updates
+    }
+
+    /// Gets the earliest timestamp at which an allocation covered an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: Address to look up.
+    ///
+    /// returns: Option<u64>, or None if the address was never allocated.
+    pub fn first_allocation_at(&self, address: usize) -> Option<u64> {
+        self.get_address_updates(address)
+            .iter()
+            .find(|update| matches!(update, MemoryUpdateType::Allocation(_)))
+            .map(|update| update.get_timestamp() as u64)
+    }
+
+    /// Gets the latest timestamp at which a free covered an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: Address to look up.
+    ///
+    /// returns: Option<u64>, or None if the address was never freed.
+    pub fn last_free_at(&self, address: usize) -> Option<u64> {
+        self.get_address_updates(address)
+            .iter()
+            .rev()
+            .find(|update| matches!(update, MemoryUpdateType::Free(_)))
+            .map(|update| update.get_timestamp() as u64)
+    }
+
+    /// Gets the reuse history of a single address: every allocation and free that covers it
+    /// across the whole run. A reallocation shows up as a free of its old region and an
+    /// allocation of its new region, whichever of them cover the address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: Address to get the timeline for.
+    ///
+    /// returns: Vec<(timestamp, kind)>, in chronological order, where kind is "Allocation" or
+    /// "Free".
+    pub fn get_address_timeline(&self, address: usize) -> Vec<(u64, String)> {
+        self.get_address_updates(address)
+            .iter()
+            .map(|update| {
+                let kind = match update {
+                    MemoryUpdateType::Free(_) => "Free",
+                    _ => "Allocation",
+                };
+                (update.get_timestamp() as u64, kind.to_string())
+            })
+            .collect()
+    }
+
+    /// Gets all update intervals with timestamps inside a window, sorted by timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp_start`: Start of the window (inclusive).
+    /// * `timestamp_end`: End of the window (inclusive).
+    ///
+    /// returns: Vec<SerializableUpdateInterval, Global>
+    pub fn get_intervals_in_window(&self, timestamp_start: usize, timestamp_end: usize) -> Vec<SerializableUpdateInterval> {
+        let mut intervals: Vec<SerializableUpdateInterval> = self.full_lapper
+            .iter()
+            .filter(|interval| (timestamp_start..=timestamp_end).contains(&interval.val.get_timestamp()))
+            .map(SerializableUpdateInterval::from)
+            .collect();
+        intervals.sort_by_key(|interval| interval.val.get_timestamp());
+        intervals
+    }
+
+    /// Replays this pool's updates against a pool of a smaller capacity, placing allocations with
+    /// first-fit, to find the first allocation that would have failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `cap_bytes`: Capacity of the simulated pool in bytes.
+    ///
+    /// returns: Some((timestamp, failing allocation)), or None if every allocation succeeds.
+    pub fn simulate_capacity(&self, cap_bytes: usize) -> Option<(usize, MemoryUpdateType)> {
+        PlacementSimulator::simulate_capacity(&self.memory_updates, cap_bytes)
+    }
+
+    /// Computes the Gini coefficient of allocation sizes in this pool, as a single number
+    /// describing how skewed the workload is towards a few large allocations.
+    ///
+    /// returns: Gini coefficient between 0 (all allocations the same size) and 1.
+    pub fn get_size_inequality(&self) -> f64 {
+        SizeDistribution::gini_coefficient(&SizeDistribution::get_allocation_sizes(&self.memory_updates))
+    }
+
+    /// Gets the size of the largest allocation with a given alignment that could be placed at a
+    /// timestamp. This can be smaller than the largest free block, as a free block that does not
+    /// start on an alignment boundary loses the padding at its start.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to check.
+    /// * `alignment`: Alignment of the allocation in bytes.
+    ///
+    /// returns: Size in bytes of the largest aligned allocation that fits.
+    pub fn largest_aligned_free(&self, timestamp: u64, alignment: usize) -> usize {
+        let free_regions = FreeRegionFinder::get_free_regions_at(
+            &self.memory_updates,
+            timestamp as usize,
+            self.map_viewer.get_lowest_address(),
+            self.map_viewer.get_highest_address(),
+        );
+        FreeRegionFinder::largest_aligned_free(&free_regions, alignment)
+    }
+
+    /// Gets the free regions of this pool at a timestamp, so they can be shaded on the map.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to find free regions at.
+    ///
+    /// returns: Vec<(start, end)> of free regions, sorted by start address.
+    pub fn get_free_blocks_at(&self, timestamp: u64) -> Vec<(usize, usize)> {
+        let mut distinct_block_counter = DistinctBlockCounter::new(
+            vec![],
+            0,
+            0,
+            Some((self.map_viewer.get_lowest_address(), self.map_viewer.get_highest_address())),
+        );
+        for allocation in UpdateQueueCompressor::live_set_at(&self.memory_updates, timestamp) {
+            distinct_block_counter.push_update(&allocation);
+        }
+        distinct_block_counter.get_free_blocks()
+    }
+
+    /// Finds anomalies in this pool, such as double frees, at or above a severity.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_severity`: Minimum severity of anomalies to return.
+    ///
+    /// returns: Vec<Anomaly>, sorted by timestamp.
+    pub fn get_anomalies(&self, min_severity: AnomalySeverity) -> Vec<Anomaly> {
+        AnomalyDetector::find_anomalies(&self.memory_updates, self.memory_accesses.as_deref(), min_severity)
+    }
+
+    /// Finds accesses (such as writes) that land in a region freed before them and not allocated
+    /// again since.
+    ///
+    /// returns: Vec<(timestamp, address)>, sorted by timestamp. Empty if the log has no accesses.
+    pub fn find_use_after_free(&self) -> Vec<(u64, usize)> {
+        if self.memory_accesses.is_none() {
+            return Vec::new();
+        }
+        self.get_anomalies(AnomalySeverity::Error)
+            .into_iter()
+            .filter(|anomaly| anomaly.get_kind() == AnomalyKind::UseAfterFree)
+            .map(|anomaly| (anomaly.get_timestamp() as u64, anomaly.get_address()))
+            .collect()
+    }
+
+    /// Gets the addresses in this pool that the most allocations landed on.
+    ///
+    /// # Arguments
+    ///
+    /// * `n`: Number of addresses to return.
+    ///
+    /// returns: Vec<(address, allocation count)>, most reused first.
+    pub fn get_most_reused_addresses(&self, n: usize) -> Vec<(usize, usize)> {
+        AddressReuseFinder::get_most_reused_addresses(&self.memory_updates, n)
+    }
+
+    /// Replays this pool's allocation requests against an empty pool of the same size, placing
+    /// them with a placement strategy, to evaluate whether a different allocator policy would
+    /// reduce fragmentation.
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy`: Strategy to place allocations with.
+    ///
+    /// returns: PlacementResult with peak usage and fragmentation.
+    pub fn simulate_placement(&self, strategy: PlacementStrategy) -> PlacementResult {
+        let capacity = self.map_viewer.get_highest_address() - self.map_viewer.get_lowest_address();
+        PlacementSimulator::simulate_placement(&self.memory_updates, capacity, strategy)
+    }
+
+    /// Gets the number of allocations and frees in this pool, as a quick check for truncated or
+    /// leaking traces. A reallocation counts as both.
+    ///
+    /// returns: (alloc_count, free_count)
+    pub fn get_event_counts(&self) -> (u64, u64) {
+        PoolRestrictedParseResults::count_events(&self.memory_updates)
+    }
+
+    /// Gets the peak fraction of the pool's capacity that was in use. Fractions above 1.0 mean the
+    /// pool's reported bounds are too small for what was allocated in it, which is flagged with a
+    /// warning.
+    ///
+    /// returns: Peak usage / pool size, unclamped. 0.0 if the pool has no capacity.
+    pub fn get_peak_utilization_fraction(&self) -> f64 {
+        let capacity = self.map_viewer.get_highest_address() - self.map_viewer.get_lowest_address();
+        if capacity == 0 {
+            return 0.0;
+        }
+        let fraction = self.graph_viewer.get_max_usage() as f64 / capacity as f64;
+        if fraction > 1.0 {
+            eprintln!("{}", format!("[DamselflyInstance::get_peak_utilization_fraction]: Peak usage of pool {} is {:.1}% of its capacity. The pool may be mis-sized.", self.name, fraction * 100.0).yellow());
+        }
+        fraction
+    }
+
+    /// Gets the range of timestamps covered by each cache snapshot, so the frontend can prefetch
+    /// the next snapshot before it is needed.
+    ///
+    /// returns: Vec<(start, end)>, where each snapshot covers [start, end).
+    pub fn get_cache_layout(&self) -> Vec<(usize, usize)> {
+        self.map_viewer.get_cache_layout()
+    }
+
+    /// Checks if the map at a timestamp can be rendered straight from the cache, or needs an
+    /// expensive replay, so the frontend only shows a loading indicator when it is needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp in operation time.
+    ///
+    /// returns: true if the render is a cache hit.
+    pub fn is_timestamp_cached(&self, timestamp: u64) -> bool {
+        self.map_viewer.is_timestamp_cached(timestamp as usize)
+    }
+
+    /// Sorts this pool's allocations into the allocator's size classes, to show how many bytes
+    /// are lost to rounding up to each class.
+    ///
+    /// # Arguments
+    ///
+    /// * `size_classes`: Size class boundaries from the allocator config, in bytes.
+    ///
+    /// returns: Vec<(class_size, count, wasted_bytes)>
+    pub fn get_size_class_distribution(&self, size_classes: &[usize]) -> Vec<(usize, usize, usize)> {
+        SizeDistribution::get_size_class_distribution(&SizeDistribution::get_allocation_sizes(&self.memory_updates), size_classes)
+    }
+
+    /// Buckets the sizes this pool's allocations requested into a histogram, to choose slab
+    /// allocator size classes. Sizes are the requested sizes, without right padding.
+    ///
+    /// # Arguments
+    ///
+    /// * `buckets`: Upper bound of each bucket in bytes.
+    ///
+    /// returns: Vec<(bucket_upper_bound, count)> in ascending order, ending with an overflow
+    /// bucket with an upper bound of usize::MAX for requests larger than every bucket.
+    pub fn get_allocation_size_histogram(&self, buckets: &[usize]) -> Vec<(usize, u64)> {
+        let requested_sizes: Vec<usize> = SizeDistribution::get_allocation_sizes(&self.memory_updates)
+            .into_iter()
+            .map(|size| size.saturating_sub(self.right_padding))
+            .collect();
+        SizeDistribution::get_size_histogram(&requested_sizes, buckets)
+    }
+
+    /// Gets how long each allocation in this pool lived, by pairing it with the free of its
+    /// address. Allocations that are never freed live until the pool's last update.
+    ///
+    /// returns: Vec<lifetime in timestamp units>, in the order the allocations were made.
+    pub fn get_allocation_lifetimes(&self) -> Vec<u64> {
+        let end_timestamp = self.memory_updates.last().map_or(0, |update| update.get_timestamp() as u64);
+        LifetimeDistribution::get_allocation_lifetimes(&self.memory_updates, end_timestamp)
+    }
+
+    /// Buckets allocation lifetimes into a histogram, to help tune pool sizing.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket_count`: Number of equal-width buckets between 0 and the longest lifetime.
+    ///
+    /// returns: Vec<(upper_bound, count)>
+    pub fn get_lifetime_histogram(&self, bucket_count: usize) -> Vec<(u64, u64)> {
+        LifetimeDistribution::get_lifetime_histogram(&self.get_allocation_lifetimes(), bucket_count)
+    }
+
+    /// Finds when total usage first reaches a threshold, and the operation that pushed it over.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold_bytes`: Usage threshold in bytes, e.g. 80% of the pool.
+    ///
+    /// returns: Some((timestamp, operation at that timestamp)), or None if usage never reaches
+    /// the threshold.
+    pub fn first_crossing(&self, threshold_bytes: u64) -> Option<(usize, MemoryUpdateType)> {
+        let timestamp = self.graph_viewer.get_first_crossing(threshold_bytes as i128)? as usize;
+        Some((timestamp, self.memory_updates.get(timestamp)?.clone()))
+    }
+
+    /// Finds the next operation where fragmentation spikes, i.e. where the distinct block count
+    /// increases by at least a threshold over the previous operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_timestamp`: Timestamp to search forward from, exclusive, e.g. the current cursor.
+    /// * `threshold_delta`: Minimum increase in distinct blocks between consecutive operations.
+    ///
+    /// returns: The timestamp of the spike, or None if there are no more spikes.
+    pub fn find_next_fragmentation_spike(&self, from_timestamp: u64, threshold_delta: u64) -> Option<u64> {
+        self.graph_viewer.get_next_distinct_blocks_spike(from_timestamp, threshold_delta as u128)
+    }
+
+    /// Finds the previous operation where fragmentation spikes. See find_next_fragmentation_spike.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_timestamp`: Timestamp to search backward from, exclusive.
+    /// * `threshold_delta`: Minimum increase in distinct blocks between consecutive operations.
+    ///
+    /// returns: The timestamp of the spike, or None if there are no earlier spikes.
+    pub fn find_prev_fragmentation_spike(&self, from_timestamp: u64, threshold_delta: u64) -> Option<u64> {
+        self.graph_viewer.get_prev_distinct_blocks_spike(from_timestamp, threshold_delta as u128)
+    }
+
+    /// Counts how many different callstacks allocated at an address over the trace.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: Address that allocations start at.
+    ///
+    /// returns: Number of distinct allocation callstacks.
+    pub fn get_address_callstack_diversity(&self, address: usize) -> usize {
+        AddressReuseFinder::get_callstack_diversity(&self.memory_updates, address)
+    }
+
+    /// Divides the pool into regions and counts the live allocations starting in each one, to
+    /// highlight hot address regions.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to count live allocations at.
+    /// * `region_count`: Number of regions to divide the pool into.
+    ///
+    /// returns: Vec of allocation counts, one per region in address order.
+    pub fn get_allocation_density(&self, timestamp: u64, region_count: usize) -> Vec<usize> {
+        AllocationDensity::get_allocation_density(
+            &self.memory_updates,
+            timestamp as usize,
+            self.map_viewer.get_lowest_address(),
+            self.map_viewer.get_highest_address(),
+            region_count,
+        )
+    }
+
+    /// Finds the call sites whose live allocations split free space at a timestamp, pointing at
+    /// long-lived allocations that should be relocated or pooled.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to look at.
+    /// * `n`: Number of call sites to return.
+    ///
+    /// returns: Vec<(callstack, gaps bordered, free bytes bordered)>, worst first.
+    pub fn get_fragmentation_culprits(&self, timestamp: u64, n: usize) -> Vec<(String, usize, usize)> {
+        FragmentationCulpritFinder::get_fragmentation_culprits(&self.memory_updates, timestamp as usize, n)
+    }
+
+    /// Ranks the callstacks of allocations live at a timestamp by the bytes they hold, for finding
+    /// the top allocators when triaging leaks.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to look at.
+    ///
+    /// returns: Vec<(callstack, live bytes, live allocations)>, largest first.
+    pub fn get_allocation_sites_ranked(&self, timestamp: u64) -> Vec<(String, usize, usize)> {
+        AllocationSiteRanker::get_allocation_sites_ranked(&self.memory_updates, timestamp as usize)
+    }
+
+    /// Stores memory accesses for this pool, timestamped in this pool's operation time.
+    /// Pass None if the log has no access records.
+    pub fn set_memory_accesses(&mut self, memory_accesses: Option<Vec<MemoryAccess>>) {
+        self.memory_accesses = memory_accesses;
+    }
+
+    /// Finds pairs of allocations that are live at the same time and claim overlapping bytes,
+    /// which indicates a corrupted trace or an allocator bug.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to check.
+    ///
+    /// returns: Vec<(earlier allocation, later allocation)>, ordered by the earlier allocation.
+    pub fn find_overlaps_at(&self, timestamp: u64) -> Vec<(MemoryUpdateType, MemoryUpdateType)> {
+        let live_allocations = UpdateQueueCompressor::live_set_at(&self.memory_updates, timestamp);
+        let update_intervals = live_allocations
+            .iter()
+            .map(UpdateIntervalFactory::convert_update_to_interval)
+            .collect();
+        let overlap_finder = OverlapFinder::new(update_intervals);
+        let mut overlaps = Vec::new();
+        for allocation in &live_allocations {
+            let (start, stop) = (allocation.get_start(), allocation.get_end());
+            for overlap in overlap_finder.find_overlaps(start, stop) {
+                if overlap.val.get_timestamp() > allocation.get_timestamp() {
+                    overlaps.push((allocation.clone(), overlap.val.clone()));
+                }
+            }
+        }
+        overlaps
+    }
+
+    pub fn set_parse_anomalies(&mut self, parse_anomalies: Vec<ParseAnomaly>) {
+        self.parse_anomalies = parse_anomalies;
+    }
+
+    /// Gets the double frees and frees of unallocated addresses found while parsing this pool.
+    ///
+    /// returns: Vec<ParseAnomaly>, sorted by timestamp.
+    pub fn get_parse_anomalies(&self) -> Vec<ParseAnomaly> {
+        self.parse_anomalies.clone()
+    }
+
+    pub fn set_collapsed_duplicates(&mut self, collapsed_duplicates: u64) {
+        self.collapsed_duplicates = collapsed_duplicates;
+    }
+
+    /// Gets the number of consecutive duplicate updates collapsed from the log this pool was
+    /// loaded from, so the user can tell their trace had duplicates. 0 if collapsing was off.
+    pub fn get_collapsed_duplicates(&self) -> u64 {
+        self.collapsed_duplicates
+    }
+
+    /// Gets allocations that were freed without being accessed in between.
+    ///
+    /// returns: Vec of unused allocations, or DamselflyError::NoAccessEvents if the log has no
+    /// access records.
+    pub fn get_unused_allocations(&self) -> Result<Vec<MemoryUpdateType>, DamselflyError> {
+        match &self.memory_accesses {
+            None => Err(DamselflyError::NoAccessEvents),
+            Some(memory_accesses) =>
+                Ok(UnusedAllocationFinder::find_unused_allocations(&self.memory_updates, memory_accesses)),
+        }
+    }
+
+    /// Sets the map's block size, which also rebuilds the map cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_size`: Requested block size in bytes.
+    /// * `mode`: Whether to use the requested size as is or snap it to a power of two.
+    ///
+    /// returns: The block size applied, or DamselflyError::InvalidConfiguration if the requested
+    /// block size is 0.
+    pub fn set_map_block_size(&mut self, new_size: usize, mode: SetBlockSizeMode) -> Result<usize, DamselflyError> {
+        let new_size = mode.resolve(new_size)?;
+        self.map_viewer.set_block_size(new_size);
+        Ok(new_size)
+    }
+
+    /// Sets what drives the colour of each block in get_map_full_at_nosync_colours_truncate.
+    /// In ColourMode::ByCallstack, each allocation callstack is given ColourMode::get_callstack_colour,
+    /// so a callstack keeps its colour across calls and time ranges. Free and unused blocks are 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `colour_mode`: New colour mode.
+    ///
+    /// returns: ()
+    pub fn set_colour_mode(&mut self, colour_mode: ColourMode) {
+        self.colour_mode = colour_mode;
+        if colour_mode == ColourMode::ByCallstack && self.callstack_colours.is_empty() {
+            self.assign_callstack_colours(0);
+        }
+    }
+
+    /// Caches the colour of each callstack allocated in the updates from first_update onwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `first_update`: Index of the first update to read.
+    ///
+    /// returns: ()
+    fn assign_callstack_colours(&mut self, first_update: usize) {
+        for update in &self.memory_updates[first_update..] {
+            if !matches!(update, MemoryUpdateType::Free(_)) {
+                self.callstack_colours
+                    .entry(update.get_callstack())
+                    .or_insert_with_key(|callstack| ColourMode::get_callstack_colour(callstack));
+            }
+        }
+    }
+
+    pub fn get_memory_updates(&self) -> &[MemoryUpdateType] {
+        &self.memory_updates
+    }
+
+    pub fn get_memory_usage_stats(&self) -> MemoryUsageStats {
+        self.graph_viewer.get_memory_usage_stats()
+    }
+
+    /// Gets the last timestamp of this instance's graphs, for bounding the timeline slider.
+    pub fn get_max_timestamp(&self) -> u64 {
+        self.graph_viewer.get_max_timestamp()
+    }
+
+    /// Gets the operation timestamp at which memory usage first peaked, for jumping the map there.
+    ///
+    /// returns: Operation timestamp of peak usage.
+    pub fn get_peak_usage_timestamp(&self) -> u64 {
+        self.graph_viewer.get_peak_usage_timestamp()
+    }
+
+    /// Gets the total bytes allocated and freed over the whole run, independent of peak usage.
+    /// Sizes include padding.
+    ///
+    /// returns: (total bytes allocated, total bytes freed)
+    pub fn get_lifetime_byte_totals(&self) -> (u128, u128) {
+        self.graph_viewer.get_lifetime_byte_totals()
+    }
+
+    pub fn get_memory_accesses(&self) -> Option<&[MemoryAccess]> {
+        self.memory_accesses.as_deref()
+    }
+
+    /// Appends updates to this pool, for logs that are followed as they are written. Only the new
+    /// updates are processed: the usage stats continue from the last update, and the graphs, map
+    /// cache and interval tree are extended rather than regenerated.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_updates`: Updates to append, in order, with padding already compensated for.
+    ///   They are retimestamped to follow the existing updates.
+    /// * `left_padding`: Padding to the left of each memory update.
+    /// * `right_padding`: Padding to the right of each memory update.
+    /// * `cache_size`: Interval between cached maps.
+    /// * `max_timestamp`: New max absolute operation timestamp to show on the graph.
+    ///
+    /// returns: ()
+    pub fn append_updates(&mut self, new_updates: Vec<MemoryUpdateType>, left_padding: usize, right_padding: usize, cache_size: u64, max_timestamp: u64) {
+        if new_updates.is_empty() {
+            return;
+        }
+        let first_timestamp = self.memory_updates.len();
+        let new_updates: Vec<MemoryUpdateType> = new_updates
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut memory_update)| {
+                memory_update.set_timestamp(first_timestamp + index);
+                memory_update
+            })
+            .collect();
+
+        let (lowest_address, highest_address) = self.get_memory_bounds();
+        let memory_updates = &self.memory_updates;
+        // Replays the stored updates on the first append only, then carries the state forward
+        let memory_usage_factory = self.memory_usage_factory.get_or_insert_with(|| {
+            let mut memory_usage_factory = MemoryUsageFactory::new(Vec::new(), left_padding, right_padding, lowest_address, highest_address);
+            memory_usage_factory.append_usage_stats(memory_updates);
+            memory_usage_factory
+        });
+        let memory_usage_stats = memory_usage_factory.append_usage_stats(&new_updates);
+        self.graph_viewer.append_memory_usage_stats(memory_usage_stats, max_timestamp);
+
+        let new_update_intervals = UpdateIntervalFactory::new(new_updates.clone()).construct_enum_vector();
+        for update_interval in &new_update_intervals {
+            self.full_lapper.insert(update_interval.clone());
+        }
+        self.map_viewer.append_update_intervals(new_update_intervals, cache_size);
+
+        self.memory_updates.extend(new_updates);
+        self.distinct_callstacks = None;
+        if !self.callstack_colours.is_empty() {
+            self.assign_callstack_colours(first_timestamp);
+        }
+    }
+
+    /// Replaces callstacks in place, such as raw addresses with the symbols they resolve to. The
+    /// map cache is regenerated, but the graphs are kept as callstacks don't affect them.
+    ///
+    /// # Arguments
+    ///
+    /// * `resolved_callstacks`: Replacement for each callstack to replace. Other callstacks are
+    ///   left as they are.
+    ///
+    /// returns: ()
+    pub fn replace_callstacks(&mut self, resolved_callstacks: &HashMap<Arc<String>, Arc<String>>) {
+        if resolved_callstacks.is_empty() {
+            return;
+        }
+        for memory_update in self.memory_updates.iter_mut() {
+            if let Some(resolved_callstack) = resolved_callstacks.get(&memory_update.get_callstack()) {
+                memory_update.set_callstack(resolved_callstack.clone());
+            }
+        }
+        let update_intervals = UpdateIntervalFactory::new(self.memory_updates.clone()).construct_enum_vector();
+        self.full_lapper = Lapper::new(update_intervals.clone());
+        self.map_viewer.replace_update_intervals(update_intervals);
+        self.distinct_callstacks = None;
+        if !self.callstack_colours.is_empty() {
+            self.callstack_colours.clear();
+            self.assign_callstack_colours(0);
+        }
+    }
+
+    /// Gets every distinct callstack, for resolving them all at once.
+    ///
+    /// returns: HashSet<Arc<String>>
+    pub fn get_callstack_set(&self) -> HashSet<Arc<String>> {
+        self.memory_updates
+            .iter()
+            .map(|memory_update| memory_update.get_callstack())
+            .collect()
+    }
+
+    pub fn get_map_block_size(&self) -> usize {
+        self.map_viewer.get_block_size()
+    }
+
+    /// Sets the padding that was added to the size of each update, so requested sizes can be
+    /// recovered.
+    pub fn set_right_padding(&mut self, right_padding: usize) {
+        self.right_padding = right_padding;
+    }
+
+    /// Sets the padding that was added around each update, so the map shows blocks holding only
+    /// padding as guard blocks rather than allocated ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `left_padding`: Padding added to the left of each update (shifting its address).
+    /// * `right_padding`: Padding added to the right of each update (increasing its size).
+    ///
+    /// returns: ()
+    pub fn set_guard_padding(&mut self, left_padding: usize, right_padding: usize) {
+        self.map_viewer.set_guard_padding(left_padding, right_padding);
+    }
+
+    /// Gets the bounds of this pool. These are the padded bounds, covering every padded update,
+    /// so they line up with the addresses of blocks on the map.
+    ///
+    /// returns: (lowest address, highest address)
+    pub fn get_memory_bounds(&self) -> (usize, usize) {
+        (self.map_viewer.get_lowest_address(), self.map_viewer.get_highest_address())
+    }
+
+    /// Gets the allocations that are live at a timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to check.
+    ///
+    /// returns: BTreeMap of address -> size.
+    pub fn get_live_allocations_at(&self, timestamp: u64) -> BTreeMap<usize, usize> {
+        FreeRegionFinder::get_live_allocations_at(&self.memory_updates, timestamp as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use crate::damselfly::consts::{OVERLAP_FINDER_TEST_LOG, TEST_BINARY_PATH};
+    use crate::damselfly::damselfly_error::DamselflyError;
+    use crate::damselfly::memory::memory_access::MemoryAccess;
+    use crate::damselfly::memory::memory_parsers::{MemoryParser, MemorySysTraceParser};
+    use crate::damselfly::memory::memory_status::MemoryStatus;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate, MemoryUpdateType, Reallocation};
+    use crate::damselfly::memory::memory_usage_factory::MemoryUsageFactory;
+    use crate::damselfly::viewer::colour_mode::ColourMode;
+    use crate::damselfly::viewer::damselfly_instance::DamselflyInstance;
+    use crate::damselfly::viewer::graph_viewer::GraphViewer;
+    use crate::damselfly::viewer::operation_log_exporter::OperationLogExporter;
+    use crate::damselfly::viewer::set_block_size_mode::SetBlockSizeMode;
+    use crate::damselfly::viewer::x_axis::XAxis;
+    use crate::damselfly::viewer::graph_metric::GraphMetric;
+
+    /// Two allocations, at 0 and 1024, 64 bytes each.
+    fn build_test_updates() -> Vec<MemoryUpdateType> {
+        let callstack = Arc::new("test".to_string());
+        vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(1024, 64, callstack, 1, "0001.001 s".to_string()).wrap_in_enum(),
+        ]
+    }
+
+    /// Builds an instance over a 2048 byte pool starting at 0, with a cache interval of 2.
+    fn initialise_test_instance(updates: Vec<MemoryUpdateType>) -> DamselflyInstance {
+        initialise_test_instance_with_bounds(updates, 0, 2048)
+    }
+
+    /// Like initialise_test_instance, but over a pool from lowest_address to highest_address.
+    fn initialise_test_instance_with_bounds(updates: Vec<MemoryUpdateType>, lowest_address: usize, highest_address: usize) -> DamselflyInstance {
+        let max_timestamp = updates.len() as u64;
+        let memory_usage_stats = MemoryUsageFactory::new(updates.clone(), 0, 0, lowest_address, highest_address)
+            .calculate_usage_stats();
+        DamselflyInstance::new("test".to_string(), updates, memory_usage_stats, lowest_address, highest_address, 2, max_timestamp)
+    }
+
+    #[test]
+    fn smooth_plot_points_test() {
+        let points = [[0.0, 0.0], [1.0, 3.0], [2.0, 6.0], [3.0, 0.0], [4.0, 3.0]];
+        let smoothed = GraphViewer::smooth_plot_points(&points, 3);
+        assert_eq!(smoothed, vec![[0.0, 1.5], [1.0, 3.0], [2.0, 3.0], [3.0, 3.0], [4.0, 1.5]]);
+        assert_eq!(GraphViewer::smooth_plot_points(&points, 1), points.to_vec());
+
+        let instance = initialise_test_instance(build_test_updates());
+        assert_eq!(instance.get_usage_graph_smoothed(5).len(), instance.get_usage_graph().len());
+    }
+
+    #[test]
+    fn high_water_plot_points_test() {
+        let points = [[0.0, 0.0], [1.0, 3.0], [2.0, 6.0], [3.0, 0.0], [4.0, 3.0]];
+        let high_water = GraphViewer::high_water_plot_points(&points);
+        assert_eq!(high_water, vec![[0.0, 0.0], [1.0, 3.0], [2.0, 6.0], [3.0, 6.0], [4.0, 6.0]]);
+
+        let instance = initialise_test_instance(build_test_updates());
+        assert_eq!(instance.get_usage_highwater_graph().len(), instance.get_usage_graph().len());
+    }
+
+    #[test]
+    fn decimate_plot_points_test() {
+        let points: Vec<[f64; 2]> = [1.0, 9.0, 2.0, 3.0, 0.0, 4.0, 5.0, 4.5]
+            .iter()
+            .enumerate()
+            .map(|(index, value)| [index as f64, *value])
+            .collect();
+        // Two runs of 4, each keeping its lowest and highest point in x order
+        assert_eq!(GraphViewer::decimate_plot_points(&points, 0.0, 7.0, 4), vec![[0.0, 1.0], [1.0, 9.0], [4.0, 0.0], [6.0, 5.0]]);
+        assert_eq!(GraphViewer::decimate_plot_points(&points, 0.0, 7.0, 1), vec![[1.0, 9.0]]);
+        assert_eq!(GraphViewer::decimate_plot_points(&points, 2.0, 4.0, 10), points[2..=4].to_vec());
+        assert!(GraphViewer::decimate_plot_points(&points, 0.0, 7.0, 0).is_empty());
+
+        let instance = initialise_test_instance(build_test_updates());
+        let realtime_graph = instance.get_usage_graph_realtime_sampled();
+        let end_ts = realtime_graph.len() as u64;
+        assert_eq!(instance.get_usage_graph_sampled_n(0, end_ts, realtime_graph.len()), realtime_graph);
+        assert!(instance.get_distinct_blocks_graph_sampled_n(0, end_ts, 2).len() <= 2);
+        assert!(instance.get_largest_free_block_graph_sampled_n(0, end_ts, 2).len() <= 2);
+    }
+
+    #[test]
+    fn event_counts_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(64, 64, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Free::new(0, 64, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Reallocation::new(64, 64, 256, 128, callstack, 3, "0001.003 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        assert_eq!(instance.get_event_counts(), (3, 2));
+    }
+
+    #[test]
+    fn peak_utilization_fraction_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        assert_eq!(instance.get_peak_utilization_fraction(), 128.0 / 2048.0);
+
+        // Overlapping allocations use more than the pool holds
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(0, 64, callstack, 1, "0001.001 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance_with_bounds(updates, 0, 64);
+        assert_eq!(instance.get_peak_utilization_fraction(), 2.0);
+    }
+
+    #[test]
+    fn guard_blocks_test() {
+        let mut instance = initialise_test_instance(build_test_updates());
+        let statuses = |instance: &DamselflyInstance, block_size: usize| -> Vec<u64> {
+            instance.get_map_full_at_with_block_size(1, block_size, None).1
+                .iter()
+                .map(|(_, status, _)| *status)
+                .collect()
+        };
+        assert_eq!(statuses(&instance, 32)[..3], [3, 3, 0]);
+
+        // Blocks holding only padding are guards, but blocks holding any requested bytes are not
+        instance.set_guard_padding(32, 0);
+        assert_eq!(statuses(&instance, 32)[..3], [5, 3, 0]);
+        assert_eq!(statuses(&instance, 32)[32..], [5, 3]);
+        instance.set_guard_padding(16, 16);
+        assert_eq!(statuses(&instance, 16)[..5], [5, 3, 3, 5, 0]);
+        assert_eq!(statuses(&instance, 32)[..3], [3, 3, 0]);
+        assert!(matches!(instance.map_viewer.paint_map_full_at_block_size(1, 16)[0], MemoryStatus::Guard(0, 64, 0, _)));
+    }
+
+    #[test]
+    fn get_free_blocks_at_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        assert_eq!(instance.get_free_blocks_at(0), vec![(64, 2048)]);
+        assert_eq!(instance.get_free_blocks_at(1), vec![(64, 1024), (1088, 2048)]);
+    }
+
+    #[test]
+    fn reallocation_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            // in place
+            Reallocation::new(0, 64, 0, 96, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            // moving
+            Reallocation::new(0, 96, 1024, 64, callstack, 2, "0001.002 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        let statuses = |timestamp: u64| -> Vec<u64> {
+            instance.get_map_full_at_with_block_size(timestamp, 32, None).1
+                .iter()
+                .map(|(_, status, _)| *status)
+                .collect()
+        };
+        assert_eq!(statuses(1)[..4], [3, 3, 3, 0]);
+        assert_eq!(statuses(1)[32..], [0, 0]);
+        assert_eq!(statuses(2)[..4], [1, 1, 1, 0]);
+        assert_eq!(statuses(2)[32..], [3, 3]);
+        assert_eq!(instance.get_free_blocks_at(2), vec![(0, 1024), (1088, 2048)]);
+
+        // A moving reallocation is one event in both its regions, but not in the gap between them
+        let timestamps = |address: usize| -> Vec<usize> {
+            instance.query_block(address, 2).iter().map(|update| update.get_timestamp()).collect()
+        };
+        assert_eq!(timestamps(0), vec![0, 1, 2]);
+        assert_eq!(timestamps(64), vec![1, 2]);
+        assert_eq!(timestamps(1024), vec![2]);
+        assert!(timestamps(512).is_empty());
+    }
+
+    #[test]
+    fn churn_graph_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        // Operations at 1s and 1.001s, so windows start at 1s rather than 0
+        assert_eq!(instance.get_churn_graph(500000), vec![[1000000.0, 2.0]]);
+        assert_eq!(instance.get_churn_graph(1000000), vec![[1000000.0, 2.0]]);
+        // 1.001s converts to 1000999us
+        let churn_graph = instance.get_churn_graph(200);
+        assert_eq!(churn_graph.len(), 5);
+        assert_eq!(churn_graph[0], [1000000.0, 1.0]);
+        assert!(churn_graph[1..4].iter().all(|point| point[1] == 0.0));
+        assert_eq!(churn_graph[4], [1000800.0, 1.0]);
+        assert_eq!(instance.get_churn_graph(1).len(), 1000);
+        assert!(instance.get_churn_graph(0).is_empty());
+
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Free::new(0, 64, callstack, 1, "0003.000 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        // 2 million 1us windows is too many
+        assert!(instance.get_churn_graph(1).is_empty());
+        assert_eq!(instance.get_churn_graph(4).len(), 500001);
+    }
+
+    #[test]
+    fn average_allocation_size_graph_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(1024, 32, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Free::new(0, 64, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Free::new(1024, 32, callstack, 3, "0001.003 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        // Padded to the max timestamp with the last size
+        assert_eq!(instance.get_average_allocation_size_graph(), vec![[0.0, 64.0], [1.0, 48.0], [2.0, 32.0], [3.0, 0.0], [4.0, 0.0]]);
+        assert!(!instance.get_average_allocation_size_graph_realtime_sampled().is_empty());
+    }
+
+    #[test]
+    fn free_gap_count_graph_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        // The free space before, between and after the two allocations is only one gap
+        assert_eq!(instance.get_free_gap_count_graph(), vec![[0.0, 0.0], [1.0, 1.0], [2.0, 1.0]]);
+        assert_eq!(instance.get_free_gap_count_graph_no_fallbacks(), vec![[0.0, 0.0], [1.0, 1.0]]);
+        assert!(!instance.get_free_gap_count_graph_realtime_sampled().is_empty());
+    }
+
+    #[test]
+    fn mean_allocated_block_size_graph_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        assert_eq!(instance.get_mean_allocated_block_size_graph(), vec![[0.0, 64.0], [1.0, 64.0], [2.0, 64.0]]);
+    }
+
+    #[test]
+    fn live_allocation_count_graph_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        // Padded to the max timestamp with the last count
+        assert_eq!(instance.get_live_allocation_count_graph(), vec![[0.0, 1.0], [1.0, 2.0], [2.0, 2.0]]);
+        assert_eq!(instance.get_live_allocation_count_graph_no_fallbacks(), vec![[0.0, 1.0], [1.0, 2.0]]);
+    }
+
+    #[test]
+    fn allocation_size_histogram_test() {
+        let mut instance = initialise_test_instance(build_test_updates());
+        assert_eq!(instance.get_allocation_size_histogram(&[32, 64]), vec![(32, 0), (64, 2), (usize::MAX, 0)]);
+        // Padding is removed, leaving the requested sizes
+        instance.set_right_padding(40);
+        assert_eq!(instance.get_allocation_size_histogram(&[32, 64]), vec![(32, 2), (64, 0), (usize::MAX, 0)]);
+    }
+
+    #[test]
+    fn get_usage_graph_with_axis_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        assert_eq!(instance.get_usage_graph_with_axis(XAxis::Operation), instance.get_usage_graph());
+        assert_eq!(instance.get_usage_graph_with_axis(XAxis::Realtime), instance.get_usage_graph_realtime_sampled());
+        assert_eq!(instance.get_graph_with_axis(GraphMetric::FreeBlocks, XAxis::Operation), instance.get_free_blocks_graph());
+        assert_eq!(instance.get_graph_with_axis(GraphMetric::FreeBlocks, XAxis::Realtime), instance.get_free_blocks_graph_realtime_sampled());
+        assert_eq!(
+            instance.get_graph_with_axis(GraphMetric::FreeSegmentFragmentation, XAxis::Operation),
+            instance.get_free_segment_fragmentation_graph_no_fallbacks()
+        );
+    }
+
+    #[test]
+    fn get_operation_history_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = (0..5)
+            .map(|timestamp| Allocation::new(timestamp * 64, 64, callstack.clone(), timestamp, "0001.000 s".to_string()).wrap_in_enum())
+            .collect();
+        let instance = initialise_test_instance(updates);
+
+        let timestamps = |history: Vec<MemoryUpdateType>| -> Vec<usize> {
+            history.iter().map(|update| update.get_timestamp()).collect()
+        };
+        assert_eq!(timestamps(instance.get_operation_history(0, 2)), vec![4, 3]);
+        assert_eq!(timestamps(instance.get_operation_history(2, 2)), vec![2, 1]);
+        assert_eq!(timestamps(instance.get_operation_history(4, 2)), vec![0]);
+        assert!(instance.get_operation_history(10, 2).is_empty());
+    }
+
+    #[test]
+    fn address_callstack_timeline_test() {
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, Arc::new("first".to_string()), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Free::new(0, 64, Arc::new("release".to_string()), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Allocation::new(32, 64, Arc::new("second".to_string()), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Allocation::new(1024, 64, Arc::new("elsewhere".to_string()), 3, "0001.003 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        assert_eq!(instance.get_address_callstack_timeline(40), vec![
+            (0, "Allocation".to_string(), "first".to_string()),
+            (1, "Free".to_string(), "release".to_string()),
+            (2, "Allocation".to_string(), "second".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn address_timeline_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Free::new(0, 64, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Allocation::new(32, 64, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            // moves out of [32..96), leaving its old region free
+            Reallocation::new(32, 64, 1024, 64, callstack.clone(), 3, "0001.003 s".to_string()).wrap_in_enum(),
+            Allocation::new(512, 64, callstack, 4, "0001.004 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        assert_eq!(instance.get_address_timeline(40), vec![
+            (0, "Allocation".to_string()),
+            (1, "Free".to_string()),
+            (2, "Allocation".to_string()),
+            (3, "Free".to_string()),
+        ]);
+        assert_eq!(instance.first_allocation_at(40), Some(0));
+        assert_eq!(instance.last_free_at(40), Some(3));
+        assert_eq!(instance.first_allocation_at(1024), Some(3));
+        assert_eq!(instance.last_free_at(1024), None);
+        // Inside the span of the moving reallocation, but not either of its regions
+        assert!(instance.get_address_timeline(600).is_empty());
+        assert_eq!(instance.first_allocation_at(512), Some(4));
+    }
+
+    #[test]
+    fn find_operations_by_callstack_test() {
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, Arc::new("src/net/Socket.c:12".to_string()), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(64, 64, Arc::new("src/ui/Window.c:40".to_string()), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Free::new(0, 64, Arc::new("src/net/socket.c:30".to_string()), 2, "0001.002 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        let timestamps: Vec<u64> = instance.find_operations_by_callstack("SOCKET")
+            .into_iter()
+            .map(|(timestamp, _)| timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![0, 2]);
+        assert!(instance.find_operations_by_callstack("missing").is_empty());
+        assert!(instance.find_operations_by_callstack("").is_empty());
+    }
+
+    #[test]
+    fn callstack_usage_graph_test() {
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, Arc::new("src/net/Socket.c:12".to_string()), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(64, 32, Arc::new("src/ui/Window.c:40".to_string()), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Allocation::new(128, 16, Arc::new("src/net/socket.c:20".to_string()), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            // Freed from elsewhere, but still released
+            Free::new(0, 64, Arc::new("src/main.c:5".to_string()), 3, "0001.003 s".to_string()).wrap_in_enum(),
+            Free::new(64, 32, Arc::new("src/ui/Window.c:50".to_string()), 4, "0001.004 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        assert_eq!(instance.get_callstack_usage_graph("SOCKET"), vec![[0.0, 64.0], [1.0, 64.0], [2.0, 80.0], [3.0, 16.0], [4.0, 16.0]]);
+        assert!(instance.get_callstack_usage_graph("missing").iter().all(|point| point[1] == 0.0));
+        assert!(instance.get_callstack_usage_graph("").is_empty());
+    }
+
+    #[test]
+    fn distinct_callstacks_test() {
+        let socket = Arc::new("src/net/Socket.c:12".to_string());
+        let window = Arc::new("src/ui/Window.c:40".to_string());
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, window.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(64, 32, socket.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Free::new(64, 32, socket.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Allocation::new(128, 16, Arc::new("src/main.c:5".to_string()), 3, "0001.003 s".to_string()).wrap_in_enum(),
+            Allocation::new(64, 16, socket, 4, "0001.004 s".to_string()).wrap_in_enum(),
+        ];
+        let mut instance = initialise_test_instance(updates);
+        let expected = vec![
+            ("src/net/Socket.c:12".to_string(), 3),
+            ("src/main.c:5".to_string(), 1),
+            ("src/ui/Window.c:40".to_string(), 1),
+        ];
+        assert_eq!(instance.get_distinct_callstacks(), expected);
+        // Served from the cache the second time
+        assert_eq!(instance.get_distinct_callstacks(), expected);
+    }
+
+    #[test]
+    fn find_use_after_free_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(64, 64, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Free::new(0, 64, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Allocation::new(0, 32, callstack, 3, "0001.003 s".to_string()).wrap_in_enum(),
+        ];
+        let mut instance = initialise_test_instance(updates);
+        assert!(instance.find_use_after_free().is_empty());
+        instance.set_memory_accesses(Some(vec![
+            // Live
+            MemoryAccess::new(8, 4, 1),
+            // Freed
+            MemoryAccess::new(16, 4, 2),
+            // Reallocated
+            MemoryAccess::new(16, 4, 3),
+        ]));
+        assert_eq!(instance.find_use_after_free(), vec![(2, 16)]);
+    }
+
+    #[test]
+    fn get_callstack_at_test() {
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, Arc::new("src/net/Socket.c:12".to_string()), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Free::new(0, 64, Arc::new("src/net/Socket.c:30".to_string()), 1, "0001.001 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        assert_eq!(instance.get_callstack_at(0), Ok("src/net/Socket.c:12".to_string()));
+        assert_eq!(instance.get_callstack_at(1), Ok("src/net/Socket.c:30".to_string()));
+        assert_eq!(
+            instance.get_callstack_at(2),
+            Err(DamselflyError::TimestampOutOfRange { timestamp: 2, max_timestamp: 1 })
+        );
+    }
+
+    #[test]
+    fn truncate_after_huge_value_returns_full_map_test() {
+        let mut instance = initialise_test_instance(build_test_updates());
+        // the canvas spans 0 -> 1088 in blocks of 32 bytes
+        let (_, full_map) = instance.get_map_full_at_nosync_colours_truncate(1, Some(u64::MAX));
+        assert_eq!(full_map.len(), 34);
+        let (_, untruncated_map) = instance.get_map_full_at_nosync_colours_truncate(1, None);
+        assert_eq!(untruncated_map, full_map);
+    }
+
+    #[test]
+    fn map_full_at_rle_test() {
+        let mut instance = initialise_test_instance(build_test_updates());
+        for truncate_after in [None, Some(4)] {
+            let (_, map) = instance.get_map_full_at_nosync_colours_truncate(1, truncate_after);
+            let (timestamp, runs) = instance.get_map_full_at_rle(1, truncate_after);
+            assert_eq!(timestamp, 1);
+            assert!(runs.len() < map.len());
+            let mut colours = Vec::new();
+            for (colour, run_length, start_offset) in runs {
+                assert_eq!(start_offset, colours.len());
+                colours.extend(std::iter::repeat(colour as u64).take(run_length as usize));
+            }
+            let expected_colours: Vec<u64> = map.iter().map(|(_, colour, _)| *colour).collect();
+            assert_eq!(colours, expected_colours);
+        }
+    }
+
+    #[test]
+    fn compacted_map_at_test() {
+        let mut instance = initialise_test_instance(build_test_updates());
+        let statuses = |map: &[(i64, u64, usize)]| -> Vec<u64> { map.iter().map(|(_, status, _)| *status).collect() };
+        // The 30 unused blocks between the allocations collapse to 4
+        let (map, offsets) = instance.get_compacted_map_at(1);
+        assert_eq!(statuses(&map), vec![3, 3, 0, 0, 0, 0, 3, 3]);
+        assert_eq!(offsets, vec![(0, 0), (6, 1024)]);
+        assert_eq!(map[6].2, 1024);
+
+        let (map, offsets) = instance.get_compacted_map_at(0);
+        assert_eq!(statuses(&map), vec![3, 3, 0, 0, 0, 0]);
+        assert_eq!(offsets, vec![(0, 0)]);
+    }
+
+    #[test]
+    fn map_occupancy_at_test() {
+        let mut instance = initialise_test_instance(build_test_updates());
+        // 34 blocks of 32 bytes span the updates
+        assert_eq!(instance.get_map_occupancy_at(0), (1088, 64, 1024));
+        assert_eq!(instance.get_map_occupancy_at(1), (1088, 128, 960));
+        // Partially allocated blocks count as used in full, like the map draws them
+        instance.set_map_block_size(48, SetBlockSizeMode::Exact).unwrap();
+        assert_eq!(instance.get_map_occupancy_at(1), (1104, 192, 912));
+    }
+
+    #[test]
+    fn truncate_after_small_value_truncates_test() {
+        let mut instance = initialise_test_instance(build_test_updates());
+        let (_, truncated_map) = instance.get_map_full_at_nosync_colours_truncate(1, Some(4));
+        // 2 allocated blocks, 5 of the 30 unused blocks, 2 allocated blocks
+        assert_eq!(truncated_map.len(), 9);
+    }
+
+    #[test]
+    fn map_full_at_with_block_size_test() {
+        let mut instance = initialise_test_instance(build_test_updates());
+        let (_, preview_map) = instance.get_map_full_at_with_block_size(1, 64, None);
+        // the canvas spans 0 -> 1088 in blocks of 64 bytes
+        assert_eq!(preview_map.len(), 17);
+        assert_eq!(preview_map[0], (0, 3, 0));
+        assert_eq!(preview_map[16], (1024, 3, 1024));
+        let (_, preview_map) = instance.get_map_full_at_with_block_size(0, 64, None);
+        assert_eq!(preview_map[16], (-1, 0, 1024));
+
+        // The cached block size is untouched
+        assert_eq!(instance.get_map_block_size(), 32);
+        let (_, cached_map) = instance.get_map_full_at_nosync_colours_truncate(1, None);
+        assert_eq!(instance.get_map_full_at_with_block_size(1, 32, None).1, cached_map);
+    }
+
+    #[test]
+    fn map_decay_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(1024, 64, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Free::new(0, 64, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Allocation::new(512, 64, callstack, 3, "0001.003 s".to_string()).wrap_in_enum(),
+        ];
+        let mut instance = initialise_test_instance(updates);
+
+        let (_, map) = instance.get_map_full_at_nosync_colours_truncate_decay(3, None, 4);
+        // blocks 0 and 32 were freed one timestamp ago
+        assert_eq!(map[0].1, 1);
+        assert_eq!(map[0].3, 0.75);
+        assert_eq!(map[1].3, 0.75);
+        assert!(map.iter().skip(2).all(|block| block.3 == 0.0));
+
+        let (_, map) = instance.get_map_full_at_nosync_colours_truncate_decay(3, None, 1);
+        assert!(map.iter().all(|block| block.3 == 0.0));
+    }
+
+    #[test]
+    fn map_full_between_test() {
+        let callstack = Arc::new("test".to_string());
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(1024, 32, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Free::new(0, 64, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Allocation::new(512, 32, callstack.clone(), 3, "0001.003 s".to_string()).wrap_in_enum(),
+            Free::new(512, 32, callstack, 4, "0001.004 s".to_string()).wrap_in_enum(),
+        ];
+        let mut instance = initialise_test_instance(updates);
+
+        let (timestamp, map) = instance.get_map_full_between(1, 4, None);
+        assert_eq!(timestamp, 4);
+        let status_at = |address: usize| map.iter().find(|block| block.2 == address).unwrap().1;
+        // 0 was freed, 512 was churned, and 1024 was allocated before the range
+        assert_eq!(status_at(0), 1);
+        assert_eq!(status_at(32), 1);
+        assert_eq!(status_at(512), 4);
+        assert_eq!(status_at(1024), 3);
+
+        let (_, map) = instance.get_map_full_between(1, 3, None);
+        assert_eq!(map.iter().find(|block| block.2 == 512).unwrap().1, 3);
+    }
+
+    #[test]
+    fn append_updates_test() {
+        let mut instance = initialise_test_instance(build_test_updates());
+        let callstack = Arc::new("test".to_string());
+        instance.append_updates(vec![
+            Free::new(0, 64, callstack, 5, "0001.002 s".to_string()).wrap_in_enum(),
+        ], 0, 0, 2, 3);
+        assert_eq!(instance.get_memory_updates().len(), 3);
+        assert_eq!(instance.get_memory_updates()[2].get_timestamp(), 2);
+        assert_eq!(instance.get_live_allocations_at(2).len(), 1);
+        // Usage drops to half of the peak after the free
+        assert_eq!(instance.get_usage_graph_no_fallbacks().last().unwrap()[1], 50.0);
+    }
+
+    #[test]
+    fn append_updates_matches_rebuild_test() {
+        let callstack = Arc::new("test".to_string());
+        let mut updates = build_test_updates();
+        updates.extend([
+            Free::new(0, 64, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Allocation::new(256, 32, callstack.clone(), 3, "0001.003 s".to_string()).wrap_in_enum(),
+            Allocation::new(512, 128, callstack, 4, "0001.004 s".to_string()).wrap_in_enum(),
+        ]);
+        let mut rebuilt = initialise_test_instance(updates.clone());
+        let mut appended = initialise_test_instance(updates[..2].to_vec());
+        appended.append_updates(updates[2..4].to_vec(), 0, 0, 2, 4);
+        appended.append_updates(updates[4..].to_vec(), 0, 0, 2, 5);
+
+        assert_eq!(appended.get_memory_updates(), rebuilt.get_memory_updates());
+        assert_eq!(appended.get_lifetime_byte_totals(), rebuilt.get_lifetime_byte_totals());
+        assert_eq!(appended.get_cache_layout(), rebuilt.get_cache_layout());
+        for metric in [GraphMetric::Usage, GraphMetric::DistinctBlocks, GraphMetric::LargestFreeBlock, GraphMetric::LiveAllocationCount] {
+            for axis in [XAxis::Operation, XAxis::Realtime] {
+                assert_eq!(appended.get_graph_with_axis(metric, axis), rebuilt.get_graph_with_axis(metric, axis));
+            }
+        }
+        for timestamp in 0..5 {
+            assert_eq!(
+                appended.get_map_full_at_nosync_colours_truncate(timestamp, None),
+                rebuilt.get_map_full_at_nosync_colours_truncate(timestamp, None)
+            );
+        }
+    }
+
+    #[test]
+    fn replace_callstacks_test() {
+        let mut instance = initialise_test_instance(build_test_updates());
+        instance.set_colour_mode(ColourMode::ByCallstack);
+        let resolved_callstacks = HashMap::from([(Arc::new("test".to_string()), Arc::new("resolved".to_string()))]);
+        instance.replace_callstacks(&resolved_callstacks);
+
+        assert!(instance.get_memory_updates().iter().all(|update| update.get_callstack().as_str() == "resolved"));
+        assert_eq!(instance.get_distinct_callstacks(), vec![("resolved".to_string(), 2)]);
+        let (_, map) = instance.get_map_full_at_nosync_colours_truncate(1, None);
+        assert_eq!(map[0].1, ColourMode::get_callstack_colour("resolved"));
+    }
+
+    #[test]
+    fn colour_by_callstack_test() {
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 32, Arc::new("first".to_string()), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(64, 32, Arc::new("second".to_string()), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Allocation::new(128, 32, Arc::new("first".to_string()), 2, "0001.002 s".to_string()).wrap_in_enum(),
+        ];
+        let mut instance = initialise_test_instance(updates);
+
+        let (_, map) = instance.get_map_full_at_nosync_colours_truncate(2, None);
+        assert_eq!((map[0].1, map[2].1, map[4].1), (3, 3, 3));
+
+        instance.set_colour_mode(ColourMode::ByCallstack);
+        let (_, map) = instance.get_map_full_at_nosync_colours_truncate(2, None);
+        let (first, second) = (ColourMode::get_callstack_colour("first"), ColourMode::get_callstack_colour("second"));
+        assert_ne!(first, second);
+        assert_eq!((map[0].1, map[1].1, map[2].1, map[4].1), (first, 0, second, first));
+
+        // Colours do not depend on which callstack was allocated first
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(64, 32, Arc::new("second".to_string()), 0, "0001.000 s".to_string()).wrap_in_enum(),
+        ];
+        let mut instance = initialise_test_instance(updates);
+        instance.set_colour_mode(ColourMode::ByCallstack);
+        let (_, map) = instance.get_map_full_at_nosync_colours_truncate(0, None);
+        assert_eq!(map[0].1, second);
+    }
+
+    #[test]
+    fn first_crossing_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        let (timestamp, operation) = instance.first_crossing(100).unwrap();
+        assert_eq!(timestamp, 1);
+        assert_eq!(operation.get_absolute_address(), 1024);
+        assert_eq!(instance.first_crossing(64).unwrap().0, 0);
+        assert!(instance.first_crossing(129).is_none());
+    }
+
+    #[test]
+    fn render_map_png_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        let path = std::env::temp_dir().join("damselfly_instance_render_map_png_test.png");
+        let path = path.to_str().unwrap();
+        instance.render_map_png(1, 32, 16, path).unwrap();
+        // The canvas spans 0 -> 1088, so 34 blocks in 3 rows of 16
+        let png = std::fs::read(path).unwrap();
+        assert_eq!(&png[16..24], &[0, 0, 0, 128, 0, 0, 0, 24]);
+        assert!(instance.render_map_png(1, 0, 16, path).is_err());
+    }
+
+    #[test]
+    fn find_fragmentation_spike_test() {
+        let callstack = Arc::new("test".to_string());
+        // Distinct blocks: 0, 1, 1, 2
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(128, 64, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+            Allocation::new(192, 64, callstack.clone(), 2, "0001.002 s".to_string()).wrap_in_enum(),
+            Allocation::new(512, 64, callstack, 3, "0001.003 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        assert_eq!(instance.find_next_fragmentation_spike(0, 1), Some(1));
+        assert_eq!(instance.find_next_fragmentation_spike(1, 1), Some(3));
+        assert_eq!(instance.find_next_fragmentation_spike(3, 1), None);
+        assert_eq!(instance.find_prev_fragmentation_spike(3, 1), Some(1));
+        assert_eq!(instance.find_prev_fragmentation_spike(1, 1), None);
+        assert_eq!(instance.find_next_fragmentation_spike(0, 2), None);
+    }
+
+    #[test]
+    fn query_block_clamps_timestamp_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        assert_eq!(instance.query_block(1024, usize::MAX).len(), 1);
+        assert_eq!(instance.query_block(1024, usize::MAX), instance.query_block(1024, 1));
+        assert_eq!(instance.query_block_realtime(1024, usize::MAX), instance.query_block(1024, 1));
+    }
+
+    #[test]
+    fn query_block_realtime_matches_operation_query_test() {
+        let callstack = Arc::new("test".to_string());
+        // Sampled every 50ms, so these fall in samples 20, 22, 24 and 26
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Free::new(0, 64, callstack.clone(), 1, "0001.100 s".to_string()).wrap_in_enum(),
+            Allocation::new(32, 64, callstack.clone(), 2, "0001.200 s".to_string()).wrap_in_enum(),
+            Allocation::new(512, 64, callstack, 3, "0001.300 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        for realtime_timestamp in 20..=30 {
+            let operation_index = instance.realtime_to_operation_index(realtime_timestamp);
+            for address in [0, 32, 512] {
+                assert_eq!(
+                    instance.query_block_realtime(address, realtime_timestamp as usize),
+                    instance.query_block(address, operation_index)
+                );
+            }
+        }
+        assert_eq!(instance.query_block_realtime(32, 22).len(), 2);
+        assert_eq!(instance.query_block_realtime(32, 24).len(), 3);
+    }
+
+    #[test]
+    fn export_operation_log_json_underflow_test() {
+        let updates = build_test_updates();
+        let instance = initialise_test_instance(updates.clone());
+        let path = std::env::temp_dir().join("damselfly_instance_export_operation_log_json_underflow_test.ndjson");
+        let path = path.to_str().unwrap();
+        instance.export_operation_log_json(path, 8, 8).unwrap();
+        let contents = std::fs::read_to_string(path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        // The allocation at 0 cannot have been padded, so it is left as is
+        assert_eq!(lines, vec![
+            OperationLogExporter::to_json_line(&updates[0]),
+            OperationLogExporter::to_json_line(&updates[1].with_padding_trimmed(8, 8)),
+        ]);
+    }
+
+    #[test]
+    fn query_range_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        assert_eq!(instance.query_range(0, 2048, 1).len(), 2);
+        assert_eq!(instance.query_range(0, 2048, 0).len(), 1);
+        // Touching either allocation by a byte is enough
+        assert_eq!(instance.query_range(63, 1025, 1).len(), 2);
+        assert!(instance.query_range(64, 1024, 1).is_empty());
+        assert!(instance.query_range(1024, 1024, 1).is_empty());
+        assert_eq!(instance.query_range(1024, 1056, 1), instance.query_block(1024, 1));
+    }
+
+    #[test]
+    fn realtime_operation_index_mapping_test() {
+        let callstack = Arc::new("test".to_string());
+        // Sampled every 50ms, so these fall in samples 20, 22 and 24
+        let updates: Vec<MemoryUpdateType> = vec![
+            Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Allocation::new(64, 64, callstack.clone(), 1, "0001.100 s".to_string()).wrap_in_enum(),
+            Allocation::new(128, 64, callstack, 2, "0001.200 s".to_string()).wrap_in_enum(),
+        ];
+        let instance = initialise_test_instance(updates);
+        assert_eq!(instance.realtime_to_operation_index(20), 0);
+        // Empty samples keep the operations of the sample before them
+        assert_eq!(instance.realtime_to_operation_index(23), 1);
+        assert_eq!(instance.realtime_to_operation_index(100), 2);
+        assert_eq!(instance.operation_index_to_realtime(0), 20);
+        assert_eq!(instance.operation_index_to_realtime(1), 22);
+        assert_eq!(instance.operation_index_to_realtime(100), 24);
+        for realtime_timestamp in [20, 22, 24] {
+            let operation_index = instance.realtime_to_operation_index(realtime_timestamp);
+            assert_eq!(instance.operation_index_to_realtime(operation_index), realtime_timestamp);
+        }
+    }
+
+    #[test]
+    fn summary_stats_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        let summary_stats = instance.get_summary_stats(1);
+        assert_eq!(summary_stats.get_timestamp(), 1);
+        assert_eq!(summary_stats.get_memory_used_absolute(), 128);
+        assert_eq!(summary_stats.get_live_allocations(), 2);
+        assert_eq!(summary_stats.get_free_blocks(), instance.get_free_block_count_at(1));
+        // Clamped to the last timestamp
+        assert_eq!(instance.get_summary_stats(100), summary_stats);
+    }
+
+    #[test]
+    fn free_block_count_at_test() {
+        let instance = initialise_test_instance(build_test_updates());
+        assert_eq!(instance.get_free_block_count_at(0), 1);
+        assert_eq!(instance.get_free_block_count_at(1), 2);
+        assert_eq!(instance.get_free_block_count_at(100), 2);
+    }
+
+    #[test]
+    fn find_overlaps_at_test() {
+        let updates = MemorySysTraceParser::new()
+            .parse_log_directly(OVERLAP_FINDER_TEST_LOG, TEST_BINARY_PATH)
+            .memory_updates;
+        let instance = initialise_test_instance_with_bounds(updates, 0, 1024);
+
+        assert!(instance.find_overlaps_at(4).is_empty());
+        let overlaps = instance.find_overlaps_at(5);
+        assert_eq!(overlaps.len(), 1);
+        let (earlier, later) = &overlaps[0];
+        assert_eq!((earlier.get_start(), earlier.get_end()), (0x16c, 0x180));
+        assert_eq!((later.get_start(), later.get_end()), (0x16c, 0x180));
+        assert!(earlier.get_timestamp() < later.get_timestamp());
+        assert!(instance.find_overlaps_at(6).is_empty());
+    }
+}
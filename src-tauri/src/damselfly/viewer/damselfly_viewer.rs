@@ -4,19 +4,35 @@
 //! DamselflyViewer also exposes methods for querying each DamselflyInstance to generate memory maps,
 //! get graphs etc.
 use std::cmp::min;
-use crate::damselfly::memory::memory_parsers::{MemoryParser};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use crate::damselfly::damselfly_error::DamselflyError;
+use crate::damselfly::memory::memory_access::MemoryAccess;
+use crate::damselfly::memory::memory_parsers::{MemoryParser, MemorySysTraceParser, ParseAnomaly, ParserKind, PoolRestrictedParseResults};
 use crate::damselfly::memory::memory_pool::MemoryPool;
+use crate::damselfly::memory::memory_pool_list::MemoryPoolList;
 use crate::damselfly::memory::memory_update::MemoryUpdateType;
 use crate::damselfly::memory::memory_usage_factory::MemoryUsageFactory;
 use crate::damselfly::memory::memory_usage_stats::MemoryUsageStats;
 use crate::damselfly::viewer::damselfly_instance::DamselflyInstance;
+use crate::damselfly::viewer::damselfly_viewer_builder::DamselflyViewerBuilder;
+use crate::damselfly::viewer::diff::{DiffStatus, PoolDiff};
+use crate::damselfly::viewer::viewer_cache::{CachedInstance, ViewerCache};
 
 pub struct DamselflyViewer {
     pub damselflies: Vec<DamselflyInstance>,
+    // Configuration the viewer was built with, kept for following the log
+    config: DamselflyViewerBuilder,
+    // Pool of each DamselflyInstance, with padding applied
+    pools: Vec<MemoryPool>,
+    max_timestamp: u64,
+    // Parser following the log, created on the first call to append_log_bytes
+    log_tail: Option<MemorySysTraceParser>,
 }
 
 impl DamselflyViewer {
-    /// Constructor.
+    /// Constructor. Prefer DamselflyViewerBuilder, which names each setting and returns an error
+    /// instead of panicking on invalid configuration.
     ///
     /// # Arguments
     ///
@@ -25,6 +41,7 @@ impl DamselflyViewer {
     /// * `cache_size`: Interval between cached maps.
     /// * `distinct_block_left_padding`: Padding to the left of each memory update (shifts the address).
     /// * `distinct_block_right_padding`: Padding to the right of each memory update (increases the size.
+    /// * `merge_overlapping_pools`: Whether to merge pools with intersecting address ranges into one instance.
     /// * `parser`: The parser used to parse the log file. You can implement your own if you like.
     ///
     /// returns: DamselflyViewer
@@ -34,14 +51,51 @@ impl DamselflyViewer {
         cache_size: u64,
         distinct_block_left_padding: usize,
         distinct_block_right_padding: usize,
+        merge_overlapping_pools: bool,
         parser: impl MemoryParser
     ) -> Self {
+        DamselflyViewerBuilder::new(log_path)
+            .binary_path(binary_path)
+            .cache_size(cache_size)
+            .left_padding(distinct_block_left_padding)
+            .right_padding(distinct_block_right_padding)
+            .merge_overlapping_pools(merge_overlapping_pools)
+            .build(parser)
+            .unwrap_or_else(|error| panic!("[DamselflyViewer::new]: {error}"))
+    }
+
+    /// Parses the log and creates a DamselflyInstance for each pool. Called by
+    /// DamselflyViewerBuilder::build once the configuration is validated.
+    ///
+    /// # Arguments
+    ///
+    /// * `builder`: Validated configuration.
+    /// * `parser`: The parser used to parse the log file.
+    ///
+    /// returns: Result<DamselflyViewer, DamselflyError>
+    pub(crate) fn from_builder(builder: &DamselflyViewerBuilder, parser: impl MemoryParser) -> Result<Self, DamselflyError> {
+        let (log_path, cache_size) = (builder.get_log_path(), builder.get_cache_size());
+        let binary_paths: Vec<(&str, &str)> = builder.get_binary_paths()
+            .iter()
+            .map(|(pattern, binary_path)| (pattern.as_str(), binary_path.as_str()))
+            .collect();
+        let (distinct_block_left_padding, distinct_block_right_padding) = (builder.get_left_padding(), builder.get_right_padding());
         let mut damselfly_viewer = DamselflyViewer {
             damselflies: Vec::new(),
+            config: builder.clone(),
+            pools: Vec::new(),
+            max_timestamp: 0,
+            log_tail: None,
         };
-        let pool_restricted_parse_results = parser.parse_log_contents_split_by_pools(log_path, binary_path, distinct_block_left_padding, distinct_block_right_padding);
+        let mut pool_restricted_parse_results = parser.parse_log_contents_split_by_pools(log_path, &binary_paths, distinct_block_left_padding, distinct_block_right_padding, builder.get_pool_filter())?;
+        if builder.get_merge_overlapping_pools() {
+            pool_restricted_parse_results = Self::merge_overlapping_pools(pool_restricted_parse_results);
+        }
         for parse_results in &pool_restricted_parse_results {
             let (memory_updates, max_timestamp) = (parse_results.memory_updates.clone(), parse_results.max_timestamp);
+            let memory_accesses = parse_results.memory_accesses
+                .as_ref()
+                .map(|memory_accesses| Self::resample_memory_accesses(&memory_updates, memory_accesses));
             let (pool_start, pool_stop) = (parse_results.pool.get_start(), parse_results.pool.get_start() + parse_results.pool.get_size());
             let mut resampled_memory_updates = Vec::new();
             // This should really be iter_mut, but I don't want to break anything
@@ -64,10 +118,16 @@ impl DamselflyViewer {
                                                              pool_start,
                                                              pool_stop,
                                                             ).calculate_usage_stats();
-            damselfly_viewer.spawn_damselfly(resampled_memory_updates, memory_usage_stats, parse_results.pool.clone(), max_timestamp, cache_size);
+            damselfly_viewer.max_timestamp = damselfly_viewer.max_timestamp.max(max_timestamp);
+            let parse_anomalies = Self::resample_parse_anomalies(&memory_updates, &parse_results.parse_anomalies, distinct_block_left_padding);
+            damselfly_viewer.spawn_damselfly(resampled_memory_updates, memory_usage_stats, parse_results.pool.clone(), max_timestamp, cache_size, memory_accesses);
+            if let Some(damselfly) = damselfly_viewer.damselflies.last_mut() {
+                damselfly.set_parse_anomalies(parse_anomalies);
+                damselfly.set_collapsed_duplicates(parse_results.collapsed_duplicates);
+            }
         }
 
-        damselfly_viewer
+        Ok(damselfly_viewer)
     }
 
     /// Spawns a DamselflyInstance. Each DamselflyInstance manages a single memory pool, encapsulating
@@ -80,19 +140,577 @@ impl DamselflyViewer {
     /// * `pool`: Pool to associate with this instance.
     /// * `max_timestamp`: Max timestamp in this instance.
     /// * `cache_size`: Cache size for this instance.
+    /// * `memory_accesses`: Memory accesses in this instance, if the log has any.
     ///
     /// returns: ()
-    fn spawn_damselfly(&mut self, memory_updates: Vec<MemoryUpdateType>, memory_usage_stats: MemoryUsageStats, pool: MemoryPool, max_timestamp: u64, cache_size: u64) {
-        self.damselflies.push(
-            DamselflyInstance::new(
-                pool.get_name().to_string(),
-                memory_updates,
-                memory_usage_stats,
-                pool.get_start(),
-                pool.get_start() + pool.get_size(),
-                cache_size as usize,
-                max_timestamp,
-            )
+    fn spawn_damselfly(&mut self, memory_updates: Vec<MemoryUpdateType>, memory_usage_stats: MemoryUsageStats, pool: MemoryPool, max_timestamp: u64, cache_size: u64,
+                       memory_accesses: Option<Vec<MemoryAccess>>) {
+        let mut damselfly_instance = DamselflyInstance::new(
+            pool.get_name().to_string(),
+            memory_updates,
+            memory_usage_stats,
+            pool.get_start(),
+            pool.get_start() + pool.get_size(),
+            cache_size as usize,
+            max_timestamp,
         );
+        damselfly_instance.set_memory_accesses(memory_accesses);
+        damselfly_instance.set_right_padding(self.config.get_right_padding());
+        damselfly_instance.set_guard_padding(self.config.get_left_padding(), self.config.get_right_padding());
+        self.damselflies.push(damselfly_instance);
+        self.pools.push(pool);
+    }
+
+    /// Saves the parsed pools to a cache file, so the log doesn't need to be reparsed and
+    /// resymbolicated next time. Map block sizes and colour modes are not saved.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: Path to write the cache to.
+    ///
+    /// returns: Result<(), DamselflyError>
+    pub fn save_to_file(&self, path: &str) -> Result<(), DamselflyError> {
+        let instances = self.damselflies
+            .iter()
+            .zip(&self.pools)
+            .map(|(damselfly, pool)| CachedInstance {
+                pool: pool.clone(),
+                memory_updates: damselfly.get_memory_updates().to_vec(),
+                memory_usage_stats: damselfly.get_memory_usage_stats(),
+                max_timestamp: damselfly.get_max_timestamp(),
+                memory_accesses: damselfly.get_memory_accesses().map(|memory_accesses| memory_accesses.to_vec()),
+                parse_anomalies: damselfly.get_parse_anomalies(),
+                collapsed_duplicates: damselfly.get_collapsed_duplicates(),
+            })
+            .collect();
+        ViewerCache {
+            config: self.config.clone(),
+            max_timestamp: self.max_timestamp,
+            instances,
+        }.write_to_file(path)
+    }
+
+    /// Loads a viewer from a cache file written by save_to_file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: Path to read the cache from.
+    ///
+    /// returns: The viewer, DamselflyError::StaleCache if the cache was written by a different
+    /// schema version, or DamselflyError::InvalidCache if it can't be decoded.
+    pub fn load_from_file(path: &str) -> Result<Self, DamselflyError> {
+        let viewer_cache = ViewerCache::read_from_file(path)?;
+        let cache_size = viewer_cache.config.get_cache_size();
+        let mut damselfly_viewer = DamselflyViewer {
+            damselflies: Vec::new(),
+            config: viewer_cache.config,
+            pools: Vec::new(),
+            max_timestamp: viewer_cache.max_timestamp,
+            log_tail: None,
+        };
+        for cached_instance in viewer_cache.instances {
+            let cache_size = min(cache_size, cached_instance.memory_updates.len() as u64);
+            damselfly_viewer.spawn_damselfly(
+                cached_instance.memory_updates,
+                cached_instance.memory_usage_stats,
+                cached_instance.pool,
+                cached_instance.max_timestamp,
+                cache_size,
+                cached_instance.memory_accesses,
+            );
+            if let Some(damselfly) = damselfly_viewer.damselflies.last_mut() {
+                damselfly.set_parse_anomalies(cached_instance.parse_anomalies);
+                damselfly.set_collapsed_duplicates(cached_instance.collapsed_duplicates);
+            }
+        }
+        Ok(damselfly_viewer)
+    }
+
+    /// Parses bytes appended to the log and appends the resulting updates to the pools they fall
+    /// in, so a log can be followed as it is written. The bytes are parsed with the same kind of
+    /// parser, and settings, the viewer was built with. Only logs read by MemorySysTraceParser can
+    /// be followed. Updates outside every existing pool are dropped, as are pools declared in the
+    /// appended bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_bytes`: Bytes appended to the log since the last call (or since the viewer was built).
+    ///
+    /// returns: Number of new updates parsed, or DamselflyError::InvalidConfiguration if the
+    /// viewer was built with a parser that can't follow a log.
+    pub fn append_log_bytes(&mut self, new_bytes: &str) -> Result<usize, DamselflyError> {
+        let mut log_tail = match self.log_tail.take() {
+            Some(log_tail) => log_tail,
+            None => self.resume_log_tail()?,
+        };
+        let new_updates = log_tail.parse_log_incremental(new_bytes);
+        self.log_tail = Some(log_tail);
+        if new_updates.is_empty() {
+            return Ok(0);
+        }
+
+        self.max_timestamp += new_updates.len() as u64;
+        let (left_padding, right_padding) = (self.config.get_left_padding(), self.config.get_right_padding());
+        for (damselfly, pool) in self.damselflies.iter_mut().zip(&self.pools) {
+            let updates_in_pool: Vec<MemoryUpdateType> = new_updates
+                .iter()
+                .filter(|update| pool.contains(update.get_start(), update.get_end()))
+                .map(|update| {
+                    // Compensate for padding
+                    let mut update = update.clone();
+                    update.set_absolute_address(update.get_absolute_address() - left_padding);
+                    update.set_absolute_size(update.get_absolute_size() + right_padding);
+                    update
+                })
+                .collect();
+            damselfly.append_updates(updates_in_pool, left_padding, right_padding, self.config.get_cache_size(), self.max_timestamp);
+        }
+        Ok(new_updates.len())
+    }
+
+    /// Creates a parser of the kind the viewer was built with, continuing from the updates
+    /// already in the viewer.
+    ///
+    /// returns: MemorySysTraceParser, or DamselflyError::InvalidConfiguration if that kind of
+    /// parser can't follow a log.
+    fn resume_log_tail(&self) -> Result<MemorySysTraceParser, DamselflyError> {
+        let ParserKind::SysTrace { collapse_duplicate_updates, gaddr2line_path, symbolicate } = self.config.get_parser_kind() else {
+            return Err(DamselflyError::InvalidConfiguration(
+                format!("Logs read by a {:?} parser can't be followed", self.config.get_parser_kind())));
+        };
+        let (left_padding, right_padding) = (self.config.get_left_padding(), self.config.get_right_padding());
+        // Undo padding compensation, so frees are sized like the raw log
+        let memory_updates = self.damselflies
+            .iter()
+            .flat_map(|damselfly| damselfly.get_memory_updates().iter().cloned())
+            .map(|mut update| {
+                update.set_absolute_address(update.get_absolute_address() + left_padding);
+                update.set_absolute_size(update.get_absolute_size() - right_padding);
+                update
+            })
+            .collect();
+        Ok(MemorySysTraceParser::new()
+            .with_duplicate_collapsing(*collapse_duplicate_updates)
+            .with_gaddr2line_path(gaddr2line_path.as_deref())
+            .with_symbolication(*symbolicate)
+            .resume_after(self.config.get_binary_path(), memory_updates, self.max_timestamp))
+    }
+
+    /// Resolves the raw-address callstacks of a log loaded with symbolication disabled, upgrading
+    /// every instance's callstacks in place. Pools are resolved against their own binaries, in a
+    /// single batch per binary. Pools without a binary keep their raw addresses. Bytes appended to
+    /// the log afterwards are symbolicated too.
+    ///
+    /// returns: (), or DamselflyError::InvalidConfiguration if the viewer was not built with
+    /// MemorySysTraceParser.
+    pub fn resymbolicate(&mut self) -> Result<(), DamselflyError> {
+        let ParserKind::SysTrace { collapse_duplicate_updates, gaddr2line_path, .. } = self.config.get_parser_kind().clone() else {
+            return Err(DamselflyError::InvalidConfiguration(
+                format!("Logs read by a {:?} parser have no raw callstacks to resymbolicate", self.config.get_parser_kind())));
+        };
+        let parser = MemorySysTraceParser::new().with_gaddr2line_path(gaddr2line_path.as_deref());
+        let binary_paths: Vec<(&str, &str)> = self.config.get_binary_paths()
+            .iter()
+            .map(|(pattern, binary_path)| (pattern.as_str(), binary_path.as_str()))
+            .collect();
+        let mut callstacks_by_binary: HashMap<&str, HashSet<Arc<String>>> = HashMap::new();
+        for damselfly in &self.damselflies {
+            let binary_path = MemorySysTraceParser::select_binary_for_pool(damselfly.get_name(), &binary_paths);
+            callstacks_by_binary.entry(binary_path).or_default().extend(damselfly.get_callstack_set());
+        }
+        let resolved_callstacks_by_binary: HashMap<&str, HashMap<Arc<String>, Arc<String>>> = callstacks_by_binary
+            .into_iter()
+            .map(|(binary_path, callstacks)| (binary_path, parser.resymbolicate_callstacks(&callstacks, binary_path)))
+            .collect();
+        for damselfly in self.damselflies.iter_mut() {
+            let binary_path = MemorySysTraceParser::select_binary_for_pool(damselfly.get_name(), &binary_paths);
+            if let Some(resolved_callstacks) = resolved_callstacks_by_binary.get(binary_path) {
+                damselfly.replace_callstacks(resolved_callstacks);
+            }
+        }
+
+        self.config.set_parser_kind(ParserKind::SysTrace { collapse_duplicate_updates, gaddr2line_path, symbolicate: true });
+        self.log_tail = self.log_tail.take().map(|log_tail| log_tail.with_symbolication(true));
+        Ok(())
+    }
+
+    /// Gets the name and padded bounds of every pool, in instance order, for labelling the map
+    /// with absolute addresses.
+    ///
+    /// returns: Vec<(name, start, stop)>
+    pub fn get_pool_bounds(&self) -> Vec<(String, usize, usize)> {
+        self.damselflies
+            .iter()
+            .map(|damselfly| {
+                let (start, stop) = damselfly.get_memory_bounds();
+                (damselfly.get_name().to_string(), start, stop)
+            })
+            .collect()
+    }
+
+    /// Compares the blocks allocated in two DamselflyInstances at a timestamp. The pools are
+    /// aligned by their start addresses and divided into blocks of instance A's block size.
+    ///
+    /// # Arguments
+    ///
+    /// * `a`: Index of instance A.
+    /// * `b`: Index of instance B.
+    /// * `timestamp`: Timestamp to compare at, in each instance's own operation time.
+    ///
+    /// returns: Vec<(offset from pool start, DiffStatus)>, one per block, or
+    /// DamselflyError::InstanceNotFound if either instance does not exist.
+    pub fn diff_instances(&self, a: usize, b: usize, timestamp: usize) -> Result<Vec<(usize, DiffStatus)>, DamselflyError> {
+        let instance_a = self.damselflies.get(a).ok_or(DamselflyError::InstanceNotFound(a as u64))?;
+        let instance_b = self.damselflies.get(b).ok_or(DamselflyError::InstanceNotFound(b as u64))?;
+        Ok(PoolDiff::diff(
+            &instance_a.get_live_allocations_at(timestamp as u64),
+            instance_a.get_memory_bounds(),
+            &instance_b.get_live_allocations_at(timestamp as u64),
+            instance_b.get_memory_bounds(),
+            instance_a.get_map_block_size(),
+        ))
+    }
+
+    /// Merges parse results of pools whose address ranges intersect, so each region is shown by a
+    /// single DamselflyInstance. Updates are concatenated and re-sorted by timestamp, with updates
+    /// in the intersection (present in both pools) kept once.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool_restricted_parse_results`: Parse results of each pool.
+    ///
+    /// returns: Parse results of each merged pool, in address order.
+    fn merge_overlapping_pools(pool_restricted_parse_results: Vec<PoolRestrictedParseResults>) -> Vec<PoolRestrictedParseResults> {
+        let mut pool_list = MemoryPoolList::default();
+        for parse_results in &pool_restricted_parse_results {
+            pool_list.add_pool(parse_results.pool.clone());
+        }
+        pool_list.merge_overlapping();
+        let mut merged_pools: Vec<MemoryPool> = pool_list.get_pools().iter().cloned().collect();
+        merged_pools.sort();
+
+        let mut merged_parse_results: Vec<PoolRestrictedParseResults> = merged_pools
+            .into_iter()
+            .map(|pool| PoolRestrictedParseResults::new(Vec::new(), 0, pool))
+            .collect();
+        for parse_results in pool_restricted_parse_results {
+            // Merged pools are in address order, so the last one starting at or before this pool
+            // is the one it was merged into
+            let Some(merged) = merged_parse_results.iter_mut().rev().find(|merged|
+                merged.pool.get_start() <= parse_results.pool.get_start()) else {
+                continue;
+            };
+            merged.memory_updates.extend(parse_results.memory_updates);
+            merged.max_timestamp = merged.max_timestamp.max(parse_results.max_timestamp);
+            merged.parse_anomalies.extend(parse_results.parse_anomalies);
+            // counted over the whole log, so every pool has the same count
+            merged.collapsed_duplicates = parse_results.collapsed_duplicates;
+            if let Some(memory_accesses) = parse_results.memory_accesses {
+                merged.memory_accesses.get_or_insert_with(Vec::new).extend(memory_accesses);
+            }
+        }
+
+        for merged in merged_parse_results.iter_mut() {
+            merged.memory_updates.sort_by_key(|update| update.get_timestamp());
+            merged.memory_updates.dedup_by_key(|update| update.get_timestamp());
+            (merged.alloc_count, merged.free_count) = PoolRestrictedParseResults::count_events(&merged.memory_updates);
+            merged.parse_anomalies.sort_by_key(|parse_anomaly| (parse_anomaly.get_timestamp(), parse_anomaly.get_address()));
+            merged.parse_anomalies.dedup();
+            if let Some(memory_accesses) = merged.memory_accesses.as_mut() {
+                memory_accesses.sort_by_key(|access| access.get_timestamp());
+                memory_accesses.dedup();
+            }
+        }
+        merged_parse_results
+    }
+
+    /// Converts the timestamps of parse anomalies from the log's operation time into the pool's
+    /// own operation time, and compensates their addresses for padding like the pool's updates.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates in the pool, still timestamped in the log's operation time.
+    /// * `parse_anomalies`: Anomalies in the pool, timestamped in the log's operation time.
+    /// * `left_padding`: Padding to the left of each memory update.
+    ///
+    /// returns: Vec<ParseAnomaly, Global>
+    fn resample_parse_anomalies(memory_updates: &[MemoryUpdateType], parse_anomalies: &[ParseAnomaly], left_padding: usize) -> Vec<ParseAnomaly> {
+        parse_anomalies
+            .iter()
+            .map(|parse_anomaly| {
+                let timestamp = memory_updates
+                    .partition_point(|update| update.get_timestamp() < parse_anomaly.get_timestamp());
+                ParseAnomaly::new(timestamp, parse_anomaly.get_address() - left_padding, parse_anomaly.get_kind())
+            })
+            .collect()
+    }
+
+    /// Converts the timestamps of accesses from the log's operation time into the pool's own
+    /// operation time, where each update in the pool is timestamped with its index in the pool.
+    /// Accesses that happen before the pool's first update are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_updates`: Updates in the pool, still timestamped in the log's operation time.
+    /// * `memory_accesses`: Accesses in the pool, timestamped in the log's operation time.
+    ///
+    /// returns: Vec<MemoryAccess, Global>
+    fn resample_memory_accesses(memory_updates: &[MemoryUpdateType], memory_accesses: &[MemoryAccess]) -> Vec<MemoryAccess> {
+        memory_accesses
+            .iter()
+            .filter_map(|access| {
+                let updates_before_access = memory_updates
+                    .partition_point(|update| update.get_timestamp() <= access.get_timestamp());
+                let mut resampled_access = access.clone();
+                resampled_access.set_timestamp(updates_before_access.checked_sub(1)?);
+                Some(resampled_access)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::consts::VIEWER_CACHE_SCHEMA_VERSION;
+    use crate::damselfly::damselfly_error::DamselflyError;
+    use crate::damselfly::memory::anomaly::AnomalyKind;
+    use crate::damselfly::memory::memory_parsers::{MemoryParser, MemorySysTraceParser, ParseAnomaly, ParseResults, ParserKind, PoolRestrictedParseResults};
+    use crate::damselfly::memory::memory_pool::MemoryPool;
+    use crate::damselfly::memory::memory_pool_list::MemoryPoolList;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate};
+    use crate::damselfly::viewer::damselfly_viewer::DamselflyViewer;
+    use crate::damselfly::viewer::damselfly_viewer_builder::DamselflyViewerBuilder;
+
+    /// Returns two pools of pre-parsed updates, regardless of the log.
+    struct TestParser;
+
+    impl MemoryParser for TestParser {
+        fn parse_log_directly(self, _log: &str, _binary_path: &str) -> ParseResults {
+            ParseResults::new(Vec::new(), MemoryPoolList::default(), 0)
+        }
+
+        fn parse_log(self, _log_path: &str, _binary_path: &str) -> ParseResults {
+            ParseResults::new(Vec::new(), MemoryPoolList::default(), 0)
+        }
+
+        fn parse_log_contents_split_by_pools(self, _log: &str, _binary_paths: &[(&str, &str)], _left_padding: usize, _right_padding: usize, pool_filter: Option<&[String]>) -> Result<Vec<PoolRestrictedParseResults>, DamselflyError> {
+            let callstack = Arc::new("test".to_string());
+            let mut first_pool = PoolRestrictedParseResults::new(vec![
+                Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+                Allocation::new(64, 32, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum(),
+                Free::new(0, 64, callstack.clone(), 3, "0001.003 s".to_string()).wrap_in_enum(),
+                Free::new(0, 64, callstack.clone(), 4, "0001.004 s".to_string()).wrap_in_enum(),
+            ], 5, MemoryPool::new(0, 1024, "first".to_string()));
+            first_pool.parse_anomalies = vec![ParseAnomaly::new(4, 0, AnomalyKind::DoubleFree)];
+            let second_pool = PoolRestrictedParseResults::new(vec![
+                Allocation::new(2048, 128, callstack, 2, "0001.002 s".to_string()).wrap_in_enum(),
+            ], 5, MemoryPool::new(2048, 1024, "second".to_string()));
+            Ok(PoolRestrictedParseResults::filter_pools(vec![first_pool, second_pool], pool_filter))
+        }
+    }
+
+    #[test]
+    fn save_and_load_test() {
+        let log_path = std::env::temp_dir().join("damselfly_viewer_save_and_load_test.log");
+        std::fs::write(&log_path, "").unwrap();
+        let cache_path = std::env::temp_dir().join("damselfly_viewer_save_and_load_test.cache");
+        let cache_path = cache_path.to_str().unwrap();
+
+        let viewer = DamselflyViewerBuilder::new(log_path.to_str().unwrap())
+            .cache_size(2)
+            .build(TestParser)
+            .unwrap();
+        viewer.save_to_file(cache_path).unwrap();
+        let loaded_viewer = DamselflyViewer::load_from_file(cache_path).unwrap();
+
+        assert_eq!(loaded_viewer.damselflies.len(), 2);
+        assert_eq!(loaded_viewer.max_timestamp, viewer.max_timestamp);
+        for (loaded, original) in loaded_viewer.damselflies.iter().zip(viewer.damselflies.iter()) {
+            assert_eq!(loaded.get_name(), original.get_name());
+            assert_eq!(loaded.get_memory_updates(), original.get_memory_updates());
+            assert_eq!(loaded.get_usage_graph(), original.get_usage_graph());
+            assert_eq!(loaded.get_lifetime_byte_totals(), original.get_lifetime_byte_totals());
+            assert_eq!(loaded.get_memory_bounds(), original.get_memory_bounds());
+            assert_eq!(loaded.get_parse_anomalies(), original.get_parse_anomalies());
+        }
+        assert_eq!(loaded_viewer.damselflies[0].get_parse_anomalies().len(), 1);
+        assert_eq!(loaded_viewer.damselflies[0].get_lifetime_byte_totals(), (96, 128));
+    }
+
+    #[test]
+    fn pool_filter_test() {
+        let log_path = std::env::temp_dir().join("damselfly_viewer_pool_filter_test.log");
+        std::fs::write(&log_path, "").unwrap();
+        let log_path = log_path.to_str().unwrap();
+
+        let viewer = DamselflyViewerBuilder::new(log_path)
+            .cache_size(2)
+            .pool_filter(vec!["second".to_string()])
+            .build(TestParser)
+            .unwrap();
+        assert_eq!(viewer.damselflies.len(), 1);
+        assert_eq!(viewer.damselflies[0].get_name(), "second");
+
+        let viewer = DamselflyViewerBuilder::new(log_path)
+            .cache_size(2)
+            .pool_filter(vec!["missing".to_string()])
+            .build(TestParser)
+            .unwrap();
+        assert!(viewer.damselflies.is_empty());
+    }
+
+    #[test]
+    fn get_pool_bounds_test() {
+        let log_path = std::env::temp_dir().join("damselfly_viewer_get_pool_bounds_test.log");
+        std::fs::write(&log_path, "").unwrap();
+
+        let viewer = DamselflyViewerBuilder::new(log_path.to_str().unwrap())
+            .cache_size(2)
+            .build(TestParser)
+            .unwrap();
+        assert_eq!(viewer.get_pool_bounds(), vec![
+            ("first".to_string(), 0, 1024),
+            ("second".to_string(), 2048, 3072),
+        ]);
+    }
+
+    /// Returns two pools with intersecting address ranges, sharing an update in the intersection.
+    struct OverlappingPoolsParser;
+
+    impl MemoryParser for OverlappingPoolsParser {
+        fn parse_log_directly(self, _log: &str, _binary_path: &str) -> ParseResults {
+            ParseResults::new(Vec::new(), MemoryPoolList::default(), 0)
+        }
+
+        fn parse_log(self, _log_path: &str, _binary_path: &str) -> ParseResults {
+            ParseResults::new(Vec::new(), MemoryPoolList::default(), 0)
+        }
+
+        fn parse_log_contents_split_by_pools(self, _log: &str, _binary_paths: &[(&str, &str)], _left_padding: usize, _right_padding: usize, _pool_filter: Option<&[String]>) -> Result<Vec<PoolRestrictedParseResults>, DamselflyError> {
+            let callstack = Arc::new("test".to_string());
+            let shared_update = Allocation::new(512, 64, callstack.clone(), 1, "0001.001 s".to_string()).wrap_in_enum();
+            let first_pool = PoolRestrictedParseResults::new(vec![
+                Allocation::new(0, 64, callstack.clone(), 0, "0001.000 s".to_string()).wrap_in_enum(),
+                shared_update.clone(),
+            ], 3, MemoryPool::new(0, 1024, "first".to_string()));
+            let second_pool = PoolRestrictedParseResults::new(vec![
+                shared_update,
+                Allocation::new(1024, 128, callstack, 2, "0001.002 s".to_string()).wrap_in_enum(),
+            ], 3, MemoryPool::new(512, 1024, "second".to_string()));
+            Ok(vec![second_pool, first_pool])
+        }
+    }
+
+    #[test]
+    fn merge_overlapping_pools_test() {
+        let log_path = std::env::temp_dir().join("damselfly_viewer_merge_overlapping_pools_test.log");
+        std::fs::write(&log_path, "").unwrap();
+        let log_path = log_path.to_str().unwrap();
+
+        let viewer = DamselflyViewerBuilder::new(log_path)
+            .cache_size(2)
+            .build(OverlappingPoolsParser)
+            .unwrap();
+        assert_eq!(viewer.damselflies.len(), 2);
+
+        let viewer = DamselflyViewerBuilder::new(log_path)
+            .cache_size(2)
+            .merge_overlapping_pools(true)
+            .build(OverlappingPoolsParser)
+            .unwrap();
+        assert_eq!(viewer.damselflies.len(), 1);
+        let damselfly = &viewer.damselflies[0];
+        assert_eq!(damselfly.get_name(), "first+second");
+        assert_eq!(damselfly.get_memory_bounds(), (0, 1536));
+        let addresses: Vec<usize> = damselfly.get_memory_updates().iter().map(|update| update.get_absolute_address()).collect();
+        assert_eq!(addresses, vec![0, 512, 1024]);
+    }
+
+    #[test]
+    fn load_stale_cache_test() {
+        let cache_path = std::env::temp_dir().join("damselfly_viewer_load_stale_cache_test.cache");
+        std::fs::write(&cache_path, bincode::serialize(&(VIEWER_CACHE_SCHEMA_VERSION + 1)).unwrap()).unwrap();
+        assert_eq!(
+            DamselflyViewer::load_from_file(cache_path.to_str().unwrap()).err(),
+            Some(DamselflyError::StaleCache { version: VIEWER_CACHE_SCHEMA_VERSION + 1, expected_version: VIEWER_CACHE_SCHEMA_VERSION })
+        );
+    }
+
+    #[test]
+    fn append_log_bytes_test() {
+        let log_path = std::env::temp_dir().join("damselfly_viewer_append_log_bytes_test.log");
+        let log = "\
+00000161: 03c305a7 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLBOUNDS 1000 256
+00000162: 03c305a7 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLNAME core0_heap
+00000165: 03c305f0 |V|A|005|        0 us   0003.938 s    < DT:  unknown > + 1000 10
+00000166: 03c305f0 |V|A|005|        0 us   0003.938 s    < DT:  unknown > ^ 1000 [e03c2221]
+";
+        let appended_log = "\
+00000167: 03c305f0 |V|A|005|        0 us   0003.939 s    < DT:  unknown > + 1020 8
+00000168: 03c305f0 |V|A|005|        0 us   0003.939 s    < DT:  unknown > ^ 1000 [e03c2221]
+00000169: 03c305f0 |V|A|005|        0 us   0003.940 s    < DT:  unknown > - 1000
+00000170: 03c305f0 |V|A|005|        0 us   0003.940 s    < DT:  unknown > ^ 1000 [e03c2221]
+";
+        std::fs::write(&log_path, log).unwrap();
+        let cache_path = std::env::temp_dir().join("damselfly_viewer_append_log_bytes_test.cache");
+        let cache_path = cache_path.to_str().unwrap();
+
+        let mut viewer = DamselflyViewerBuilder::new(log_path.to_str().unwrap())
+            .cache_size(1)
+            .build(MemorySysTraceParser::new().with_symbolication(false))
+            .unwrap();
+        assert_eq!(
+            viewer.config.get_parser_kind(),
+            &ParserKind::SysTrace { collapse_duplicate_updates: false, gaddr2line_path: None, symbolicate: false }
+        );
+        viewer.save_to_file(cache_path).unwrap();
+        // The free is held back until the next update, as its stacktrace may still be on its way
+        assert_eq!(viewer.append_log_bytes(appended_log), Ok(1));
+        assert_eq!(viewer.damselflies[0].get_memory_updates().len(), 2);
+
+        // The parser kind is kept in the cache, so a loaded viewer can follow the log too
+        let mut loaded_viewer = DamselflyViewer::load_from_file(cache_path).unwrap();
+        assert_eq!(loaded_viewer.append_log_bytes(appended_log), Ok(1));
+
+        let mut viewer = DamselflyViewerBuilder::new(log_path.to_str().unwrap())
+            .cache_size(2)
+            .build(TestParser)
+            .unwrap();
+        assert!(matches!(viewer.append_log_bytes(appended_log), Err(DamselflyError::InvalidConfiguration(_))));
+    }
+
+    #[test]
+    fn collapsed_duplicates_test() {
+        let log_path = std::env::temp_dir().join("damselfly_viewer_collapsed_duplicates_test.log");
+        let log = "\
+00000161: 03c305a7 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLBOUNDS 1000 256
+00000162: 03c305a7 |V|A|005|        0 us   0003.937 s    < DT:  unknown > POOLNAME core0_heap
+00000165: 03c305f0 |V|A|005|        0 us   0003.938 s    < DT:  unknown > + 1000 10
+00000166: 03c305f0 |V|A|005|        0 us   0003.938 s    < DT:  unknown > ^ 1000 [e03c2221]
+00000167: 03c305f0 |V|A|005|        0 us   0003.939 s    < DT:  unknown > - 1000
+00000168: 03c305f0 |V|A|005|        0 us   0003.939 s    < DT:  unknown > ^ 1000 [e03c2221]
+00000169: 03c305f0 |V|A|005|        0 us   0003.939 s    < DT:  unknown > - 1000
+00000170: 03c305f0 |V|A|005|        0 us   0003.939 s    < DT:  unknown > ^ 1000 [e03c2221]
+";
+        std::fs::write(&log_path, log).unwrap();
+        let cache_path = std::env::temp_dir().join("damselfly_viewer_collapsed_duplicates_test.cache");
+        let cache_path = cache_path.to_str().unwrap();
+
+        let viewer = DamselflyViewerBuilder::new(log_path.to_str().unwrap())
+            .cache_size(1)
+            .build(MemorySysTraceParser::new().with_symbolication(false).with_duplicate_collapsing(true))
+            .unwrap();
+        assert_eq!(viewer.damselflies[0].get_collapsed_duplicates(), 1);
+        assert_eq!(viewer.damselflies[0].get_memory_updates().len(), 2);
+        viewer.save_to_file(cache_path).unwrap();
+        assert_eq!(DamselflyViewer::load_from_file(cache_path).unwrap().damselflies[0].get_collapsed_duplicates(), 1);
+
+        let viewer = DamselflyViewerBuilder::new(log_path.to_str().unwrap())
+            .cache_size(1)
+            .build(MemorySysTraceParser::new().with_symbolication(false))
+            .unwrap();
+        assert_eq!(viewer.damselflies[0].get_collapsed_duplicates(), 0);
+        assert_eq!(viewer.damselflies[0].get_memory_updates().len(), 3);
     }
 }
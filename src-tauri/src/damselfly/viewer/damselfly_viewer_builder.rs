@@ -0,0 +1,196 @@
+//! Builder for DamselflyViewer, so configuration is set by name rather than by position and
+//! validated before the (slow) parse begins.
+use std::fs::File;
+use serde::{Deserialize, Serialize};
+use crate::damselfly::consts::DEFAULT_CACHE_INTERVAL;
+use crate::damselfly::damselfly_error::DamselflyError;
+use crate::damselfly::memory::memory_parsers::{MemoryParser, ParserKind};
+use crate::damselfly::viewer::damselfly_viewer::DamselflyViewer;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DamselflyViewerBuilder {
+    log_path: String,
+    // (pool name pattern, binary path)
+    binary_paths: Vec<(String, String)>,
+    cache_size: u64,
+    left_padding: usize,
+    right_padding: usize,
+    merge_overlapping_pools: bool,
+    // Names of the pools to build instances for, or None for every pool
+    pool_filter: Option<Vec<String>>,
+    // Kind of parser passed to build, so a followed log is parsed the same way
+    parser_kind: ParserKind,
+}
+
+impl DamselflyViewerBuilder {
+    /// Constructor. Defaults to no binary, a cache interval of DEFAULT_CACHE_INTERVAL and no
+    /// padding.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_path`: Path to log file.
+    ///
+    /// returns: DamselflyViewerBuilder
+    pub fn new(log_path: &str) -> Self {
+        Self {
+            log_path: log_path.to_string(),
+            binary_paths: Vec::new(),
+            cache_size: DEFAULT_CACHE_INTERVAL,
+            left_padding: 0,
+            right_padding: 0,
+            merge_overlapping_pools: false,
+            pool_filter: None,
+            parser_kind: ParserKind::default(),
+        }
+    }
+
+    /// Sets the path to the threadxApp binary for debuginfo. If left empty, the parser may use
+    /// the binary named in the log's header.
+    pub fn binary_path(mut self, binary_path: &str) -> Self {
+        self.binary_paths = vec![(String::new(), binary_path.to_string())];
+        self
+    }
+
+    /// Sets a binary per pool, for firmware that links a separate binary per core. Each pool
+    /// uses the binary of the first pattern its name contains, or the first binary if none match.
+    ///
+    /// # Arguments
+    ///
+    /// * `binary_paths`: (pool name pattern, binary path) pairs.
+    ///
+    /// returns: DamselflyViewerBuilder
+    pub fn binary_paths(mut self, binary_paths: Vec<(String, String)>) -> Self {
+        self.binary_paths = binary_paths;
+        self
+    }
+
+    /// Sets the interval between cached maps. Must be non-zero.
+    pub fn cache_size(mut self, cache_size: u64) -> Self {
+        self.cache_size = cache_size;
+        self
+    }
+
+    /// Sets the padding to the left of each memory update (shifts the address).
+    pub fn left_padding(mut self, left_padding: usize) -> Self {
+        self.left_padding = left_padding;
+        self
+    }
+
+    /// Sets the padding to the right of each memory update (increases the size).
+    pub fn right_padding(mut self, right_padding: usize) -> Self {
+        self.right_padding = right_padding;
+        self
+    }
+
+    /// Sets whether pools with intersecting address ranges are merged into a single instance.
+    /// Off by default.
+    pub fn merge_overlapping_pools(mut self, merge_overlapping_pools: bool) -> Self {
+        self.merge_overlapping_pools = merge_overlapping_pools;
+        self
+    }
+
+    /// Only builds instances for pools with these names, skipping the slow usage stats calculation
+    /// for the rest. If no pool matches, the viewer has no instances.
+    pub fn pool_filter(mut self, pool_filter: Vec<String>) -> Self {
+        self.pool_filter = Some(pool_filter);
+        self
+    }
+
+    pub fn get_log_path(&self) -> &str {
+        &self.log_path
+    }
+
+    /// Gets the first binary path, or an empty string if none is set.
+    pub fn get_binary_path(&self) -> &str {
+        self.binary_paths.first().map_or("", |(_, binary_path)| binary_path.as_str())
+    }
+
+    pub fn get_binary_paths(&self) -> &[(String, String)] {
+        &self.binary_paths
+    }
+
+    pub fn get_cache_size(&self) -> u64 {
+        self.cache_size
+    }
+
+    pub fn get_left_padding(&self) -> usize {
+        self.left_padding
+    }
+
+    pub fn get_right_padding(&self) -> usize {
+        self.right_padding
+    }
+
+    pub fn get_merge_overlapping_pools(&self) -> bool {
+        self.merge_overlapping_pools
+    }
+
+    pub fn get_pool_filter(&self) -> Option<&[String]> {
+        self.pool_filter.as_deref()
+    }
+
+    /// Gets the kind of parser the viewer was built with. ParserKind::Custom until build is called.
+    pub fn get_parser_kind(&self) -> &ParserKind {
+        &self.parser_kind
+    }
+
+    pub(crate) fn set_parser_kind(&mut self, parser_kind: ParserKind) {
+        self.parser_kind = parser_kind;
+    }
+
+    /// Checks that the cache size is non-zero, and that the log and binaries (if set) can be read.
+    ///
+    /// returns: Result<(), DamselflyError>
+    pub fn validate(&self) -> Result<(), DamselflyError> {
+        if self.cache_size == 0 {
+            return Err(DamselflyError::InvalidConfiguration("Cache size must be non-zero".to_string()));
+        }
+        File::open(&self.log_path).map_err(|error|
+            DamselflyError::InvalidConfiguration(format!("Unable to read log {}: {error}", self.log_path)))?;
+        for (_, binary_path) in self.binary_paths.iter().filter(|(_, binary_path)| !binary_path.is_empty()) {
+            File::open(binary_path).map_err(|error|
+                DamselflyError::InvalidConfiguration(format!("Unable to read binary {binary_path}: {error}")))?;
+        }
+        Ok(())
+    }
+
+    /// Validates the configuration, then parses the log and builds the viewer.
+    ///
+    /// # Arguments
+    ///
+    /// * `parser`: The parser used to parse the log file.
+    ///
+    /// returns: Result<DamselflyViewer, DamselflyError>
+    pub fn build(mut self, parser: impl MemoryParser) -> Result<DamselflyViewer, DamselflyError> {
+        self.validate()?;
+        self.parser_kind = parser.get_kind();
+        DamselflyViewer::from_builder(&self, parser)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::damselfly::damselfly_error::DamselflyError;
+    use crate::damselfly::viewer::damselfly_viewer_builder::DamselflyViewerBuilder;
+
+    #[test]
+    fn validate_test() {
+        let log_path = std::env::temp_dir().join("damselfly_viewer_builder_validate_test.log");
+        std::fs::write(&log_path, "").unwrap();
+        let log_path = log_path.to_str().unwrap();
+
+        assert!(DamselflyViewerBuilder::new(log_path).validate().is_ok());
+        assert!(matches!(
+            DamselflyViewerBuilder::new(log_path).cache_size(0).validate(),
+            Err(DamselflyError::InvalidConfiguration(_))
+        ));
+        assert!(matches!(
+            DamselflyViewerBuilder::new(log_path).binary_path("/nonexistent/threadxApp").validate(),
+            Err(DamselflyError::InvalidConfiguration(_))
+        ));
+        assert!(matches!(
+            DamselflyViewerBuilder::new("/nonexistent/trace.log").validate(),
+            Err(DamselflyError::InvalidConfiguration(_))
+        ));
+    }
+}
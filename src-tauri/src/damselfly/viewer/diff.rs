@@ -0,0 +1,102 @@
+//! Compares the blocks allocated in two pools, aligned by their offset from each pool's start.
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+/// Which of the two compared pools a block is allocated in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiffStatus {
+    AOnly,
+    BOnly,
+    Both,
+    Neither,
+}
+
+impl DiffStatus {
+    fn from_allocated(allocated_in_a: bool, allocated_in_b: bool) -> DiffStatus {
+        match (allocated_in_a, allocated_in_b) {
+            (true, true) => DiffStatus::Both,
+            (true, false) => DiffStatus::AOnly,
+            (false, true) => DiffStatus::BOnly,
+            (false, false) => DiffStatus::Neither,
+        }
+    }
+}
+
+pub struct PoolDiff;
+
+impl PoolDiff {
+    /// Diffs the live allocations of two pools block by block. Blocks past the end of the shorter
+    /// pool are never allocated in it.
+    ///
+    /// # Arguments
+    ///
+    /// * `a_allocations`: Live allocations in pool A, address -> size.
+    /// * `a_bounds`: (start, stop) of pool A.
+    /// * `b_allocations`: Live allocations in pool B, address -> size.
+    /// * `b_bounds`: (start, stop) of pool B.
+    /// * `block_size`: Bytes spanned by each block.
+    ///
+    /// returns: Vec<(offset from pool start, DiffStatus)>, one per block.
+    pub fn diff(
+        a_allocations: &BTreeMap<usize, usize>,
+        a_bounds: (usize, usize),
+        b_allocations: &BTreeMap<usize, usize>,
+        b_bounds: (usize, usize),
+        block_size: usize,
+    ) -> Vec<(usize, DiffStatus)> {
+        let span = (a_bounds.1 - a_bounds.0).max(b_bounds.1 - b_bounds.0);
+        let a_blocks = Self::get_allocated_blocks(a_allocations, a_bounds, block_size, span);
+        let b_blocks = Self::get_allocated_blocks(b_allocations, b_bounds, block_size, span);
+        a_blocks
+            .iter()
+            .zip(b_blocks.iter())
+            .enumerate()
+            .map(|(block_index, (allocated_in_a, allocated_in_b))|
+                (block_index * block_size, DiffStatus::from_allocated(*allocated_in_a, *allocated_in_b)))
+            .collect()
+    }
+
+    /// Marks the blocks that overlap a live allocation.
+    ///
+    /// # Arguments
+    ///
+    /// * `allocations`: Live allocations, address -> size.
+    /// * `bounds`: (start, stop) of the pool.
+    /// * `block_size`: Bytes spanned by each block.
+    /// * `span`: Bytes to cover, which may extend past the end of the pool.
+    ///
+    /// returns: Vec<bool> with one entry per block, true if the block is allocated.
+    fn get_allocated_blocks(allocations: &BTreeMap<usize, usize>, bounds: (usize, usize), block_size: usize, span: usize) -> Vec<bool> {
+        let mut blocks = vec![false; span.div_ceil(block_size)];
+        for (&address, &size) in allocations.range(bounds.0..bounds.1) {
+            if size == 0 {
+                continue;
+            }
+            let first_block = (address - bounds.0) / block_size;
+            let last_block = (address + size - bounds.0).div_ceil(block_size).min(blocks.len());
+            for block in blocks.iter_mut().take(last_block).skip(first_block) {
+                *block = true;
+            }
+        }
+        blocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use crate::damselfly::viewer::diff::{DiffStatus, PoolDiff};
+
+    #[test]
+    fn diff_test() {
+        let a_allocations = BTreeMap::from([(1000, 64)]);
+        let b_allocations = BTreeMap::from([(5032, 40)]);
+        let diff = PoolDiff::diff(&a_allocations, (1000, 1128), &b_allocations, (5000, 5096), 32);
+        assert_eq!(diff, vec![
+            (0, DiffStatus::AOnly),
+            (32, DiffStatus::Both),
+            (64, DiffStatus::BOnly),
+            (96, DiffStatus::Neither),
+        ]);
+    }
+}
@@ -0,0 +1,118 @@
+//! Exports graphs to files that can be shared outside of the app.
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use crate::damselfly::damselfly_error::DamselflyError;
+
+pub struct GraphExporter;
+
+impl GraphExporter {
+    /// Draws graph points as a polyline in a self-contained SVG document, with the peak marked
+    /// by a circle.
+    ///
+    /// # Arguments
+    ///
+    /// * `points`: Graph points, e.g. from DamselflyInstance::get_usage_graph.
+    /// * `width`: Width of the SVG in pixels.
+    /// * `height`: Height of the SVG in pixels.
+    ///
+    /// returns: SVG document as a String.
+    pub fn to_svg_sparkline(points: &[[f64; 2]], width: u32, height: u32) -> String {
+        let width = width as f64;
+        let height = height as f64;
+        let min_x = points.iter().map(|point| point[0]).fold(f64::INFINITY, f64::min);
+        let max_x = points.iter().map(|point| point[0]).fold(f64::NEG_INFINITY, f64::max);
+        let max_y = points.iter().map(|point| point[1]).fold(0.0, f64::max);
+        let x_span = if max_x > min_x { max_x - min_x } else { 1.0 };
+        let y_span = if max_y > 0.0 { max_y } else { 1.0 };
+        let scale = |point: &[f64; 2]| -> (f64, f64) {
+            ((point[0] - min_x) * width / x_span, height - (point[1] * height / y_span))
+        };
+
+        let mut polyline_points = String::new();
+        for point in points {
+            let (x, y) = scale(point);
+            let _ = write!(polyline_points, "{x:.2},{y:.2} ");
+        }
+
+        let mut svg = String::new();
+        let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#);
+        let _ = writeln!(svg, r#"<polyline fill="none" stroke="steelblue" stroke-width="1" points="{}"/>"#, polyline_points.trim_end());
+        let peak = points.iter().max_by(|prev, next| prev[1].total_cmp(&next[1]));
+        if let Some(peak) = peak {
+            let (x, y) = scale(peak);
+            let _ = writeln!(svg, r#"<circle cx="{x:.2}" cy="{y:.2}" r="2" fill="red"><title>peak: {} at {}</title></circle>"#, peak[1], peak[0]);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Writes graph points to an SVG sparkline file.
+    ///
+    /// # Arguments
+    ///
+    /// * `points`: Graph points.
+    /// * `path`: File path to write to.
+    /// * `width`: Width of the SVG in pixels.
+    /// * `height`: Height of the SVG in pixels.
+    ///
+    /// returns: Result<(), DamselflyError>
+    pub fn export_svg_sparkline(points: &[[f64; 2]], path: &str, width: u32, height: u32) -> Result<(), DamselflyError> {
+        std::fs::write(path, Self::to_svg_sparkline(points, width, height))?;
+        Ok(())
+    }
+
+    /// Formats graph points as two-column CSV with a header row.
+    ///
+    /// # Arguments
+    ///
+    /// * `points`: Graph points, e.g. from DamselflyInstance::get_usage_graph.
+    ///
+    /// returns: CSV as a String.
+    pub fn to_csv(points: &[[f64; 2]]) -> String {
+        let mut csv = String::from("timestamp,bytes\n");
+        for point in points {
+            let _ = writeln!(csv, "{},{}", point[0], point[1]);
+        }
+        csv
+    }
+
+    /// Writes graph points to a CSV file. The file is flushed and synced to disk before
+    /// returning, so a completed export is never left partially written.
+    ///
+    /// # Arguments
+    ///
+    /// * `points`: Graph points.
+    /// * `path`: File path to write to.
+    ///
+    /// returns: Result<(), DamselflyError>
+    pub fn export_csv(points: &[[f64; 2]], path: &str) -> Result<(), DamselflyError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(Self::to_csv(points).as_bytes())?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::damselfly::viewer::graph_exporter::GraphExporter;
+
+    #[test]
+    fn svg_sparkline_test() {
+        let points = [[0.0, 0.0], [1.0, 100.0], [2.0, 50.0]];
+        let svg = GraphExporter::to_svg_sparkline(&points, 100, 20);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r#"points="0.00,20.00 50.00,0.00 100.00,10.00""#));
+        assert!(svg.contains(r#"<circle cx="50.00" cy="0.00""#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn csv_test() {
+        let points = [[0.0, 0.0], [1.0, 100.0], [2.5, 50.0]];
+        assert_eq!(GraphExporter::to_csv(&points), "timestamp,bytes\n0,0\n1,100\n2.5,50\n");
+        assert_eq!(GraphExporter::to_csv(&[]), "timestamp,bytes\n");
+    }
+}
@@ -0,0 +1,23 @@
+//! Metrics that can be graphed against either x-axis.
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum GraphMetric {
+    /// Bytes in use.
+    #[default]
+    Usage,
+    /// Number of distinct allocated blocks.
+    DistinctBlocks,
+    /// Free space outside the largest free block. Has no filler values on the operation axis.
+    FreeSegmentFragmentation,
+    /// Size of the largest free block.
+    LargestFreeBlock,
+    /// Number of free blocks.
+    FreeBlocks,
+    /// Number of live allocations.
+    LiveAllocationCount,
+    /// Number of free blocks with a live allocation on both sides.
+    FreeGapCount,
+    /// Mean live allocation size.
+    AverageAllocationSize,
+}
@@ -0,0 +1,13 @@
+//! Variants of a graph that can be requested, e.g. for exporting.
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum GraphMode {
+    /// With filler values, so that all pools have the same number of points.
+    #[default]
+    Default,
+    /// Without filler values, so different pools may have different numbers of points.
+    NoFallbacks,
+    /// Sampled in realtime.
+    RealtimeSampled,
+}
@@ -2,7 +2,11 @@
 //! 
 //! Most of these methods are called in DamselflyInstance. Consult its documentation to see how each one 
 //! might be used.
+use std::cmp::{min, Ordering};
+use owo_colors::OwoColorize;
+use crate::damselfly::consts::MAX_CHURN_WINDOWS;
 use crate::damselfly::memory::memory_usage::MemoryUsage;
+use crate::damselfly::memory::memory_usage_stats::MemoryUsageStats;
 use crate::damselfly::memory::sampled_memory_usages::SampledMemoryUsages;
 
 
@@ -12,16 +16,19 @@ pub struct GraphViewer {
     current_highlight: Option<usize>,
     saved_highlight: usize,
     max_usage: i128,
+    peak_usage_timestamp: u64,
     max_free_blocks: u128,
     max_distinct_blocks: usize,
     max_free_segment_fragmentation: u128,
     max_largest_free_block: u128,
-    max_timestamp: u64
+    max_timestamp: u64,
+    // (total bytes allocated, total bytes freed)
+    lifetime_byte_totals: (u128, u128),
 }
 
 impl GraphViewer {
     pub fn new(memory_usage_snapshots: Vec<MemoryUsage>, sampled_memory_usage_snapshots: SampledMemoryUsages, 
-               max_usage: i128, max_free_blocks: u128, max_distinct_blocks: usize,
+               max_usage: i128, peak_usage_timestamp: u64, max_free_blocks: u128, max_distinct_blocks: usize,
                max_free_segment_fragmentation: u128,
                max_largest_free_block: u128, max_timestamp: u64) 
         -> GraphViewer {
@@ -31,11 +38,13 @@ impl GraphViewer {
             current_highlight: None,
             saved_highlight: 0,
             max_usage,
+            peak_usage_timestamp,
             max_free_blocks,
             max_distinct_blocks,
             max_free_segment_fragmentation,
             max_largest_free_block,
             max_timestamp,
+            lifetime_byte_totals: (0, 0),
         }
     }
 
@@ -115,6 +124,25 @@ impl GraphViewer {
         vector
     }
 
+    pub fn get_free_segment_fragmentation_percentage_plot_points_no_fallbacks(&self) -> Vec<[f64; 2]> {
+        let mut vector = Vec::new();
+        for (index, usage) in self.memory_usage_snapshots.iter().enumerate() {
+            vector.push([index as f64, usage.get_free_segment_fragmentation_percentage()]);
+        }
+
+        vector
+    }
+
+    /// Gets memory usage as a percentage of the pool size, rather than of the peak usage.
+    pub fn get_usage_percentage_plot_points_no_fallbacks(&self) -> Vec<[f64; 2]> {
+        let mut vector = Vec::new();
+        for (index, usage) in self.memory_usage_snapshots.iter().enumerate() {
+            vector.push([index as f64, usage.get_memory_used_percentage()]);
+        }
+
+        vector
+    }
+
     pub fn get_free_segment_fragmentation_plot_points_realtime_sampled(&self) -> Vec<[f64; 2]> {
         let mut vector = Vec::new();
         for (index, snapshot) in self.sampled_memory_usage_snapshots.get_samples().iter().enumerate() {
@@ -206,14 +234,419 @@ impl GraphViewer {
         vector
     }
     
-    pub fn get_operation_timestamp_of_realtime_timestamp(&self, realtime_timestamp: u64) -> u64 {
-        self.sampled_memory_usage_snapshots.get_operation_timestamps_in_realtime_timestamp(realtime_timestamp).1
+    /// Gets the number of live allocations after each timestamp. Unlike most plots, this is a count
+    /// rather than a percentage of its maximum.
+    ///
+    /// returns: Vec<[timestamp, live allocations]>, with a point for every timestamp up to the
+    /// max timestamp. Timestamps without a usage repeat the previous count.
+    pub fn get_live_allocations_plot_points(&self) -> Vec<[f64; 2]> {
+        let mut vector = Vec::new();
+        let mut fallback_value = 0.0;
+
+        for timestamp in 0..=self.max_timestamp {
+            match self.memory_usage_snapshots.get(timestamp as usize) {
+                None => vector.push([timestamp as f64, fallback_value]),
+                Some(snapshot) => {
+                    fallback_value = snapshot.get_live_allocations() as f64;
+                    vector.push([timestamp as f64, fallback_value]);
+                }
+            }
+        }
+
+        vector
+    }
+
+    pub fn get_live_allocations_plot_points_no_fallbacks(&self) -> Vec<[f64; 2]> {
+        let mut vector = Vec::new();
+
+        for (index, usage) in self.memory_usage_snapshots.iter().enumerate() {
+            vector.push([index as f64, usage.get_live_allocations() as f64]);
+        }
+
+        vector
+    }
+
+    pub fn get_live_allocations_plot_points_realtime_sampled(&self) -> Vec<[f64; 2]> {
+        let mut vector = Vec::new();
+        for (index, snapshot) in self.sampled_memory_usage_snapshots.get_samples().iter().enumerate() {
+            vector.push([index as f64, snapshot.get_sampled_usage().get_live_allocations() as f64]);
+        }
+        vector
+    }
+
+    /// Gets the number of free gaps (free blocks with a live allocation on both sides) after each
+    /// timestamp. Free space at either end of the pool is not a gap. Like live allocations, this is
+    /// a count rather than a percentage of its maximum.
+    ///
+    /// returns: Vec<[timestamp, free gaps]>, with a point for every timestamp up to the max
+    /// timestamp. Timestamps without a usage repeat the previous count.
+    pub fn get_free_gap_count_plot_points(&self) -> Vec<[f64; 2]> {
+        let mut vector = Vec::new();
+        let mut fallback_value = 0.0;
+
+        for timestamp in 0..=self.max_timestamp {
+            match self.memory_usage_snapshots.get(timestamp as usize) {
+                None => vector.push([timestamp as f64, fallback_value]),
+                Some(snapshot) => {
+                    fallback_value = snapshot.get_free_gaps() as f64;
+                    vector.push([timestamp as f64, fallback_value]);
+                }
+            }
+        }
+
+        vector
+    }
+
+    pub fn get_free_gap_count_plot_points_no_fallbacks(&self) -> Vec<[f64; 2]> {
+        let mut vector = Vec::new();
+
+        for (index, usage) in self.memory_usage_snapshots.iter().enumerate() {
+            vector.push([index as f64, usage.get_free_gaps() as f64]);
+        }
+
+        vector
+    }
+
+    pub fn get_free_gap_count_plot_points_realtime_sampled(&self) -> Vec<[f64; 2]> {
+        let mut vector = Vec::new();
+        for (index, snapshot) in self.sampled_memory_usage_snapshots.get_samples().iter().enumerate() {
+            vector.push([index as f64, snapshot.get_sampled_usage().get_free_gaps() as f64]);
+        }
+        vector
+    }
+
+    /// Gets the mean size of the live allocated blocks after each timestamp, in bytes. Unlike
+    /// get_average_allocation_size_plot_points, adjacent allocations count as one block.
+    ///
+    /// returns: Vec<[timestamp, mean allocated block size]>, with a point for every timestamp up
+    /// to the max timestamp. Timestamps without a usage repeat the previous size.
+    pub fn get_mean_allocated_block_size_plot_points(&self) -> Vec<[f64; 2]> {
+        let mut vector = Vec::new();
+        let mut fallback_value = 0.0;
+
+        for timestamp in 0..=self.max_timestamp {
+            match self.memory_usage_snapshots.get(timestamp as usize) {
+                None => vector.push([timestamp as f64, fallback_value]),
+                Some(snapshot) => {
+                    fallback_value = snapshot.get_mean_allocated_block_size();
+                    vector.push([timestamp as f64, fallback_value]);
+                }
+            }
+        }
+
+        vector
+    }
+
+    /// Gets the mean size of the live allocations after each timestamp, in bytes.
+    ///
+    /// returns: Vec<[timestamp, average allocation size]>, with a point for every timestamp up to
+    /// the max timestamp. Timestamps without a usage repeat the previous size.
+    pub fn get_average_allocation_size_plot_points(&self) -> Vec<[f64; 2]> {
+        let mut vector = Vec::new();
+        let mut fallback_value = 0.0;
+
+        for timestamp in 0..=self.max_timestamp {
+            match self.memory_usage_snapshots.get(timestamp as usize) {
+                None => vector.push([timestamp as f64, fallback_value]),
+                Some(snapshot) => {
+                    fallback_value = snapshot.get_average_allocation_size();
+                    vector.push([timestamp as f64, fallback_value]);
+                }
+            }
+        }
+
+        vector
     }
 
-    fn get_max_usage(&self) -> i128 {
+    pub fn get_average_allocation_size_plot_points_realtime_sampled(&self) -> Vec<[f64; 2]> {
+        let mut vector = Vec::new();
+        for (index, snapshot) in self.sampled_memory_usage_snapshots.get_samples().iter().enumerate() {
+            vector.push([index as f64, snapshot.get_sampled_usage().get_average_allocation_size()]);
+        }
+        vector
+    }
+
+    /// Gets the memory usage stats at an operation timestamp, clamped to the last timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Operation timestamp.
+    ///
+    /// returns: The MemoryUsage at that timestamp, or None if there are no usages.
+    pub fn get_memory_usage_at(&self, timestamp: u64) -> Option<&MemoryUsage> {
+        let index = min(timestamp as usize, self.memory_usage_snapshots.len().checked_sub(1)?);
+        self.memory_usage_snapshots.get(index)
+    }
+
+    /// Finds the first operation timestamp where total memory usage reaches a threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold`: Usage threshold in bytes.
+    ///
+    /// returns: The first timestamp where usage >= threshold, or None if it is never reached.
+    pub fn get_first_crossing(&self, threshold: i128) -> Option<u64> {
+        self.memory_usage_snapshots
+            .iter()
+            .find(|memory_usage| memory_usage.get_memory_used_absolute() >= threshold)
+            .map(|memory_usage| memory_usage.get_timestamp())
+    }
+
+    /// Finds the first operation after a timestamp where the distinct block count jumps by at
+    /// least a threshold since the previous operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_timestamp`: Timestamp to search forward from, exclusive.
+    /// * `threshold_delta`: Minimum increase in distinct blocks.
+    ///
+    /// returns: The timestamp of the spike, or None if there are no more spikes.
+    pub fn get_next_distinct_blocks_spike(&self, from_timestamp: u64, threshold_delta: u128) -> Option<u64> {
+        (from_timestamp as usize + 1..self.memory_usage_snapshots.len())
+            .find(|index| self.is_distinct_blocks_spike(*index, threshold_delta))
+            .map(|index| index as u64)
+    }
+
+    /// Finds the last operation before a timestamp where the distinct block count jumps by at
+    /// least a threshold since the previous operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_timestamp`: Timestamp to search backward from, exclusive.
+    /// * `threshold_delta`: Minimum increase in distinct blocks.
+    ///
+    /// returns: The timestamp of the spike, or None if there are no earlier spikes.
+    pub fn get_prev_distinct_blocks_spike(&self, from_timestamp: u64, threshold_delta: u128) -> Option<u64> {
+        (1..min(from_timestamp as usize, self.memory_usage_snapshots.len()))
+            .rev()
+            .find(|index| self.is_distinct_blocks_spike(*index, threshold_delta))
+            .map(|index| index as u64)
+    }
+
+    fn is_distinct_blocks_spike(&self, index: usize, threshold_delta: u128) -> bool {
+        let previous = self.memory_usage_snapshots[index - 1].get_distinct_blocks();
+        let current = self.memory_usage_snapshots[index].get_distinct_blocks();
+        current > previous && current - previous >= threshold_delta
+    }
+
+    /// Smooths plot points with a centered simple moving average over their y-values. Near the
+    /// edges the window shrinks to fit, so no points are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `points`: Points to smooth.
+    /// * `window`: Number of points to average over. A window of 0 or 1 leaves the points unchanged.
+    ///
+    /// returns: Vec<[timestamp, smoothed y-value]>, the same length as points.
+    pub fn smooth_plot_points(points: &[[f64; 2]], window: usize) -> Vec<[f64; 2]> {
+        let left_reach = window.saturating_sub(1) / 2;
+        let right_reach = window / 2;
+        let mut prefix_sums = Vec::with_capacity(points.len() + 1);
+        prefix_sums.push(0.0);
+        for point in points {
+            prefix_sums.push(prefix_sums.last().unwrap() + point[1]);
+        }
+
+        points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| {
+                let start = index.saturating_sub(left_reach);
+                let stop = min(index + right_reach + 1, points.len());
+                [point[0], (prefix_sums[stop] - prefix_sums[start]) / (stop - start) as f64]
+            })
+            .collect()
+    }
+
+    /// Reduces the points within an x window to at most sample_count points, keeping the lowest
+    /// and highest point of each run of points so spikes survive however far the graph is zoomed
+    /// out.
+    ///
+    /// # Arguments
+    ///
+    /// * `points`: Points to decimate, in x order.
+    /// * `start`: Lowest x to keep.
+    /// * `end`: Highest x to keep.
+    /// * `sample_count`: Maximum number of points to return.
+    ///
+    /// returns: Vec<[timestamp, y-value]> in x order. Every point in the window if there are at
+    /// most sample_count of them. A sample_count of 1 keeps only the highest point.
+    pub fn decimate_plot_points(points: &[[f64; 2]], start: f64, end: f64, sample_count: usize) -> Vec<[f64; 2]> {
+        let window: Vec<[f64; 2]> = points
+            .iter()
+            .filter(|point| start <= point[0] && point[0] <= end)
+            .copied()
+            .collect();
+        if window.len() <= sample_count {
+            return window;
+        }
+        let highest = |run: &[[f64; 2]]| run.iter().copied().enumerate().max_by(|(_, first), (_, second)| first[1].total_cmp(&second[1]));
+        if sample_count < 2 {
+            return highest(&window).map(|(_, point)| point).into_iter().take(sample_count).collect();
+        }
+
+        let mut decimated = Vec::with_capacity(sample_count);
+        for run in window.chunks(window.len().div_ceil(sample_count / 2)) {
+            let (lowest_index, lowest) = run
+                .iter()
+                .copied()
+                .enumerate()
+                .min_by(|(_, first), (_, second)| first[1].total_cmp(&second[1]))
+                .unwrap();
+            let (highest_index, highest) = highest(run).unwrap();
+            match lowest_index.cmp(&highest_index) {
+                Ordering::Less => decimated.extend([lowest, highest]),
+                Ordering::Greater => decimated.extend([highest, lowest]),
+                Ordering::Equal => decimated.push(lowest),
+            }
+        }
+        decimated
+    }
+
+    /// Replaces each y-value with the running maximum of the y-values up to it, giving a
+    /// non-decreasing high water mark.
+    ///
+    /// # Arguments
+    ///
+    /// * `points`: Points to take the running maximum of, in x order.
+    ///
+    /// returns: Vec<[timestamp, high water mark]>, the same length as points.
+    pub fn high_water_plot_points(points: &[[f64; 2]]) -> Vec<[f64; 2]> {
+        let mut high_water_mark = f64::NEG_INFINITY;
+        points
+            .iter()
+            .map(|point| {
+                high_water_mark = high_water_mark.max(point[1]);
+                [point[0], high_water_mark]
+            })
+            .collect()
+    }
+
+    /// Counts the operations (allocations and frees) in each fixed window of real time.
+    ///
+    /// # Arguments
+    ///
+    /// * `window`: Window length in microseconds.
+    ///
+    /// returns: Vec<[window start in microseconds, operation count]>, with windows running from the
+    /// window of the first operation to the window of the last. Empty windows have a count of 0.
+    /// Empty if window is 0, there are no operations, or the operations span more than
+    /// MAX_CHURN_WINDOWS windows.
+    pub fn get_churn_plot_points(&self, window: u64) -> Vec<[f64; 2]> {
+        if window == 0 {
+            return Vec::new();
+        }
+        let timestamps = self.memory_usage_snapshots
+            .iter()
+            .map(|memory_usage| memory_usage.get_timestamp_microseconds());
+        let (Some(first_timestamp), Some(last_timestamp)) = (timestamps.clone().min(), timestamps.max()) else {
+            return Vec::new();
+        };
+        let first_window = first_timestamp / window;
+        let window_count = last_timestamp / window - first_window + 1;
+        if window_count > MAX_CHURN_WINDOWS {
+            eprintln!("{}", format!("[GraphViewer::get_churn_plot_points]: Window of {window} us gives {window_count} windows, more than {MAX_CHURN_WINDOWS}").yellow());
+            return Vec::new();
+        }
+        let mut operation_counts = vec![0u64; window_count as usize];
+        for memory_usage in &self.memory_usage_snapshots {
+            operation_counts[(memory_usage.get_timestamp_microseconds() / window - first_window) as usize] += 1;
+        }
+        operation_counts
+            .into_iter()
+            .enumerate()
+            .map(|(index, count)| [((first_window + index as u64) * window) as f64, count as f64])
+            .collect()
+    }
+
+    /// Gets the last operation timestamp in a realtime sample, clamping the realtime timestamp to
+    /// the last sample.
+    ///
+    /// # Arguments
+    ///
+    /// * `realtime_timestamp`: Realtime timestamp.
+    ///
+    /// returns: The operation timestamp, or None if there are no samples.
+    pub fn get_operation_timestamp_of_realtime_timestamp(&self, realtime_timestamp: u64) -> Option<u64> {
+        self.sampled_memory_usage_snapshots
+            .get_operation_timestamps_in_realtime_timestamp(realtime_timestamp)
+            .map(|(_, last)| last)
+    }
+
+    /// Gets the realtime sample containing an operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation_timestamp`: Operation timestamp, clamped to the last sample.
+    ///
+    /// returns: The realtime timestamp, or None if there are no samples.
+    pub fn get_realtime_timestamp_of_operation_timestamp(&self, operation_timestamp: u64) -> Option<u64> {
+        self.sampled_memory_usage_snapshots.get_realtime_timestamp_of_operation_timestamp(operation_timestamp)
+    }
+
+    pub fn get_max_timestamp(&self) -> u64 {
+        self.max_timestamp
+    }
+
+    /// Gets the stats this graph was built from, so the graph can be rebuilt without
+    /// recalculating them.
+    ///
+    /// returns: MemoryUsageStats
+    pub fn get_memory_usage_stats(&self) -> MemoryUsageStats {
+        MemoryUsageStats::new(
+            self.memory_usage_snapshots.clone(),
+            self.max_usage,
+            self.peak_usage_timestamp,
+            self.max_free_blocks,
+            self.max_distinct_blocks as u128,
+            self.max_free_segment_fragmentation,
+            self.max_largest_free_block,
+        ).with_lifetime_byte_totals(self.lifetime_byte_totals.0, self.lifetime_byte_totals.1)
+    }
+
+    /// Appends the usages of updates appended to the log, extending the graphs without
+    /// recomputing the existing points.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_usage_stats`: Stats from MemoryUsageFactory::append_usage_stats, holding the new
+    ///   usages and the max stats over every update.
+    /// * `max_timestamp`: New max absolute operation timestamp to show on the graph.
+    ///
+    /// returns: ()
+    pub fn append_memory_usage_stats(&mut self, memory_usage_stats: MemoryUsageStats, max_timestamp: u64) {
+        let new_memory_usages = memory_usage_stats.get_memory_usages().clone();
+        self.memory_usage_snapshots.extend(new_memory_usages.iter().cloned());
+        self.sampled_memory_usage_snapshots.append_memory_usages(new_memory_usages);
+        self.max_usage = memory_usage_stats.get_max_usage();
+        self.peak_usage_timestamp = memory_usage_stats.get_peak_usage_timestamp();
+        self.max_free_blocks = memory_usage_stats.get_max_free_blocks();
+        self.max_distinct_blocks = memory_usage_stats.get_max_distinct_blocks() as usize;
+        self.max_free_segment_fragmentation = memory_usage_stats.get_max_free_segment_fragmentation();
+        self.max_largest_free_block = memory_usage_stats.get_max_largest_free_block();
+        self.max_timestamp = max_timestamp;
+        self.lifetime_byte_totals = (memory_usage_stats.get_total_bytes_allocated(), memory_usage_stats.get_total_bytes_freed());
+    }
+
+    /// Gets the total bytes allocated and freed over the whole run.
+    ///
+    /// returns: (total bytes allocated, total bytes freed)
+    pub fn get_lifetime_byte_totals(&self) -> (u128, u128) {
+        self.lifetime_byte_totals
+    }
+
+    pub fn set_lifetime_byte_totals(&mut self, lifetime_byte_totals: (u128, u128)) {
+        self.lifetime_byte_totals = lifetime_byte_totals;
+    }
+
+    pub fn get_max_usage(&self) -> i128 {
         self.max_usage
     }
 
+    pub fn get_peak_usage_timestamp(&self) -> u64 {
+        self.peak_usage_timestamp
+    }
+
     fn get_max_distinct_blocks(&self) -> usize {
         self.max_distinct_blocks
     }
@@ -0,0 +1,107 @@
+//! Exports memory maps to images that can be shared outside of the app.
+use image::{ImageFormat, Rgb, RgbImage};
+use crate::damselfly::damselfly_error::DamselflyError;
+
+/// Colour of each block status, matching the frontend's map grid: unused, free, partially
+/// allocated, allocated, churned (in maps between two timestamps), then guard.
+const STATUS_COLOURS: [Rgb<u8>; 6] = [
+    Rgb([211, 211, 211]), // lightgrey
+    Rgb([144, 238, 144]), // lightgreen
+    Rgb([255, 255, 0]),   // yellow
+    Rgb([255, 0, 0]),     // red
+    Rgb([255, 165, 0]),   // orange
+    Rgb([128, 0, 128]),   // purple
+];
+
+/// Colour of statuses past the end of STATUS_COLOURS, i.e. callstack colours.
+const ALLOCATED_COLOUR: Rgb<u8> = Rgb([255, 0, 0]);
+
+pub struct MapExporter;
+
+impl MapExporter {
+    /// Gets the colour the frontend draws a block in.
+    ///
+    /// # Arguments
+    ///
+    /// * `status`: Status (or callstack colour) of the block.
+    ///
+    /// returns: Rgb<u8>
+    pub fn get_block_colour(status: u64) -> Rgb<u8> {
+        STATUS_COLOURS.get(status as usize).copied().unwrap_or(ALLOCATED_COLOUR)
+    }
+
+    /// Draws a map as an image, laying blocks out left to right in rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `map`: Map blocks, e.g. from DamselflyInstance::get_map_full_at_with_block_size.
+    /// * `row_length`: Number of blocks in each row. Must be non-zero.
+    /// * `block_pixels`: Width and height of each block in pixels. Must be non-zero.
+    ///
+    /// returns: RgbImage. Pixels after the last block in the final row are left black.
+    pub fn to_image(map: &[(i64, u64, usize)], row_length: usize, block_pixels: u32) -> RgbImage {
+        let rows = map.len().div_ceil(row_length);
+        let mut image = RgbImage::new(row_length as u32 * block_pixels, rows as u32 * block_pixels);
+        for (index, (_, status, _)) in map.iter().enumerate() {
+            let colour = Self::get_block_colour(*status);
+            let x = (index % row_length) as u32 * block_pixels;
+            let y = (index / row_length) as u32 * block_pixels;
+            for pixel_y in y..y + block_pixels {
+                for pixel_x in x..x + block_pixels {
+                    image.put_pixel(pixel_x, pixel_y, colour);
+                }
+            }
+        }
+        image
+    }
+
+    /// Writes a map to a PNG file.
+    ///
+    /// # Arguments
+    ///
+    /// * `map`: Map blocks.
+    /// * `path`: File path to write to.
+    /// * `row_length`: Number of blocks in each row.
+    /// * `block_pixels`: Width and height of each block in pixels.
+    ///
+    /// returns: Result<(), DamselflyError>, or DamselflyError::InvalidConfiguration if the row
+    /// length or block pixels is 0.
+    pub fn export_png(map: &[(i64, u64, usize)], path: &str, row_length: usize, block_pixels: u32) -> Result<(), DamselflyError> {
+        if row_length == 0 || block_pixels == 0 {
+            return Err(DamselflyError::InvalidConfiguration("row length and block pixels must be greater than 0".to_string()));
+        }
+        Self::to_image(map, row_length, block_pixels)
+            .save_with_format(path, ImageFormat::Png)
+            .map_err(|error| DamselflyError::Io(error.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+    use crate::damselfly::viewer::map_exporter::MapExporter;
+
+    #[test]
+    fn to_image_test() {
+        let map = [(0, 3, 0), (0, 2, 32), (-1, 1, 64), (-1, 0, 96), (5, 7, 128), (5, 4, 160), (6, 5, 192)];
+        let image = MapExporter::to_image(&map, 2, 2);
+        assert_eq!(image.dimensions(), (4, 8));
+        assert_eq!(*image.get_pixel(1, 1), Rgb([255, 0, 0]));
+        assert_eq!(*image.get_pixel(2, 0), Rgb([255, 255, 0]));
+        assert_eq!(*image.get_pixel(0, 2), Rgb([144, 238, 144]));
+        assert_eq!(*image.get_pixel(3, 3), Rgb([211, 211, 211]));
+        // Callstack colours are drawn like allocated blocks
+        assert_eq!(*image.get_pixel(0, 4), Rgb([255, 0, 0]));
+        assert_eq!(*image.get_pixel(2, 4), Rgb([255, 165, 0]));
+        assert_eq!(*image.get_pixel(0, 6), Rgb([128, 0, 128]));
+    }
+
+    #[test]
+    fn export_png_test() {
+        let path = std::env::temp_dir().join("map_exporter_export_png_test.png");
+        let path = path.to_str().unwrap();
+        MapExporter::export_png(&[(0, 3, 0), (0, 0, 32)], path, 2, 1).unwrap();
+        assert!(std::fs::read(path).unwrap().starts_with(b"\x89PNG"));
+        assert!(MapExporter::export_png(&[], path, 0, 1).is_err());
+    }
+}
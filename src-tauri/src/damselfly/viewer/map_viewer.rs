@@ -9,6 +9,8 @@ use crate::damselfly::memory::memory_cache::MemoryCache;
 use crate::damselfly::memory::memory_status::MemoryStatus;
 use crate::damselfly::memory::memory_update::MemoryUpdateType;
 use crate::damselfly::update_interval::UpdateInterval;
+use crate::damselfly::update_interval::utility::Utility;
+use crate::damselfly::viewer::memory_canvas::MemoryCanvas;
 
 pub struct MapViewer {
     map_name: String,
@@ -20,6 +22,8 @@ pub struct MapViewer {
     block_size: usize,
     lowest_address: usize,
     highest_address: usize,
+    // (left padding, right padding) added around each update
+    guard_padding: (usize, usize),
 }
 
 impl MapViewer {
@@ -48,22 +52,29 @@ impl MapViewer {
             block_size: DEFAULT_BLOCK_SIZE,
             lowest_address: min(lowest_address, analysed_lowest_address),
             highest_address: max(highest_address, analysed_highest_address),
+            guard_padding: (0, 0),
         }
     }
 
-    pub fn get_update_history(&self, history_size: usize) -> Vec<MemoryUpdateType> {
+    /// Gets a page of the updates up to the current timestamp, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset`: Number of updates to skip, counting back from the current timestamp.
+    /// * `limit`: Max number of updates to return.
+    ///
+    /// returns: Vec<MemoryUpdateType>, empty if offset is past the first update.
+    pub fn get_update_history(&self, offset: usize, limit: usize) -> Vec<MemoryUpdateType> {
         println!("[get_update_history]: current timestamp: {}", self.current_timestamp);
-        let mut update_history = Vec::new();
-        for update in &self.update_intervals {
-            if update.val.get_timestamp() > self.current_timestamp {
-                break;
-            }
-            update_history.push(update);
-        }
-        update_history
+        let visible_updates = self.update_intervals
+            .iter()
+            .take_while(|update| update.val.get_timestamp() <= self.current_timestamp)
+            .count();
+        self.update_intervals[..visible_updates]
             .iter()
             .rev()
-            .take(history_size)
+            .skip(offset)
+            .take(limit)
             .map(|update_interval| update_interval.val.clone())
             .collect()
     }
@@ -72,6 +83,49 @@ impl MapViewer {
         self.current_timestamp = new_timestamp.clamp(usize::MIN, self.update_intervals.last().unwrap().val.get_timestamp());
     }
     
+    /// Appends updates that follow the existing ones and moves the map to the last update. The
+    /// cache is extended rather than regenerated, unless the log was shorter than cache_size, in
+    /// which case the cache interval grows with it.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_update_intervals`: Updates to append, in order.
+    /// * `cache_size`: Interval between cached maps requested when the viewer was built.
+    ///
+    /// returns: ()
+    pub fn append_update_intervals(&mut self, new_update_intervals: Vec<UpdateInterval>, cache_size: u64) {
+        if new_update_intervals.is_empty() {
+            return;
+        }
+        for update_interval in &new_update_intervals {
+            self.lowest_address = min(self.lowest_address, update_interval.val.get_absolute_address());
+            self.highest_address = max(self.highest_address, update_interval.val.get_absolute_address());
+        }
+        let cache_size = min(cache_size as usize, self.update_intervals.len() + new_update_intervals.len());
+        if cache_size == self.cache.get_interval() {
+            self.update_intervals.extend(new_update_intervals.iter().cloned());
+            self.cache.append_update_intervals(new_update_intervals, self.block_size);
+        } else {
+            self.update_intervals.extend(new_update_intervals);
+            self.cache = MemoryCache::new(self.block_size, self.update_intervals.clone(), cache_size);
+        }
+        self.current_timestamp = self.update_intervals.len() - 1;
+    }
+
+    /// Replaces the updates with copies that differ only in their callstacks, such as after
+    /// resymbolication. The cache is regenerated at the same interval and block size, and the
+    /// current timestamp is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `update_intervals`: Updates to replace the existing ones with, in the same order.
+    ///
+    /// returns: ()
+    pub fn replace_update_intervals(&mut self, update_intervals: Vec<UpdateInterval>) {
+        self.cache = MemoryCache::new(self.block_size, update_intervals.clone(), self.cache.get_interval());
+        self.update_intervals = update_intervals;
+    }
+
     pub fn set_map_span(&mut self, new_span: usize) {
         self.canvas_span = new_span;
     }
@@ -80,6 +134,14 @@ impl MapViewer {
         self.block_size
     }
     
+    pub fn get_lowest_address(&self) -> usize {
+        self.lowest_address
+    }
+
+    pub fn get_highest_address(&self) -> usize {
+        self.highest_address
+    }
+
     pub fn set_block_size(&mut self, new_size: usize) {
         let span_scale_factor = new_size as f64 / self.block_size as f64;
         self.set_map_span((self.canvas_span as f64 * span_scale_factor).round() as usize);
@@ -87,8 +149,74 @@ impl MapViewer {
         self.cache.change_block_size(new_size);
     }
 
+    pub fn get_cache_layout(&self) -> Vec<(usize, usize)> {
+        self.cache.get_cache_layout()
+    }
+
+    pub fn is_timestamp_cached(&self, timestamp: usize) -> bool {
+        self.cache.is_timestamp_cached(timestamp)
+    }
+
+    pub fn set_guard_padding(&mut self, left_padding: usize, right_padding: usize) {
+        self.guard_padding = (left_padding, right_padding);
+    }
+
     pub fn paint_map_full_from_cache(&self) -> Vec<MemoryStatus> {
-        self.cache.query_cache(self.current_timestamp).unwrap()
+        self.mark_guard_blocks(self.cache.query_cache(self.current_timestamp).unwrap(), self.block_size)
+    }
+
+    /// Paints the map at a timestamp at a given block size, directly from the updates rather than
+    /// the cache. Neither the cache nor the current block size or timestamp are changed. This
+    /// replays every update up to the timestamp, so it is much slower than
+    /// paint_map_full_from_cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp`: Timestamp to paint the map at. Clamped to the last update.
+    /// * `block_size`: Bytes spanned by each block of the map. Must be non-zero.
+    ///
+    /// returns: Vec<MemoryStatus>, one per block, spanning the same addresses as the cached map.
+    pub fn paint_map_full_at_block_size(&self, timestamp: usize, block_size: usize) -> Vec<MemoryStatus> {
+        let (start, stop) = Utility::get_canvas_span(&self.update_intervals);
+        let updates_till_now = self.update_intervals
+            .iter()
+            .take_while(|update| update.val.get_timestamp() <= timestamp)
+            .cloned()
+            .collect();
+        self.mark_guard_blocks(MemoryCanvas::new(start, stop, block_size, updates_till_now).render(), block_size)
+    }
+
+    /// Marks blocks whose bytes from the allocation painting them are all padding as guard blocks,
+    /// so redzones are not mistaken for allocated memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `map`: Painted map.
+    /// * `block_size`: Bytes spanned by each block of the map.
+    ///
+    /// returns: Vec<MemoryStatus>, the same length as map.
+    fn mark_guard_blocks(&self, map: Vec<MemoryStatus>, block_size: usize) -> Vec<MemoryStatus> {
+        let (left_padding, right_padding) = self.guard_padding;
+        if left_padding == 0 && right_padding == 0 {
+            return map;
+        }
+        map.into_iter()
+            .map(|block| match block {
+                MemoryStatus::Allocated(parent_address, size, address, callstack)
+                | MemoryStatus::PartiallyAllocated(parent_address, size, address, callstack)
+                if Self::is_guard_only(parent_address, size, address, block_size, self.guard_padding) =>
+                    MemoryStatus::Guard(parent_address, size, address, callstack),
+                block => block,
+            })
+            .collect()
+    }
+
+    fn is_guard_only(parent_address: usize, size: usize, address: usize, block_size: usize, guard_padding: (usize, usize)) -> bool {
+        let payload_start = parent_address.saturating_add(guard_padding.0);
+        let payload_end = (parent_address + size).saturating_sub(guard_padding.1);
+        let overlap_start = max(address, parent_address);
+        let overlap_end = min(address + block_size, parent_address + size);
+        payload_start >= payload_end || overlap_end <= payload_start || overlap_start >= payload_end
     }
 
     pub fn get_current_operation(&self) -> MemoryUpdateType {
@@ -43,7 +43,7 @@ impl Block {
             max(self.block_bounds.0, update_interval.get_start()),
             min(self.block_bounds.1, update_interval.get_end())
         );
-        let bytes_consumed = constrained_bounds.1 - constrained_bounds.0;
+        let bytes_consumed = constrained_bounds.1.saturating_sub(constrained_bounds.0);
         match &update_interval {
             MemoryUpdateType::Allocation(allocation) => {
                 self.remaining_bytes = self.remaining_bytes.saturating_sub(bytes_consumed);
@@ -54,6 +54,16 @@ impl Block {
                     .clamp(usize::MIN, self.block_bounds.1 - self.block_bounds.0);
                 self.update_block_status(free.get_absolute_address(), free.get_absolute_size(), free.get_callstack());
             }
+            // frees the old region, then allocates the new one, painting only the parts that
+            // overlap this block
+            MemoryUpdateType::Reallocation(reallocation) => {
+                let (free, allocation) = reallocation.to_free_and_allocation();
+                for update in [free.wrap_in_enum(), allocation.wrap_in_enum()] {
+                    if update.get_start() < self.block_bounds.1 && self.block_bounds.0 < update.get_end() {
+                        self.paint_block(&update);
+                    }
+                }
+            }
         }
     }
 
@@ -108,6 +108,13 @@ impl MemoryCanvas {
         block_statuses
     }
 
+    /// Gets the addresses spanned by the canvas.
+    ///
+    /// returns: (start, stop)
+    pub fn get_span(&self) -> (usize, usize) {
+        (self.start, self.stop)
+    }
+
     pub fn insert_blocks(&mut self) {
         for block_address in self.get_block_iter() {
             self.blocks.push(Block::new(block_address, self.block_size));
@@ -0,0 +1,94 @@
+//! Compact representation of the memory map with one bit per block.
+//!
+//! Blocks that are allocated or partially allocated are set to 1. Free and unused blocks are 0.
+//! Bits are packed least significant bit first, so block n is bit (n % 8) of byte (n / 8).
+use serde::Serialize;
+use crate::damselfly::memory::memory_status::MemoryStatus;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct OccupancyBitmap {
+    start: usize,
+    block_size: usize,
+    block_count: usize,
+    bits: Vec<u8>,
+}
+
+impl OccupancyBitmap {
+    /// Packs a rendered map into a bitmap.
+    ///
+    /// # Arguments
+    ///
+    /// * `map`: Rendered map, e.g. from MemoryCache::query_cache.
+    /// * `block_size`: Bytes spanned by each block of the map.
+    ///
+    /// returns: OccupancyBitmap
+    pub fn from_map(map: &[MemoryStatus], block_size: usize) -> Self {
+        let start = map.first().map(|block| block.get_address()).unwrap_or(0);
+        let mut bits = vec![0u8; map.len().div_ceil(8)];
+        for (index, block) in map.iter().enumerate() {
+            if matches!(block, MemoryStatus::Allocated(..) | MemoryStatus::PartiallyAllocated(..) | MemoryStatus::Guard(..)) {
+                bits[index / 8] |= 1 << (index % 8);
+            }
+        }
+
+        Self {
+            start,
+            block_size,
+            block_count: map.len(),
+            bits,
+        }
+    }
+
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+
+    pub fn get_block_size(&self) -> usize {
+        self.block_size
+    }
+
+    pub fn get_block_count(&self) -> usize {
+        self.block_count
+    }
+
+    pub fn get_bits(&self) -> &Vec<u8> {
+        &self.bits
+    }
+
+    /// Checks whether the block at the specified index is occupied.
+    pub fn is_occupied(&self, index: usize) -> bool {
+        index < self.block_count && self.bits[index / 8] & (1 << (index % 8)) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::memory_status::MemoryStatus;
+    use crate::damselfly::viewer::occupancy_bitmap::OccupancyBitmap;
+
+    #[test]
+    fn pack_bits_test() {
+        let callstack = Arc::new("test".to_string());
+        let mut map = vec![
+            MemoryStatus::Allocated(16, 4, 16, callstack.clone()),
+            MemoryStatus::Free(20, 4, 20, callstack.clone()),
+            MemoryStatus::Unused(24),
+            MemoryStatus::PartiallyAllocated(28, 2, 28, callstack.clone()),
+        ];
+        for address in (32..64).step_by(4) {
+            map.push(MemoryStatus::Unused(address));
+        }
+        map.push(MemoryStatus::Allocated(64, 4, 64, callstack));
+
+        let bitmap = OccupancyBitmap::from_map(&map, 4);
+        assert_eq!(bitmap.get_start(), 16);
+        assert_eq!(bitmap.get_block_count(), 13);
+        assert_eq!(bitmap.get_bits(), &vec![0b0000_1001, 0b0001_0000]);
+        assert!(bitmap.is_occupied(0));
+        assert!(!bitmap.is_occupied(1));
+        assert!(bitmap.is_occupied(3));
+        assert!(bitmap.is_occupied(12));
+        assert!(!bitmap.is_occupied(13));
+    }
+}
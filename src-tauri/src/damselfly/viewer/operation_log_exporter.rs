@@ -0,0 +1,98 @@
+//! Exports operation logs to newline-delimited JSON, one operation per line, so complete
+//! histories can be analysed offline without loading them into memory at once.
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use serde::Serialize;
+use crate::damselfly::damselfly_error::DamselflyError;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+
+/// A single line of the exported log.
+#[derive(Serialize)]
+struct OperationLogRecord<'a> {
+    timestamp: usize,
+    kind: &'static str,
+    address: usize,
+    size: usize,
+    callstack: &'a str,
+}
+
+pub struct OperationLogExporter;
+
+impl OperationLogExporter {
+    /// Formats an operation as a single line of JSON, without the trailing newline.
+    ///
+    /// # Arguments
+    ///
+    /// * `update`: Operation to format.
+    ///
+    /// returns: JSON object with timestamp, kind ("alloc" or "free"), address, size and callstack.
+    pub fn to_json_line(update: &MemoryUpdateType) -> String {
+        let callstack = update.get_callstack();
+        serde_json::to_string(&Self::to_record(update, &callstack))
+            .expect("[OperationLogExporter::to_json_line]: Failed to serialise operation")
+    }
+
+    /// Writes operations to a file as newline-delimited JSON. Operations are written as they are
+    /// pulled from the iterator, so the log is never held in memory as a whole. The file is
+    /// flushed and synced to disk before returning.
+    ///
+    /// # Arguments
+    ///
+    /// * `updates`: Operations to write, in the order they should appear.
+    /// * `path`: File path to write to.
+    ///
+    /// returns: Result<(), DamselflyError>
+    pub fn export_ndjson(updates: impl IntoIterator<Item = MemoryUpdateType>, path: &str) -> Result<(), DamselflyError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        for update in updates {
+            let callstack = update.get_callstack();
+            serde_json::to_writer(&mut writer, &Self::to_record(&update, &callstack))
+                .map_err(std::io::Error::from)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        Ok(())
+    }
+
+    fn to_record<'a>(update: &MemoryUpdateType, callstack: &'a str) -> OperationLogRecord<'a> {
+        OperationLogRecord {
+            timestamp: update.get_timestamp(),
+            kind: match update {
+                MemoryUpdateType::Allocation(_) => "alloc",
+                MemoryUpdateType::Free(_) => "free",
+                MemoryUpdateType::Reallocation(_) => "realloc",
+            },
+            address: update.get_absolute_address(),
+            size: update.get_absolute_size(),
+            callstack,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::damselfly::memory::memory_update::{Allocation, Free, MemoryUpdate};
+    use crate::damselfly::viewer::operation_log_exporter::OperationLogExporter;
+
+    #[test]
+    fn export_ndjson_test() {
+        let updates = vec![
+            Allocation::new(16, 8, Arc::new("main".to_string()), 0, "0001.000 s".to_string()).wrap_in_enum(),
+            Free::new(16, 8, Arc::new("main".to_string()), 1, "0001.001 s".to_string()).wrap_in_enum(),
+        ];
+        assert_eq!(
+            OperationLogExporter::to_json_line(&updates[0]),
+            r#"{"timestamp":0,"kind":"alloc","address":16,"size":8,"callstack":"main"}"#
+        );
+
+        let path = std::env::temp_dir().join("operation_log_exporter_export_ndjson_test.ndjson");
+        let path = path.to_str().unwrap();
+        OperationLogExporter::export_ndjson(updates.clone(), path).unwrap();
+        let contents = std::fs::read_to_string(path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1], OperationLogExporter::to_json_line(&updates[1]));
+    }
+}
@@ -0,0 +1,65 @@
+//! How a requested map block size is applied.
+use serde::Deserialize;
+use crate::damselfly::consts::MIN_ROW_LENGTH;
+use crate::damselfly::damselfly_error::DamselflyError;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum SetBlockSizeMode {
+    /// Use the requested block size as is.
+    #[default]
+    Exact,
+    /// Round to the nearest power of two, and at least MIN_ROW_LENGTH, so blocks stay aligned.
+    SnapPow2,
+}
+
+impl SetBlockSizeMode {
+    /// Gets the block size to apply for a requested block size.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_size`: Requested block size in bytes.
+    ///
+    /// returns: The block size to apply, or DamselflyError::InvalidConfiguration if the requested
+    /// block size is 0. Ties between two powers of two round up.
+    pub fn resolve(&self, block_size: usize) -> Result<usize, DamselflyError> {
+        if block_size == 0 {
+            return Err(DamselflyError::InvalidConfiguration("block size must be greater than 0".to_string()));
+        }
+        match self {
+            SetBlockSizeMode::Exact => Ok(block_size),
+            SetBlockSizeMode::SnapPow2 => {
+                let lower: usize = 1 << (usize::BITS - 1 - block_size.leading_zeros());
+                let nearest = match lower.checked_mul(2) {
+                    Some(upper) if upper - block_size <= block_size - lower => upper,
+                    _ => lower,
+                };
+                Ok(nearest.max(MIN_ROW_LENGTH.next_power_of_two()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::damselfly::damselfly_error::DamselflyError;
+    use crate::damselfly::viewer::set_block_size_mode::SetBlockSizeMode;
+
+    #[test]
+    fn resolve_exact_test() {
+        assert!(matches!(SetBlockSizeMode::Exact.resolve(0), Err(DamselflyError::InvalidConfiguration(_))));
+        assert_eq!(SetBlockSizeMode::Exact.resolve(3), Ok(3));
+        assert_eq!(SetBlockSizeMode::Exact.resolve(5), Ok(5));
+        assert_eq!(SetBlockSizeMode::Exact.resolve(4096), Ok(4096));
+    }
+
+    #[test]
+    fn resolve_snap_pow2_test() {
+        assert!(matches!(SetBlockSizeMode::SnapPow2.resolve(0), Err(DamselflyError::InvalidConfiguration(_))));
+        // Raised to MIN_ROW_LENGTH
+        assert_eq!(SetBlockSizeMode::SnapPow2.resolve(3), Ok(4));
+        assert_eq!(SetBlockSizeMode::SnapPow2.resolve(5), Ok(4));
+        assert_eq!(SetBlockSizeMode::SnapPow2.resolve(6), Ok(8));
+        assert_eq!(SetBlockSizeMode::SnapPow2.resolve(4096), Ok(4096));
+        assert_eq!(SetBlockSizeMode::SnapPow2.resolve(usize::MAX), Ok(1 << (usize::BITS - 1)));
+    }
+}
@@ -0,0 +1,77 @@
+//! On-disk cache of a parsed DamselflyViewer, so large logs don't need to be reparsed and
+//! resymbolicated on every launch. The file starts with VIEWER_CACHE_SCHEMA_VERSION, followed
+//! by the bincode-encoded ViewerCache.
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use serde::{Deserialize, Serialize};
+use crate::damselfly::consts::VIEWER_CACHE_SCHEMA_VERSION;
+use crate::damselfly::damselfly_error::DamselflyError;
+use crate::damselfly::memory::memory_access::MemoryAccess;
+use crate::damselfly::memory::memory_parsers::ParseAnomaly;
+use crate::damselfly::memory::memory_pool::MemoryPool;
+use crate::damselfly::memory::memory_update::MemoryUpdateType;
+use crate::damselfly::memory::memory_usage_stats::MemoryUsageStats;
+use crate::damselfly::viewer::damselfly_viewer_builder::DamselflyViewerBuilder;
+
+/// Everything needed to rebuild a DamselflyViewer without parsing its log.
+#[derive(Serialize, Deserialize)]
+pub struct ViewerCache {
+    pub config: DamselflyViewerBuilder,
+    pub max_timestamp: u64,
+    pub instances: Vec<CachedInstance>,
+}
+
+/// Everything needed to rebuild a single DamselflyInstance.
+#[derive(Serialize, Deserialize)]
+pub struct CachedInstance {
+    /// Pool of the instance, with padding applied.
+    pub pool: MemoryPool,
+    pub memory_updates: Vec<MemoryUpdateType>,
+    pub memory_usage_stats: MemoryUsageStats,
+    pub max_timestamp: u64,
+    pub memory_accesses: Option<Vec<MemoryAccess>>,
+    pub parse_anomalies: Vec<ParseAnomaly>,
+    pub collapsed_duplicates: u64,
+}
+
+impl ViewerCache {
+    /// Writes the cache to a file, prefixed with the schema version.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: Path to write to. Overwritten if it exists.
+    ///
+    /// returns: Result<(), DamselflyError>
+    pub fn write_to_file(&self, path: &str) -> Result<(), DamselflyError> {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        bincode::serialize_into(&mut writer, &VIEWER_CACHE_SCHEMA_VERSION)
+            .map_err(|error| DamselflyError::InvalidCache(error.to_string()))?;
+        bincode::serialize_into(&mut writer, self)
+            .map_err(|error| DamselflyError::InvalidCache(error.to_string()))?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        Ok(())
+    }
+
+    /// Reads a cache from a file, rejecting caches written by a different schema version before
+    /// decoding the rest of the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: Path to read from.
+    ///
+    /// returns: The cache, DamselflyError::StaleCache if its schema version doesn't match, or
+    /// DamselflyError::InvalidCache if it can't be decoded.
+    pub fn read_from_file(path: &str) -> Result<ViewerCache, DamselflyError> {
+        let file = File::open(path)?;
+        let mut reader = BufReader::new(file);
+        let version: u32 = bincode::deserialize_from(&mut reader)
+            .map_err(|error| DamselflyError::InvalidCache(error.to_string()))?;
+        if version != VIEWER_CACHE_SCHEMA_VERSION {
+            return Err(DamselflyError::StaleCache { version, expected_version: VIEWER_CACHE_SCHEMA_VERSION });
+        }
+        bincode::deserialize_from(&mut reader)
+            .map_err(|error| DamselflyError::InvalidCache(error.to_string()))
+    }
+}
@@ -0,0 +1,89 @@
+//! Options for loading a log, as sent by the frontend. Maps onto DamselflyViewerBuilder, plus the
+//! settings used to pick and configure the parser.
+use serde::Deserialize;
+use crate::damselfly::consts::DEFAULT_CACHE_INTERVAL;
+use crate::damselfly::damselfly_error::DamselflyError;
+use crate::damselfly::memory::json_event_parser::JsonEventParser;
+use crate::damselfly::memory::massif_parser::MassifParser;
+use crate::damselfly::memory::memory_parsers::MemorySysTraceParser;
+use crate::damselfly::memory::snapshot_parser::SnapshotParser;
+use crate::damselfly::viewer::damselfly_viewer::DamselflyViewer;
+use crate::damselfly::viewer::damselfly_viewer_builder::DamselflyViewerBuilder;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ViewerOptions {
+    pub log_path: String,
+    /// Shorthand for one binary shared by every pool. Ignored if binary_paths is set.
+    pub binary_path: Option<String>,
+    /// (pool name pattern, binary path) pairs.
+    pub binary_paths: Option<Vec<(String, String)>>,
+    #[serde(default = "default_cache_size")]
+    pub cache_size: u64,
+    #[serde(default)]
+    pub left_padding: usize,
+    #[serde(default)]
+    pub right_padding: usize,
+    #[serde(default)]
+    pub merge_overlapping_pools: bool,
+    pub pool_filter: Option<Vec<String>>,
+    /// Whether the log holds snapshots. Detected from the log's header if not set.
+    pub snapshot_mode: Option<bool>,
+    #[serde(default)]
+    pub collapse_duplicate_updates: bool,
+    pub gaddr2line_path: Option<String>,
+    /// If false, callstacks keep their raw addresses until the viewer is resymbolicated.
+    #[serde(default = "default_symbolicate")]
+    pub symbolicate: bool,
+}
+
+fn default_cache_size() -> u64 {
+    DEFAULT_CACHE_INTERVAL
+}
+
+fn default_symbolicate() -> bool {
+    true
+}
+
+impl ViewerOptions {
+    /// Converts the options into a builder. Parser settings are not part of the builder, and are
+    /// applied by build.
+    ///
+    /// returns: DamselflyViewerBuilder
+    pub fn to_builder(&self) -> DamselflyViewerBuilder {
+        let mut builder = DamselflyViewerBuilder::new(&self.log_path)
+            .cache_size(self.cache_size)
+            .left_padding(self.left_padding)
+            .right_padding(self.right_padding)
+            .merge_overlapping_pools(self.merge_overlapping_pools);
+        if let Some(binary_paths) = &self.binary_paths {
+            builder = builder.binary_paths(binary_paths.clone());
+        } else if let Some(binary_path) = &self.binary_path {
+            builder = builder.binary_path(binary_path);
+        }
+        if let Some(pool_filter) = &self.pool_filter {
+            builder = builder.pool_filter(pool_filter.clone());
+        }
+        builder
+    }
+
+    /// Picks a parser for the log and builds the viewer. Snapshot, massif and JSON event logs are
+    /// detected from their contents, and anything else is read by MemorySysTraceParser.
+    ///
+    /// returns: Result<DamselflyViewer, DamselflyError>
+    pub fn build(&self) -> Result<DamselflyViewer, DamselflyError> {
+        let builder = self.to_builder();
+        let snapshot_mode = self.snapshot_mode.unwrap_or_else(|| SnapshotParser::is_snapshot_log_file(&self.log_path));
+        if snapshot_mode {
+            builder.build(SnapshotParser::new())
+        } else if MassifParser::is_massif_log_file(&self.log_path) {
+            builder.build(MassifParser::new())
+        } else if JsonEventParser::is_json_event_log_file(&self.log_path) {
+            builder.build(JsonEventParser::new())
+        } else {
+            builder.build(MemorySysTraceParser::new()
+                .with_duplicate_collapsing(self.collapse_duplicate_updates)
+                .with_gaddr2line_path(self.gaddr2line_path.as_deref())
+                .with_symbolication(self.symbolicate))
+        }
+    }
+}
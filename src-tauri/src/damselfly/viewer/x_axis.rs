@@ -0,0 +1,11 @@
+//! What the x-axis of a graph is spaced by.
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum XAxis {
+    /// One point per operation, spaced by operation timestamp.
+    #[default]
+    Operation,
+    /// Sampled at regular intervals of wall-clock time.
+    Realtime,
+}
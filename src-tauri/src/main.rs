@@ -4,8 +4,24 @@
 
 use damselfly3::damselfly::memory::memory_update::MemoryUpdateType;
 use damselfly3::damselfly::viewer::damselfly_viewer::DamselflyViewer;
+use damselfly3::damselfly::viewer::viewer_options::ViewerOptions;
 use std::sync::{Arc, Mutex};
-use damselfly3::damselfly::memory::memory_parsers::MemorySysTraceParser;
+use damselfly3::damselfly::memory::memory_parsers::ParseAnomaly;
+use damselfly3::damselfly::memory::anomaly::{Anomaly, AnomalySeverity};
+use damselfly3::damselfly::memory::update_sort_key::UpdateSortKey;
+use damselfly3::damselfly::memory::placement_result::PlacementResult;
+use damselfly3::damselfly::memory::summary_stats::SummaryStats;
+use damselfly3::damselfly::memory::placement_simulator::PlacementStrategy;
+use damselfly3::damselfly::damselfly_error::DamselflyError;
+use damselfly3::damselfly::consts::DEFAULT_OPERATION_LOG_SIZE;
+use damselfly3::damselfly::viewer::occupancy_bitmap::OccupancyBitmap;
+use damselfly3::damselfly::viewer::graph_mode::GraphMode;
+use damselfly3::damselfly::viewer::x_axis::XAxis;
+use damselfly3::damselfly::viewer::graph_metric::GraphMetric;
+use damselfly3::damselfly::viewer::diff::DiffStatus;
+use damselfly3::damselfly::viewer::colour_mode::ColourMode;
+use damselfly3::damselfly::viewer::set_block_size_mode::SetBlockSizeMode;
+use damselfly3::damselfly::update_interval::serializable_update_interval::SerializableUpdateInterval;
 
 struct AppState {
     viewer: Arc<Mutex<Option<DamselflyViewer>>>,
@@ -33,10 +49,13 @@ fn main() {
             get_viewer_free_blocks_graph_sampled,
             get_viewer_free_segment_fragmentation_graph_no_fallbacks,
             get_viewer_free_segment_fragmentation_graph_sampled,
+            get_viewer_free_segment_fragmentation_percentage_graph,
             get_viewer_largest_free_block_graph_no_fallbacks,
             get_viewer_largest_free_block_graph_sampled,
+            get_cumulative_leak_graph,
             get_viewer_map_full_at_colours,
             get_viewer_map_full_at_colours_realtime_sampled,
+            get_occupancy_bitmap_at,
             choose_files,
             set_block_size,
             get_operation_log,
@@ -44,19 +63,120 @@ fn main() {
             query_block,
             query_block_realtime,
             get_pool_list,
+            get_intervals_in_window,
+            simulate_capacity,
+            export_usage_svg,
+            get_unused_allocations,
+            get_free_block_count_at,
+            get_size_inequality,
+            largest_aligned_free,
+            get_anomalies,
+            get_efficiency_graph,
+            get_most_reused_addresses,
+            simulate_placement,
+            get_cache_layout,
+            get_address_callstack_timeline,
+            is_timestamp_cached,
+            get_size_class_distribution,
+            first_crossing,
+            get_viewer_map_full_at_colours_decay,
+            get_address_callstack_diversity,
+            get_allocation_density,
+            get_fragmentation_culprits,
+            export_usage_graph_csv,
+            get_viewer_map_full_between,
+            diff_pools,
+            append_log_bytes,
+            set_colour_mode,
+            get_allocation_sites_ranked,
+            get_parse_anomalies,
+            get_overlaps_at,
+            save_viewer,
+            load_viewer,
+            get_peak_usage_timestamp,
+            get_viewer_usage_graph_smoothed,
+            get_allocation_lifetime_histogram,
+            export_operation_log,
+            get_viewer_usage_graph_with_axis,
+            get_viewer_graph_with_axis,
+            get_leak_candidates,
+            get_viewer_map_full_at_block_size,
+            get_viewer_live_allocation_count_graph,
+            get_viewer_live_allocation_count_graph_no_fallbacks,
+            get_viewer_live_allocation_count_graph_sampled,
+            find_operations_by_callstack,
+            get_lifetime_byte_totals,
+            get_pool_bounds,
+            get_allocation_size_histogram,
+            get_callstack_at,
+            get_usage_percentage_graph,
+            find_next_fragmentation_spike,
+            find_prev_fragmentation_spike,
+            get_viewer_usage_highwater_graph,
+            export_map_png,
+            get_summary_stats,
+            get_max_timestamp,
+            realtime_to_operation_index,
+            operation_index_to_realtime,
+            get_viewer_map_full_at_rle,
+            get_viewer_churn_graph,
+            get_free_blocks_at,
+            get_viewer_average_allocation_size_graph,
+            get_viewer_average_allocation_size_graph_realtime_sampled,
+            get_compacted_map_at,
+            get_callstack_usage_graph,
+            get_map_occupancy_at,
+            get_distinct_callstacks,
+            find_use_after_free,
+            get_viewer_usage_graph_sampled_n,
+            get_viewer_distinct_blocks_graph_sampled_n,
+            get_viewer_largest_free_block_graph_sampled_n,
+            get_peak_utilization,
+            query_range,
+            get_event_counts,
+            get_viewer_free_gap_count_graph,
+            get_viewer_free_gap_count_graph_no_fallbacks,
+            get_viewer_free_gap_count_graph_realtime_sampled,
+            clear_viewer,
+            get_viewer_mean_allocated_block_size_graph,
+            get_address_timeline,
+            get_collapsed_duplicates,
+            resymbolicate,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn initialise_viewer(state: tauri::State<AppState>, log_path: String, binary_path: String, cache_size: u64, distinct_block_left_padding: usize, distinct_block_right_padding: usize) {
-    let viewer = DamselflyViewer::new(&log_path, &binary_path, cache_size, distinct_block_left_padding, distinct_block_right_padding, MemorySysTraceParser::new());
+fn initialise_viewer(state: tauri::State<AppState>, options: ViewerOptions) -> Result<(), DamselflyError> {
+    let viewer = options.build()?;
     state.viewer.lock().unwrap().replace(viewer);
+    Ok(())
 }
 
+/// Resolves the raw-address callstacks of a log loaded with symbolicate set to false.
 #[tauri::command]
-async fn choose_files() -> Result<String, String> {
+fn resymbolicate(state: tauri::State<AppState>) -> Result<(), DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        viewer.resymbolicate()
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Unloads the viewer, freeing every instance's updates and caches. Clearing when no viewer is
+/// loaded does nothing.
+#[tauri::command]
+fn clear_viewer(state: tauri::State<AppState>) -> Result<(), DamselflyError> {
+    // Taken out first so the viewer is dropped after the lock is released
+    let viewer = state.viewer.lock().unwrap().take();
+    drop(viewer);
+    Ok(())
+}
+
+#[tauri::command]
+async fn choose_files() -> Result<String, DamselflyError> {
     use tauri::api::dialog::blocking::FileDialogBuilder;
     let file = String::from(
         FileDialogBuilder::new()
@@ -69,252 +189,329 @@ async fn choose_files() -> Result<String, String> {
 }
 
 #[tauri::command]
-fn get_viewer_usage_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_usage_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         let res = Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_usage_graph]: damselfly_instance not found: {damselfly_instance}")
-            .get_usage_graph());
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_usage_graph_with_axis(XAxis::Operation));
         res
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_usage_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_usage_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         let res = Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_usage_graph]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_usage_graph_no_fallbacks());
         eprintln!("viewer usage graph no fallbacks: res len = {}", res.as_ref().unwrap().len());
         res
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_usage_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_usage_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_usage_graph_sampled]: damselfly_instance not found: {damselfly_instance}")
-           .get_usage_graph_realtime_sampled())
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+           .get_usage_graph_with_axis(XAxis::Realtime))
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_distinct_blocks_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_distinct_blocks_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_distinct_blocks_graph]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_distinct_blocks_graph())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_distinct_blocks_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_distinct_blocks_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_distinct_blocks_graph]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_distinct_blocks_graph_no_fallbacks())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_distinct_blocks_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_distinct_blocks_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_distinct_blocks_graph_sampled]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_distinct_blocks_graph_realtime_sampled())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_largest_block_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_largest_block_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_largest_block_graph]: damselfly instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_largest_block_graph())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_largest_block_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_largest_block_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_largest_block_graph]: damselfly instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_largest_block_graph_no_fallbacks())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_largest_block_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_largest_block_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_largest_block_graph_sampled]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_largest_block_graph_realtime_sampled())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_free_blocks_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_free_blocks_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_free_blocks_graph]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_free_blocks_graph())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_free_blocks_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_free_blocks_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_free_blocks_graph]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_free_blocks_graph_no_fallbacks())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_free_blocks_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_free_blocks_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_free_blocks_graph_sampled]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_free_blocks_graph_realtime_sampled())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_free_segment_fragmentation_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_free_segment_fragmentation_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_free_blocks_graph]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_free_segment_fragmentation_graph_no_fallbacks())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command]
+fn get_viewer_free_segment_fragmentation_percentage_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_free_segment_fragmentation_percentage_graph())
+    } else {
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_free_segment_fragmentation_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_free_segment_fragmentation_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_free_blocks_graph_sampled]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_free_segment_fragmentation_graph_realtime_sampled())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_largest_free_block_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_largest_free_block_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_free_blocks_graph_sampled]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_largest_free_block_graph_no_fallbacks())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_viewer_largest_free_block_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, String> {
+fn get_viewer_largest_free_block_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_free_blocks_graph_sampled]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_largest_free_block_graph_realtime_sampled())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the usage graph between two realtime timestamps, min/max decimated to at most sample_count
+/// points.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_usage_graph_sampled_n(state: tauri::State<AppState>, damselfly_instance: u64, start_ts: u64, end_ts: u64, sample_count: usize) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_usage_graph_sampled_n(start_ts, end_ts, sample_count))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the distinct blocks graph between two realtime timestamps, min/max decimated to at most sample_count
+/// points.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_distinct_blocks_graph_sampled_n(state: tauri::State<AppState>, damselfly_instance: u64, start_ts: u64, end_ts: u64, sample_count: usize) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_distinct_blocks_graph_sampled_n(start_ts, end_ts, sample_count))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the largest free block graph between two realtime timestamps, min/max decimated to at most sample_count
+/// points.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_largest_free_block_graph_sampled_n(state: tauri::State<AppState>, damselfly_instance: u64, start_ts: u64, end_ts: u64, sample_count: usize) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_largest_free_block_graph_sampled_n(start_ts, end_ts, sample_count))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command]
+fn get_cumulative_leak_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_cumulative_leak_graph())
+    } else {
+        Err(DamselflyError::NotInitialised)
     }
 }
 
+/// truncate_after is a number of blocks. Leave it out to get the full map.
 #[tauri::command]
 fn get_viewer_map_full_at_colours(
     damselfly_instance: u64,
     state: tauri::State<AppState>,
     timestamp: u64,
-    truncate_after: u64,
-) -> Result<(u64, Vec<(i64, u64, usize)>), String> {
+    truncate_after: Option<u64>,
+) -> Result<(u64, Vec<(i64, u64, usize)>), DamselflyError> {
     eprintln!("[tauri::get_viewer_map_full_at_colours]: timestamp: {timestamp}");
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         let res = viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_map_full_at_colours]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_map_full_at_nosync_colours_truncate(timestamp, truncate_after);
         eprintln!("[tauri::get_viewer_map_full_at_colours]: res length: {}", &res.1.len());
         
         Ok(res)
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
@@ -323,128 +520,177 @@ fn get_viewer_map_full_at_colours_realtime_sampled(
     damselfly_instance: u64,
     state: tauri::State<AppState>,
     timestamp: u64,
-    truncate_after: u64,
-) -> Result<(u64, Vec<(i64, u64, usize)>), String> {
+    truncate_after: Option<u64>,
+) -> Result<(u64, Vec<(i64, u64, usize)>), DamselflyError> {
     eprintln!("[tauri::get_viewer_map_full_at_colours_realtime_sampled]: realtime_timestamp: {timestamp}");
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         let res = viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_viewer_map_full_at_colours]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_map_full_at_nosync_colours_truncate_realtime_sampled(timestamp, truncate_after);
         eprintln!("[tauri::get_viewer_map_full_at_colours_realtime_sampled]: realtime sampled size: {}", res.1.len());
         Ok(res)
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn set_block_size(state: tauri::State<AppState>, damselfly_instance: u64, new_block_size: u64) -> Result<(), String> {
+fn get_occupancy_bitmap_at(
+    damselfly_instance: u64,
+    state: tauri::State<AppState>,
+    timestamp: u64,
+) -> Result<OccupancyBitmap, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
-        viewer
-        .damselflies
-        .get_mut(damselfly_instance as usize)
-        .expect("[tauri::command::set_block_size]: damselfly_instance not found: {damselfly_instance}")
-        .set_map_block_size(new_block_size as usize);
-        Ok(())
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_occupancy_bitmap_at(timestamp))
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
+/// Sets the map's block size. By default the size is used as is; pass SnapPow2 as the mode to
+/// round it to a power of two. Returns the block size applied. A block size of 0 is rejected.
 #[tauri::command]
-fn get_operation_log(state: tauri::State<AppState>, damselfly_instance: u64, left_padding: u64, right_padding: u64) -> Result<Vec<String>, String> {
+fn set_block_size(state: tauri::State<AppState>, damselfly_instance: u64, new_block_size: u64, mode: Option<SetBlockSizeMode>) -> Result<u64, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
-        Ok(viewer
+        let block_size = viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .set_map_block_size(new_block_size as usize, mode.unwrap_or_default())?;
+        Ok(block_size as u64)
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets a page of the most recent operations, skipping the newest offset operations. Without an
+/// offset and limit, this is the first DEFAULT_OPERATION_LOG_SIZE operations. By default they are
+/// newest first. Pass sort_by to reorder the page by ascending time, address or size instead.
+#[tauri::command]
+fn get_operation_log(state: tauri::State<AppState>, damselfly_instance: u64, offset: Option<u64>, limit: Option<u64>, left_padding: u64, right_padding: u64, sort_by: Option<UpdateSortKey>) -> Result<Vec<String>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        let offset = offset.unwrap_or(0) as usize;
+        let limit = limit.map_or(DEFAULT_OPERATION_LOG_SIZE, |limit| limit as usize);
+        let mut operation_history: Vec<MemoryUpdateType> = viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_operation_log]: damselfly_instance not found")
-            .get_operation_history()
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_operation_history(offset, limit);
+        if let Some(sort_by) = sort_by {
+            sort_by.sort_updates(&mut operation_history);
+        }
+        Ok(operation_history
             .iter()
-            .take(128)
-            .map(|update| {
-                let mut update_with_padding_trimmed = update.clone();
-                update_with_padding_trimmed.set_absolute_size(
-                    update_with_padding_trimmed.get_absolute_size() - right_padding as usize
-                );
-                update_with_padding_trimmed.set_absolute_address(
-                    update_with_padding_trimmed.get_absolute_address() - left_padding as usize
-                );
-                update_with_padding_trimmed.to_string()
-            })
+            .map(|update| update.to_string_with_padding_trimmed(left_padding as usize, right_padding as usize))
             .collect())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 #[tauri::command]
-fn get_callstack(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<String, String> {
+fn get_callstack(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<String, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         Ok(viewer
             .damselflies
             .get_mut(damselfly_instance as usize)
-            .expect("[tauri::command::get_callstack]: damselfly_instance not found: {damselfly_instance}")
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
             .get_current_operation().get_callstack().to_string())
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
+/// Gets updates overlapping a block up to a timestamp, in ascending timestamp order unless
+/// sort_by says otherwise.
 #[tauri::command]
 fn query_block(
     damselfly_instance: u64,
     state: tauri::State<AppState>,
     address: usize,
     timestamp: usize,
-) -> Result<Vec<MemoryUpdateType>, String> {
+    sort_by: Option<UpdateSortKey>,
+) -> Result<Vec<MemoryUpdateType>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         let mut updates = viewer
         .damselflies
         .get_mut(damselfly_instance as usize)
-        .expect("[tauri::command::query_block]: damselfly_instance not found: {damselfly_instance}")
+        .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
         .query_block(address, timestamp);
         eprintln!("[Tauri::query_block]: updates.len: {}", updates.len());
-        updates.sort_by_key(|next| std::cmp::Reverse(next.get_timestamp()));
-        updates.reverse();
+        sort_by.unwrap_or_default().sort_updates(&mut updates);
+        Ok(updates)
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets updates overlapping [start_address, end_address) up to a timestamp, in ascending
+/// timestamp order unless sort_by says otherwise.
+#[tauri::command(rename_all = "snake_case")]
+fn query_range(
+    damselfly_instance: u64,
+    state: tauri::State<AppState>,
+    start_address: usize,
+    end_address: usize,
+    timestamp: usize,
+    sort_by: Option<UpdateSortKey>,
+) -> Result<Vec<MemoryUpdateType>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        let mut updates = viewer
+        .damselflies
+        .get_mut(damselfly_instance as usize)
+        .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+        .query_range(start_address, end_address, timestamp);
+        eprintln!("[Tauri::query_range]: updates.len: {}", updates.len());
+        sort_by.unwrap_or_default().sort_updates(&mut updates);
         Ok(updates)
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
+/// Gets updates overlapping a block up to a realtime timestamp, in ascending timestamp order
+/// unless sort_by says otherwise.
 #[tauri::command]
 fn query_block_realtime(
     state: tauri::State<AppState>,
     damselfly_instance: u64,
     address: usize,
     timestamp: usize,
-) -> Result<Vec<MemoryUpdateType>, String> {
+    sort_by: Option<UpdateSortKey>,
+) -> Result<Vec<MemoryUpdateType>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         let mut updates = viewer
         .damselflies
         .get_mut(damselfly_instance as usize)
-        .expect("[tauri::command::query_block_realtime]: damselfly_instance not found: {damselfly_instance}")
+        .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
         .query_block_realtime(address, timestamp);
         eprintln!("[Tauri::query_block_realtime]: damselfly_instance: {} address: {} timestamp: {} updates.len: {}", damselfly_instance, address, timestamp, updates.len());
-        updates.sort_by_key(|next| std::cmp::Reverse(next.get_timestamp()));
-        updates.reverse();
+        sort_by.unwrap_or_default().sort_updates(&mut updates);
         Ok(updates)
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
     }
 }
 
 
 #[tauri::command]
-fn get_pool_list(state: tauri::State<AppState>) -> Result<Vec<String>, String> {
+fn get_pool_list(state: tauri::State<AppState>) -> Result<Vec<String>, DamselflyError> {
     let mut viewer_lock = state.viewer.lock().unwrap();
     if let Some(viewer) = &mut *viewer_lock {
         return Ok(viewer
@@ -453,6 +699,1119 @@ fn get_pool_list(state: tauri::State<AppState>) -> Result<Vec<String>, String> {
         .map(|damselfly| String::from(damselfly.get_name()))
         .collect());
     } else {
-        Err("Viewer is not initialised".to_string())
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the (pool, allocs, frees) of every pool, to spot truncated or leaking traces at a glance.
+#[tauri::command(rename_all = "snake_case")]
+fn get_event_counts(state: tauri::State<AppState>) -> Result<Vec<(String, u64, u64)>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .iter()
+            .map(|damselfly| {
+                let (alloc_count, free_count) = damselfly.get_event_counts();
+                (damselfly.get_name().to_string(), alloc_count, free_count)
+            })
+            .collect())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the name and peak utilization fraction of every pool. Fractions are not clamped, so
+/// pools whose usage exceeded their capacity show up above 1.0.
+#[tauri::command(rename_all = "snake_case")]
+fn get_peak_utilization(state: tauri::State<AppState>) -> Result<Vec<(String, f64)>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .iter()
+            .map(|damselfly| (damselfly.get_name().to_string(), damselfly.get_peak_utilization_fraction()))
+            .collect())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command]
+fn get_intervals_in_window(
+    state: tauri::State<AppState>,
+    damselfly_instance: u64,
+    timestamp_start: usize,
+    timestamp_end: usize,
+) -> Result<Vec<SerializableUpdateInterval>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_intervals_in_window(timestamp_start, timestamp_end))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command]
+fn simulate_capacity(
+    state: tauri::State<AppState>,
+    damselfly_instance: u64,
+    cap_bytes: usize,
+) -> Result<Option<(usize, MemoryUpdateType)>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .simulate_capacity(cap_bytes))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command]
+fn export_usage_svg(
+    state: tauri::State<AppState>,
+    damselfly_instance: u64,
+    path: String,
+    width: u32,
+    height: u32,
+) -> Result<(), DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .export_usage_svg(&path, width, height)
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command]
+fn get_unused_allocations(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<MemoryUpdateType>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_unused_allocations()
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command]
+fn get_free_block_count_at(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64) -> Result<usize, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_free_block_count_at(timestamp))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_size_inequality(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<f64, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_size_inequality())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the free regions of a pool at a timestamp as (start, end) ranges, sorted by start.
+#[tauri::command(rename_all = "snake_case")]
+fn get_free_blocks_at(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64) -> Result<Vec<(usize, usize)>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_free_blocks_at(timestamp))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn largest_aligned_free(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64, alignment: usize) -> Result<usize, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .largest_aligned_free(timestamp, alignment))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_anomalies(state: tauri::State<AppState>, damselfly_instance: u64, min_severity: AnomalySeverity) -> Result<Vec<Anomaly>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_anomalies(min_severity))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the (timestamp, address) of every access to memory that was freed and not reallocated.
+#[tauri::command(rename_all = "snake_case")]
+fn find_use_after_free(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<(u64, usize)>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .find_use_after_free())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_efficiency_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_efficiency_graph())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_most_reused_addresses(state: tauri::State<AppState>, damselfly_instance: u64, n: usize) -> Result<Vec<(usize, usize)>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_most_reused_addresses(n))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn simulate_placement(state: tauri::State<AppState>, damselfly_instance: u64, strategy: PlacementStrategy) -> Result<PlacementResult, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .simulate_placement(strategy))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_cache_layout(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<(usize, usize)>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_cache_layout())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets every allocation and free touching an exact address across the whole run, for
+/// reconstructing how a slot was reused.
+#[tauri::command(rename_all = "snake_case")]
+fn get_address_timeline(state: tauri::State<AppState>, damselfly_instance: u64, address: usize) -> Result<Vec<(u64, String)>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_address_timeline(address))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_address_callstack_timeline(state: tauri::State<AppState>, damselfly_instance: u64, address: usize) -> Result<Vec<(usize, String, String)>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_address_callstack_timeline(address))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn is_timestamp_cached(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64) -> Result<bool, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .is_timestamp_cached(timestamp))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_size_class_distribution(state: tauri::State<AppState>, damselfly_instance: u64, size_classes: Vec<usize>) -> Result<Vec<(usize, usize, usize)>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_size_class_distribution(&size_classes))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn first_crossing(state: tauri::State<AppState>, damselfly_instance: u64, threshold_bytes: u64) -> Result<Option<(usize, MemoryUpdateType)>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .first_crossing(threshold_bytes))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// (timestamp, Vec<(parent_address, status, address, heat)>)
+type MapWithHeat = (u64, Vec<(i64, u64, usize, f64)>);
+
+/// Like get_viewer_map_full_at_colours, with a heat per block that fades recently freed blocks
+/// over decay_window timestamps.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_map_full_at_colours_decay(
+    state: tauri::State<AppState>,
+    damselfly_instance: u64,
+    timestamp: u64,
+    truncate_after: Option<u64>,
+    decay_window: u64,
+) -> Result<MapWithHeat, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_map_full_at_nosync_colours_truncate_decay(timestamp, truncate_after, decay_window))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_address_callstack_diversity(state: tauri::State<AppState>, damselfly_instance: u64, address: usize) -> Result<usize, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_address_callstack_diversity(address))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_allocation_density(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64, region_count: usize) -> Result<Vec<usize>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_allocation_density(timestamp, region_count))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_fragmentation_culprits(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64, n: usize) -> Result<Vec<(String, usize, usize)>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_fragmentation_culprits(timestamp, n))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn export_usage_graph_csv(
+    state: tauri::State<AppState>,
+    damselfly_instance: u64,
+    path: String,
+    mode: Option<GraphMode>,
+) -> Result<(), DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .export_usage_csv(&path, mode.unwrap_or_default())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+type Map = (u64, Vec<(i64, u64, usize)>);
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_map_full_between(
+    damselfly_instance: u64,
+    state: tauri::State<AppState>,
+    start_timestamp: u64,
+    end_timestamp: u64,
+    truncate_after: Option<u64>,
+) -> Result<Map, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_map_full_between(start_timestamp, end_timestamp, truncate_after))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn diff_pools(state: tauri::State<AppState>, damselfly_instance_a: u64, damselfly_instance_b: u64, timestamp: u64) -> Result<Vec<(usize, DiffStatus)>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        viewer.diff_instances(damselfly_instance_a as usize, damselfly_instance_b as usize, timestamp as usize)
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn append_log_bytes(state: tauri::State<AppState>, new_bytes: String) -> Result<usize, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        viewer.append_log_bytes(&new_bytes)
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn set_colour_mode(state: tauri::State<AppState>, damselfly_instance: u64, colour_mode: ColourMode) -> Result<(), DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .set_colour_mode(colour_mode);
+        Ok(())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_allocation_sites_ranked(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64) -> Result<Vec<(String, usize, usize)>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_allocation_sites_ranked(timestamp))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_parse_anomalies(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<ParseAnomaly>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_parse_anomalies())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the number of consecutive duplicate updates collapsed from the log, or 0 if the viewer
+/// was initialised without duplicate collapsing.
+#[tauri::command(rename_all = "snake_case")]
+fn get_collapsed_duplicates(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<u64, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_collapsed_duplicates())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_overlaps_at(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64) -> Result<Vec<(MemoryUpdateType, MemoryUpdateType)>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .find_overlaps_at(timestamp))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn save_viewer(state: tauri::State<AppState>, path: String) -> Result<(), DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        viewer.save_to_file(&path)
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn load_viewer(state: tauri::State<AppState>, path: String) -> Result<(), DamselflyError> {
+    let viewer = DamselflyViewer::load_from_file(&path)?;
+    state.viewer.lock().unwrap().replace(viewer);
+    Ok(())
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_peak_usage_timestamp(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<u64, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_peak_usage_timestamp())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_usage_graph_smoothed(state: tauri::State<AppState>, damselfly_instance: u64, window: usize) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_usage_graph_smoothed(window))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_allocation_lifetime_histogram(state: tauri::State<AppState>, damselfly_instance: u64, bucket_count: usize) -> Result<Vec<(u64, u64)>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_lifetime_histogram(bucket_count))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Writes every operation to a file as newline-delimited JSON, unlike get_operation_log which is
+/// capped to the most recent operations.
+#[tauri::command(rename_all = "snake_case")]
+fn export_operation_log(state: tauri::State<AppState>, damselfly_instance: u64, path: String, left_padding: u64, right_padding: u64) -> Result<(), DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .export_operation_log_json(&path, left_padding as usize, right_padding as usize)
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the usage graph with the x-axis spaced by operation (the default) or by wall-clock time.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_usage_graph_with_axis(state: tauri::State<AppState>, damselfly_instance: u64, axis: Option<XAxis>) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_usage_graph_with_axis(axis.unwrap_or_default()))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets a graph of any metric with the x-axis spaced by operation (the default) or by wall-clock
+/// time.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_graph_with_axis(state: tauri::State<AppState>, damselfly_instance: u64, metric: GraphMetric, axis: Option<XAxis>) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_graph_with_axis(metric, axis.unwrap_or_default()))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_leak_candidates(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<(MemoryUpdateType, u64)>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_leak_candidates())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Like get_viewer_map_full_at_colours, but at an ad-hoc block size, to preview a zoom level
+/// without recomputing the cache. This bypasses the cache, so it is much slower.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_map_full_at_block_size(
+    state: tauri::State<AppState>,
+    damselfly_instance: u64,
+    timestamp: u64,
+    block_size: u64,
+    truncate_after: Option<u64>,
+) -> Result<Map, DamselflyError> {
+    if block_size == 0 {
+        return Err(DamselflyError::InvalidConfiguration("Block size must be non-zero".to_string()));
+    }
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_map_full_at_with_block_size(timestamp, block_size as usize, truncate_after))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the number of live allocations after each operation, to correlate with fragmentation.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_live_allocation_count_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_live_allocation_count_graph())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_live_allocation_count_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_live_allocation_count_graph_no_fallbacks())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_live_allocation_count_graph_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_live_allocation_count_graph_realtime_sampled())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the number of free gaps between live allocations after each operation. Free space at
+/// either end of the pool is not counted as a gap.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_free_gap_count_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_free_gap_count_graph())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Like get_viewer_free_gap_count_graph, but without filler values.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_free_gap_count_graph_no_fallbacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_free_gap_count_graph_no_fallbacks())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Like get_viewer_free_gap_count_graph, but averaged over each realtime sample.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_free_gap_count_graph_realtime_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_free_gap_count_graph_realtime_sampled())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the mean size of the live allocated blocks after each operation, merging adjacent
+/// allocations into one block.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_mean_allocated_block_size_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_mean_allocated_block_size_graph())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the mean live allocation size after each operation, for sizing decisions.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_average_allocation_size_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_average_allocation_size_graph())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_average_allocation_size_graph_realtime_sampled(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_average_allocation_size_graph_realtime_sampled())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Finds every operation whose callstack contains a substring (case-insensitive), sorted by
+/// timestamp, for the UI's jump list.
+#[tauri::command(rename_all = "snake_case")]
+fn find_operations_by_callstack(state: tauri::State<AppState>, damselfly_instance: u64, substring: String) -> Result<Vec<(u64, MemoryUpdateType)>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .find_operations_by_callstack(&substring))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets (total bytes allocated, total bytes freed) over the whole run, for the summary panel.
+#[tauri::command(rename_all = "snake_case")]
+fn get_lifetime_byte_totals(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<(u128, u128), DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_lifetime_byte_totals())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets (name, start, stop) of every pool, in the same order as get_pool_list, so the UI can
+/// render an address ruler.
+#[tauri::command]
+fn get_pool_bounds(state: tauri::State<AppState>) -> Result<Vec<(String, usize, usize)>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer.get_pool_bounds())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets a histogram of requested allocation sizes, bucketed by the given upper bounds, with an
+/// overflow bucket (upper bound usize::MAX) for larger requests.
+#[tauri::command(rename_all = "snake_case")]
+fn get_allocation_size_histogram(state: tauri::State<AppState>, damselfly_instance: u64, buckets: Vec<usize>) -> Result<Vec<(usize, u64)>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_allocation_size_histogram(&buckets))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the callstack of the operation at a timestamp, so any operation in the log can be
+/// inspected without moving the map's current operation.
+#[tauri::command(rename_all = "snake_case")]
+fn get_callstack_at(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64) -> Result<String, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_callstack_at(timestamp)
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets memory usage as a percentage of the pool size, for comparing pools of different sizes.
+#[tauri::command(rename_all = "snake_case")]
+fn get_usage_percentage_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_usage_percentage_graph())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Finds the next operation after from_timestamp where the distinct block count jumps by at
+/// least threshold_delta, for the "next spike" button.
+#[tauri::command(rename_all = "snake_case")]
+fn find_next_fragmentation_spike(state: tauri::State<AppState>, damselfly_instance: u64, from_timestamp: u64, threshold_delta: u64) -> Result<Option<u64>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .find_next_fragmentation_spike(from_timestamp, threshold_delta))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Finds the previous operation before from_timestamp where the distinct block count jumps by at
+/// least threshold_delta, for the "previous spike" button.
+#[tauri::command(rename_all = "snake_case")]
+fn find_prev_fragmentation_spike(state: tauri::State<AppState>, damselfly_instance: u64, from_timestamp: u64, threshold_delta: u64) -> Result<Option<u64>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .find_prev_fragmentation_spike(from_timestamp, threshold_delta))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the running peak of the usage graph, the same length as get_viewer_usage_graph so the two
+/// can be overlaid.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_usage_highwater_graph(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_usage_highwater_graph())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the number of allocations and frees in each window of real time.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_churn_graph(state: tauri::State<AppState>, damselfly_instance: u64, window: u64) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_churn_graph(window))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Writes the full map at a timestamp to a PNG, coloured like the map grid.
+#[tauri::command(rename_all = "snake_case")]
+fn export_map_png(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64, block_size: usize, row_length: usize, path: String) -> Result<(), DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .render_map_png(timestamp, block_size, row_length, &path)
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the stats panel's numbers at a timestamp in one call, so they all come from the same
+/// snapshot.
+#[tauri::command(rename_all = "snake_case")]
+fn get_summary_stats(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64) -> Result<SummaryStats, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_summary_stats(timestamp))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the last timestamp of an instance, so the frontend can bound its timeline slider.
+#[tauri::command(rename_all = "snake_case")]
+fn get_max_timestamp(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<u64, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_max_timestamp())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Maps an x value of the realtime sampled graphs to the operation index the map should show.
+#[tauri::command(rename_all = "snake_case")]
+fn realtime_to_operation_index(state: tauri::State<AppState>, damselfly_instance: u64, realtime_timestamp: u64) -> Result<usize, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .realtime_to_operation_index(realtime_timestamp))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Maps an operation index to its x value on the realtime sampled graphs.
+#[tauri::command(rename_all = "snake_case")]
+fn operation_index_to_realtime(state: tauri::State<AppState>, damselfly_instance: u64, operation_index: usize) -> Result<u64, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .operation_index_to_realtime(operation_index))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the live bytes allocated from callstacks containing a substring after each operation, to
+/// overlay on the usage graph.
+#[tauri::command(rename_all = "snake_case")]
+fn get_callstack_usage_graph(state: tauri::State<AppState>, damselfly_instance: u64, callstack_substring: String) -> Result<Vec<[f64; 2]>, DamselflyError> {
+    let viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &*viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_callstack_usage_graph(&callstack_substring))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// (map, Vec<(compacted offset, address)>)
+type CompactedMap = (Vec<(i64, u64, usize)>, Vec<(usize, usize)>);
+
+/// Gets the full map with long free runs collapsed, plus the compacted offset -> address map so
+/// clicks on the compacted map still resolve to real addresses.
+#[tauri::command(rename_all = "snake_case")]
+fn get_compacted_map_at(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64) -> Result<CompactedMap, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_compacted_map_at(timestamp))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets every distinct callstack with its occurrence count, most frequent first.
+#[tauri::command(rename_all = "snake_case")]
+fn get_distinct_callstacks(state: tauri::State<AppState>, damselfly_instance: u64) -> Result<Vec<(String, u64)>, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_distinct_callstacks())
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// Gets the (total_bytes, used_bytes, free_bytes) of the map at a timestamp, counted the way
+/// the map draws its blocks so the numbers always agree with what is shown.
+#[tauri::command(rename_all = "snake_case")]
+fn get_map_occupancy_at(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64) -> Result<(usize, usize, usize), DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_map_occupancy_at(timestamp))
+    } else {
+        Err(DamselflyError::NotInitialised)
+    }
+}
+
+/// (timestamp, Vec<(colour, run_length, start_offset)>)
+type RleMap = (u64, Vec<(usize, u64, usize)>);
+
+/// Like get_viewer_map_full_at_colours, but run-length encoded as (colour, run_length,
+/// start_offset), to cut the payload for sparse maps.
+#[tauri::command(rename_all = "snake_case")]
+fn get_viewer_map_full_at_rle(state: tauri::State<AppState>, damselfly_instance: u64, timestamp: u64, truncate_after: Option<u64>) -> Result<RleMap, DamselflyError> {
+    let mut viewer_lock = state.viewer.lock().unwrap();
+    if let Some(viewer) = &mut *viewer_lock {
+        Ok(viewer
+            .damselflies
+            .get_mut(damselfly_instance as usize)
+            .ok_or(DamselflyError::InstanceNotFound(damselfly_instance))?
+            .get_map_full_at_rle(timestamp, truncate_after))
+    } else {
+        Err(DamselflyError::NotInitialised)
     }
 }
@@ -97,7 +97,15 @@ function App() {
       setRightPadding(parseInt(right_padding));
 
       if (logFilePath && binaryFilePath) {
-        await invoke("initialise_viewer", { log_path: logFilePath, binary_path: binaryFilePath, cache_size: cacheSizeInt, distinct_block_left_padding: parseInt(left_padding), distinct_block_right_padding: parseInt(right_padding) });
+        await invoke("initialise_viewer", {
+          options: {
+            log_path: logFilePath,
+            binary_paths: [["", binaryFilePath]],
+            cache_size: cacheSizeInt,
+            left_padding: parseInt(left_padding),
+            right_padding: parseInt(right_padding),
+          },
+        });
         setDataLoaded(true);
       }
     } catch (error) {
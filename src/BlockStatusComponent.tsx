@@ -20,11 +20,16 @@ interface MemoryUpdate {
 
 interface Allocation extends MemoryUpdate {}
 interface Free extends MemoryUpdate {}
+interface Reallocation extends MemoryUpdate {
+  old_address: number;
+  old_size: number;
+}
 
 // A wrapper type that could be returned from the backend
 type MemoryUpdateType = {
   Allocation?: Allocation;
   Free?: Free;
+  Reallocation?: Reallocation;
 };
 
 function BlockStatus({ activeInstance, lookupTile, timestamp, realtimeGraph, leftPadding, rightPadding }: BlockStatusProps) {
@@ -61,9 +66,9 @@ function BlockStatus({ activeInstance, lookupTile, timestamp, realtimeGraph, lef
   }, [realtimeGraph, lookupTile, timestamp]);
 
   const renderUpdate = (update: MemoryUpdateType) => {
-    // Determine if it's an Allocation or Free
-    const isAllocation = update.hasOwnProperty("Allocation");
-    const updateData = isAllocation ? update.Allocation : update.Free;
+    // Determine if it's an Allocation, Free or Reallocation
+    const updateType = update.Allocation ? "Allocation" : update.Reallocation ? "Reallocation" : "Free";
+    const updateData = update.Allocation ?? update.Reallocation ?? update.Free;
 
     return (
       <div style={{ padding: "10px", borderBottom: "1px solid #ccc" }}>
@@ -71,8 +76,14 @@ function BlockStatus({ activeInstance, lookupTile, timestamp, realtimeGraph, lef
           <strong>Address: {(lookupTile + leftPadding).toString(16)}</strong>
         </div>
         <div>
-          <strong>Type:</strong> {isAllocation ? "Allocation" : "Free"}
+          <strong>Type:</strong> {updateType}
         </div>
+        {update.Reallocation && (
+          <div>
+            <strong>Moved from:</strong> 0x{(update.Reallocation.old_address + leftPadding).toString(16)} (
+            {update.Reallocation.old_size - rightPadding} bytes)
+          </div>
+        )}
         <div>
           <strong>Start:</strong> 0x{updateData ? (updateData.address + leftPadding).toString(16) : 0}
         </div>
@@ -1,6 +1,15 @@
 import { useRef, useEffect } from "react";
 import Data from "./Data.tsx";
 
+// Matches ColourMode in the backend's set_colour_mode command
+export type ColourMode = "ByStatus" | "ByCallstack";
+
+// In ByCallstack mode, block values are stable callstack hashes, so each maps to a fixed entry
+const CALLSTACK_PALETTE = [
+    "#e6194b", "#3cb44b", "#ffe119", "#4363d8", "#f58231", "#911eb4", "#46f0f0", "#f032e6",
+    "#bcf60c", "#fabebe", "#008080", "#e6beff", "#9a6324", "#800000", "#aaffc3", "#808000",
+];
+
 interface MapGridProps {
     memoryData: Data;
     blockSize: number;
@@ -10,9 +19,10 @@ interface MapGridProps {
     setLookupTile: (block: number) => void;
     selectedTile: number;
     setSelectedTile: (block: number) => void;
+    colourMode?: ColourMode;
 }
 
-function MapGrid({ memoryData, blockSize, squareSize, selectedBlock, setSelectedBlock, setLookupTile, selectedTile, setSelectedTile }: MapGridProps) {
+function MapGrid({ memoryData, blockSize, squareSize, selectedBlock, setSelectedBlock, setLookupTile, selectedTile, setSelectedTile, colourMode = "ByStatus" }: MapGridProps) {
     const canvasRef = useRef<HTMLCanvasElement>(null);
 
     useEffect(() => {
@@ -31,7 +41,7 @@ function MapGrid({ memoryData, blockSize, squareSize, selectedBlock, setSelected
                 canvas.removeEventListener('click', handleCanvasClick);
             }
         }
-    }, [selectedBlock, selectedTile, squareSize, memoryData, blockSize]);
+    }, [selectedBlock, selectedTile, squareSize, memoryData, blockSize, colourMode]);
 
     const handleCanvasClick = (event: MouseEvent) => {
         const canvas = canvasRef.current;
@@ -127,10 +137,16 @@ function MapGrid({ memoryData, blockSize, squareSize, selectedBlock, setSelected
     };
 
     const getColorForBlock = (blockValue: number) => {
+        if (colourMode == "ByCallstack") {
+            // 0 is a block with no allocation
+            return blockValue == 0 ? "lightgrey" : CALLSTACK_PALETTE[blockValue % CALLSTACK_PALETTE.length];
+        }
         switch(blockValue) {
             case 0: return "lightgrey";
             case 1: return "lightgreen";
             case 2: return "yellow";
+            case 4: return "orange";
+            case 5: return "purple";
             default: return "red";
         }
     };